@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use wordgame::words::*;
+
+const DICTIONARY: &str = include_str!("../assets/words/words_alpha.txt");
+const DICTIONARY_SIZES: [usize; 3] = [10_000, 100_000, 370_000];
+
+// the smaller dictionaries are alphabetical prefixes, so they hold fewer answers for this board
+const BOARD_LETTERS: &str = "EFGHIRT";
+const REQUIRED_LETTER: char = 'E';
+
+fn dictionary_of_size(size: usize) -> String {
+    DICTIONARY.lines().take(size).collect::<Vec<&str>>().join("\n")
+}
+
+fn board(wordlist: &WordList) -> GameState {
+    let target_bits = word_to_bits(BOARD_LETTERS);
+    let required_bit = 1 << alphabet_index(REQUIRED_LETTER as u8);
+    GameState {
+        target_string: String::from(BOARD_LETTERS),
+        target_bits,
+        required_letter: REQUIRED_LETTER,
+        required_bit,
+        correct_words: vec![
+            FoundWord { word: String::from("freight"), player: 0 },
+            FoundWord { word: String::from("eight"), player: 0 },
+        ],
+        answers: find_answers(wordlist, target_bits, required_bit, MIN_WORD_LENGTH, None),
+        ..GameState::default()
+    }
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_word_list");
+    for size in DICTIONARY_SIZES {
+        let contents = dictionary_of_size(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &contents, |b, contents| {
+            b.iter(|| WordList::parse(black_box(contents)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_answers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_answers");
+    for size in DICTIONARY_SIZES {
+        let wordlist = WordList::parse(&dictionary_of_size(size));
+        let gamestate = board(&wordlist);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &wordlist, |b, wordlist| {
+            b.iter(|| find_answers(black_box(wordlist), gamestate.target_bits, gamestate.required_bit, gamestate.min_word_length, None))
+        });
+    }
+    group.finish();
+}
+
+fn bench_check_word(c: &mut Criterion) {
+    let guesses = [
+        ("accepted", "FIGHTER"),
+        ("already_found", "EIGHT"),
+        ("not_in_list", "GREFFIT"),
+        ("wrong_letters", "QUIZ"),
+        ("too_short", "THE"),
+    ];
+
+    let mut group = c.benchmark_group("check_word");
+    for size in DICTIONARY_SIZES {
+        let wordlist = WordList::parse(&dictionary_of_size(size));
+        let gamestate = board(&wordlist);
+        for (name, guess) in guesses {
+            group.bench_with_input(BenchmarkId::new(name, size), guess, |b, guess| {
+                b.iter(|| check_word(black_box(guess), &gamestate, &wordlist))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_answers, bench_check_word);
+criterion_main!(benches);
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use std::collections::VecDeque;
+
+use crate::achievements::{Achievement, AchievementUnlockedEvent};
+use crate::layout::Layout;
+use crate::tween::{Easing, Track, Tween, TweenFinishedEvent};
+
+// A card dropping in from the top of the screen for each achievement unlocked, with its badge,
+// name and description. Unlocks that come together wait their turn in a queue, one card at a time.
+// The card's stay is timed here rather than in its tween, so reduced motion still leaves it up
+// long enough to read.
+pub struct AchievementPopupPlugin;
+
+impl Plugin for AchievementPopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PopupQueue>()
+            .add_system(queue_popups)
+            .add_system(show_popups.after(queue_popups))
+            .add_system(finish_popups);
+    }
+}
+
+const POPUP_SECONDS: f32 = 3.;
+const POPUP_SIZE: Vec2 = Vec2::new(440., 90.);
+const POPUP_Z: f32 = 70.;
+const BADGE_RADIUS: f32 = 32.;
+const SLIDE_IN: &str = "achievement in";
+const SLIDE_OUT: &str = "achievement out";
+
+#[derive(Resource, Default)]
+struct PopupQueue {
+    waiting: VecDeque<Achievement>,
+    showing: Option<Entity>,
+}
+
+#[derive(Component)]
+struct Popup {
+    seconds_left: f32,
+    shown_at: Vec3,
+}
+
+fn queue_popups(mut ev_unlocked: EventReader<AchievementUnlockedEvent>, mut queue: ResMut<PopupQueue>) {
+    for ev in ev_unlocked.iter() {
+        queue.waiting.push_back(ev.achievement);
+    }
+}
+
+fn spawn_popup(commands: &mut Commands, asset_server: &AssetServer, layout: &Layout, achievement: Achievement) -> Entity {
+    let name_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 32., color: Color::GOLD };
+    let description_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::WHITE };
+    let badge_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 28., color: Color::BLACK };
+
+    // just off the top of the design area, and just inside it
+    let top = layout.design_size().y / 2.;
+    let hidden_at = Vec3::new(0., top + POPUP_SIZE.y, POPUP_Z);
+    let shown_at = Vec3::new(0., top - POPUP_SIZE.y / 2. - 12., POPUP_Z);
+    let badge_x = -POPUP_SIZE.x / 2. + BADGE_RADIUS + 16.;
+    let text_x = badge_x + BADGE_RADIUS + 16.;
+
+    commands.spawn(SpatialBundle::from_transform(Transform::from_translation(hidden_at)))
+        .insert(Popup { seconds_left: POPUP_SECONDS, shown_at })
+        .insert(Tween::new(SLIDE_IN).then(0.4, Easing::BackOut, [Track::Translation(hidden_at, shown_at)]))
+        .with_children(|popup| {
+            popup.spawn(SpriteBundle {
+                sprite: Sprite { color: Color::rgba(0., 0., 0., 0.85), custom_size: Some(POPUP_SIZE), ..default() },
+                ..default()
+            });
+            popup.spawn(GeometryBuilder::build_as(
+                &shapes::RegularPolygon { sides: 6, feature: shapes::RegularPolygonFeature::Radius(BADGE_RADIUS), ..default() },
+                DrawMode::Fill(FillMode::color(Color::GOLD)),
+                Transform::from_translation(Vec3::new(badge_x, 0., 0.1)),
+            ));
+            popup.spawn(Text2dBundle {
+                text: Text::from_section(achievement.badge(), badge_style).with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(Vec3::new(badge_x, 0., 0.2)),
+                ..default()
+            });
+            popup.spawn(Text2dBundle {
+                text: Text::from_sections([
+                    TextSection::new(format!("{}\n", achievement.name()), name_style),
+                    TextSection::new(achievement.description(), description_style),
+                ]).with_alignment(TextAlignment::CENTER_LEFT),
+                transform: Transform::from_translation(Vec3::new(text_x, 0., 0.1)),
+                ..default()
+            });
+        })
+        .id()
+}
+
+// the next card only drops in once the last one has gone
+fn show_popups(mut commands: Commands,
+               asset_server: Res<AssetServer>,
+               layout: Res<Layout>,
+               time: Res<Time>,
+               mut queue: ResMut<PopupQueue>,
+               mut popups: Query<(Entity, &mut Popup)>,
+            ) {
+    if queue.showing.is_none() {
+        if let Some(achievement) = queue.waiting.pop_front() {
+            queue.showing = Some(spawn_popup(&mut commands, &asset_server, &layout, achievement));
+        }
+        return;
+    }
+
+    for (entity, mut popup) in popups.iter_mut() {
+        if popup.seconds_left <= 0. {
+            continue;
+        }
+        popup.seconds_left -= time.delta_seconds();
+        if popup.seconds_left <= 0. {
+            let gone = popup.shown_at + Vec3::new(0., 2. * POPUP_SIZE.y, 0.);
+            commands.entity(entity).insert(Tween::new(SLIDE_OUT)
+                .then(0.3, Easing::CubicOut, [Track::Translation(popup.shown_at, gone)])
+                .despawning());
+        }
+    }
+}
+
+fn finish_popups(mut ev_tween_finished: EventReader<TweenFinishedEvent>, mut queue: ResMut<PopupQueue>) {
+    for ev in ev_tween_finished.iter().filter(|ev| ev.tag == SLIDE_OUT) {
+        if queue.showing == Some(ev.entity) {
+            queue.showing = None;
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use wordgame::words::{is_pangram, GameState};
+
+use crate::storage;
+use crate::{record_correct_words, AppState, DailyBoard};
+
+// Milestones unlocked once and kept for good in achievements.ron, next to the settings.
+// Anything else that wants to know (the popup and the Steam integration) watches
+// AchievementUnlockedEvent.
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Achievements>()
+            .add_event::<AchievementUnlockedEvent>()
+            .add_startup_system(load_achievements)
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(check_achievements.after(record_correct_words)))
+            .add_system(save_achievements);
+    }
+}
+
+pub const ACHIEVEMENTS_FILE: &str = "achievements.ron";
+const CENTURY_SCORE: u32 = 100;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Achievement {
+    FirstWord,
+    FirstPangram,
+    CenturyBoard,
+    AllWords,
+    DailyPuzzle,
+}
+
+pub const ALL_ACHIEVEMENTS: [Achievement; 5] = [
+    Achievement::FirstWord,
+    Achievement::FirstPangram,
+    Achievement::CenturyBoard,
+    Achievement::AllWords,
+    Achievement::DailyPuzzle,
+];
+
+impl Achievement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Achievement::FirstWord => "First Word",
+            Achievement::FirstPangram => "Every Letter",
+            Achievement::CenturyBoard => "Century",
+            Achievement::AllWords => "Cleared Out",
+            Achievement::DailyPuzzle => "Daily Habit",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstWord => "Find a word",
+            Achievement::FirstPangram => "Find a word using all seven letters",
+            Achievement::CenturyBoard => "Score 100 points on one board",
+            Achievement::AllWords => "Find every word on a board",
+            Achievement::DailyPuzzle => "Find a word on the daily puzzle",
+        }
+    }
+
+    // a few letters for the popup's badge
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Achievement::FirstWord => "A",
+            Achievement::FirstPangram => "7",
+            Achievement::CenturyBoard => "100",
+            Achievement::AllWords => "All",
+            Achievement::DailyPuzzle => "D",
+        }
+    }
+
+    fn earned(&self, gamestate: &GameState, daily_board: &DailyBoard) -> bool {
+        let found_any = !gamestate.correct_words.is_empty();
+        match self {
+            Achievement::FirstWord => found_any,
+            Achievement::FirstPangram => gamestate.correct_words.iter().any(|found| is_pangram(&found.word)),
+            Achievement::CenturyBoard => gamestate.score() >= CENTURY_SCORE,
+            Achievement::AllWords => found_any && gamestate.correct_words.len() >= gamestate.answers.len(),
+            Achievement::DailyPuzzle => found_any && daily_board.0.is_some(),
+        }
+    }
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    pub unlocked: Vec<Achievement>,
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+}
+
+pub struct AchievementUnlockedEvent {
+    pub achievement: Achievement,
+}
+
+// a broken or unwritable file only costs the record, so it's a warning rather than a dialog
+fn load_achievements(mut achievements: ResMut<Achievements>) {
+    let loaded = storage::read(ACHIEVEMENTS_FILE).and_then(|contents| match contents {
+        Some(contents) => ron::from_str(&contents).map_err(|err| format!("Achievements file {} is corrupt: {}", storage::location(ACHIEVEMENTS_FILE), err)),
+        None => Ok(Achievements::default()),
+    });
+
+    match loaded {
+        Ok(loaded) => *achievements = loaded,
+        Err(message) => warn!("{}", message),
+    }
+}
+
+fn save_achievements(achievements: Res<Achievements>) {
+    if !achievements.is_changed() || achievements.is_added() {
+        return;
+    }
+
+    let saved = ron::ser::to_string_pretty(&*achievements, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| storage::write(ACHIEVEMENTS_FILE, &contents));
+    if let Err(message) = saved {
+        warn!("{}", message);
+    }
+}
+
+fn check_achievements(gamestate: Res<GameState>,
+                      daily_board: Res<DailyBoard>,
+                      mut achievements: ResMut<Achievements>,
+                      mut ev_unlocked: EventWriter<AchievementUnlockedEvent>,
+                    ) {
+    if !gamestate.is_changed() {
+        return;
+    }
+
+    for achievement in ALL_ACHIEVEMENTS {
+        if !achievements.is_unlocked(achievement) && achievement.earned(&gamestate, &daily_board) {
+            info!("achievement unlocked: {}", achievement.name());
+            achievements.unlocked.push(achievement);
+            ev_unlocked.send(AchievementUnlockedEvent { achievement });
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+use wordgame::words::{GameState, RejectReason};
+
+use crate::combo::{ComboEndedEvent, ComboExtendedEvent};
+use crate::focus::Focusable;
+use crate::settings::Settings;
+use crate::{AppState, GuessErasedEvent, LetterAcceptedEvent, LetterRefusedEvent, TileHoveredEvent, WordAcceptedEvent, WordRejectedEvent};
+
+// One shot sound effects for the game events, all played at the settings effect volume,
+// plus the mute toggle (Ctrl+M, since a bare M is a letter on some boards, or the speaker icon).
+// Everything the colours say can be heard too: the required letter sounds a fifth higher when
+// hovered or typed, a guess missing it gets its own lower rejection and one using a letter off the
+// board a higher one. Each new rank gets a rising arpeggio. Combos chime higher with each word kept and drop a low note when they run out.
+pub struct SoundEffectsPlugin;
+
+impl Plugin for SoundEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_sound_effects)
+            .add_startup_system(spawn_mute_button)
+            .add_system(play_sound_effects)
+            .add_system(play_rank_sounds)
+            .add_system(play_combo_sounds)
+            .add_system(play_ambient_sounds)
+            .add_system(toggle_mute)
+            .add_system(show_mute_icon.after(toggle_mute));
+    }
+}
+
+#[derive(Resource)]
+struct SoundEffects {
+    letter: Handle<AudioSource>,
+    accepted: Handle<AudioSource>,
+    rejected: Handle<AudioSource>,
+    pangram: Handle<AudioSource>,
+    hover: Handle<AudioSource>,
+    erase: Handle<AudioSource>,
+    clear: Handle<AudioSource>,
+    rank_up: Handle<AudioSource>,
+}
+
+fn load_sound_effects(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundEffects {
+        letter: asset_server.load("audio/letter.wav"),
+        accepted: asset_server.load("audio/accepted.wav"),
+        rejected: asset_server.load("audio/rejected.wav"),
+        pangram: asset_server.load("audio/pangram.wav"),
+        hover: asset_server.load("audio/hover.wav"),
+        erase: asset_server.load("audio/erase.wav"),
+        clear: asset_server.load("audio/clear.wav"),
+        rank_up: asset_server.load("audio/rank_up.wav"),
+    });
+}
+
+const MAX_LENGTH_SEMITONES: usize = 7;
+const MAX_STREAK_SEMITONES: u32 = 5;
+const LAYER_FROM_LENGTH: usize = 7;
+
+// playback speed shifts pitch, so a semitone up is a 2^(1/12) speed up
+fn semitones_to_speed(semitones: f32) -> f32 {
+    f32::powf(2., semitones / 12.)
+}
+
+// longer words and longer runs of correct guesses climb the scale
+fn accepted_pitch(word: &str, streak: u32) -> f32 {
+    let length_steps = word.len().saturating_sub(4).min(MAX_LENGTH_SEMITONES);
+    let streak_steps = streak.saturating_sub(1).min(MAX_STREAK_SEMITONES);
+    semitones_to_speed((length_steps as u32 + streak_steps) as f32)
+}
+
+// said a fourth down, so missing the required letter doesn't sound like any other mistake, and a
+// letter off the board a minor third up
+const MISSING_REQUIRED_SEMITONES: f32 = -5.;
+const OFF_BOARD_SEMITONES: f32 = 3.;
+
+fn play_sound_effects(audio: Res<Audio>,
+                      sounds: Res<SoundEffects>,
+                      settings: Res<Settings>,
+                      gamestate: Res<GameState>,
+                      mut streak: Local<u32>,
+                      mut ev_letter_accepted: EventReader<LetterAcceptedEvent>,
+                      mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                      mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                    ) {
+    let playback = PlaybackSettings::ONCE.with_volume(settings.effective_sfx_volume());
+
+    for ev in ev_letter_accepted.iter() {
+        if !settings.letter_sounds {
+            continue;
+        }
+
+        let required = ev.letter.eq_ignore_ascii_case(&gamestate.required_letter);
+        let speed = if required { semitones_to_speed(7.) } else { 1. };
+        audio.play_with_settings(sounds.letter.clone(), playback.clone().with_speed(speed));
+    }
+
+    for ev in ev_word_accepted.iter() {
+        *streak += 1;
+
+        if ev.pangram {
+            audio.play_with_settings(sounds.pangram.clone(), playback.clone());
+        }
+        else {
+            let pitch = accepted_pitch(&ev.word, *streak);
+            audio.play_with_settings(sounds.accepted.clone(), playback.clone().with_speed(pitch));
+
+            // long words get a quieter fifth on top
+            if ev.word.len() >= LAYER_FROM_LENGTH {
+                let layer = playback.clone().with_volume(playback.volume * 0.5).with_speed(pitch * semitones_to_speed(7.));
+                audio.play_with_settings(sounds.accepted.clone(), layer);
+            }
+        }
+    }
+
+    for ev in ev_word_rejected.iter() {
+        *streak = 0;
+        let speed = match ev.reason {
+            RejectReason::MissingRequired => semitones_to_speed(MISSING_REQUIRED_SEMITONES),
+            RejectReason::OffBoardLetter | RejectReason::OutOfTiles => semitones_to_speed(OFF_BOARD_SEMITONES),
+            _ => 1.,
+        };
+        audio.play_with_settings(sounds.rejected.clone(), playback.clone().with_speed(speed));
+    }
+}
+
+// ranks are counted off rank_times like the telemetry, so one lost to hints and won back again
+// isn't heard twice, and several passed with one word only play once
+fn play_rank_sounds(audio: Res<Audio>,
+                    sounds: Res<SoundEffects>,
+                    settings: Res<Settings>,
+                    gamestate: Res<GameState>,
+                    mut ranks_heard: Local<usize>,
+                ) {
+    if gamestate.rank_times.len() < *ranks_heard {
+        *ranks_heard = 0;
+    }
+    if gamestate.rank_times.len() > *ranks_heard {
+        audio.play_with_settings(sounds.rank_up.clone(), PlaybackSettings::ONCE.with_volume(settings.effective_sfx_volume()));
+    }
+    *ranks_heard = gamestate.rank_times.len();
+}
+
+// a chime over the accepted sound, a tone higher for each word in the combo
+const MAX_COMBO_SEMITONES: u32 = 12;
+
+fn play_combo_sounds(audio: Res<Audio>,
+                     sounds: Res<SoundEffects>,
+                     settings: Res<Settings>,
+                     mut ev_combo_extended: EventReader<ComboExtendedEvent>,
+                     mut ev_combo_ended: EventReader<ComboEndedEvent>,
+                    ) {
+    let playback = PlaybackSettings::ONCE.with_volume(settings.effective_sfx_volume());
+
+    for ev in ev_combo_extended.iter() {
+        let semitones = (2 * ev.words).min(MAX_COMBO_SEMITONES);
+        audio.play_with_settings(sounds.pangram.clone(), playback.clone().with_volume(playback.volume * 0.5).with_speed(semitones_to_speed(semitones as f32)));
+    }
+    for _ in ev_combo_ended.iter() {
+        audio.play_with_settings(sounds.clear.clone(), playback.clone().with_speed(semitones_to_speed(-7.)));
+    }
+}
+
+// the quiet feedback for hovering, erasing and letters strict entry turns away, kept well under
+// the main effects
+const AMBIENT_VOLUME: f32 = 0.4;
+const REFUSED_LETTER_SEMITONES: f32 = -12.;
+
+fn play_ambient_sounds(audio: Res<Audio>,
+                       sounds: Res<SoundEffects>,
+                       settings: Res<Settings>,
+                       gamestate: Res<GameState>,
+                       mut ev_tile_hovered: EventReader<TileHoveredEvent>,
+                       mut ev_guess_erased: EventReader<GuessErasedEvent>,
+                       mut ev_letter_refused: EventReader<LetterRefusedEvent>,
+                    ) {
+    let playback = PlaybackSettings::ONCE.with_volume(settings.effective_sfx_volume() * AMBIENT_VOLUME);
+
+    for ev in ev_tile_hovered.iter() {
+        if settings.hover_sounds {
+            // the required letter clicks a fifth higher so it stands out by ear too
+            let speed = if ev.letter == gamestate.required_letter { semitones_to_speed(7.) } else { 1. };
+            audio.play_with_settings(sounds.hover.clone(), playback.clone().with_speed(speed));
+        }
+    }
+
+    for ev in ev_guess_erased.iter() {
+        if settings.erase_sounds {
+            let sound = if ev.whole_word { sounds.clear.clone() } else { sounds.erase.clone() };
+            audio.play_with_settings(sound, playback.clone());
+        }
+    }
+
+    // a low buzz, an octave under the rejected word sound
+    for _ in ev_letter_refused.iter() {
+        audio.play_with_settings(sounds.rejected.clone(), playback.clone().with_speed(semitones_to_speed(REFUSED_LETTER_SEMITONES)));
+    }
+}
+
+#[derive(Component)]
+struct MuteButton {}
+
+#[derive(Resource)]
+struct MuteIcons {
+    on: Handle<Image>,
+    muted: Handle<Image>,
+}
+
+fn spawn_mute_button(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let icons = MuteIcons {
+        on: asset_server.load("icons/speaker_on.png"),
+        muted: asset_server.load("icons/speaker_muted.png"),
+    };
+
+    commands.spawn(ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(40.), Val::Px(40.)),
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(12.), top: Val::Px(12.), ..default() },
+            ..default()
+        },
+        image: UiImage(icons.on.clone()),
+        background_color: Color::WHITE.into(), // tints the icon, so white keeps it as drawn
+        ..default()
+    }).insert(MuteButton {}).insert(Focusable { state: AppState::Playing, order: 40 });
+
+    commands.insert_resource(icons);
+}
+
+fn toggle_mute(keys: Res<Input<KeyCode>>,
+               buttons: Query<&Interaction, (Changed<Interaction>, With<MuteButton>)>,
+               mut settings: ResMut<Settings>,
+            ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let mut toggled = ctrl && keys.just_pressed(KeyCode::M);
+
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Clicked {
+            toggled = true;
+        }
+    }
+
+    if toggled {
+        settings.muted = !settings.muted;
+    }
+}
+
+fn show_mute_icon(settings: Res<Settings>,
+                  icons: Res<MuteIcons>,
+                  mut buttons: Query<&mut UiImage, With<MuteButton>>,
+                ) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for mut image in buttons.iter_mut() {
+        image.0 = if settings.muted { icons.muted.clone() } else { icons.on.clone() };
+    }
+}
@@ -0,0 +1,254 @@
+use clap::Parser;
+use rand::seq::SliceRandom;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::{fs, mem, process, thread};
+use wordgame::protocol::NetMessage;
+use wordgame::puzzle_code::PuzzleCode;
+use wordgame::words::{alphabet_index, bits_to_letters, check_word, find_answers, word_to_bits, FoundWord, GameState, RejectReason, WordList};
+
+// A server for online games that stands in for the host, so neither player's machine has to be
+// reachable and neither one can make up words. Both players --join the relay, which pairs them
+// into a room in the order they connect, shows itself as a ready host in the lobby, and picks the
+// board once they're both ready. Every co-op word is checked against the relay's own word list
+// before it's passed on, so a word the other side sees was really found. Races are passed on as
+// they are, only the scores go over the wire there. A rematch needs a host, so after a race both
+// players join again.
+#[derive(Parser, Debug)]
+#[command(version, about = "Hosts online games of Spelling Tiles for players joining from anywhere")]
+struct Args {
+    /// Port to listen for players on
+    #[arg(long, default_value_t = 7878)]
+    port: u16,
+
+    /// Word list to check guesses against, the same one the players load, the built in list if not given
+    #[arg(long, value_name = "PATH")]
+    words: Option<PathBuf>,
+}
+
+const ROOM_SIZE: usize = 2;
+const MAX_WORD_LENGTH: usize = 32; // longer than any board's answers, anything past it isn't worth looking up
+
+struct Room {
+    number: usize,
+    clients: Vec<TcpStream>,
+    ready: [bool; ROOM_SIZE],
+    gamestate: Option<GameState>, // None until both are ready
+}
+
+impl Room {
+    // a client that's gone shows up on its reader thread, this only logs
+    fn send(&mut self, client: usize, message: &NetMessage) {
+        let mut line = ron::to_string(message).unwrap();
+        line.push('\n');
+        if let Err(err) = self.clients[client].write_all(line.as_bytes()) {
+            eprintln!("room {}: could not send {:?} to player {}: {}", self.number, message, client + 1, err);
+        }
+    }
+
+    fn send_others(&mut self, from: usize, message: &NetMessage) {
+        for client in (0..self.clients.len()).filter(|client| *client != from) {
+            self.send(client, message);
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let contents = match &args.words {
+        Some(path) => fs::read_to_string(path).unwrap_or_else(|err| exit_with(format!("Could not read word list {}: {}", path.display(), err))),
+        None => String::from(include_str!("../../assets/words/dict_words.txt")),
+    };
+    let wordlist = Arc::new(WordList::parse(&contents));
+    if wordlist.potential_pangrams.is_empty() {
+        exit_with(String::from("The word list has no seven letter words to make boards from"));
+    }
+    println!("{} words loaded, {} possible boards", wordlist.all_valid_words.len(), wordlist.potential_pangrams.len());
+
+    let listener = TcpListener::bind(("0.0.0.0", args.port)).unwrap_or_else(|err| exit_with(format!("Could not listen on port {}: {}", args.port, err)));
+    println!("waiting for players on port {}", args.port);
+
+    let mut waiting = Vec::new();
+    let mut rooms = 0;
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("could not accept a player: {}", err);
+                continue;
+            }
+        };
+        println!("player connected from {}", stream.peer_addr().map_or(String::from("?"), |addr| addr.to_string()));
+        waiting.push(stream);
+
+        if waiting.len() == ROOM_SIZE {
+            rooms += 1;
+            let room = Room { number: rooms, clients: mem::take(&mut waiting), ready: [false; ROOM_SIZE], gamestate: None };
+            let wordlist = wordlist.clone();
+            thread::spawn(move || run_room(room, &wordlist));
+        }
+    }
+}
+
+fn exit_with(message: String) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+// each client is read on a thread of its own, and everything they send comes through here in order
+fn run_room(mut room: Room, wordlist: &WordList) {
+    println!("room {}: opened", room.number);
+    let (sender, receiver) = mpsc::channel();
+    for (client, stream) in room.clients.iter().enumerate() {
+        let reader = BufReader::new(stream.try_clone().expect("could not share the connection with its reader"));
+        let sender = sender.clone();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                match ron::from_str::<NetMessage>(&line) {
+                    Ok(message) => {
+                        sender.send((client, Some(message))).ok();
+                    }
+                    Err(err) => eprintln!("unreadable message {:?}: {}", line, err),
+                }
+            }
+            sender.send((client, None)).ok();
+        });
+    }
+
+    for client in 0..ROOM_SIZE {
+        room.send(client, &NetMessage::Ready { ready: true });
+    }
+
+    while let Ok((client, message)) = receiver.recv() {
+        match message {
+            Some(NetMessage::Ready { ready }) if room.gamestate.is_none() => {
+                room.ready[client] = ready;
+                if room.ready == [true; ROOM_SIZE] {
+                    start_game(&mut room, wordlist);
+                }
+            }
+            Some(NetMessage::Found { word, .. }) => accept_word(&mut room, client, &word, wordlist),
+            Some(message @ (NetMessage::Score { .. } | NetMessage::Finished { .. })) => room.send_others(client, &message),
+            Some(_) => {}
+            // the game can't go on with one player, dropping the room closes the other connection
+            None => {
+                println!("room {}: player {} left, closing", room.number, client + 1);
+                break;
+            }
+        }
+    }
+}
+
+fn start_game(room: &mut Room, wordlist: &WordList) {
+    let mut rng = rand::thread_rng();
+    let mut gamestate = GameState { player_count: ROOM_SIZE, ..GameState::default() };
+    // checked for at startup, a room can't be the first to find the list has none
+    let target_pangram = match wordlist.potential_pangrams.choose(&mut rng) {
+        Some(pangram) => pangram,
+        None => return,
+    };
+    gamestate.target_bits = word_to_bits(target_pangram);
+    gamestate.target_string = bits_to_letters(gamestate.target_bits);
+    gamestate.required_letter = *gamestate.target_string.as_bytes().choose(&mut rng).unwrap() as char;
+    gamestate.required_bit = 1 << alphabet_index(gamestate.required_letter as u8);
+    gamestate.answers = find_answers(wordlist, gamestate.target_bits, gamestate.required_bit, gamestate.min_word_length, None);
+
+    // no mode in the code, each player keeps the one their lobby settled on
+    let board = PuzzleCode { letter_bits: gamestate.target_bits, required_letter: gamestate.required_letter, mode: None };
+    let code = match board.encode() {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("room {}: could not start on {}: {}", room.number, gamestate.target_string, message);
+            return;
+        }
+    };
+    println!("room {}: starting on {}, {} answers", room.number, code, gamestate.answers.len());
+    room.gamestate = Some(gamestate);
+
+    for client in 0..ROOM_SIZE {
+        room.send(client, &NetMessage::Start);
+        room.send(client, &NetMessage::Board { code: code.clone() });
+    }
+}
+
+// both players joined, so each is player two on their own screen and the other is player one
+fn accept_word(room: &mut Room, client: usize, word: &str, wordlist: &WordList) {
+    let gamestate = match room.gamestate.as_mut() {
+        Some(gamestate) => gamestate,
+        None => return,
+    };
+
+    let word = match check_guess(word, gamestate, wordlist) {
+        Ok(word) => word,
+        Err(reason) => {
+            let shown: String = word.chars().take(MAX_WORD_LENGTH).collect();
+            println!("room {}: player {} sent {:?}, which {}", room.number, client + 1, shown, reason);
+            return;
+        }
+    };
+
+    gamestate.correct_words.push(FoundWord { word: word.clone(), player: client });
+    room.send_others(client, &NetMessage::Found { word, player: 0 });
+}
+
+// a client can send anything at all, so the word's looked at before check_word gets it
+fn check_guess(word: &str, gamestate: &GameState, wordlist: &WordList) -> Result<String, RejectReason> {
+    if word.len() > MAX_WORD_LENGTH {
+        return Err(RejectReason::Custom("is too long"));
+    }
+    if !word.bytes().all(|c| c.is_ascii_alphabetic()) {
+        return Err(RejectReason::NotLetters);
+    }
+
+    let word = word.to_ascii_lowercase();
+    check_word(&word, gamestate, wordlist)?;
+    Ok(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room_board() -> (GameState, WordList) {
+        let wordlist = WordList::parse("plan\nplant\nplanted");
+        let target_bits = word_to_bits("planted");
+        let required_bit = 1 << alphabet_index(b'a');
+        let gamestate = GameState {
+            target_string: bits_to_letters(target_bits),
+            target_bits,
+            required_letter: 'A',
+            required_bit,
+            answers: find_answers(&wordlist, target_bits, required_bit, 4, None),
+            player_count: ROOM_SIZE,
+            ..GameState::default()
+        };
+        (gamestate, wordlist)
+    }
+
+    #[test]
+    fn guesses_are_lowercased() {
+        let (gamestate, wordlist) = room_board();
+        assert_eq!(check_guess("PLANT", &gamestate, &wordlist), Ok(String::from("plant")));
+    }
+
+    #[test]
+    fn non_letter_guesses_are_turned_down() {
+        let (gamestate, wordlist) = room_board();
+        assert_eq!(check_guess("ab1", &gamestate, &wordlist), Err(RejectReason::NotLetters));
+        assert_eq!(check_guess("plánt", &gamestate, &wordlist), Err(RejectReason::NotLetters));
+        assert_eq!(check_guess("plan\n", &gamestate, &wordlist), Err(RejectReason::NotLetters));
+    }
+
+    #[test]
+    fn long_guesses_are_turned_down() {
+        let (gamestate, wordlist) = room_board();
+        assert_eq!(check_guess(&"a".repeat(MAX_WORD_LENGTH + 1), &gamestate, &wordlist), Err(RejectReason::Custom("is too long")));
+    }
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::{setup_goals, AppState, BoardEntity, GameMode, SpeakBoardEvent, TileLabel, TILE_RADIUS};
+
+// Blind mode: the tiles are blank and the letters are only ever heard, read out once when the
+// board starts and again on Ctrl+R. The reading comes from the screen reader, so the mode is
+// refused (at launch, or played as classic for a code) unless that's built in and has a voice.
+// Nothing on screen or in the sounds gives a letter away either: any letter can be typed, and
+// the guess isn't greyed where it leaves the board.
+pub struct BlindPlugin;
+
+impl Plugin for BlindPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(start_blind.after(setup_goals))
+                .with_system(spawn_blind_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(hide_letters)
+                .with_system(repeat_letters));
+    }
+}
+
+// put in by whatever can read the letters out
+#[derive(Resource)]
+pub struct LetterVoice {}
+
+#[derive(Component)]
+struct BlindHud {}
+
+fn start_blind(mode: Res<GameMode>, mut ev_speak_board: EventWriter<SpeakBoardEvent>) {
+    if *mode == GameMode::Blind {
+        ev_speak_board.send(SpeakBoardEvent {});
+    }
+}
+
+fn spawn_blind_hud(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if *mode != GameMode::Blind {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("Blind mode, listen for the letters\nCtrl+R to hear them again", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(BlindHud {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
+
+// the labels are spawned with the tiles, so they're caught here once they exist
+fn hide_letters(mode: Res<GameMode>, mut labels: Query<&mut Visibility, With<TileLabel>>) {
+    if *mode != GameMode::Blind {
+        return;
+    }
+
+    for mut visibility in labels.iter_mut() {
+        if visibility.is_visible {
+            visibility.is_visible = false;
+        }
+    }
+}
+
+fn repeat_letters(keys: Res<Input<KeyCode>>, mode: Res<GameMode>, mut ev_speak_board: EventWriter<SpeakBoardEvent>) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if *mode == GameMode::Blind && ctrl && keys.just_pressed(KeyCode::R) {
+        ev_speak_board.send(SpeakBoardEvent {});
+    }
+}
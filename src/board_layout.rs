@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::entity::ShapeBundle;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// How the seven tiles sit on the board, picked in the settings and used from the next board on:
+// the hexagon flower, the six letters on a ring of round tiles around the required one, or two
+// rows of square tiles with the required letter in the middle of the top row. Clicks and touches
+// find the nearest tile to them, so every layout keeps its tiles a tile's width apart. The mega
+// board's nineteen tiles always go in two rings of hexagons, whatever's picked.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BoardLayout {
+    #[default]
+    Flower,
+    Circle,
+    Grid,
+    TwoRings, // only for the mega board, see mega.rs
+}
+
+pub const ALL_BOARD_LAYOUTS: [BoardLayout; 3] = [BoardLayout::Flower, BoardLayout::Circle, BoardLayout::Grid];
+
+const RING_DISTANCE: f32 = 2.2; // tile radii from the middle of the board, for the circle
+const ROUND_TILE_SIZE: f32 = 0.9;
+const SQUARE_TILE_SIZE: f32 = 1.7; // across, in tile radii
+
+impl BoardLayout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoardLayout::Flower => "Flower",
+            BoardLayout::Circle => "Circle",
+            BoardLayout::Grid => "Grid",
+            BoardLayout::TwoRings => "Two rings",
+        }
+    }
+
+    // the next layout along for the settings menu, wrapping round
+    pub fn step(&self, direction: f32) -> BoardLayout {
+        let index = ALL_BOARD_LAYOUTS.iter().position(|layout| layout == self).unwrap_or(0);
+        let count = ALL_BOARD_LAYOUTS.len();
+        ALL_BOARD_LAYOUTS[if direction < 0. { (index + count - 1) % count } else { (index + 1) % count }]
+    }
+
+    // where a tile goes from the middle of the board, index 0 is the required letter
+    pub fn tile_offset(&self, index: usize, radius: f32) -> Vec3 {
+        if index == 0 && *self != BoardLayout::Grid {
+            return Vec3::ZERO;
+        }
+
+        match self {
+            BoardLayout::Flower => around(index - 1, 2. * radius),
+            // the outer ring goes corner, side, corner, round the inner one
+            BoardLayout::TwoRings if index > 6 => {
+                let place = index - 7;
+                let corner = around(place / 2, 4. * radius);
+                if place.is_multiple_of(2) { corner } else { (corner + around(place / 2 + 1, 4. * radius)) / 2. }
+            }
+            BoardLayout::TwoRings => around(index - 1, 2. * radius),
+            BoardLayout::Circle => around(index - 1, RING_DISTANCE * radius),
+            // the required letter and two more on top, four underneath
+            BoardLayout::Grid => match index {
+                0 => Vec3::new(0., radius, 0.),
+                1 => Vec3::new(-2. * radius, radius, 0.),
+                2 => Vec3::new(2. * radius, radius, 0.),
+                _ => Vec3::new((2. * (index - 3) as f32 - 3.) * radius, -radius, 0.),
+            },
+        }
+    }
+
+    pub fn tile_bundle(&self, radius: f32, draw_mode: DrawMode, transform: Transform) -> ShapeBundle {
+        match self {
+            BoardLayout::Flower | BoardLayout::TwoRings => GeometryBuilder::build_as(&shapes::RegularPolygon {
+                sides: 6,
+                feature: shapes::RegularPolygonFeature::Radius(radius),
+                ..shapes::RegularPolygon::default()
+            }, draw_mode, transform),
+            BoardLayout::Circle => GeometryBuilder::build_as(&shapes::Circle { radius: ROUND_TILE_SIZE * radius, center: Vec2::ZERO }, draw_mode, transform),
+            BoardLayout::Grid => GeometryBuilder::build_as(&shapes::Rectangle {
+                extents: Vec2::splat(SQUARE_TILE_SIZE * radius),
+                origin: RectangleOrigin::Center,
+            }, draw_mode, transform),
+        }
+    }
+}
+
+// six places round the middle, the first straight up
+fn around(place: usize, distance: f32) -> Vec3 {
+    let angle = place as f32 * std::f32::consts::TAU / 6.;
+    Vec3::new(angle.sin() * distance, angle.cos() * distance, 0.)
+}
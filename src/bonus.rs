@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use wordgame::words::{GameState, WordList};
+
+use crate::celebration::{check_genius, GeniusReachedEvent};
+use crate::focus::Focusable;
+use crate::layout::Layout;
+use crate::{guess_word, setup_goals, wrong_word_hint, AppState, BoardEntity, GameMode, GameRng, HintText, WordAcceptedEvent, WordRejectedEvent};
+
+// Giving up on a board (Ctrl+G or the button) starts a quick bonus round: a few of the answers that
+// weren't found come up scrambled one at a time, and unscrambling one earns half its points.
+// Reaching Genius offers the same round for the words still left, the button turning into it.
+// Guesses go through the usual guess line, just checked against the one word being unscrambled.
+// A word is skipped after three misses, and Ctrl+G again skips the rest of the round. Only for
+// boards played alone, the other modes have their own endings.
+pub struct BonusPlugin;
+
+impl Plugin for BonusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BonusRound>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_give_up_button.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(offer_bonus_round.after(check_genius))
+                .with_system(give_up.before(guess_word))
+                .with_system(score_bonus_words.after(guess_word))
+                .with_system(show_bonus_round.after(score_bonus_words).after(wrong_word_hint)))
+            .add_system_set(SystemSet::on_exit(AppState::Playing).with_system(end_bonus_round));
+    }
+}
+
+const BONUS_WORDS: usize = 5;
+const BONUS_TRIES: u32 = 3;
+
+#[derive(Resource, Default)]
+pub struct BonusRound {
+    pub active: bool,
+    words: Vec<String>,
+    scrambled: Vec<String>,
+    current: usize,
+    misses: u32,
+    points: u32,
+}
+
+impl BonusRound {
+    fn finished(&self) -> bool {
+        self.active && self.current >= self.words.len()
+    }
+
+    // what guess_word checks against instead of the dictionary while the round is on, nothing once it's over
+    pub fn word_list(&self) -> Option<WordList> {
+        if !self.active {
+            return None;
+        }
+
+        let all_valid_words = self.words.get(self.current).cloned().into_iter().collect();
+        Some(WordList { all_valid_words, potential_pangrams: Vec::new() })
+    }
+
+    fn next_word(&mut self) {
+        self.current += 1;
+        self.misses = 0;
+    }
+}
+
+#[derive(Component)]
+struct GiveUpButton {}
+
+#[derive(Component)]
+struct GiveUpLabel {}
+
+fn bonus_allowed(mode: GameMode) -> bool {
+    matches!(mode, GameMode::Classic | GameMode::Daily)
+}
+
+// anagrams that happen to come out as the word itself are shuffled again
+fn scramble(word: &str, rng: &mut GameRng) -> String {
+    let mut letters: Vec<char> = word.to_ascii_uppercase().chars().collect();
+    for _ in 0..10 {
+        letters.shuffle(&mut rng.0);
+        if letters.iter().collect::<String>() != word.to_ascii_uppercase() {
+            break;
+        }
+    }
+    letters.iter().map(|letter| letter.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+fn spawn_give_up_button(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if !bonus_allowed(*mode) {
+        return;
+    }
+
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+
+    // above the leaderboard button of daily boards
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { right: Val::Px(12.), bottom: Val::Px(12. + layout.button_row_height()), ..default() },
+            padding: layout.button_padding(),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(GiveUpButton {}).insert(Focusable { state: AppState::Playing, order: 35 }).with_children(|button| {
+        button.spawn(TextBundle::from_section("Give up", button_style.clone())).insert(GiveUpLabel {});
+    });
+}
+
+// after the genius card's been asked for, the line's there under it once it's closed
+fn offer_bonus_round(mut ev_genius_reached: EventReader<GeniusReachedEvent>,
+                     mode: Res<GameMode>,
+                     gamestate: Res<GameState>,
+                     bonus: Res<BonusRound>,
+                     mut labels: Query<&mut Text, (With<GiveUpLabel>, Without<HintText>)>,
+                     mut hints: Query<&mut Text, (With<HintText>, Without<GiveUpLabel>)>,
+                    ) {
+    if ev_genius_reached.iter().count() == 0 || !bonus_allowed(*mode) || bonus.active {
+        return;
+    }
+
+    let left = gamestate.answers.iter().filter(|answer| !gamestate.is_found(answer)).count();
+    if left == 0 {
+        return;
+    }
+
+    info!("offering a bonus round, {} words left", left);
+    for mut text in labels.iter_mut() {
+        text.sections[0].value = String::from("Bonus round");
+    }
+    for mut text in hints.iter_mut() {
+        text.sections[0].value = format!("Genius! Ctrl+G for a bonus round of the {} words left, or keep going", left);
+    }
+}
+
+fn give_up(mut keys: ResMut<Input<KeyCode>>,
+           buttons: Query<&Interaction, (Changed<Interaction>, With<GiveUpButton>)>,
+           mode: Res<GameMode>,
+           gamestate: Res<GameState>,
+           mut game_rng: ResMut<GameRng>,
+           mut bonus: ResMut<BonusRound>,
+           mut state: ResMut<State<AppState>>,
+        ) {
+    if !bonus_allowed(*mode) {
+        return;
+    }
+
+    // once it's over Enter moves on, a daily board only comes round once a day though
+    if bonus.finished() && *mode == GameMode::Classic && keys.just_pressed(KeyCode::Return) {
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.set(AppState::NewBoard) {
+            debug!("no new board, the state is already changing: {:?}", err);
+        }
+        return;
+    }
+
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let pressed = (ctrl && keys.just_pressed(KeyCode::G)) || buttons.iter().any(|interaction| *interaction == Interaction::Clicked);
+    if !pressed {
+        return;
+    }
+
+    if bonus.active {
+        let count = bonus.words.len();
+        bonus.current = count;
+        return;
+    }
+
+    let mut unfound: Vec<String> = gamestate.answers.iter().filter(|answer| !gamestate.is_found(answer)).cloned().collect();
+    // with every word found there's nothing to unscramble, so a classic board just moves on
+    if unfound.is_empty() {
+        info!("giving up with every word found, no bonus round");
+        if *mode == GameMode::Classic {
+            if let Err(err) = state.set(AppState::NewBoard) {
+                debug!("no new board, the state is already changing: {:?}", err);
+            }
+        }
+        return;
+    }
+    unfound.shuffle(&mut game_rng.0);
+    unfound.truncate(BONUS_WORDS);
+    info!("giving up, bonus round with {} words", unfound.len());
+
+    let scrambled = unfound.iter().map(|word| scramble(word, &mut game_rng)).collect();
+    *bonus = BonusRound { active: true, words: unfound, scrambled, ..default() };
+}
+
+fn score_bonus_words(mut bonus: ResMut<BonusRound>,
+                     mut gamestate: ResMut<GameState>,
+                     mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                     mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                    ) {
+    let accepted: Vec<String> = ev_word_accepted.iter().map(|ev| ev.word.to_ascii_lowercase()).collect();
+    let misses = ev_word_rejected.iter().count();
+    if !bonus.active {
+        return;
+    }
+
+    for word in accepted {
+        let points = gamestate.scoring.word_score(&word).div_ceil(2);
+        bonus.points += points;
+        gamestate.bonus_score += points;
+        bonus.next_word();
+    }
+    for _ in 0..misses {
+        if bonus.finished() {
+            continue;
+        }
+        bonus.misses += 1;
+        if bonus.misses >= BONUS_TRIES {
+            bonus.next_word();
+        }
+    }
+}
+
+fn show_bonus_round(bonus: Res<BonusRound>, mode: Res<GameMode>, mut hints: Query<&mut Text, With<HintText>>) {
+    if !bonus.is_changed() || !bonus.active {
+        return;
+    }
+
+    let line = if bonus.finished() {
+        let next = if *mode == GameMode::Classic { ", Enter for a new board" } else { "" };
+        format!("Bonus round over, +{} points{}", bonus.points, next)
+    }
+    else {
+        let tries = BONUS_TRIES - bonus.misses;
+        format!("Bonus {}/{}: unscramble {} ({} {} left)", bonus.current + 1, bonus.words.len(), bonus.scrambled[bonus.current], tries, if tries == 1 { "try" } else { "tries" })
+    };
+    for mut text in hints.iter_mut() {
+        text.sections[0].value = line.clone();
+    }
+}
+
+fn end_bonus_round(mut bonus: ResMut<BonusRound>) {
+    *bonus = BonusRound::default();
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::settings::Settings;
+use crate::WordAcceptedEvent;
+
+// A pangram gives the whole view a short, small shake. The camera is nudged off where it was by a
+// shrinking random offset each frame and put back exactly when the shake is over, so nothing else
+// has to know. Reduced motion leaves the camera still.
+pub struct CameraShakePlugin;
+
+impl Plugin for CameraShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(start_shake)
+            .add_system(shake_camera.after(start_shake));
+    }
+}
+
+const SHAKE_SECONDS: f32 = 0.3;
+const SHAKE_DISTANCE: f32 = 6.; // at the start, easing down to nothing
+
+#[derive(Component)]
+struct CameraShake {
+    seconds_left: f32,
+    offset: Vec2, // applied last frame, taken off again before the next
+}
+
+// a pangram during a shake starts it over, keeping the offset so it still comes off
+fn start_shake(mut commands: Commands,
+               settings: Res<Settings>,
+               mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+               mut cameras: Query<(Entity, Option<&mut CameraShake>), With<Camera2d>>,
+            ) {
+    if !ev_word_accepted.iter().any(|ev| ev.pangram) || settings.reduced_motion {
+        return;
+    }
+
+    for (camera, shake) in cameras.iter_mut() {
+        match shake {
+            Some(mut shake) => shake.seconds_left = SHAKE_SECONDS,
+            None => {
+                commands.entity(camera).insert(CameraShake { seconds_left: SHAKE_SECONDS, offset: Vec2::ZERO });
+            }
+        }
+    }
+}
+
+fn shake_camera(mut commands: Commands, time: Res<Time>, mut cameras: Query<(Entity, &mut CameraShake, &mut Transform)>) {
+    let mut rng = rand::thread_rng();
+    for (camera, mut shake, mut transform) in cameras.iter_mut() {
+        transform.translation -= shake.offset.extend(0.);
+        shake.seconds_left -= time.delta_seconds();
+        if shake.seconds_left <= 0. {
+            commands.entity(camera).remove::<CameraShake>();
+            continue;
+        }
+
+        let distance = SHAKE_DISTANCE * shake.seconds_left / SHAKE_SECONDS;
+        shake.offset = Vec2::new(rng.gen_range(-1f32..1.), rng.gen_range(-1f32..1.)) * distance;
+        transform.translation += shake.offset.extend(0.);
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use wordgame::words::GameState;
+
+use crate::achievements::AchievementUnlockedEvent;
+use crate::settings::Settings;
+use crate::{BoardRefreshedEvent, WordAcceptedEvent, WordRejectedEvent};
+
+// An optional running log of what just happened, in words, down the left hand side: accepted
+// and rejected guesses, pangrams and achievements. For anyone who misses the sounds and the
+// squishes, or just wants to check what the last guess did. Turned on in the settings.
+pub struct CaptionsPlugin;
+
+impl Plugin for CaptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CaptionLog>()
+            .add_startup_system(spawn_caption_panel)
+            .add_system(log_captions)
+            .add_system(show_captions.after(log_captions));
+    }
+}
+
+const CAPTION_LINES: usize = 6;
+
+#[derive(Resource, Default)]
+struct CaptionLog {
+    lines: VecDeque<String>,
+}
+
+impl CaptionLog {
+    fn push(&mut self, line: String) {
+        if self.lines.len() == CAPTION_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+}
+
+#[derive(Component)]
+struct CaptionPanel {}
+
+fn spawn_caption_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::WHITE };
+
+    commands.spawn(TextBundle::from_section("", style).with_style(Style {
+        position_type: PositionType::Absolute,
+        // the gap left of the board, under the HUD and above the buttons, in either layout
+        position: UiRect { left: Val::Px(12.), top: Val::Px(200.), ..default() },
+        max_size: Size::new(Val::Px(220.), Val::Auto),
+        ..default()
+    })).insert(CaptionPanel {});
+}
+
+fn log_captions(mut log: ResMut<CaptionLog>,
+                gamestate: Res<GameState>,
+                mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                mut ev_unlocked: EventReader<AchievementUnlockedEvent>,
+                mut ev_board_refreshed: EventReader<BoardRefreshedEvent>,
+            ) {
+    for ev in ev_word_accepted.iter() {
+        log.push(format!("{} accepted, +{}", ev.word, gamestate.word_points(&ev.word.to_ascii_lowercase())));
+        if ev.pangram {
+            log.push(String::from("Pangram!"));
+        }
+    }
+    for ev in ev_word_rejected.iter() {
+        log.push(format!("{} {}", ev.word, ev.reason));
+    }
+    for ev in ev_unlocked.iter() {
+        log.push(format!("Achievement: {}", ev.achievement.name()));
+    }
+    for _ in ev_board_refreshed.iter() {
+        log.push(String::from("New board"));
+    }
+}
+
+// newest at the bottom, older lines fading out above it
+fn show_captions(log: Res<CaptionLog>, settings: Res<Settings>, mut panels: Query<(&mut Text, &mut Visibility), With<CaptionPanel>>) {
+    if !log.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    for (mut text, mut visibility) in panels.iter_mut() {
+        visibility.is_visible = settings.captions;
+
+        let style = text.sections[0].style.clone();
+        let count = log.lines.len();
+        text.sections = log.lines.iter().enumerate().map(|(i, line)| {
+            let mut style = style.clone();
+            // high contrast keeps every line at full strength
+            let age = (count - 1 - i) as f32;
+            let alpha = if settings.high_contrast { 1. } else { 1. - 0.1 * age };
+            style.color.set_a(alpha);
+            TextSection::new(if i + 1 < count { format!("{}\n", line) } else { line.clone() }, style)
+        }).collect();
+        if text.sections.is_empty() {
+            text.sections = vec![TextSection::new("", style)];
+        }
+    }
+}
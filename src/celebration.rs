@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use wordgame::words::{GameState, RANKS};
+
+use crate::palette::Overlay;
+use crate::{record_correct_words, setup_goals, AppState, BoardRefreshedEvent, GameMode};
+
+// Reaching Genius, the top rank, gets a card over the board to say so, closed again with Esc,
+// Enter or a click, and with the particles feature some fireworks around it (see fireworks.rs).
+// It only happens once a board, and the race carries on without the card since the other player
+// isn't waiting.
+pub struct CelebrationPlugin;
+
+impl Plugin for CelebrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Celebrated>()
+            .add_event::<GeniusReachedEvent>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(reset_celebration.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(reset_celebration_on_refresh)
+                .with_system(check_genius.after(record_correct_words).after(reset_celebration_on_refresh)))
+            .add_system_set(SystemSet::on_enter(AppState::Celebration).with_system(spawn_card))
+            .add_system_set(SystemSet::on_update(AppState::Celebration).with_system(close_card))
+            .add_system_set(SystemSet::on_exit(AppState::Celebration).with_system(despawn_card));
+    }
+}
+
+pub struct GeniusReachedEvent {}
+
+// whether this board has had its celebration already
+#[derive(Resource, Default)]
+pub struct Celebrated(bool);
+
+#[derive(Component)]
+struct CelebrationRoot {}
+
+#[derive(Component)]
+struct CelebrationCard {}
+
+fn reset_celebration(mut celebrated: ResMut<Celebrated>) {
+    celebrated.0 = false;
+}
+
+fn reset_celebration_on_refresh(mut ev_board_refreshed: EventReader<BoardRefreshedEvent>, mut celebrated: ResMut<Celebrated>) {
+    if ev_board_refreshed.iter().count() > 0 {
+        celebrated.0 = false;
+    }
+}
+
+pub fn check_genius(gamestate: Res<GameState>,
+                mode: Res<GameMode>,
+                mut celebrated: ResMut<Celebrated>,
+                mut state: ResMut<State<AppState>>,
+                mut ev_genius_reached: EventWriter<GeniusReachedEvent>,
+            ) {
+    if celebrated.0 || !gamestate.is_changed() || gamestate.correct_words.is_empty() || gamestate.rank() < RANKS.len() - 1 {
+        return;
+    }
+
+    celebrated.0 = true;
+    info!("genius reached with {} points", gamestate.score());
+    ev_genius_reached.send(GeniusReachedEvent {});
+
+    if *mode == GameMode::Race {
+        return;
+    }
+    // the modes that move on by themselves are ordered before this, so one moving on at the same
+    // moment wins and the fireworks still go off
+    if let Err(err) = state.push(AppState::Celebration) {
+        debug!("no genius card, the state is already changing: {:?}", err);
+    }
+}
+
+fn spawn_card(mut commands: Commands, asset_server: Res<AssetServer>, gamestate: Res<GameState>) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 72., color: Color::GOLD };
+    let body_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 36., color: Color::WHITE };
+    let help_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::GRAY };
+
+    let found = format!("{} words for {} points, the top rank on this board", gamestate.correct_words.len(), gamestate.score());
+
+    // only the card is dimmed, so the fireworks show around it
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(CelebrationRoot {}).with_children(|parent| {
+        parent.spawn(ButtonBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(24.)),
+                max_size: Size::new(Val::Percent(80.), Val::Auto),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.85).into(),
+            ..default()
+        }).insert(CelebrationCard {}).insert(Overlay {}).with_children(|card| {
+            card.spawn(TextBundle::from_section("Genius!", title_style));
+            card.spawn(TextBundle::from_section(found, body_style).with_style(Style {
+                margin: UiRect::all(Val::Px(12.)),
+                ..default()
+            }));
+            card.spawn(TextBundle::from_section("Keep going for the rest of the words, Esc, Enter or click to close", help_style));
+        });
+    });
+}
+
+fn close_card(mut keys: ResMut<Input<KeyCode>>,
+              cards: Query<&Interaction, (Changed<Interaction>, With<CelebrationCard>)>,
+              mut state: ResMut<State<AppState>>,
+            ) {
+    let clicked = cards.iter().any(|interaction| *interaction == Interaction::Clicked);
+    if clicked || keys.any_just_pressed([KeyCode::Escape, KeyCode::Return]) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.pop() {
+            debug!("not closing the genius card, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn despawn_card(mut commands: Commands, screens: Query<Entity, With<CelebrationRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::puzzle_code::PuzzleCode;
+
+// Someone's result on a board, sent to a friend to try and beat. Saved as a small RON file,
+// or pasted in the short form "4M18-0Z0G.42.13" (code, score, word count) which drops the name.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Challenge {
+    pub code: String, // a puzzle code, kept as text so the file reads sensibly
+    pub name: String,
+    pub score: u32,
+    pub words: usize,
+}
+
+const SHORT_FORM_NAME: &str = "Your friend";
+
+impl Challenge {
+    pub fn puzzle(&self) -> Result<PuzzleCode, String> {
+        PuzzleCode::decode(&self.code)
+    }
+
+    pub fn to_short(&self) -> String {
+        format!("{}.{}.{}", self.code, self.score, self.words)
+    }
+
+    pub fn to_file(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap()
+    }
+
+    // takes either the file contents or the short form
+    pub fn parse(text: &str) -> Result<Challenge, String> {
+        let text = text.trim();
+        let challenge = if text.starts_with('(') {
+            ron::from_str(text).map_err(|err| format!("That challenge file is damaged: {}", err))?
+        }
+        else {
+            let parts: Vec<&str> = text.split('.').collect();
+            if parts.len() != 3 {
+                return Err(String::from("Challenges look like a puzzle code, a score and a word count with dots between"));
+            }
+
+            Challenge {
+                code: String::from(parts[0]),
+                name: String::from(SHORT_FORM_NAME),
+                score: parts[1].parse().map_err(|_| format!("'{}' isn't a score", parts[1]))?,
+                words: parts[2].parse().map_err(|_| format!("'{}' isn't a word count", parts[2]))?,
+            }
+        };
+
+        challenge.puzzle()?;
+        Ok(challenge)
+    }
+}
@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+#[cfg(not(feature = "web"))]
+use directories::UserDirs;
+use std::{fs, path::Path};
+use wordgame::challenge::Challenge;
+use wordgame::words::GameState;
+
+use crate::code_menu::GameClipboard;
+use crate::focus::Focusable;
+use crate::layout::Layout;
+use crate::palette::Overlay;
+use crate::settings::Settings;
+use crate::{current_puzzle_code, setup_goals, AppState, BoardEntity, GameMode};
+
+// "Challenge a friend" saves your score on this board to a file in Downloads and copies its
+// short form. Opening one (--challenge FILE, or pasting the short form as a puzzle code) plays
+// the same board with their score to beat, and "Compare" shows how the two results stack up.
+pub struct ChallengeMenuPlugin;
+
+impl Plugin for ChallengeMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveChallenge>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_challenge_buttons.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(click_challenge_buttons))
+            .add_system_set(SystemSet::on_enter(AppState::ChallengeResult).with_system(spawn_challenge_result))
+            .add_system_set(SystemSet::on_update(AppState::ChallengeResult).with_system(close_challenge_result))
+            .add_system_set(SystemSet::on_exit(AppState::ChallengeResult).with_system(despawn_challenge_result));
+    }
+}
+
+// the challenge being played against, only while its board is up
+#[derive(Resource, Default)]
+pub struct ActiveChallenge(pub Option<Challenge>);
+
+pub fn load_challenge(path: &Path) -> Result<Challenge, String> {
+    let contents = fs::read_to_string(path).map_err(|err| format!("Could not read challenge {}: {}", path.display(), err))?;
+    Challenge::parse(&contents)
+}
+
+// gives back where it was saved
+#[cfg(not(feature = "web"))]
+fn save_challenge(challenge: &Challenge) -> Result<String, String> {
+    let dir = UserDirs::new()
+        .and_then(|dirs| dirs.download_dir().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let path = dir.join(format!("challenge-{}.ron", challenge.code));
+
+    fs::write(&path, challenge.to_file()).map_err(|err| format!("Could not save {}: {}", path.display(), err))?;
+    Ok(path.display().to_string())
+}
+
+// the browser can't write files, so there the short form is all there is to share
+#[cfg(feature = "web")]
+fn save_challenge(_challenge: &Challenge) -> Result<String, String> {
+    Err(String::from("Challenge files can't be saved from the browser"))
+}
+
+#[derive(Component)]
+struct ChallengeButton {}
+
+#[derive(Component)]
+struct ChallengeLabel {}
+
+#[derive(Component)]
+struct CompareButton {}
+
+#[derive(Component)]
+struct ChallengeResultRoot {}
+
+fn spawn_challenge_buttons(mut commands: Commands,
+                           asset_server: Res<AssetServer>,
+                           gamestate: Res<GameState>,
+                           layout: Res<Layout>,
+                           mut active_challenge: ResMut<ActiveChallenge>,
+                        ) {
+    // a challenge only lasts for its own board
+    let on_challenge_board = active_challenge.0.as_ref()
+        .and_then(|challenge| challenge.puzzle().ok())
+        .is_some_and(|code| code.letter_bits == gamestate.target_bits && code.required_letter == gamestate.required_letter);
+    if !on_challenge_board {
+        active_challenge.0 = None;
+    }
+
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+
+    // sits just above the puzzle code buttons
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(12.), bottom: Val::Px(12. + layout.button_row_height()), ..default() },
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        ..default()
+    }).insert(BoardEntity {}).with_children(|parent| {
+        let button = ButtonBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(4.)),
+                padding: layout.button_padding(),
+                ..default()
+            },
+            background_color: Color::ALICE_BLUE.into(),
+            ..default()
+        };
+
+        parent.spawn(button.clone()).insert(ChallengeButton {}).insert(Focusable { state: AppState::Playing, order: 20 }).with_children(|button| {
+            button.spawn(TextBundle::from_section("Challenge a friend", button_style.clone())).insert(ChallengeLabel {});
+        });
+
+        if let Some(challenge) = &active_challenge.0 {
+            parent.spawn(button).insert(CompareButton {}).insert(Focusable { state: AppState::Playing, order: 21 }).with_children(|button| {
+                button.spawn(TextBundle::from_section(format!("Compare with {}'s {} points", challenge.name, challenge.score), button_style.clone()));
+            });
+        }
+    });
+}
+
+fn click_challenge_buttons(challenge_buttons: Query<&Interaction, (Changed<Interaction>, With<ChallengeButton>)>,
+                           compare_buttons: Query<&Interaction, (Changed<Interaction>, With<CompareButton>)>,
+                           mut labels: Query<&mut Text, With<ChallengeLabel>>,
+                           gamestate: Res<GameState>,
+                           mode: Res<GameMode>,
+                           settings: Res<Settings>,
+                           mut clipboard: NonSendMut<GameClipboard>,
+                           mut state: ResMut<State<AppState>>,
+                        ) {
+    if challenge_buttons.iter().any(|interaction| *interaction == Interaction::Clicked) {
+        let label = match current_puzzle_code(&gamestate, *mode).encode() {
+            Ok(code) => send_challenge(code, &gamestate, &settings, &mut clipboard),
+            Err(message) => message,
+        };
+
+        for mut text in labels.iter_mut() {
+            text.sections[0].value = label.clone();
+        }
+    }
+
+    if compare_buttons.iter().any(|interaction| *interaction == Interaction::Clicked) {
+        if let Err(err) = state.push(AppState::ChallengeResult) {
+            debug!("not opening the challenge result, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+// saved and copied, the label says which of those worked
+fn send_challenge(code: String, gamestate: &GameState, settings: &Settings, clipboard: &mut GameClipboard) -> String {
+    let challenge = Challenge {
+        code,
+        name: settings.player_name.clone(),
+        score: gamestate.score(),
+        words: gamestate.correct_words.len(),
+    };
+
+    let saved = save_challenge(&challenge);
+    let copied = clipboard.copy(&challenge.to_short());
+
+    match (saved, copied) {
+        (Ok(path), Ok(())) => format!("Saved {} and copied {}", path, challenge.to_short()),
+        (Ok(path), Err(_)) => format!("Saved {}", path),
+        (Err(message), Ok(())) => {
+            warn!("{}", message);
+            format!("Copied {}", challenge.to_short())
+        }
+        (Err(message), Err(_)) => {
+            warn!("{}", message);
+            format!("Challenge code {}", challenge.to_short())
+        }
+    }
+}
+
+fn spawn_challenge_result(mut commands: Commands,
+                          asset_server: Res<AssetServer>,
+                          active_challenge: Res<ActiveChallenge>,
+                          gamestate: Res<GameState>,
+                        ) {
+    let challenge = match &active_challenge.0 {
+        Some(challenge) => challenge,
+        None => return,
+    };
+
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let body_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 36., color: Color::WHITE };
+
+    let score = gamestate.score();
+    let outcome = if score > challenge.score {
+        format!("You beat {}!", challenge.name)
+    }
+    else if score == challenge.score {
+        format!("Level with {}", challenge.name)
+    }
+    else {
+        format!("{} more points to beat {}", challenge.score - score, challenge.name)
+    };
+    let comparison = format!("{}: {} points, {} words\nYou: {} points, {} words\n\nEsc or Enter to go back to the board",
+                             challenge.name, challenge.score, challenge.words, score, gamestate.correct_words.len());
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(ChallengeResultRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(outcome, title_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section(comparison, body_style));
+    });
+}
+
+fn close_challenge_result(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::Return]) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.pop() {
+            debug!("not closing the challenge result, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn despawn_challenge_result(mut commands: Commands, screens: Query<Entity, With<ChallengeResultRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use wordgame::words::{is_pangram, GameState};
+
+use crate::{AppState, WordAcceptedEvent};
+
+// Hotkeys for getting to the end of a board without solving it, only compiled with the "cheats"
+// feature, for screenshots and for trying out the screens that come once words are found:
+//   F5  finds one answer, the longest left
+//   F6  finds every answer left
+//   F7  opens the summary
+// Words go through the usual accepted event, so the score, rank and celebrations follow as in play.
+pub struct CheatsPlugin;
+
+impl Plugin for CheatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Playing).with_system(cheat_keys));
+    }
+}
+
+fn cheat_keys(keys: Res<Input<KeyCode>>,
+              gamestate: Res<GameState>,
+              mut state: ResMut<State<AppState>>,
+              mut ev_word_accepted: EventWriter<WordAcceptedEvent>,
+            ) {
+    let mut left: Vec<&String> = gamestate.answers.iter().filter(|answer| !gamestate.is_found(answer)).collect();
+    left.sort_by_key(|answer| std::cmp::Reverse(answer.len()));
+
+    let found = if keys.just_pressed(KeyCode::F5) { 1 } else if keys.just_pressed(KeyCode::F6) { left.len() } else { 0 };
+    for answer in left.into_iter().take(found) {
+        info!("cheat: found {}", answer);
+        ev_word_accepted.send(WordAcceptedEvent { word: answer.clone(), pangram: is_pangram(answer) });
+    }
+
+    if keys.just_pressed(KeyCode::F7) {
+        if let Err(err) = state.push(AppState::Summary) {
+            debug!("not jumping to the summary, the state is already changing: {:?}", err);
+        }
+    }
+}
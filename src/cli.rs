@@ -0,0 +1,190 @@
+use bevy::log::{Level, LogPlugin};
+use clap::Parser;
+use std::path::PathBuf;
+use wordgame::puzzle_code::PuzzleCode;
+use wordgame::words::{rank_by_name, RANKS};
+
+#[cfg(feature = "online")]
+use crate::lobby::room_address;
+#[cfg(feature = "online")]
+use crate::net::{NetRole, DEFAULT_PORT};
+#[cfg(feature = "online")]
+use crate::spectate::SPECTATOR_PORT_OFFSET;
+#[cfg(feature = "status_server")]
+use crate::status_server::DEFAULT_STATUS_PORT;
+use crate::streamer::StreamerBackground;
+use crate::versus::BotLevel;
+use crate::GameMode;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Find as many words as you can from seven letter tiles")]
+pub struct Args {
+    /// Seed for picking the puzzle, random if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Word list to load instead of the built in dictionary, one word per line
+    #[arg(long, value_name = "FILE")]
+    pub dictionary: Option<PathBuf>,
+
+    /// Puzzle code to play instead of a random board, as shown by the copy button in game
+    #[arg(long)]
+    pub code: Option<PuzzleCode>,
+
+    /// Challenge file from a friend, to play their board and try to beat their score
+    #[arg(long, value_name = "FILE", conflicts_with = "code")]
+    pub challenge: Option<PathBuf>,
+
+    /// Puzzle in the JSON interchange format to play, with its own answers if it lists them
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["code", "challenge"])]
+    pub import: Option<PathBuf>,
+
+    /// Writes each board out in the JSON interchange format as it starts
+    #[arg(long, value_name = "FILE")]
+    pub export: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t = GameMode::Classic)]
+    pub mode: GameMode,
+
+    /// Boards to clear in a marathon run
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    pub marathon_boards: u32,
+
+    /// Rank to reach on each marathon board before moving on to the next
+    #[arg(long, value_name = "RANK", default_value = "solid", value_parser = parse_rank)]
+    pub marathon_rank: usize,
+
+    /// Words to find on each time attack board before it's swapped for a new one, a pangram always swaps it
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    pub time_attack_words: u32,
+
+    /// Rank to reach on each endless board before it moves on to the next
+    #[arg(long, value_name = "RANK", default_value = "good", value_parser = parse_rank)]
+    pub endless_rank: usize,
+
+    /// Seconds to lock out guessing after three wrong guesses in a row, in the daily, race, marathon and time attack modes
+    #[arg(long, value_name = "SECONDS")]
+    pub guess_cooldown: Option<f32>,
+
+    /// Words found in a row build up a points multiplier, any rejected guess starts it over
+    #[arg(long)]
+    pub streak_multiplier: bool,
+
+    /// Finding a word within 30 seconds of the last keeps a combo going for extra points
+    #[arg(long)]
+    pub combo: bool,
+
+    /// Race your best run on a board seen before, shown under the clock as it would have gone
+    #[arg(long)]
+    pub ghost: bool,
+
+    /// Show a speedrun timer with a split at each rank, Ctrl+X saves the splits for LiveSplit
+    #[arg(long)]
+    pub speedrun: bool,
+
+    /// Modes where rarer words earn bonus points, half as much again for uncommon words and double for rare ones
+    #[arg(long, value_enum, value_name = "MODES", value_delimiter = ',')]
+    pub rarity_scoring: Vec<GameMode>,
+
+    /// Minutes between swaps of the required letter for another on the board
+    #[arg(long, value_name = "MINUTES")]
+    pub rotate_required: Option<f32>,
+
+    /// How often the versus mode bot finds a word, and how many of the answers it knows
+    #[arg(long, value_enum, default_value_t = BotLevel::Medium)]
+    pub bot_level: BotLevel,
+
+    #[arg(long, default_value_t = 1280.)]
+    pub width: f32,
+
+    #[arg(long, default_value_t = 720.)]
+    pub height: f32,
+
+    /// Streamer mode, the board on a plain green or transparent background with no buttons and no spoilers in the log
+    #[arg(long, value_enum, value_name = "BACKGROUND", num_args = 0..=1, default_missing_value = "green")]
+    pub streamer: Option<StreamerBackground>,
+
+    /// Host a co-op or race game for another player to join
+    #[cfg(feature = "online")]
+    #[arg(long)]
+    pub host: bool,
+
+    /// Join a co-op or race game someone else is hosting, by room code, address or host name
+    #[cfg(feature = "online")]
+    #[arg(long, value_name = "ADDR", conflicts_with = "host")]
+    pub join: Option<String>,
+
+    /// Watch a co-op or race game someone else is hosting without playing, by room code, address or host name
+    #[cfg(feature = "online")]
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["host", "join"])]
+    pub spectate: Option<String>,
+
+    /// Port to host on, or to join when ADDR doesn't give one, spectators watch on the port after it
+    #[cfg(feature = "online")]
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    pub port: u16,
+
+    /// Take guesses from this Twitch channel's chat, crediting whoever sent each word found
+    #[cfg(feature = "online")]
+    #[arg(long, value_name = "CHANNEL")]
+    pub twitch_channel: Option<String>,
+
+    /// Port on this machine to serve the board's progress on as JSON, for stream overlays
+    #[cfg(feature = "status_server")]
+    #[arg(long, default_value_t = DEFAULT_STATUS_PORT)]
+    pub status_port: u16,
+
+    /// Play this many boards with a greedy solver and print statistics about them, then exit
+    #[arg(long, value_name = "BOARDS")]
+    pub simulate: Option<usize>,
+
+    /// Run without a window, just generate the puzzle and exit
+    #[arg(long)]
+    pub headless: bool,
+
+    #[arg(long, default_value_t = Level::INFO)]
+    pub log_level: Level,
+
+    /// Show debug output from the game itself, including the puzzle solution
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+impl Args {
+    pub fn log_plugin(&self) -> LogPlugin {
+        let mut filter = String::from("wgpu=error");
+        if self.verbose {
+            filter.push_str(",wordgame=debug");
+        }
+        // the answers shouldn't end up on stream from a console in the capture
+        if self.streamer.is_some() {
+            filter.push_str(",wordgame::spoiler=off");
+        }
+
+        LogPlugin { level: self.log_level, filter }
+    }
+
+    #[cfg(feature = "online")]
+    pub fn net_role(&self) -> Option<NetRole> {
+        match (&self.join, &self.spectate) {
+            (Some(code), _) if room_address(code).is_some() => Some(NetRole::Join { addr: room_address(code)?.to_string() }),
+            (Some(addr), _) if addr.contains(':') => Some(NetRole::Join { addr: addr.clone() }),
+            (Some(addr), _) => Some(NetRole::Join { addr: format!("{}:{}", addr, self.port) }),
+            (None, Some(code)) if room_address(code).is_some() => {
+                let addr = room_address(code)?;
+                Some(NetRole::Spectate { addr: format!("{}:{}", addr.ip(), addr.port() + SPECTATOR_PORT_OFFSET) })
+            }
+            (None, Some(addr)) if addr.contains(':') => Some(NetRole::Spectate { addr: addr.clone() }),
+            (None, Some(addr)) => Some(NetRole::Spectate { addr: format!("{}:{}", addr, self.port + SPECTATOR_PORT_OFFSET) }),
+            (None, None) if self.host => Some(NetRole::Host { port: self.port }),
+            (None, None) => None,
+        }
+    }
+}
+
+fn parse_rank(name: &str) -> Result<usize, String> {
+    rank_by_name(name).ok_or_else(|| {
+        let names: Vec<&str> = RANKS.iter().map(|(rank, _)| *rank).collect();
+        format!("not a rank, expected one of: {}", names.join(", "))
+    })
+}
@@ -0,0 +1,316 @@
+use bevy::prelude::*;
+#[cfg(not(any(feature = "web", target_os = "android", target_os = "ios")))]
+use arboard::Clipboard;
+use wordgame::challenge::Challenge;
+use wordgame::puzzle_code::PuzzleCode;
+use wordgame::words::{GameState, WordList};
+
+use crate::challenge_menu::ActiveChallenge;
+use crate::focus::Focusable;
+use crate::layout::Layout;
+use crate::palette::Overlay;
+use crate::{current_puzzle_code, setup_goals, AppState, BoardEntity, GameMode, NextPuzzle};
+
+// Sharing boards: a button (or Ctrl+C) copies the current board's puzzle code, and another
+// (or Ctrl+V, which pastes straight in) opens a screen to type one in and play that board.
+// The same screen takes the short form of a friend's challenge.
+pub struct CodeMenuPlugin;
+
+impl Plugin for CodeMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_non_send_resource(GameClipboard::default())
+            .init_resource::<CodeEntry>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_code_buttons.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(share_puzzle_code))
+            .add_system_set(SystemSet::on_enter(AppState::PuzzleCode).with_system(spawn_code_entry))
+            .add_system_set(SystemSet::on_update(AppState::PuzzleCode)
+                .with_system(type_puzzle_code)
+                .with_system(show_code_entry.after(type_puzzle_code)))
+            .add_system_set(SystemSet::on_exit(AppState::PuzzleCode).with_system(despawn_code_entry));
+    }
+}
+
+// kept for the whole run, on Linux copied text is only offered to other programs while it lives
+#[cfg(not(any(feature = "web", target_os = "android", target_os = "ios")))]
+#[derive(Default)]
+pub struct GameClipboard(Option<Clipboard>);
+
+#[cfg(not(any(feature = "web", target_os = "android", target_os = "ios")))]
+impl GameClipboard {
+    fn get(&mut self) -> Result<&mut Clipboard, String> {
+        if self.0.is_none() {
+            self.0 = Some(Clipboard::new().map_err(|err| err.to_string())?);
+        }
+        Ok(self.0.as_mut().unwrap())
+    }
+
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        self.get()?.set_text(text).map_err(|err| err.to_string())
+    }
+
+    pub fn paste(&mut self) -> Result<String, String> {
+        self.get()?.get_text().map_err(|err| err.to_string())
+    }
+}
+
+// the browser only hands out the clipboard asynchronously and after asking, and phones aren't
+// covered by arboard, so codes are typed in there
+#[cfg(any(feature = "web", target_os = "android", target_os = "ios"))]
+#[derive(Default)]
+pub struct GameClipboard {}
+
+#[cfg(any(feature = "web", target_os = "android", target_os = "ios"))]
+impl GameClipboard {
+    pub fn copy(&mut self, _text: &str) -> Result<(), String> {
+        Err(String::from("No clipboard on this platform"))
+    }
+
+    pub fn paste(&mut self) -> Result<String, String> {
+        Err(String::from("No clipboard on this platform"))
+    }
+}
+
+#[derive(Resource, Default)]
+struct CodeEntry {
+    text: String,
+    error: Option<String>,
+}
+
+const MAX_ENTRY_LENGTH: usize = 20;
+
+#[derive(Component)]
+struct CopyCodeButton {}
+
+#[derive(Component)]
+struct CopyCodeLabel {}
+
+#[derive(Component)]
+struct EnterCodeButton {}
+
+#[derive(Component)]
+struct CodeEntryRoot {}
+
+#[derive(Component)]
+struct CodeEntryText {}
+
+#[derive(Component)]
+struct CodeEntryError {}
+
+fn spawn_code_buttons(mut commands: Commands,
+                      asset_server: Res<AssetServer>,
+                      gamestate: Res<GameState>,
+                      mode: Res<GameMode>,
+                      layout: Res<Layout>,
+                    ) {
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+    // a board without a code only gets the button for entering one
+    let code = current_puzzle_code(&gamestate, *mode).encode().ok();
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(12.), bottom: Val::Px(12.), ..default() },
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        ..default()
+    }).insert(BoardEntity {}).with_children(|parent| {
+        let button = ButtonBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(4.)),
+                padding: layout.button_padding(),
+                ..default()
+            },
+            background_color: Color::ALICE_BLUE.into(),
+            ..default()
+        };
+
+        if let Some(code) = code {
+            parent.spawn(button.clone()).insert(CopyCodeButton {}).insert(Focusable { state: AppState::Playing, order: 10 }).with_children(|button| {
+                button.spawn(TextBundle::from_section(format!("Copy puzzle code {}", code), button_style.clone())).insert(CopyCodeLabel {});
+            });
+        }
+        parent.spawn(button).insert(EnterCodeButton {}).insert(Focusable { state: AppState::Playing, order: 11 }).with_children(|button| {
+            button.spawn(TextBundle::from_section("Enter puzzle code", button_style.clone()));
+        });
+    });
+}
+
+fn share_puzzle_code(keys: Res<Input<KeyCode>>,
+                     gamestate: Res<GameState>,
+                     mode: Res<GameMode>,
+                     mut clipboard: NonSendMut<GameClipboard>,
+                     mut entry: ResMut<CodeEntry>,
+                     mut state: ResMut<State<AppState>>,
+                     copy_buttons: Query<&Interaction, (Changed<Interaction>, With<CopyCodeButton>)>,
+                     enter_buttons: Query<&Interaction, (Changed<Interaction>, With<EnterCodeButton>)>,
+                     mut copy_labels: Query<&mut Text, With<CopyCodeLabel>>,
+                    ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let copy = (ctrl && keys.just_pressed(KeyCode::C)) || copy_buttons.iter().any(|interaction| *interaction == Interaction::Clicked);
+    let paste = ctrl && keys.just_pressed(KeyCode::V);
+    let enter = paste || enter_buttons.iter().any(|interaction| *interaction == Interaction::Clicked);
+
+    if copy {
+        let label = match current_puzzle_code(&gamestate, *mode).encode() {
+            Ok(code) => match clipboard.copy(&code) {
+                Ok(()) => format!("Copied {}", code),
+                Err(err) => {
+                    warn!("could not copy puzzle code: {}", err);
+                    format!("Puzzle code {}", code)
+                }
+            },
+            Err(message) => message,
+        };
+
+        for mut text in copy_labels.iter_mut() {
+            text.sections[0].value = label.clone();
+        }
+    }
+
+    if enter {
+        if let Err(err) = state.push(AppState::PuzzleCode) {
+            debug!("not entering a puzzle code, the state is already changing: {:?}", err);
+            return;
+        }
+        *entry = CodeEntry::default();
+        if paste {
+            match clipboard.paste() {
+                Ok(text) => entry.text = text.trim().chars().take(MAX_ENTRY_LENGTH).collect(),
+                Err(err) => entry.error = Some(format!("Nothing to paste: {}", err)),
+            }
+        }
+    }
+}
+
+fn spawn_code_entry(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let entry_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 80., color: Color::CYAN };
+    let error_style = TextStyle { font: font.clone(), font_size: 30., color: Color::ORANGE_RED };
+    let help_style = TextStyle { font: font.clone(), font_size: 24., color: Color::GRAY };
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(CodeEntryRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Enter puzzle code", title_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+
+        parent.spawn(TextBundle::from_section("_", entry_style.clone())).insert(CodeEntryText {});
+
+        parent.spawn(TextBundle::from_section("", error_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(12.)),
+            ..default()
+        })).insert(CodeEntryError {});
+
+        parent.spawn(TextBundle::from_section("A puzzle or challenge code, Enter to play it, Ctrl+V to paste, Esc to go back", help_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+// checks everything up front so a bad code leaves the current board running
+fn validate_code(text: &str, wordlist: &WordList) -> Result<(PuzzleCode, Option<Challenge>), String> {
+    // only challenges have dots in them
+    let challenge = if text.contains('.') { Some(Challenge::parse(text)?) } else { None };
+    let code = match &challenge {
+        Some(challenge) => challenge.puzzle()?,
+        None => PuzzleCode::decode(text)?,
+    };
+    code.check_playable(wordlist)?;
+
+    if code.mode.is_some_and(|mode| GameMode::from_code_index(mode).is_none()) {
+        return Err(String::from("That puzzle code is for a mode this version of the game doesn't have"));
+    }
+
+    Ok((code, challenge))
+}
+
+fn type_puzzle_code(mut keys: ResMut<Input<KeyCode>>,
+                    mut char_evr: EventReader<ReceivedCharacter>,
+                    mut clipboard: NonSendMut<GameClipboard>,
+                    mut entry: ResMut<CodeEntry>,
+                    mut next_puzzle: ResMut<NextPuzzle>,
+                    mut active_challenge: ResMut<ActiveChallenge>,
+                    mut state: ResMut<State<AppState>>,
+                    wordlist: Res<WordList>,
+                ) {
+    for ev in char_evr.iter() {
+        if (ev.char.is_ascii_alphanumeric() || ev.char == '-' || ev.char == '.') && entry.text.len() < MAX_ENTRY_LENGTH {
+            entry.text.push(ev.char.to_ascii_uppercase());
+            entry.error = None;
+        }
+    }
+
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if ctrl && keys.just_pressed(KeyCode::V) {
+        if let Ok(text) = clipboard.paste() {
+            entry.text = text.trim().chars().take(MAX_ENTRY_LENGTH).collect();
+            entry.error = None;
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        entry.text.pop();
+        entry.error = None;
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        // the board would otherwise take the same press as a guess once it starts
+        keys.clear_just_pressed(KeyCode::Return);
+
+        match validate_code(&entry.text, &wordlist) {
+            Ok((code, challenge)) => match state.replace(AppState::Playing) {
+                Ok(()) => {
+                    info!("starting board from puzzle code {}", entry.text);
+                    next_puzzle.0 = Some(code);
+                    active_challenge.0 = challenge;
+                }
+                Err(err) => debug!("not starting the code's board, the state is already changing: {:?}", err),
+            },
+            Err(message) => entry.error = Some(message),
+        }
+    }
+    else if keys.just_pressed(KeyCode::Escape) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        if let Err(err) = state.pop() {
+            debug!("not leaving the puzzle code, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn show_code_entry(entry: Res<CodeEntry>,
+                   mut entry_text: Query<&mut Text, (With<CodeEntryText>, Without<CodeEntryError>)>,
+                   mut error_text: Query<&mut Text, (With<CodeEntryError>, Without<CodeEntryText>)>,
+                ) {
+    if !entry.is_changed() {
+        return;
+    }
+
+    for mut text in entry_text.iter_mut() {
+        text.sections[0].value = if entry.text.is_empty() { String::from("_") } else { entry.text.clone() };
+    }
+
+    for mut text in error_text.iter_mut() {
+        text.sections[0].value = entry.error.clone().unwrap_or_default();
+    }
+}
+
+fn despawn_code_entry(mut commands: Commands, screens: Query<Entity, With<CodeEntryRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use wordgame::words::GameState;
+
+use crate::bonus::BonusRound;
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::{record_correct_words, setup_goals, AppState, BoardEntity, WordAcceptedEvent, TILE_RADIUS};
+
+// The arcade rule from --combo: finding a word within COMBO_SECONDS of the last one keeps a combo
+// going, and each word in a combo after the first earns a point more than the one before, up to
+// MAX_COMBO_POINTS. The time left drains out of a bar under the HUD (under the streak, when that's
+// on too), and keeping or losing a combo has its own sound, see audio.rs. The clock only runs on
+// the board, menus don't cost any of it.
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            .add_event::<ComboExtendedEvent>()
+            .add_event::<ComboEndedEvent>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(reset_combo.after(setup_goals))
+                .with_system(spawn_combo_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(score_combo.after(record_correct_words))
+                .with_system(tick_combo.after(score_combo))
+                .with_system(show_combo.after(tick_combo)));
+    }
+}
+
+const COMBO_SECONDS: f32 = 30.;
+const MAX_COMBO_POINTS: u32 = 5;
+const BAR_WIDTH: f32 = 2.5 * TILE_RADIUS;
+const BAR_HEIGHT: f32 = 12.;
+
+// from the command line
+#[derive(Resource)]
+pub struct ComboRules {
+    pub enabled: bool,
+}
+
+#[derive(Resource, Default)]
+struct Combo {
+    words: u32, // in this combo, 0 when there isn't one
+    seconds_left: f32,
+}
+
+impl Combo {
+    // the extra points for the next word, if it's found in time
+    fn next_points(&self) -> u32 {
+        self.words.min(MAX_COMBO_POINTS)
+    }
+}
+
+pub struct ComboExtendedEvent {
+    #[cfg(feature = "audio")] // only the chime's pitch needs it
+    pub words: u32,
+}
+
+pub struct ComboEndedEvent {}
+
+#[derive(Component)]
+struct ComboText {}
+
+#[derive(Component)]
+struct ComboBar {}
+
+fn reset_combo(mut combo: ResMut<Combo>) {
+    *combo = Combo::default();
+}
+
+// one anchor for the text and bar, dropped below where the streak readout sits
+fn spawn_combo_hud(mut commands: Commands, asset_server: Res<AssetServer>, rules: Res<ComboRules>, layout: Res<Layout>) {
+    if !rules.enabled {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(SpatialBundle::from_transform(layout.transform(LayoutSlot::Scoring)))
+        .insert(BoardEntity {})
+        .insert(LayoutAnchor { slot: LayoutSlot::Scoring })
+        .with_children(|hud| {
+            hud.spawn(Text2dBundle{
+                text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+                transform: Transform::from_translation(Vec3::new(0., -0.7 * TILE_RADIUS, 0.)),
+                ..default()
+            }).insert(ComboText {});
+            hud.spawn(SpriteBundle {
+                sprite: Sprite { color: Color::DARK_GRAY, custom_size: Some(Vec2::new(BAR_WIDTH, BAR_HEIGHT)), ..default() },
+                transform: Transform::from_translation(Vec3::new(0., -1.5 * TILE_RADIUS, 0.)),
+                ..default()
+            });
+            hud.spawn(SpriteBundle {
+                sprite: Sprite { color: Color::ALICE_BLUE, custom_size: Some(Vec2::new(BAR_WIDTH, BAR_HEIGHT)), anchor: Anchor::CenterLeft, ..default() },
+                transform: Transform::from_translation(Vec3::new(-BAR_WIDTH / 2., -1.5 * TILE_RADIUS, 0.1)).with_scale(Vec3::new(0., 1., 1.)),
+                ..default()
+            }).insert(ComboBar {});
+        });
+}
+
+fn score_combo(rules: Res<ComboRules>,
+               bonus: Res<BonusRound>,
+               mut combo: ResMut<Combo>,
+               mut gamestate: ResMut<GameState>,
+               mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+               mut ev_combo_extended: EventWriter<ComboExtendedEvent>,
+            ) {
+    if !rules.enabled || bonus.active {
+        return;
+    }
+
+    for _ in ev_word_accepted.iter() {
+        if combo.words > 0 {
+            gamestate.bonus_score += combo.next_points();
+            ev_combo_extended.send(ComboExtendedEvent {
+                #[cfg(feature = "audio")]
+                words: combo.words + 1,
+            });
+        }
+        combo.words += 1;
+        combo.seconds_left = COMBO_SECONDS;
+    }
+}
+
+fn tick_combo(time: Res<Time>, mut combo: ResMut<Combo>, mut ev_combo_ended: EventWriter<ComboEndedEvent>) {
+    if combo.words == 0 {
+        return;
+    }
+
+    combo.seconds_left -= time.delta_seconds();
+    if combo.seconds_left <= 0. {
+        info!("combo of {} words ran out", combo.words);
+        *combo = Combo::default();
+        ev_combo_ended.send(ComboEndedEvent {});
+    }
+}
+
+fn show_combo(combo: Res<Combo>,
+              mut texts: Query<&mut Text, With<ComboText>>,
+              mut bars: Query<&mut Transform, With<ComboBar>>,
+            ) {
+    let status = match combo.words {
+        0 => String::from("No combo, find a word to start one"),
+        words => format!("Combo {}, +{} for the next word", words, combo.next_points()),
+    };
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+
+    for mut transform in bars.iter_mut() {
+        transform.scale.x = (combo.seconds_left / COMBO_SECONDS).clamp(0., 1.);
+    }
+}
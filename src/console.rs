@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext, EguiSystem};
+use wordgame::words::{is_pangram, is_valid_word, rank_by_name, word_to_bits, FoundWord, GameState, WordList, WordValidators, RANKS};
+
+use crate::{AppState, GameRng, WordAcceptedEvent};
+
+// Developer console, only compiled with the "dev" feature next to the inspector (whose egui it
+// draws with). Backtick opens and closes it, and while it's open the board doesn't see the keys.
+// The commands are for getting a board into the state a new feature needs without playing it
+// there, see HELP. Words are given as found through the usual accepted event, so everything that
+// follows a word (score, rank, sounds) happens as it would in play.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_system_to_stage(CoreStage::PreUpdate, toggle_console.after(EguiSystem::ProcessInput))
+            .add_system(console_window);
+    }
+}
+
+const HELP: [&str; 6] = [
+    "reveal-pangram      lists the board's pangrams",
+    "set-seed N          the boards picked from now on come from seed N",
+    "add-word WORD       adds a word to the word list, and to the answers if it fits the board",
+    "skip-to-rank RANK   finds the best words left until the rank is reached, by name or number",
+    "dump-answers        lists every answer, found ones marked with *",
+    "help                this list",
+];
+const MAX_LINES: usize = 200;
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    lines: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: String) {
+        info!("console: {}", line);
+        self.lines.push(line);
+        if self.lines.len() > MAX_LINES {
+            self.lines.remove(0);
+        }
+    }
+}
+
+// after egui has taken its input for the frame, so the console still gets the keys the board doesn't
+fn toggle_console(mut console: ResMut<Console>,
+                  mut keys: ResMut<Input<KeyCode>>,
+                  mut chars: ResMut<Events<ReceivedCharacter>>,
+                ) {
+    if keys.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+    }
+
+    if console.open {
+        keys.reset_all();
+        chars.clear();
+    }
+}
+
+fn console_window(mut egui_context: ResMut<EguiContext>,
+                  mut console: ResMut<Console>,
+                  state: Res<State<AppState>>,
+                  mut gamestate: ResMut<GameState>,
+                  mut wordlist: ResMut<WordList>,
+                  validators: Res<WordValidators>,
+                  mut rng: ResMut<GameRng>,
+                  mut ev_word_accepted: EventWriter<WordAcceptedEvent>,
+                ) {
+    if !console.open {
+        return;
+    }
+
+    let mut submitted = None;
+    egui::Window::new("Console").show(egui_context.ctx_mut(), |ui| {
+        egui::ScrollArea::vertical().max_height(300.).stick_to_bottom(true).show(ui, |ui| {
+            for line in console.lines.iter() {
+                ui.monospace(line);
+            }
+        });
+
+        let input = ui.add(egui::TextEdit::singleline(&mut console.input).desired_width(f32::INFINITY).font(egui::TextStyle::Monospace));
+        if input.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+            submitted = Some(std::mem::take(&mut console.input));
+        }
+        input.request_focus();
+    });
+
+    // the backtick that closes the console can end up typed into it first
+    let command = match submitted {
+        Some(command) => command.replace('`', ""),
+        None => return,
+    };
+    if command.trim().is_empty() {
+        return;
+    }
+    console.print(format!("> {}", command.trim()));
+
+    let mut words = command.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let argument = words.next();
+    let reply = match (name, argument) {
+        ("help", _) => HELP.iter().map(|line| line.to_string()).collect(),
+        ("reveal-pangram", _) => {
+            let pangrams: Vec<String> = gamestate.answers.iter().filter(|answer| is_pangram(answer)).cloned().collect();
+            vec![if pangrams.is_empty() { String::from("no pangrams on this board") } else { pangrams.join(", ") }]
+        }
+        ("set-seed", Some(seed)) => match seed.parse() {
+            Ok(seed) => {
+                *rng = GameRng::new(Some(seed));
+                vec![format!("seed set to {}, the next board will use it", seed)]
+            }
+            Err(_) => vec![format!("{} is not a seed", seed)],
+        },
+        ("add-word", Some(word)) => add_word(&word.to_ascii_lowercase(), &mut gamestate, &mut wordlist, &validators),
+        ("skip-to-rank", Some(rank)) if *state.current() == AppState::Playing => {
+            let rank = rank.parse().ok().filter(|rank| *rank < RANKS.len()).or_else(|| rank_by_name(rank));
+            match rank {
+                Some(rank) => skip_to_rank(rank, &gamestate, &mut ev_word_accepted),
+                None => vec![String::from("no such rank, they are 0 to 8 or their names with - for spaces")],
+            }
+        }
+        ("skip-to-rank", Some(_)) => vec![String::from("only on the board")],
+        ("dump-answers", _) => gamestate.answers.iter()
+            .map(|answer| format!("{}{} {}", if gamestate.is_found(answer) { "*" } else { " " }, answer, gamestate.word_points(answer)))
+            .collect(),
+        ("set-seed" | "add-word" | "skip-to-rank", None) => vec![format!("{} needs an argument, see help", name)],
+        _ => vec![format!("unknown command {}, see help", name)],
+    };
+    for line in reply {
+        console.print(line);
+    }
+}
+
+// only for the running game, the word list file is left alone
+fn add_word(word: &str, gamestate: &mut GameState, wordlist: &mut WordList, validators: &WordValidators) -> Vec<String> {
+    if !is_valid_word(word) {
+        return vec![format!("{} can't be a word", word)];
+    }
+
+    let mut reply = Vec::new();
+    if wordlist.all_valid_words.iter().any(|listed| listed == word) {
+        reply.push(format!("{} is already in the word list", word));
+    }
+    else {
+        wordlist.all_valid_words.push(word.to_string());
+        if word_to_bits(word).count_ones() == 7 {
+            wordlist.potential_pangrams.push(word.to_string());
+        }
+        reply.push(format!("{} added to the word list", word));
+    }
+
+    if gamestate.answers.iter().any(|answer| answer == word) {
+        return reply;
+    }
+    // curated boards only take their own answers, so the check is made as if the word were one
+    gamestate.answers.push(word.to_string());
+    match validators.check_word(word, gamestate, wordlist) {
+        Ok(_) => reply.push(format!("{} is now an answer on this board", word)),
+        Err(reason) => {
+            gamestate.answers.pop();
+            reply.push(format!("not an answer on this board, it {}", reason));
+        }
+    }
+    reply
+}
+
+fn skip_to_rank(rank: usize, gamestate: &GameState, ev_word_accepted: &mut EventWriter<WordAcceptedEvent>) -> Vec<String> {
+    let mut best_first: Vec<&String> = gamestate.answers.iter().filter(|answer| !gamestate.is_found(answer)).collect();
+    best_first.sort_by_key(|answer| std::cmp::Reverse(gamestate.word_points(answer)));
+
+    // played out on a copy, the real board records them as the events come through
+    let mut played = gamestate.clone();
+    let mut found = 0;
+    for answer in best_first {
+        if played.rank() >= rank {
+            break;
+        }
+        played.correct_words.push(FoundWord { word: answer.clone(), player: played.current_player });
+        ev_word_accepted.send(WordAcceptedEvent { word: answer.clone(), pangram: is_pangram(answer) });
+        found += 1;
+    }
+
+    if played.rank() >= rank {
+        vec![format!("found {} words to reach {}", found, RANKS[rank].0)]
+    }
+    else {
+        vec![format!("found every answer and only reached {}", RANKS[played.rank()].0)]
+    }
+}
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+use crate::{guess_word, setup_goals, wrong_word_hint, AppState, GameMode, HintText, WordAcceptedEvent, WordRejectedEvent};
+
+// The anti brute force rule from --guess-cooldown: three wrong guesses in a row lock out guessing
+// for that many seconds, with the time left counting down in the hint line. Letters can still be
+// typed while it runs, only submitting waits. It's only for the modes played against other people
+// or the clock, practice boards never lock.
+pub struct CooldownPlugin;
+
+impl Plugin for CooldownPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GuessCooldown>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(reset_cooldown.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(count_misses.after(guess_word))
+                .with_system(tick_cooldown.after(count_misses).after(wrong_word_hint)));
+    }
+}
+
+const MISSES_BEFORE_COOLDOWN: u32 = 3;
+
+// from the command line
+#[derive(Resource)]
+pub struct CooldownRules {
+    pub seconds: Option<f32>,
+}
+
+#[derive(Resource, Default)]
+pub struct GuessCooldown {
+    misses: u32, // in a row
+    seconds_left: f32,
+}
+
+impl GuessCooldown {
+    pub fn active(&self) -> bool {
+        self.seconds_left > 0.
+    }
+}
+
+fn competitive(mode: GameMode) -> bool {
+    matches!(mode, GameMode::Daily | GameMode::Race | GameMode::Marathon | GameMode::TimeAttack)
+}
+
+fn reset_cooldown(mut cooldown: ResMut<GuessCooldown>) {
+    *cooldown = GuessCooldown::default();
+}
+
+fn count_misses(rules: Res<CooldownRules>,
+                mode: Res<GameMode>,
+                mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                mut cooldown: ResMut<GuessCooldown>,
+            ) {
+    let seconds = match rules.seconds {
+        Some(seconds) if competitive(*mode) => seconds,
+        _ => return,
+    };
+
+    if ev_word_accepted.iter().count() > 0 {
+        cooldown.misses = 0;
+    }
+    for _ in ev_word_rejected.iter() {
+        cooldown.misses += 1;
+        if cooldown.misses >= MISSES_BEFORE_COOLDOWN {
+            cooldown.misses = 0;
+            cooldown.seconds_left = seconds;
+            info!("{} misses in a row, guessing locked for {} seconds", MISSES_BEFORE_COOLDOWN, seconds);
+        }
+    }
+}
+
+fn tick_cooldown(time: Res<Time>,
+                 settings: Res<Settings>,
+                 mut cooldown: ResMut<GuessCooldown>,
+                 mut hint_text: Query<&mut Text, With<HintText>>,
+                ) {
+    if !cooldown.active() {
+        return;
+    }
+
+    cooldown.seconds_left -= time.delta_seconds();
+    let message = if cooldown.active() {
+        format!("Too many misses, wait {}s to guess again", cooldown.seconds_left.ceil())
+    }
+    else {
+        String::from("Go ahead")
+    };
+
+    for mut text in hint_text.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+            text.sections[0].style.color = settings.colours().rejected();
+        }
+    }
+}
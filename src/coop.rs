@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use wordgame::words::{FoundWord, GameState};
+
+use crate::net::{share_board_on_connect, NetInbox, NetMessage, NetPeer, PeerConnectedEvent};
+use crate::{record_correct_words, setup_goals, AppState, GameMode, HintText, WordAcceptedEvent};
+
+// Both players work on the same board, every accepted word goes into one shared list
+// coloured by whoever found it. Only active when the mode is co-op and someone is connected.
+pub struct CoopPlugin;
+
+impl Plugin for CoopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(share_found_words_on_connect.after(share_board_on_connect))
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(take_player_slot.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(send_found_words.after(record_correct_words))
+                .with_system(receive_found_words));
+    }
+}
+
+// the host's words were found before the other player joined, so they're sent over too
+fn share_found_words_on_connect(mut ev_peer_connected: EventReader<PeerConnectedEvent>,
+                                peer: Option<ResMut<NetPeer>>,
+                                gamestate: Res<GameState>,
+                                mode: Res<GameMode>,
+                            ) {
+    let mut peer = match peer {
+        Some(peer) if *mode == GameMode::Coop => peer,
+        _ => return,
+    };
+
+    for _ev in ev_peer_connected.iter() {
+        for found in gamestate.correct_words.iter() {
+            peer.send(&NetMessage::Found { word: found.word.clone(), player: found.player });
+        }
+    }
+}
+
+// words found here are credited to this side's player
+fn take_player_slot(peer: Option<Res<NetPeer>>, mut gamestate: ResMut<GameState>, mode: Res<GameMode>) {
+    if let (Some(peer), GameMode::Coop) = (peer, *mode) {
+        gamestate.current_player = peer.local_player;
+    }
+}
+
+fn send_found_words(mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                    peer: Option<ResMut<NetPeer>>,
+                    mode: Res<GameMode>,
+                ) {
+    let mut peer = match peer {
+        Some(peer) if *mode == GameMode::Coop => peer,
+        _ => return,
+    };
+
+    for ev in ev_word_accepted.iter() {
+        let player = peer.local_player;
+        peer.send(&NetMessage::Found { word: ev.word.to_ascii_lowercase(), player });
+    }
+}
+
+fn receive_found_words(mut inbox: ResMut<NetInbox>,
+                       mut gamestate: ResMut<GameState>,
+                       mode: Res<GameMode>,
+                       mut hint_text: Query<&mut Text, With<HintText>>,
+                    ) {
+    if *mode != GameMode::Coop {
+        return;
+    }
+
+    for message in inbox.take(|message| matches!(message, NetMessage::Found { .. })) {
+        if let NetMessage::Found { word, player } = message {
+            // both players can get the same word in before hearing about the other's
+            if gamestate.is_found(&word) {
+                continue;
+            }
+
+            for mut text in hint_text.iter_mut() {
+                text.sections[0].value = format!("Player {} found {}", player + 1, word.to_ascii_uppercase());
+            }
+            gamestate.correct_words.push(FoundWord { word, player });
+        }
+    }
+}
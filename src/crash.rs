@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::panic;
+use std::sync::Mutex;
+use wordgame::puzzle_code::PuzzleCode;
+use wordgame::words::{FoundWord, GameState};
+
+use crate::recovery::{RecoveryAction, RecoveryChosenEvent, RecoveryDialog, RecoveryKind, RecoveryQueue};
+use crate::storage;
+use crate::{current_puzzle_code, setup_goals, AppState, BoardRefreshedEvent, GameMode, NextPuzzle, WordAcceptedEvent, WordRejectedEvent};
+
+// When the game panics, a report goes to crash_report.txt next to the settings before it closes:
+// the panic message, the last few things that happened and the board as it was. The board is also
+// autosaved as words are found, and after a crash the next launch offers to put it back, found
+// words and clock included. The autosave is only offered once, whatever's picked.
+pub struct CrashPlugin;
+
+impl Plugin for CrashPlugin {
+    fn build(&self, app: &mut App) {
+        install_panic_hook();
+        app.insert_resource(PendingRestore::load())
+            .init_resource::<RestoredBoard>()
+            .add_startup_system(offer_restore)
+            .add_system(log_recent_events)
+            .add_system(autosave_board)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(restore_found_words.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(apply_restore_choice));
+    }
+}
+
+const REPORT_FILE: &str = "crash_report.txt";
+const AUTOSAVE_FILE: &str = "autosave.ron";
+const RECENT_EVENTS: usize = 30;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct AutoSave {
+    code: String,
+    found: Vec<(String, usize)>, // word and player
+    time_played: f32,
+    crashed: bool, // only set by the panic hook
+}
+
+// what the panic hook has to go on, kept up to date from the systems below
+struct CrashContext {
+    recent: VecDeque<String>,
+    board: String,
+    autosave: Option<AutoSave>,
+}
+
+static CRASH_CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+
+fn with_context(update: impl FnOnce(&mut CrashContext)) {
+    if let Ok(mut context) = CRASH_CONTEXT.lock() {
+        update(context.get_or_insert_with(|| CrashContext { recent: VecDeque::new(), board: String::new(), autosave: None }));
+    }
+}
+
+fn save_autosave(autosave: &AutoSave) {
+    if let Err(message) = storage::write(AUTOSAVE_FILE, &ron::to_string(autosave).unwrap()) {
+        warn!("{}", message);
+    }
+}
+
+// the default hook still prints the panic afterwards, the report is only written alongside it
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // a panic while the context is held can't wait for it
+        let (recent, board) = match CRASH_CONTEXT.try_lock() {
+            Ok(mut context) => match context.as_mut() {
+                Some(context) => {
+                    if let Some(autosave) = context.autosave.as_mut() {
+                        autosave.crashed = true;
+                        save_autosave(autosave);
+                    }
+                    (context.recent.iter().cloned().collect::<Vec<String>>().join("\n"), context.board.clone())
+                }
+                None => (String::new(), String::new()),
+            },
+            Err(_) => (String::from("(not available)"), String::from("(not available)")),
+        };
+
+        let report = format!("Spelling Tiles {} crashed\n\n{}\n\nRecent events:\n{}\n\nBoard:\n{}\n", env!("CARGO_PKG_VERSION"), info, recent, board);
+        match storage::write(REPORT_FILE, &report) {
+            Ok(()) => eprintln!("crash report saved to {}", storage::location(REPORT_FILE)),
+            Err(message) => eprintln!("{}", message),
+        }
+        default_hook(info);
+    }));
+}
+
+// the autosave from a crashed run, until the player has said what to do with it
+#[derive(Resource, Default)]
+struct PendingRestore(Option<AutoSave>);
+
+impl PendingRestore {
+    // a broken autosave is only a board that can't be put back, so it's a warning rather than a dialog
+    fn load() -> PendingRestore {
+        let autosave = storage::read(AUTOSAVE_FILE).and_then(|contents| match contents {
+            Some(contents) => ron::from_str::<AutoSave>(&contents).map(Some).map_err(|err| format!("Autosave {} is corrupt: {}", storage::location(AUTOSAVE_FILE), err)),
+            None => Ok(None),
+        });
+
+        match autosave {
+            Ok(autosave) => PendingRestore(autosave.filter(|autosave| autosave.crashed)),
+            Err(message) => {
+                warn!("{}", message);
+                PendingRestore(None)
+            }
+        }
+    }
+}
+
+// the found words to put back once the restored board is set up
+#[derive(Resource, Default)]
+struct RestoredBoard(Option<AutoSave>);
+
+fn offer_restore(pending: Res<PendingRestore>, mut queue: ResMut<RecoveryQueue>) {
+    let autosave = match pending.0.as_ref() {
+        Some(autosave) => autosave,
+        None => return,
+    };
+
+    // offered once, a second crash before the board is played again writes a new one anyway
+    save_autosave(&AutoSave { crashed: false, ..autosave.clone() });
+    queue.push(RecoveryDialog {
+        kind: RecoveryKind::Crash,
+        message: format!("The game closed unexpectedly last time, with {} words found on board {}. A report was saved to {}.",
+                         autosave.found.len(), autosave.code, storage::location(REPORT_FILE)),
+        actions: vec![RecoveryAction::Restore, RecoveryAction::Continue],
+    });
+}
+
+// once the dialog has closed and the board is back
+fn apply_restore_choice(mut ev_recovery_chosen: EventReader<RecoveryChosenEvent>,
+                        mut pending: ResMut<PendingRestore>,
+                        mut restored: ResMut<RestoredBoard>,
+                        mut next_puzzle: ResMut<NextPuzzle>,
+                        mut state: ResMut<State<AppState>>,
+                        mut restoring: Local<bool>,
+                    ) {
+    for ev in ev_recovery_chosen.iter() {
+        if ev.kind != RecoveryKind::Crash {
+            continue;
+        }
+        let autosave = match pending.0.take() {
+            Some(autosave) => autosave,
+            None => continue,
+        };
+        if ev.action != RecoveryAction::Restore {
+            continue;
+        }
+
+        match PuzzleCode::decode(&autosave.code) {
+            Ok(code) => {
+                info!("restoring board {} with {} words found", autosave.code, autosave.found.len());
+                next_puzzle.0 = Some(code);
+                restored.0 = Some(autosave);
+                *restoring = true;
+            }
+            Err(message) => warn!("Could not restore board {}: {}", autosave.code, message),
+        }
+    }
+
+    // another transition may already be queued this frame, in which case try again next frame
+    if *restoring && state.set(AppState::NewBoard).is_ok() {
+        *restoring = false;
+    }
+}
+
+// only words that are answers on the board it came back as, in case the word list changed since
+fn restore_found_words(mut restored: ResMut<RestoredBoard>, mut gamestate: ResMut<GameState>) {
+    let autosave = match restored.0.take() {
+        Some(autosave) => autosave,
+        None => return,
+    };
+
+    let last_player = gamestate.player_count.max(1) - 1;
+    for (word, player) in autosave.found {
+        if gamestate.answers.contains(&word) && !gamestate.is_found(&word) {
+            gamestate.correct_words.push(FoundWord { word, player: player.min(last_player) });
+        }
+    }
+    gamestate.time_played = autosave.time_played;
+}
+
+fn log_recent_events(state: Res<State<AppState>>,
+                     mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                     mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                     mut ev_board_refreshed: EventReader<BoardRefreshedEvent>,
+                    ) {
+    let mut events = Vec::new();
+    if state.is_changed() {
+        events.push(format!("state {:?}", state.current()));
+    }
+    events.extend(ev_word_accepted.iter().map(|ev| format!("accepted {}", ev.word)));
+    events.extend(ev_word_rejected.iter().map(|ev| format!("rejected {}, {}", ev.word, ev.reason)));
+    events.extend(ev_board_refreshed.iter().map(|_| String::from("board refreshed")));
+    if events.is_empty() {
+        return;
+    }
+
+    with_context(|context| {
+        context.recent.extend(events);
+        while context.recent.len() > RECENT_EVENTS {
+            context.recent.pop_front();
+        }
+    });
+}
+
+// saved as words are found rather than every frame, the clock only counts as of the last word
+fn autosave_board(gamestate: Res<GameState>, mode: Res<GameMode>, mut saved: Local<Option<(String, usize)>>) {
+    if gamestate.target_string.is_empty() {
+        return;
+    }
+
+    // a board without a code, like a mega one, can't be put back
+    let code = match current_puzzle_code(&gamestate, *mode).encode() {
+        Ok(code) => code,
+        Err(_) => return,
+    };
+    let progress = Some((code.clone(), gamestate.correct_words.len()));
+    if *saved == progress {
+        return;
+    }
+    *saved = progress;
+
+    let autosave = AutoSave {
+        code: code.clone(),
+        found: gamestate.correct_words.iter().map(|found| (found.word.clone(), found.player)).collect(),
+        time_played: gamestate.time_played,
+        crashed: false,
+    };
+    save_autosave(&autosave);
+
+    let found: Vec<&str> = gamestate.correct_words.iter().map(|found| found.word.as_str()).collect();
+    let board = format!("{:?} board {}, letters {}, required {}, score {} of {}, {} of {} words found: {}",
+                        *mode, code, gamestate.target_string, gamestate.required_letter, gamestate.score(), gamestate.max_score(),
+                        found.len(), gamestate.answers.len(), found.join(", "));
+    with_context(|context| {
+        context.autosave = Some(autosave);
+        context.board = board;
+    });
+}
@@ -0,0 +1,28 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Days counted from 1970-01-01 in UTC, so everyone moves on to the next daily board at the same moment
+pub fn today() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    now.as_secs() / (24 * 60 * 60)
+}
+
+// the board for a day is picked with this as the seed, so it only changes if the word list does
+pub fn daily_seed(day: u64) -> u64 {
+    day ^ 0x5745_4c4c_4441_4e45
+}
+
+// YYYY-MM-DD, using the days to civil date conversion from Howard Hinnant's date algorithms
+pub fn date_string(day: u64) -> String {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day_of_month = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wordgame::daily;
+use wordgame::words::{GameState, WordList, RANKS};
+
+use crate::focus::Focusable;
+use crate::layout::Layout;
+use crate::palette::Overlay;
+use crate::storage;
+use crate::{format_clock, pick_board, record_rank_times, setup_goals, AppState, BoardEntity, DailyBoard};
+
+// The daily boards played recently, kept in daily.ron: the letters, every answer and the words
+// found. On a daily board the Yesterday button or Ctrl+Y lists the day before's answers with the
+// found ones picked out. A day that wasn't played is picked again from its seed, so its answers
+// can still be looked over.
+pub struct DailyHistoryPlugin;
+
+impl Plugin for DailyHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DailyHistory>()
+            .add_startup_system(load_daily_history)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_yesterday_button.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(record_daily_board.after(record_rank_times))
+                .with_system(open_yesterday))
+            .add_system_set(SystemSet::on_enter(AppState::Yesterday).with_system(spawn_yesterday))
+            .add_system_set(SystemSet::on_update(AppState::Yesterday).with_system(close_yesterday))
+            .add_system_set(SystemSet::on_exit(AppState::Yesterday).with_system(despawn_yesterday))
+            .add_system(save_daily_history);
+    }
+}
+
+pub const HISTORY_FILE: &str = "daily.ron";
+const HISTORY_DAYS: u64 = 30; // older days are dropped as new ones are played
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct DailyRecord {
+    letters: String,
+    required_letter: char,
+    answers: Vec<String>,
+    found: Vec<String>,
+    #[serde(default)]
+    rank_times: Vec<(usize, f32)>, // see GameState, missing from history saved before it was kept
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyHistory {
+    days: BTreeMap<u64, DailyRecord>,
+}
+
+#[cfg(feature = "notifications")]
+impl DailyHistory {
+    // any word found counts, a board opened and left alone doesn't
+    pub fn played(&self, day: u64) -> bool {
+        self.days.get(&day).is_some_and(|record| !record.found.is_empty())
+    }
+}
+
+#[derive(Component)]
+struct YesterdayButton {}
+
+#[derive(Component)]
+struct YesterdayRoot {}
+
+// a broken or unwritable file only loses the history, so it's a warning rather than a dialog
+fn load_daily_history(mut history: ResMut<DailyHistory>) {
+    let loaded = storage::read(HISTORY_FILE).and_then(|contents| match contents {
+        Some(contents) => ron::from_str(&contents).map_err(|err| format!("Daily history file {} is corrupt: {}", storage::location(HISTORY_FILE), err)),
+        None => Ok(DailyHistory::default()),
+    });
+
+    match loaded {
+        Ok(loaded) => *history = loaded,
+        Err(message) => warn!("{}", message),
+    }
+}
+
+fn save_daily_history(history: Res<DailyHistory>) {
+    if !history.is_changed() || history.is_added() {
+        return;
+    }
+
+    let saved = ron::ser::to_string_pretty(&*history, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| storage::write(HISTORY_FILE, &contents));
+    if let Err(message) = saved {
+        warn!("{}", message);
+    }
+}
+
+// only touches the history when the record actually differs, the board changes every frame
+fn record_daily_board(daily_board: Res<DailyBoard>, gamestate: Res<GameState>, mut history: ResMut<DailyHistory>) {
+    let day = match daily_board.0 {
+        Some(day) if gamestate.is_changed() => day,
+        _ => return,
+    };
+
+    let record = DailyRecord {
+        letters: gamestate.target_string.clone(),
+        required_letter: gamestate.required_letter,
+        answers: gamestate.answers.clone(),
+        found: gamestate.correct_words.iter().map(|found| found.word.clone()).collect(),
+        rank_times: gamestate.rank_times.clone(),
+    };
+    if history.days.get(&day) == Some(&record) {
+        return;
+    }
+
+    history.days.insert(day, record);
+    history.days.retain(|recorded, _| recorded + HISTORY_DAYS > day);
+}
+
+fn spawn_yesterday_button(mut commands: Commands, asset_server: Res<AssetServer>, daily_board: Res<DailyBoard>, layout: Res<Layout>) {
+    if daily_board.0.is_none() {
+        return;
+    }
+
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+
+    // above the summary button
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { right: Val::Px(12.), bottom: Val::Px(12. + 3. * layout.button_row_height()), ..default() },
+            padding: layout.button_padding(),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(YesterdayButton {}).insert(Focusable { state: AppState::Playing, order: 35 }).with_children(|button| {
+        button.spawn(TextBundle::from_section("Yesterday", button_style.clone()));
+    });
+}
+
+fn open_yesterday(keys: Res<Input<KeyCode>>,
+                  buttons: Query<&Interaction, (Changed<Interaction>, With<YesterdayButton>)>,
+                  daily_board: Res<DailyBoard>,
+                  mut state: ResMut<State<AppState>>,
+                ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let clicked = buttons.iter().any(|interaction| *interaction == Interaction::Clicked);
+    if daily_board.0.is_some() && ((ctrl && keys.just_pressed(KeyCode::Y)) || clicked) {
+        if let Err(err) = state.push(AppState::Yesterday) {
+            debug!("not opening yesterday's board, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+// the board as it was picked that day, with nothing found
+fn replay_daily_board(day: u64, wordlist: &WordList) -> DailyRecord {
+    let mut board = GameState::default();
+    pick_board(&mut board, wordlist, &mut StdRng::seed_from_u64(daily::daily_seed(day)), None);
+    DailyRecord {
+        letters: board.target_string,
+        required_letter: board.required_letter,
+        answers: board.answers,
+        found: Vec::new(),
+        rank_times: Vec::new(),
+    }
+}
+
+fn spawn_yesterday(mut commands: Commands, asset_server: Res<AssetServer>, history: Res<DailyHistory>, wordlist: Res<WordList>) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let body_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 36., color: Color::WHITE };
+    let missed_style = TextStyle { color: Color::GRAY, ..body_style.clone() };
+    let help_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::GRAY };
+
+    let yesterday = daily::today().saturating_sub(1);
+    let played = history.days.get(&yesterday).cloned();
+    let record = played.clone().unwrap_or_else(|| replay_daily_board(yesterday, &wordlist));
+
+    let letters: Vec<String> = record.letters.chars().map(|letter| letter.to_string()).collect();
+    let mut header = format!("Letters {}, {} required\n", letters.join(" "), record.required_letter);
+    header.push_str(&match played {
+        Some(_) => {
+            let reached = match record.rank_times.last() {
+                Some((rank, time)) => format!(", reaching {} in {}", RANKS[*rank].0, format_clock(*time)),
+                None => String::new(),
+            };
+            format!("You found {} of {} words{}, the ones you missed are in grey\n\n", record.found.len(), record.answers.len(), reached)
+        }
+        None => format!("You didn't play this one, here are all {} words\n\n", record.answers.len()),
+    });
+
+    let mut sections = vec![TextSection::new(header, body_style.clone())];
+    for (index, answer) in record.answers.iter().enumerate() {
+        let style = if record.found.contains(answer) { body_style.clone() } else { missed_style.clone() };
+        let separator = if index + 1 < record.answers.len() { ", " } else { "" };
+        sections.push(TextSection::new(format!("{}{}", answer.to_ascii_uppercase(), separator), style));
+    }
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(YesterdayRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(format!("Daily puzzle {}", daily::date_string(yesterday)), title_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_sections(sections).with_style(Style {
+            max_size: Size::new(Val::Percent(80.), Val::Auto),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section("Esc or Enter to go back to the board", help_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+fn close_yesterday(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::Return]) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.pop() {
+            debug!("not closing yesterday's board, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn despawn_yesterday(mut commands: Commands, screens: Query<Entity, With<YesterdayRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,26 @@
+use bevy::prelude::Resource;
+use std::collections::HashMap;
+
+// Short definitions of answer words, read from assets/words/definitions.txt. Each line is a word,
+// a tab and the definition, and lines starting with # are comments. Only a few hundred everyday
+// words are covered, anything else has no definition.
+#[derive(Resource, Default)]
+pub struct Definitions {
+    entries: HashMap<String, String>,
+}
+
+impl Definitions {
+    pub fn parse(file_contents: &str) -> Definitions {
+        let entries = file_contents.lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(word, definition)| (word.trim().to_ascii_lowercase(), definition.trim().to_string()))
+            .filter(|(word, definition)| !word.is_empty() && !definition.is_empty())
+            .collect();
+        Definitions { entries }
+    }
+
+    pub fn get(&self, word: &str) -> Option<&str> {
+        self.entries.get(&word.to_ascii_lowercase()).map(|definition| definition.as_str())
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use std::path::PathBuf;
+use wordgame::puzzle_file::PuzzleFile;
+
+use crate::interchange::{load_puzzle_file, ImportedPuzzle};
+use crate::loading::DictionarySource;
+use crate::palette::Overlay;
+use crate::{AppState, NextPuzzle};
+
+// Files dropped onto the window while a board is up: a puzzle in the JSON interchange format
+// (see interchange.rs) is played like --import, and a .txt word list is loaded like --dictionary.
+// Either leaves the current board behind, so it's confirmed on a screen of its own first.
+pub struct DropFilesPlugin;
+
+impl Plugin for DropFilesPlugin {
+    fn build(&self, app: &mut App) {
+        // dragging a file in usually means the window lost focus to the file manager, and paused
+        app.add_system_set(SystemSet::on_update(AppState::Playing).with_system(receive_dropped_files))
+            .add_system_set(SystemSet::on_update(AppState::Paused).with_system(receive_dropped_files))
+            .add_system_set(SystemSet::on_enter(AppState::DroppedFile).with_system(spawn_drop_confirmation))
+            .add_system_set(SystemSet::on_update(AppState::DroppedFile).with_system(confirm_drop))
+            .add_system_set(SystemSet::on_exit(AppState::DroppedFile).with_system(despawn_drop_confirmation));
+    }
+}
+
+enum DroppedFile {
+    Puzzle(PuzzleFile),
+    WordList(PathBuf),
+    Unreadable(String), // only shown, there's nothing to load
+}
+
+#[derive(Resource)]
+struct PendingDrop {
+    file: DroppedFile,
+    name: String,
+}
+
+#[derive(Component)]
+struct DropConfirmationRoot {}
+
+// only the first of several files dropped together is looked at
+fn receive_dropped_files(mut commands: Commands,
+                         mut ev_dropped: EventReader<FileDragAndDrop>,
+                         mut state: ResMut<State<AppState>>,
+                        ) {
+    let path = match ev_dropped.iter().find_map(|ev| match ev {
+        FileDragAndDrop::DroppedFile { path_buf, .. } => Some(path_buf.clone()),
+        _ => None,
+    }) {
+        Some(path) => path,
+        None => return,
+    };
+
+    let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string());
+    let extension = path.extension().map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    let file = match extension.as_deref() {
+        Some("json") => match load_puzzle_file(&path) {
+            Ok(puzzle) => DroppedFile::Puzzle(puzzle),
+            Err(message) => DroppedFile::Unreadable(message),
+        },
+        Some("txt") => DroppedFile::WordList(path.clone()),
+        _ => DroppedFile::Unreadable(String::from("Only puzzles (.json) and word lists (.txt) can be opened")),
+    };
+
+    info!("{} dropped on the window", path.display());
+    if let Err(err) = state.push(AppState::DroppedFile) {
+        debug!("not asking about {}: {:?}", name, err);
+        return;
+    }
+    commands.insert_resource(PendingDrop { file, name });
+}
+
+fn spawn_drop_confirmation(mut commands: Commands, asset_server: Res<AssetServer>, pending: Res<PendingDrop>) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let body_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 36., color: Color::WHITE };
+    let help_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::GRAY };
+
+    let (message, help) = match &pending.file {
+        DroppedFile::Puzzle(puzzle) => {
+            let answers = if puzzle.answers.is_empty() { String::from("the usual word list") } else { format!("its own {} answers", puzzle.answers.len()) };
+            (format!("Play the board {} with {}?\nThe board you're on now will be left behind.", puzzle.letters.to_ascii_uppercase(), answers), "Enter to play it, Esc to go back")
+        }
+        DroppedFile::WordList(_) => (String::from("Load this word list and start a new board with it?\nThe board you're on now will be left behind."), "Enter to load it, Esc to go back"),
+        DroppedFile::Unreadable(message) => (message.clone(), "Esc or Enter to go back"),
+    };
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(DropConfirmationRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(pending.name.clone(), title_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section(message, body_style).with_style(Style {
+            max_size: Size::new(Val::Percent(80.), Val::Auto),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section(help, help_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+fn confirm_drop(mut commands: Commands,
+                mut keys: ResMut<Input<KeyCode>>,
+                mut state: ResMut<State<AppState>>,
+                mut pending: ResMut<PendingDrop>,
+                mut next_puzzle: ResMut<NextPuzzle>,
+                mut imported_puzzle: ResMut<ImportedPuzzle>,
+                mut source: ResMut<DictionarySource>,
+            ) {
+    let confirmed = keys.just_pressed(KeyCode::Return);
+    if !confirmed && !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    // the board would otherwise take the same press as a guess once it's back
+    keys.clear_just_pressed(KeyCode::Escape);
+    keys.clear_just_pressed(KeyCode::Return);
+
+    let changed = match &pending.file {
+        DroppedFile::Puzzle(_) if confirmed => state.replace(AppState::Playing),
+        DroppedFile::WordList(_) if confirmed => state.replace(AppState::Loading),
+        _ => state.pop(),
+    };
+    if let Err(err) = changed {
+        debug!("not leaving the dropped file, the state is already changing: {:?}", err);
+        return;
+    }
+
+    match std::mem::replace(&mut pending.file, DroppedFile::Unreadable(String::new())) {
+        DroppedFile::Puzzle(puzzle) if confirmed => {
+            info!("starting the board from {}", pending.name);
+            next_puzzle.0 = puzzle.puzzle().ok();
+            imported_puzzle.0 = Some(puzzle);
+        }
+        DroppedFile::WordList(path) if confirmed => {
+            info!("loading the word list {}", path.display());
+            source.path = Some(path);
+        }
+        _ => {}
+    }
+    commands.remove_resource::<PendingDrop>();
+}
+
+fn despawn_drop_confirmation(mut commands: Commands, screens: Query<Entity, With<DropConfirmationRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
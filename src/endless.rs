@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use wordgame::frequency::WordFrequency;
+use wordgame::words::{GameState, WordList, WordValidators, RANKS};
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::{record_correct_words, refresh_board, setup_goals, AppState, BoardEntity, BoardRefreshedEvent, GameMode, GameRng, TILE_RADIUS};
+
+// No clock and no last board: reaching the target rank swaps a new board in under the same tiles,
+// and every board cleared raises a multiplier on the points from the boards after it. The words
+// and points add up for as long as the session lasts, leaving the board for a new one starts over.
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Endless>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(start_endless.after(setup_goals))
+                .with_system(spawn_endless_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(next_endless_board.after(record_correct_words))
+                .with_system(show_endless_hud.after(next_endless_board)));
+    }
+}
+
+const MULTIPLIER_STEP: f32 = 0.25; // added for each board cleared
+const MAX_MULTIPLIER: f32 = 3.;
+
+// from the command line
+#[derive(Resource)]
+pub struct EndlessRules {
+    pub target_rank: usize, // an index into RANKS
+}
+
+#[derive(Resource, Default)]
+struct Endless {
+    boards: u32, // cleared so far
+    words: usize,
+    banked_score: u32, // already multiplied
+}
+
+impl Endless {
+    fn multiplier(&self) -> f32 {
+        (1. + MULTIPLIER_STEP * self.boards as f32).min(MAX_MULTIPLIER)
+    }
+
+    // the board being played counts at the current multiplier
+    fn total(&self, gamestate: &GameState) -> u32 {
+        self.banked_score + (gamestate.score() as f32 * self.multiplier()).round() as u32
+    }
+}
+
+#[derive(Component)]
+struct EndlessHud {}
+
+fn start_endless(mut endless: ResMut<Endless>) {
+    *endless = Endless::default();
+}
+
+fn spawn_endless_hud(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if *mode != GameMode::Endless {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(EndlessHud {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
+
+fn next_endless_board(mut endless: ResMut<Endless>,
+                      rules: Res<EndlessRules>,
+                      mode: Res<GameMode>,
+                      mut gamestate: ResMut<GameState>,
+                      wordlist: Res<WordList>,
+                      validators: Res<WordValidators>,
+                      frequency: Res<WordFrequency>,
+                      mut game_rng: ResMut<GameRng>,
+                      mut ev_board_refreshed: EventWriter<BoardRefreshedEvent>,
+                    ) {
+    if *mode != GameMode::Endless || !gamestate.is_changed() || gamestate.rank() < rules.target_rank {
+        return;
+    }
+    // even the lowest rank needs a word, or a fresh board would be cleared straight away
+    if gamestate.correct_words.is_empty() {
+        return;
+    }
+
+    endless.banked_score = endless.total(&gamestate);
+    endless.words += gamestate.correct_words.len();
+    endless.boards += 1;
+    info!("endless board {} cleared, {} points so far, multiplier now {:.2}", endless.boards, endless.banked_score, endless.multiplier());
+    refresh_board(&mut gamestate, &wordlist, &validators, &frequency, &mut game_rng, &mut ev_board_refreshed);
+}
+
+fn show_endless_hud(endless: Res<Endless>,
+                    rules: Res<EndlessRules>,
+                    gamestate: Res<GameState>,
+                    mut huds: Query<&mut Text, With<EndlessHud>>,
+                ) {
+    let status = format!("Board {}, points x{:.2}\nTotal {}, {} words\n{} for the next board, now {}",
+                         endless.boards + 1, endless.multiplier(),
+                         endless.total(&gamestate), endless.words + gamestate.correct_words.len(),
+                         RANKS[rules.target_rank].0, RANKS[gamestate.rank()].0);
+
+    for mut text in huds.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
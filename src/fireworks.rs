@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+use wordgame::words::{alphabet_index, GameState};
+
+use crate::celebration::GeniusReachedEvent;
+use crate::settings::Settings;
+use crate::tween::{Easing, Track, Tween};
+use crate::{LetterAcceptedEvent, LetterTile};
+
+// The fireworks for reaching Genius (the particles feature): a few bursts of sparks at random
+// spots over the board, each spark falling and fading out on its own. Drawn in the world rather
+// than the UI, so they run behind the genius card whichever state the game is in, and reduced
+// motion skips them. Each spark fades out and despawns on its own tween. Every letter entered
+// throws a much smaller puff of sparks off its tile too, in the tile's own colour.
+pub struct FireworksPlugin;
+
+impl Plugin for FireworksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Fireworks>()
+            .add_system(launch_fireworks)
+            .add_system(burst_fireworks.after(launch_fireworks))
+            .add_system(burst_tiles)
+            .add_system(move_sparks);
+    }
+}
+
+const BURSTS: u32 = 6;
+const BURST_INTERVAL: f32 = 0.35;
+const SPARKS_PER_BURST: usize = 40;
+const SPARK_RADIUS: f32 = 4.;
+const SPARK_LIFETIME: f32 = 1.4;
+const SPARK_GRAVITY: f32 = -260.;
+const SPARK_DRAG: f32 = 1.5; // fraction of the speed lost per second
+const SPARK_Z: f32 = 60.; // over the board and its text
+const TILE_SPARKS: usize = 10;
+const TILE_SPARK_RADIUS: f32 = 3.;
+const TILE_SPARK_LIFETIME: f32 = 0.45;
+
+#[derive(Resource, Default)]
+struct Fireworks {
+    bursts_left: u32,
+    next_burst: f32,
+}
+
+#[derive(Component)]
+struct Spark {
+    velocity: Vec2,
+}
+
+fn launch_fireworks(mut ev_genius_reached: EventReader<GeniusReachedEvent>, settings: Res<Settings>, mut fireworks: ResMut<Fireworks>) {
+    if ev_genius_reached.iter().count() > 0 && !settings.reduced_motion {
+        *fireworks = Fireworks { bursts_left: BURSTS, next_burst: 0. };
+    }
+}
+
+fn burst_fireworks(mut commands: Commands, windows: Res<Windows>, time: Res<Time>, mut fireworks: ResMut<Fireworks>) {
+    if fireworks.bursts_left == 0 {
+        return;
+    }
+    fireworks.next_burst -= time.delta_seconds();
+    if fireworks.next_burst > 0. {
+        return;
+    }
+    fireworks.bursts_left -= 1;
+    fireworks.next_burst = BURST_INTERVAL;
+
+    // anywhere but the very edges, leaning to the top half where there's room to fall
+    let size = windows.get_primary().map_or(Vec2::new(1280., 720.), |window| Vec2::new(window.width(), window.height()));
+    let mut rng = rand::thread_rng();
+    let center = Vec2::new(rng.gen_range(-0.35f32..0.35) * size.x, rng.gen_range(-0.1f32..0.35) * size.y);
+    let color = Color::hsl(rng.gen_range(0f32..360.), 0.9, 0.65);
+
+    for i in 0..SPARKS_PER_BURST {
+        let angle = i as f32 / SPARKS_PER_BURST as f32 * TAU + rng.gen_range(-0.1f32..0.1);
+        let speed: f32 = rng.gen_range(140. ..320.);
+        commands.spawn(GeometryBuilder::build_as(
+            &shapes::Circle { radius: SPARK_RADIUS, center: Vec2::ZERO },
+            DrawMode::Fill(FillMode::color(color)),
+            Transform::from_translation(center.extend(SPARK_Z)),
+        )).insert(Spark { velocity: Vec2::new(angle.cos(), angle.sin()) * speed }).insert(Tween::new("spark").then(SPARK_LIFETIME, Easing::Linear, [Track::Alpha(1., 0.)]).despawning());
+    }
+}
+
+// free entry takes letters that aren't on any tile, those have nothing to burst from
+fn burst_tiles(mut commands: Commands,
+               settings: Res<Settings>,
+               gamestate: Res<GameState>,
+               mut ev_letter_accepted: EventReader<LetterAcceptedEvent>,
+               tiles: Query<(&LetterTile, &GlobalTransform)>,
+            ) {
+    if settings.reduced_motion {
+        ev_letter_accepted.clear();
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    for ev in ev_letter_accepted.iter() {
+        let tile = tiles.iter().find(|(tile, _)| tile.letter.eq_ignore_ascii_case(&ev.letter));
+        let (tile, transform) = match tile {
+            Some(tile) => tile,
+            None => continue,
+        };
+
+        let colours = settings.colours();
+        // by letter, a mega board has two required tiles and a rotated one isn't in the middle
+        let required = gamestate.required_bit & 1 << alphabet_index(tile.letter as u8) != 0;
+        let color = if required { colours.required() } else { colours.tile() };
+        let center = transform.translation().truncate();
+        for i in 0..TILE_SPARKS {
+            let angle = i as f32 / TILE_SPARKS as f32 * TAU + rng.gen_range(-0.3f32..0.3);
+            let speed: f32 = rng.gen_range(180. ..300.);
+            commands.spawn(GeometryBuilder::build_as(
+                &shapes::Circle { radius: TILE_SPARK_RADIUS, center: Vec2::ZERO },
+                DrawMode::Fill(FillMode::color(color)),
+                Transform::from_translation(center.extend(SPARK_Z)),
+            )).insert(Spark { velocity: Vec2::new(angle.cos(), angle.sin()) * speed }).insert(Tween::new("spark").then(TILE_SPARK_LIFETIME, Easing::Linear, [Track::Alpha(1., 0.)]).despawning());
+        }
+    }
+}
+
+fn move_sparks(time: Res<Time>, mut sparks: Query<(&mut Spark, &mut Transform)>) {
+    let delta = time.delta_seconds();
+    for (mut spark, mut transform) in sparks.iter_mut() {
+        spark.velocity.y += SPARK_GRAVITY * delta;
+        spark.velocity *= (1. - SPARK_DRAG * delta).max(0.);
+        transform.translation += (spark.velocity * delta).extend(0.);
+    }
+}
@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+
+use crate::{guess_word, AppState};
+
+// Keyboard focus for the buttons on every screen. Tab and Shift+Tab move between the buttons of
+// the current screen (the arrow keys too, once something has focus), Enter or Space presses the
+// focused one as if it had been clicked, and a ring is drawn round it. Dialogs focus their first
+// button straight away, the board waits for a Tab so typing and Enter still go to the guess.
+// Screens with their own keyboard navigation (the settings) just point the focus at their
+// selected row to get the same ring.
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Focus>()
+            .add_system(release_pressed)
+            .add_system(move_focus.after(release_pressed))
+            .add_system(press_focused.after(move_focus).before(guess_word))
+            .add_system(show_focus_ring.after(move_focus));
+    }
+}
+
+const FOCUS_RING_COLOR: Color = Color::YELLOW;
+const FOCUS_RING_WIDTH: f32 = 3.;
+const FOCUS_RING_GAP: f32 = 3.;
+
+// a button that can take keyboard focus while its screen is up, in ascending order
+#[derive(Component)]
+pub struct Focusable {
+    pub state: AppState,
+    pub order: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct Focus {
+    pub entity: Option<Entity>,
+    pressed: Option<Entity>, // clicked by a key last frame, released again this one
+}
+
+#[derive(Component)]
+struct FocusRing {}
+
+// the current screen's buttons in tab order
+fn focus_order(state: &AppState, focusables: &Query<(Entity, &Focusable)>) -> Vec<Entity> {
+    let mut order: Vec<(u32, Entity)> = focusables.iter()
+        .filter(|(_, focusable)| focusable.state == *state)
+        .map(|(entity, focusable)| (focusable.order, entity))
+        .collect();
+    order.sort();
+    order.into_iter().map(|(_, entity)| entity).collect()
+}
+
+fn release_pressed(mut focus: ResMut<Focus>, mut interactions: Query<&mut Interaction>) {
+    // checked first, taking from it every frame would count as a change and redraw the ring
+    if focus.pressed.is_none() {
+        return;
+    }
+
+    if let Some(entity) = focus.pressed.take() {
+        if let Ok(mut interaction) = interactions.get_mut(entity) {
+            *interaction = Interaction::None;
+        }
+    }
+}
+
+fn move_focus(keys: Res<Input<KeyCode>>,
+              mouse: Res<Input<MouseButton>>,
+              mut char_evr: EventReader<ReceivedCharacter>,
+              state: Res<State<AppState>>,
+              focusables: Query<(Entity, &Focusable)>,
+              mut focus: ResMut<Focus>,
+            ) {
+    let order = focus_order(state.current(), &focusables);
+    let position = focus.entity.and_then(|entity| order.iter().position(|other| *other == entity));
+
+    let on_board = *state.current() == AppState::Playing;
+    // typing or clicking on the board goes back to the guess
+    let typed = on_board && char_evr.iter().any(|ev| !ev.char.is_whitespace());
+    let clicked = on_board && mouse.any_just_pressed([MouseButton::Left, MouseButton::Right]);
+    if position.is_some() && (typed || clicked) {
+        focus.entity = None;
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let forward = (keys.just_pressed(KeyCode::Tab) && !shift) || (position.is_some() && keys.any_just_pressed([KeyCode::Down, KeyCode::Right]));
+    let back = (keys.just_pressed(KeyCode::Tab) && shift) || (position.is_some() && keys.any_just_pressed([KeyCode::Up, KeyCode::Left]));
+
+    if order.is_empty() {
+        return;
+    }
+    let count = order.len();
+    let next = match position {
+        Some(position) if forward => Some((position + 1) % count),
+        Some(position) if back => Some((position + count - 1) % count),
+        None if forward || !on_board => Some(0),
+        None if back => Some(count - 1),
+        _ => None,
+    };
+    if let Some(next) = next {
+        focus.entity = Some(order[next]);
+    }
+}
+
+fn press_focused(mut keys: ResMut<Input<KeyCode>>,
+                 mut focus: ResMut<Focus>,
+                 mut interactions: Query<&mut Interaction, With<Focusable>>,
+                ) {
+    let entity = match focus.entity {
+        Some(entity) => entity,
+        None => return,
+    };
+    if !keys.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        return;
+    }
+
+    if let Ok(mut interaction) = interactions.get_mut(entity) {
+        // the focused button takes the press rather than the guess line or the screen behind
+        keys.clear_just_pressed(KeyCode::Return);
+        keys.clear_just_pressed(KeyCode::Space);
+        *interaction = Interaction::Clicked;
+        focus.pressed = Some(entity);
+    }
+}
+
+// four bars just outside the focused node, so nothing inside it is covered
+fn show_focus_ring(mut commands: Commands,
+                   mut focus: ResMut<Focus>,
+                   nodes: Query<(), With<Node>>,
+                   rings: Query<Entity, With<FocusRing>>,
+                ) {
+    // the focused node went with its screen
+    if focus.entity.is_some_and(|entity| nodes.get(entity).is_err()) {
+        focus.entity = None;
+    }
+    if !focus.is_changed() {
+        return;
+    }
+
+    for ring in rings.iter() {
+        commands.entity(ring).despawn_recursive();
+    }
+
+    let entity = match focus.entity {
+        Some(entity) => entity,
+        None => return,
+    };
+    let outside = Val::Px(-(FOCUS_RING_GAP + FOCUS_RING_WIDTH));
+    let bars = [
+        (UiRect { left: outside, right: outside, top: outside, ..default() }, Size::new(Val::Auto, Val::Px(FOCUS_RING_WIDTH))),
+        (UiRect { left: outside, right: outside, bottom: outside, ..default() }, Size::new(Val::Auto, Val::Px(FOCUS_RING_WIDTH))),
+        (UiRect { left: outside, top: outside, bottom: outside, ..default() }, Size::new(Val::Px(FOCUS_RING_WIDTH), Val::Auto)),
+        (UiRect { right: outside, top: outside, bottom: outside, ..default() }, Size::new(Val::Px(FOCUS_RING_WIDTH), Val::Auto)),
+    ];
+    commands.entity(entity).with_children(|parent| {
+        for (position, size) in bars {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position,
+                    size,
+                    ..default()
+                },
+                background_color: FOCUS_RING_COLOR.into(),
+                ..default()
+            }).insert(FocusRing {});
+        }
+    });
+}
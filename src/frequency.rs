@@ -0,0 +1,59 @@
+use bevy::prelude::Resource;
+use std::collections::HashSet;
+
+// How everyday a word is, judged against a list of the most common English words (the MIT list
+// in assets/words). Words on it are common, words made from one of them with a plain ending
+// (plurals, -ed, -ing, -er, -ly) are uncommon, and anything else is rare.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Obscurity {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+impl Obscurity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Obscurity::Common => "Common",
+            Obscurity::Uncommon => "Uncommon",
+            Obscurity::Rare => "Rare",
+        }
+    }
+
+    // extra points on top of a word's score under rarity scoring: uncommon words are worth half as
+    // much again, rare ones double
+    pub fn bonus(&self, points: u32) -> u32 {
+        match self {
+            Obscurity::Common => 0,
+            Obscurity::Uncommon => points / 2,
+            Obscurity::Rare => points,
+        }
+    }
+}
+
+const ENDINGS: [&str; 7] = ["s", "es", "ed", "d", "ing", "er", "ly"];
+
+#[derive(Resource, Default)]
+pub struct WordFrequency {
+    common: HashSet<String>,
+}
+
+impl WordFrequency {
+    pub fn parse(file_contents: &str) -> WordFrequency {
+        WordFrequency { common: file_contents.split_whitespace().map(|word| word.to_ascii_lowercase()).collect() }
+    }
+
+    pub fn rate(&self, word: &str) -> Obscurity {
+        let word = word.to_ascii_lowercase();
+        if self.common.contains(&word) {
+            return Obscurity::Common;
+        }
+
+        // "baked" is "bake" with a d, "baking" has lost its e
+        let derived = ENDINGS.iter()
+            .filter_map(|ending| word.strip_suffix(ending))
+            .filter(|stem| stem.len() >= 3)
+            .any(|stem| self.common.contains(stem) || self.common.contains(&format!("{}e", stem)));
+        if derived { Obscurity::Uncommon } else { Obscurity::Rare }
+    }
+}
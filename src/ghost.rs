@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wordgame::daily;
+use wordgame::puzzle_code::PuzzleCode;
+use wordgame::words::{GameState, ScoringRules};
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::storage;
+use crate::{record_correct_words, setup_goals, tick_time_played, AppState, BoardEntity, TILE_RADIUS};
+
+// Racing your own best attempt at a board: each board's best run is kept in replays.ron as the
+// words found and the time played when each was found, whatever the mode. With --ghost, playing a
+// board seen before shows that run's score and word count ticking up under the clock as the same
+// time passes, and how far ahead or behind of it you are. A better run replaces the ghost for
+// next time, not the one being raced.
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Replays>()
+            .init_resource::<GhostRace>()
+            .add_startup_system(load_replays)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_ghost_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(record_replay.after(record_correct_words).after(tick_time_played))
+                .with_system(show_ghost.after(record_replay)))
+            .add_system(save_replays);
+    }
+}
+
+const REPLAYS_FILE: &str = "replays.ron";
+const MAX_REPLAYS: usize = 200; // the boards played longest ago go first
+
+// from the command line
+#[derive(Resource)]
+pub struct GhostRules {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct Replay {
+    words: Vec<(f32, String)>, // time_played when each word was found
+    day: u64,                  // when it was played, for dropping old ones
+}
+
+impl Replay {
+    fn score_at(&self, time: f32, scoring: &ScoringRules) -> (u32, usize) {
+        let found: Vec<&String> = self.words.iter().filter(|(found_at, _)| *found_at <= time).map(|(_, word)| word).collect();
+        (found.iter().map(|word| scoring.word_score(word)).sum(), found.len())
+    }
+
+    fn score(&self, scoring: &ScoringRules) -> u32 {
+        self.words.iter().map(|(_, word)| scoring.word_score(word)).sum()
+    }
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Replays {
+    boards: BTreeMap<String, Replay>, // by puzzle code without a mode, the same letters whatever the mode
+}
+
+// the board being played, the run so far and the one it's up against
+#[derive(Resource, Default)]
+struct GhostRace {
+    board: String,
+    run: Replay,
+    ghost: Option<Replay>,
+}
+
+#[derive(Component)]
+struct GhostText {}
+
+fn board_key(gamestate: &GameState) -> Result<String, String> {
+    PuzzleCode { letter_bits: gamestate.target_bits, required_letter: gamestate.required_letter, mode: None }.encode()
+}
+
+// a broken or unwritable file only loses the ghosts, so it's a warning rather than a dialog
+fn load_replays(mut replays: ResMut<Replays>) {
+    let loaded = storage::read(REPLAYS_FILE).and_then(|contents| match contents {
+        Some(contents) => ron::from_str(&contents).map_err(|err| format!("Replays file {} is corrupt: {}", storage::location(REPLAYS_FILE), err)),
+        None => Ok(Replays::default()),
+    });
+
+    match loaded {
+        Ok(loaded) => *replays = loaded,
+        Err(message) => warn!("{}", message),
+    }
+}
+
+fn save_replays(replays: Res<Replays>) {
+    if !replays.is_changed() || replays.is_added() {
+        return;
+    }
+
+    let saved = ron::ser::to_string_pretty(&*replays, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| storage::write(REPLAYS_FILE, &contents));
+    if let Err(message) = saved {
+        warn!("{}", message);
+    }
+}
+
+// under the session clock, for one player boards only
+fn spawn_ghost_hud(mut commands: Commands,
+                   asset_server: Res<AssetServer>,
+                   rules: Res<GhostRules>,
+                   gamestate: Res<GameState>,
+                   layout: Res<Layout>,
+                ) {
+    if !rules.enabled || gamestate.player_count > 1 {
+        return;
+    }
+
+    let ghost_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 3.,
+        color: Color::GRAY,
+    };
+
+    commands.spawn(SpatialBundle::from_transform(layout.transform(LayoutSlot::Board)))
+        .insert(BoardEntity {})
+        .insert(LayoutAnchor { slot: LayoutSlot::Board })
+        .with_children(|hud| {
+            hud.spawn(Text2dBundle{
+                text: Text::from_section("", ghost_text_style).with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(Vec3::new(0., -3.8 * TILE_RADIUS, 1.)),
+                ..default()
+            }).insert(GhostText {});
+        });
+}
+
+// a new board, entered or swapped in under the tiles, starts a new run and picks up its ghost
+fn record_replay(gamestate: Res<GameState>, mut race: ResMut<GhostRace>, mut replays: ResMut<Replays>) {
+    if !gamestate.is_changed() || gamestate.player_count > 1 {
+        return;
+    }
+
+    // a board without a code, like a mega one, has nowhere to keep its ghost
+    let board = match board_key(&gamestate) {
+        Ok(board) => board,
+        Err(_) => {
+            *race = GhostRace::default();
+            return;
+        }
+    };
+    if race.board != board || gamestate.correct_words.len() < race.run.words.len() {
+        let ghost = replays.boards.get(&board).cloned();
+        *race = GhostRace { board, run: Replay { words: Vec::new(), day: daily::today() }, ghost };
+    }
+
+    let found = race.run.words.len();
+    if gamestate.correct_words.len() == found {
+        return;
+    }
+    for new in gamestate.correct_words[found..].iter() {
+        race.run.words.push((gamestate.time_played, new.word.clone()));
+    }
+
+    let best = replays.boards.get(&race.board).map_or(0, |replay| replay.score(&gamestate.scoring));
+    if race.run.score(&gamestate.scoring) > best {
+        replays.boards.insert(race.board.clone(), race.run.clone());
+        if replays.boards.len() > MAX_REPLAYS {
+            let oldest = replays.boards.iter().min_by_key(|(_, replay)| replay.day).map(|(board, _)| board.clone());
+            if let Some(oldest) = oldest {
+                replays.boards.remove(&oldest);
+            }
+        }
+    }
+}
+
+fn show_ghost(gamestate: Res<GameState>, race: Res<GhostRace>, mut texts: Query<&mut Text, With<GhostText>>) {
+    let status = match &race.ghost {
+        Some(ghost) => {
+            let (ghost_score, ghost_words) = ghost.score_at(gamestate.time_played, &gamestate.scoring);
+            let (score, _) = race.run.score_at(gamestate.time_played, &gamestate.scoring);
+            let lead = match score.cmp(&ghost_score) {
+                std::cmp::Ordering::Greater => format!(", {} ahead", score - ghost_score),
+                std::cmp::Ordering::Less => format!(", {} behind", ghost_score - score),
+                std::cmp::Ordering::Equal => String::from(", level"),
+            };
+            format!("Ghost {} points, {} words{}", ghost_score, ghost_words, lead)
+        }
+        None => String::from("No ghost yet, this run will be the one to beat"),
+    };
+
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::{guess_word, setup_goals, AppState, BoardEntity, GameMode, WordAcceptedEvent, WordRejectedEvent, TILE_RADIUS};
+
+// Golf mode: the daily board, played for the fewest guesses it takes to find a pangram. Every
+// word entered counts as a stroke, accepted or not, and the count is shown large above the board.
+// Finding a pangram holes out and freezes the count, the board can still be played after that
+// but nothing more is counted. On the daily board the leaderboard ranks the holed counts, lowest
+// first, see leaderboard.rs.
+pub struct GolfPlugin;
+
+impl Plugin for GolfPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Golf>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(reset_golf)
+                .with_system(spawn_golf_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(count_strokes.after(guess_word))
+                .with_system(show_strokes.after(count_strokes)));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Golf {
+    pub strokes: u32,
+    pub holed: bool,
+}
+
+#[derive(Component)]
+struct GolfHud {}
+
+fn reset_golf(mut golf: ResMut<Golf>) {
+    *golf = Golf::default();
+}
+
+fn spawn_golf_hud(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if *mode != GameMode::Golf {
+        return;
+    }
+
+    let strokes_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"),
+        font_size: TILE_RADIUS,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", strokes_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(GolfHud {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
+
+// the pangram itself is the last stroke
+fn count_strokes(mode: Res<GameMode>,
+                 mut golf: ResMut<Golf>,
+                 mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                 mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                ) {
+    let rejected = ev_word_rejected.iter().count() as u32;
+    if *mode != GameMode::Golf || golf.holed {
+        return;
+    }
+
+    golf.strokes += rejected;
+    for ev in ev_word_accepted.iter() {
+        golf.strokes += 1;
+        if ev.pangram {
+            golf.holed = true;
+            info!("holed out in {} guesses", golf.strokes);
+            break;
+        }
+    }
+}
+
+fn show_strokes(golf: Res<Golf>, mut huds: Query<&mut Text, With<GolfHud>>) {
+    if !golf.is_changed() {
+        return;
+    }
+
+    let status = match (golf.holed, golf.strokes) {
+        (true, 1) => String::from("Hole in one!"),
+        (true, strokes) => format!("Pangram in {} guesses", strokes),
+        (false, 1) => String::from("1 guess"),
+        (false, strokes) => format!("{} guesses", strokes),
+    };
+
+    for mut text in huds.iter_mut() {
+        text.sections[0].value = status.clone();
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Repeat, Replay, Ticks};
+use gilrs::Gilrs;
+
+use crate::settings::Settings;
+use crate::{WordAcceptedEvent, WordRejectedEvent};
+
+// Buzzes for what a guess did, felt rather than seen: a short one for a word, a long one for a
+// pangram and two quick ones for a rejection. They go to every gamepad that can rumble and, on
+// Android, the phone's own vibration motor. iOS and the browser have nothing here yet. Turned off
+// with "Vibration" in the settings.
+pub struct HapticsPlugin;
+
+impl Plugin for HapticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HapticPulse>()
+            .insert_non_send_resource(Rumble::default())
+            .add_system(pulse_guesses)
+            .add_system(rumble_gamepads.after(pulse_guesses));
+
+        #[cfg(target_os = "android")]
+        app.add_system(vibrate_phone.after(pulse_guesses));
+    }
+}
+
+const RUMBLE_STRENGTH: u16 = 40_000; // of u16::MAX, enough to feel without rattling the desk
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum HapticPulse {
+    Accepted,
+    Pangram,
+    Rejected,
+}
+
+impl HapticPulse {
+    // milliseconds on then off, in turn
+    fn pattern(&self) -> &'static [(u32, u32)] {
+        match self {
+            HapticPulse::Accepted => &[(40, 0)],
+            HapticPulse::Pangram => &[(300, 0)],
+            HapticPulse::Rejected => &[(40, 70), (40, 0)],
+        }
+    }
+
+    fn milliseconds(&self) -> u32 {
+        self.pattern().iter().map(|(on, off)| on + off).sum()
+    }
+}
+
+// gilrs stops an effect once its handle is dropped, so the one playing is held on to until it's done
+#[derive(Default)]
+struct Rumble {
+    playing: Option<(gilrs::ff::Effect, f32)>,
+}
+
+fn pulse_guesses(settings: Res<Settings>,
+                 mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                 mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                 mut ev_pulse: EventWriter<HapticPulse>,
+                ) {
+    let accepted: Vec<bool> = ev_word_accepted.iter().map(|ev| ev.pangram).collect();
+    let rejected = ev_word_rejected.iter().count() > 0;
+    if !settings.haptics {
+        return;
+    }
+
+    if accepted.contains(&true) {
+        ev_pulse.send(HapticPulse::Pangram);
+    }
+    else if !accepted.is_empty() {
+        ev_pulse.send(HapticPulse::Accepted);
+    }
+    else if rejected {
+        ev_pulse.send(HapticPulse::Rejected);
+    }
+}
+
+fn rumble_effect(pulse: HapticPulse, gilrs: &mut Gilrs) -> Result<gilrs::ff::Effect, gilrs::ff::Error> {
+    let pads: Vec<gilrs::GamepadId> = gilrs.gamepads().filter(|(_, pad)| pad.is_ff_supported()).map(|(id, _)| id).collect();
+    let mut builder = EffectBuilder::new();
+    let mut start = 0;
+    for (on, off) in pulse.pattern() {
+        builder.add_effect(BaseEffect {
+            kind: BaseEffectType::Strong { magnitude: RUMBLE_STRENGTH },
+            scheduling: Replay { after: Ticks::from_ms(start), play_for: Ticks::from_ms(*on), ..default() },
+            ..default()
+        });
+        start += on + off;
+    }
+    builder.gamepads(&pads)
+        .repeat(Repeat::For(Ticks::from_ms(pulse.milliseconds())))
+        .finish(gilrs)
+}
+
+// gilrs isn't there when it failed to start, and then there are no gamepads to rumble anyway
+fn rumble_gamepads(time: Res<Time>,
+                   mut ev_pulse: EventReader<HapticPulse>,
+                   gilrs: Option<NonSendMut<Gilrs>>,
+                   mut rumble: NonSendMut<Rumble>,
+                ) {
+    let finished = match rumble.playing.as_mut() {
+        Some((_, seconds_left)) => {
+            *seconds_left -= time.delta_seconds();
+            *seconds_left <= 0.
+        }
+        None => false,
+    };
+    if finished {
+        rumble.playing = None;
+    }
+
+    let (pulse, mut gilrs) = match (ev_pulse.iter().last(), gilrs) {
+        (Some(pulse), Some(gilrs)) => (*pulse, gilrs),
+        _ => return,
+    };
+    if gilrs.gamepads().all(|(_, pad)| !pad.is_ff_supported()) {
+        return;
+    }
+
+    match rumble_effect(pulse, &mut gilrs).and_then(|effect| effect.play().map(|_| effect)) {
+        Ok(effect) => rumble.playing = Some((effect, pulse.milliseconds() as f32 / 1000.)),
+        Err(err) => warn!("Could not rumble the gamepad: {}", err),
+    }
+}
+
+// Vibrator.vibrate(long[], int), long deprecated but still there on every version the game runs on
+#[cfg(target_os = "android")]
+fn vibrate(pulse: HapticPulse) -> jni::errors::Result<()> {
+    use jni::objects::{JObject, JValue};
+
+    let context = ndk_context::android_context();
+    let vm = unsafe { jni::JavaVM::from_raw(context.vm().cast()) }?;
+    let env = vm.attach_current_thread()?;
+    let activity = JObject::from(context.context() as jni::sys::jobject);
+
+    let name = env.new_string("vibrator")?;
+    let vibrator = env.call_method(activity, "getSystemService", "(Ljava/lang/String;)Ljava/lang/Object;", &[JValue::Object(name.into())])?.l()?;
+
+    // Android's patterns start with a wait, then alternate on and off
+    let mut timings = vec![0];
+    for (on, off) in pulse.pattern() {
+        timings.push(*on as i64);
+        timings.push(*off as i64);
+    }
+    let array = env.new_long_array(timings.len() as i32)?;
+    env.set_long_array_region(array, 0, &timings)?;
+    env.call_method(vibrator, "vibrate", "([JI)V", &[JValue::Object(JObject::from(array)), JValue::Int(-1)])?;
+    Ok(())
+}
+
+#[cfg(target_os = "android")]
+fn vibrate_phone(mut ev_pulse: EventReader<HapticPulse>) {
+    if let Some(pulse) = ev_pulse.iter().last() {
+        if let Err(err) = vibrate(*pulse) {
+            warn!("Could not vibrate: {}", err);
+        }
+    }
+}
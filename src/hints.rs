@@ -0,0 +1,324 @@
+use bevy::prelude::*;
+use bevy::text::Text2dBounds;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wordgame::definitions::Definitions;
+use wordgame::words::GameState;
+
+use crate::focus::Focusable;
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::rotation::RequiredRotatedEvent;
+use crate::settings::Settings;
+use crate::storage;
+use crate::{guess_word, record_correct_words, record_rank_times, setup_goals, AppState, BoardEntity, BoardRefreshedEvent, GameRng, HintText, WordAcceptedEvent, TILE_RADIUS};
+
+// Hints paid for out of the board's points: Ctrl+H or the Hint button takes HINT_COST points and
+// shows the first two letters and the length of a word still to find, listed under the HUD until
+// it's found. Ctrl+D or the Define button costs the same and shows what an unfound word means
+// instead, for the words assets/words/definitions.txt covers, and Ctrl+K or the Two letter list
+// button shows how many words are left starting with each pair of letters. Every pangram and
+// every rank reached earns a hint token, kept in tokens.ron across boards, and a token is spent
+// before any points are. The board summary counts every hint, so a clean solve is one without
+// any. Hints for words a rotated required letter leaves out are dropped, see rotation.rs. Kids mode
+// has its own free hints instead, see kids.rs.
+pub struct HintsPlugin;
+
+impl Plugin for HintsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RevealedHints>()
+            .init_resource::<HintTokens>()
+            .insert_resource(Definitions::parse(include_str!("../assets/words/definitions.txt")))
+            .add_startup_system(load_hint_tokens)
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(reset_hints.after(setup_goals))
+                .with_system(spawn_hints.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(reset_hints_on_refresh)
+                .with_system(drop_rotated_hints)
+                .with_system(earn_hint_tokens.after(record_rank_times))
+                .with_system(buy_hint.after(guess_word))
+                .with_system(show_hints.after(buy_hint).after(record_correct_words).after(earn_hint_tokens)))
+            .add_system(save_hint_tokens);
+    }
+}
+
+pub const HINT_COST: u32 = 3;
+const HINT_LETTERS: usize = 2;
+pub const TOKENS_FILE: &str = "tokens.ron";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HintKind {
+    Letters,
+    Definition,
+    TwoLetters, // for the whole board rather than one word, so its hint has no word
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct HintTokens {
+    balance: u32,
+}
+
+struct Hint {
+    word: String,
+    kind: HintKind,
+}
+
+// the answers hinted at on this board, in the order they were bought
+#[derive(Resource, Default)]
+struct RevealedHints {
+    hints: Vec<Hint>,
+}
+
+impl RevealedHints {
+    fn contains(&self, word: &str, kind: HintKind) -> bool {
+        self.hints.iter().any(|hint| hint.word == word && hint.kind == kind)
+    }
+}
+
+#[derive(Component)]
+struct HintButton {
+    kind: HintKind,
+}
+
+#[derive(Component)]
+struct HintsList {}
+
+// a broken or unwritable file only costs the tokens, so it's a warning rather than a dialog
+fn load_hint_tokens(mut tokens: ResMut<HintTokens>) {
+    let loaded = storage::read(TOKENS_FILE).and_then(|contents| match contents {
+        Some(contents) => ron::from_str(&contents).map_err(|err| format!("Hint tokens file {} is corrupt: {}", storage::location(TOKENS_FILE), err)),
+        None => Ok(HintTokens::default()),
+    });
+
+    match loaded {
+        Ok(loaded) => *tokens = loaded,
+        Err(message) => warn!("{}", message),
+    }
+}
+
+fn save_hint_tokens(tokens: Res<HintTokens>) {
+    if !tokens.is_changed() || tokens.is_added() {
+        return;
+    }
+
+    let saved = ron::ser::to_string_pretty(&*tokens, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| storage::write(TOKENS_FILE, &contents));
+    if let Err(message) = saved {
+        warn!("{}", message);
+    }
+}
+
+// ranks are counted off rank_times, which starts over with each board
+fn earn_hint_tokens(gamestate: Res<GameState>,
+                    mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                    mut tokens: ResMut<HintTokens>,
+                    mut ranks_seen: Local<usize>,
+                ) {
+    let pangrams = ev_word_accepted.iter().filter(|ev| ev.pangram).count() as u32;
+    if gamestate.rank_times.len() < *ranks_seen {
+        *ranks_seen = 0;
+    }
+    let ranks = (gamestate.rank_times.len() - *ranks_seen) as u32;
+    *ranks_seen = gamestate.rank_times.len();
+
+    if pangrams + ranks > 0 {
+        tokens.balance += pangrams + ranks;
+        info!("earned {} hint tokens, {} to spend", pangrams + ranks, tokens.balance);
+    }
+}
+
+fn reset_hints(mut hints: ResMut<RevealedHints>) {
+    *hints = RevealedHints::default();
+}
+
+fn reset_hints_on_refresh(mut ev_board_refreshed: EventReader<BoardRefreshedEvent>, mut hints: ResMut<RevealedHints>) {
+    if ev_board_refreshed.iter().count() > 0 {
+        *hints = RevealedHints::default();
+    }
+}
+
+// a new required letter can leave a hinted word out of the answers, found ones are kept in them
+fn drop_rotated_hints(mut ev_rotated: EventReader<RequiredRotatedEvent>, gamestate: Res<GameState>, mut hints: ResMut<RevealedHints>) {
+    if ev_rotated.iter().count() > 0 {
+        hints.hints.retain(|hint| hint.kind == HintKind::TwoLetters || gamestate.answers.contains(&hint.word));
+    }
+}
+
+fn spawn_hints(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>, layout: Res<Layout>) {
+    if settings.kids_mode() {
+        return;
+    }
+
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    // above the puzzle packs button
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(16.), bottom: Val::Px(12. + 2. * layout.button_row_height()), ..default() },
+            padding: layout.button_padding(),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(HintButton { kind: HintKind::Letters }).insert(Focusable { state: AppState::Playing, order: 13 }).with_children(|button| {
+        button.spawn(TextBundle::from_section(format!("Hint ({} points)", HINT_COST), button_style.clone()));
+    });
+
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(16.), bottom: Val::Px(12. + 3. * layout.button_row_height()), ..default() },
+            padding: layout.button_padding(),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(HintButton { kind: HintKind::Definition }).insert(Focusable { state: AppState::Playing, order: 14 }).with_children(|button| {
+        button.spawn(TextBundle::from_section(format!("Define ({} points)", HINT_COST), button_style.clone()));
+    });
+
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(16.), bottom: Val::Px(12. + 4. * layout.button_row_height()), ..default() },
+            padding: layout.button_padding(),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(HintButton { kind: HintKind::TwoLetters }).insert(Focusable { state: AppState::Playing, order: 15 }).with_children(|button| {
+        button.spawn(TextBundle::from_section(format!("Two letter list ({} points)", HINT_COST), button_style.clone()));
+    });
+
+    // definitions run long, so the list wraps rather than running across the board
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        text_2d_bounds: Text2dBounds { size: Vec2::new(5. * TILE_RADIUS, f32::INFINITY) },
+        transform: layout.transform(LayoutSlot::Hints),
+        ..default()
+    }).insert(BoardEntity {}).insert(HintsList {}).insert(LayoutAnchor { slot: LayoutSlot::Hints });
+}
+
+fn buy_hint(keys: Res<Input<KeyCode>>,
+            buttons: Query<(&Interaction, &HintButton), Changed<Interaction>>,
+            settings: Res<Settings>,
+            definitions: Res<Definitions>,
+            mut gamestate: ResMut<GameState>,
+            mut game_rng: ResMut<GameRng>,
+            mut hints: ResMut<RevealedHints>,
+            mut tokens: ResMut<HintTokens>,
+            mut hint_text: Query<&mut Text, With<HintText>>,
+        ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let clicked = |kind: HintKind| buttons.iter().any(|(interaction, button)| *interaction == Interaction::Clicked && button.kind == kind);
+    let kind = if (ctrl && keys.just_pressed(KeyCode::H)) || clicked(HintKind::Letters) {
+        HintKind::Letters
+    }
+    else if (ctrl && keys.just_pressed(KeyCode::D)) || clicked(HintKind::Definition) {
+        HintKind::Definition
+    }
+    else if (ctrl && keys.just_pressed(KeyCode::K)) || clicked(HintKind::TwoLetters) {
+        HintKind::TwoLetters
+    }
+    else {
+        return;
+    };
+    if settings.kids_mode() {
+        return;
+    }
+
+    let unhinted: Vec<&String> = gamestate.answers.iter()
+        .filter(|answer| !gamestate.is_found(answer) && !hints.contains(answer, kind))
+        .filter(|answer| kind != HintKind::Definition || definitions.get(answer).is_some())
+        .collect();
+    let message = if kind == HintKind::TwoLetters && hints.contains("", kind) {
+        String::from("The two letter list is already showing")
+    }
+    else if unhinted.is_empty() && kind == HintKind::Definition {
+        String::from("No definitions for the words left")
+    }
+    else if unhinted.is_empty() {
+        String::from("Nothing left to hint at")
+    }
+    else if tokens.balance == 0 && gamestate.score() < HINT_COST {
+        format!("Hints cost {} points, find a few more words first", HINT_COST)
+    }
+    else {
+        let word = match kind {
+            HintKind::TwoLetters => String::new(),
+            _ => unhinted.choose(&mut game_rng.0).map(|word| (*word).clone()).unwrap(),
+        };
+        hints.hints.push(Hint { word, kind });
+        gamestate.hints_used += 1;
+        if tokens.balance > 0 {
+            tokens.balance -= 1;
+            String::from("Hint bought with a token")
+        }
+        else {
+            gamestate.spent_score += HINT_COST;
+            format!("Hint bought, -{} points", HINT_COST)
+        }
+    };
+
+    for mut text in hint_text.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+// the words still to find by their first two letters, like "AB 2  AC 1"
+fn two_letter_list(gamestate: &GameState) -> String {
+    let mut starts = BTreeMap::new();
+    for answer in gamestate.answers.iter().filter(|answer| !gamestate.is_found(answer)) {
+        *starts.entry(answer[..2].to_ascii_uppercase()).or_insert(0) += 1;
+    }
+    let starts: Vec<String> = starts.iter().map(|(start, count)| format!("{} {}", start, count)).collect();
+    starts.join("  ")
+}
+
+// found hints are spelled out in full, the rest only as far as they were paid for
+fn show_hints(hints: Res<RevealedHints>,
+              tokens: Res<HintTokens>,
+              gamestate: Res<GameState>,
+              definitions: Res<Definitions>,
+              mut lists: Query<&mut Text, With<HintsList>>,
+            ) {
+    if !hints.is_changed() && !gamestate.is_changed() && !tokens.is_changed() {
+        return;
+    }
+
+    let mut lines: Vec<String> = hints.hints.iter().map(|hint| {
+        let word = &hint.word;
+        if hint.kind == HintKind::TwoLetters {
+            two_letter_list(&gamestate)
+        }
+        else if gamestate.is_found(word) {
+            word.to_ascii_uppercase()
+        }
+        else if hint.kind == HintKind::Definition {
+            format!("\"{}\"  ({})", definitions.get(word).unwrap_or_default(), word.len())
+        }
+        else {
+            let hidden = vec!["_"; word.len() - HINT_LETTERS].join(" ");
+            format!("{} {}  ({})", word[..HINT_LETTERS].to_ascii_uppercase(), hidden, word.len())
+        }
+    }).collect();
+    if tokens.balance > 0 {
+        lines.insert(0, format!("{} hint tokens to spend", tokens.balance));
+    }
+
+    let value = if lines.is_empty() { String::new() } else { format!("Hints\n{}", lines.join("\n")) };
+    for mut text in lists.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
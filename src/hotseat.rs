@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use wordgame::words::GameState;
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::{record_correct_words, setup_goals, AppState, BoardEntity, GameMode, WordAcceptedEvent, WordRejectedEvent, TILE_RADIUS};
+
+// Two players taking turns on one board. Every guess, right or wrong, passes the turn,
+// so submitting an empty guess works as a pass. Does nothing in the other modes.
+pub struct HotseatPlugin;
+
+impl Plugin for HotseatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing).with_system(setup_turn_indicator.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(advance_turn.after(record_correct_words))
+                .with_system(show_turn_indicator.after(advance_turn)));
+    }
+}
+
+// also used by the other multiplayer modes to show who found what
+const PLAYER_COLORS: [Color; 2] = [Color::ORANGE, Color::LIME_GREEN];
+
+pub fn player_color(player: usize) -> Color {
+    PLAYER_COLORS[player % PLAYER_COLORS.len()]
+}
+
+#[derive(Component)]
+struct TurnIndicator {}
+
+fn setup_turn_indicator(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if *mode != GameMode::Hotseat {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(TurnIndicator {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
+
+fn advance_turn(mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                mut gamestate: ResMut<GameState>,
+                mode: Res<GameMode>,
+            ) {
+    if *mode != GameMode::Hotseat {
+        return;
+    }
+
+    let guesses = ev_word_accepted.iter().count() + ev_word_rejected.iter().count();
+    if guesses > 0 {
+        gamestate.current_player = (gamestate.current_player + guesses) % gamestate.player_count;
+    }
+}
+
+fn show_turn_indicator(gamestate: Res<GameState>, mut indicators: Query<&mut Text, With<TurnIndicator>>) {
+    if !gamestate.is_changed() {
+        return;
+    }
+
+    for mut text in indicators.iter_mut() {
+        let mut style = text.sections[0].style.clone();
+        style.color = player_color(gamestate.current_player);
+
+        let mut sections = vec![TextSection::new(format!("Player {}'s turn", gamestate.current_player + 1), style.clone())];
+        for player in 0..gamestate.player_count {
+            let mut style = style.clone();
+            style.color = player_color(player);
+            sections.push(TextSection::new(format!("\nPlayer {}: {}", player + 1, gamestate.player_score(player)), style));
+        }
+        text.sections = sections;
+    }
+}
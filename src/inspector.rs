@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext, EguiPlugin};
+use wordgame::words::{GameState, WordList};
+
+use crate::mods::InstalledMods;
+use crate::palette::{Palette, ALL_PALETTES};
+use crate::settings::Settings;
+use crate::tween::{Easing, Tween};
+use crate::{squish_easing, tile_squish, LetterTile, SQUISH_RETURN_SECONDS, SQUISH_SCALE};
+
+// Debug overlay only compiled with the "dev" feature, hidden until F12 toggles it
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InspectorVisible(false))
+            .init_resource::<SquishTuning>()
+            .add_plugin(EguiPlugin)
+            .add_system(toggle_inspector)
+            .add_system(inspector_panel);
+    }
+}
+
+#[derive(Resource)]
+struct InspectorVisible(bool);
+
+// the tile squish being tried out, starting from the one the board uses
+#[derive(Resource)]
+struct SquishTuning {
+    scale: f32,
+    duration: f32,
+    easing: Easing,
+}
+
+impl Default for SquishTuning {
+    fn default() -> SquishTuning {
+        SquishTuning { scale: SQUISH_SCALE, duration: SQUISH_RETURN_SECONDS, easing: Easing::BackOut }
+    }
+}
+
+const EASINGS: [Easing; 4] = [Easing::Linear, Easing::CubicOut, Easing::BackOut, Easing::ElasticOut];
+
+fn toggle_inspector(keys: Res<Input<KeyCode>>, mut visible: ResMut<InspectorVisible>) {
+    if keys.just_pressed(KeyCode::F12) {
+        visible.0 = !visible.0;
+    }
+}
+
+fn inspector_panel(mut egui_context: ResMut<EguiContext>,
+                   visible: Res<InspectorVisible>,
+                   gamestate: Res<GameState>,
+                   wordlist: Res<WordList>,
+                   mut tuning: ResMut<SquishTuning>,
+                   mut squishes: Query<(&LetterTile, &mut Tween)>,
+                   mut settings: ResMut<Settings>,
+                   mut mods: ResMut<InstalledMods>,
+                ) {
+    if !visible.0 {
+        return;
+    }
+
+    egui::Window::new("Inspector").show(egui_context.ctx_mut(), |ui| {
+        ui.collapsing("Game State", |ui| {
+            ui.label(format!("Letters: {}", gamestate.target_string));
+            ui.label(format!("Required letter: {}", gamestate.required_letter));
+            ui.label(format!("Found: {} / {}", gamestate.correct_words.len(), gamestate.answers.len()));
+            ui.collapsing("Answers", |ui| {
+                for answer in gamestate.answers.iter() {
+                    if gamestate.is_found(answer) {
+                        ui.label(format!("{} (found)", answer));
+                    }
+                    else {
+                        ui.label(answer.as_str());
+                    }
+                }
+            });
+        });
+
+        ui.collapsing("Word List", |ui| {
+            ui.label(format!("Valid words: {}", wordlist.all_valid_words.len()));
+            ui.label(format!("Potential pangrams: {}", wordlist.potential_pangrams.len()));
+        });
+
+        ui.collapsing("Tile Squish", |ui| {
+            let mut changed = false;
+            changed |= ui.add(egui::Slider::new(&mut tuning.scale, 0.0..=2.0).text("squish scale")).changed();
+            changed |= ui.add(egui::Slider::new(&mut tuning.duration, 0.0..=1.0).text("return")).changed();
+            egui::ComboBox::from_label("easing").selected_text(format!("{:?}", tuning.easing)).show_ui(ui, |ui| {
+                for easing in EASINGS {
+                    changed |= ui.selectable_value(&mut tuning.easing, easing, format!("{:?}", easing)).changed();
+                }
+            });
+
+            // every tile gets the same squish bar the required tile's own easing, played once so the change shows
+            if changed {
+                for (tile, mut squish) in squishes.iter_mut() {
+                    *squish = tile_squish(tuning.scale, tuning.duration, squish_easing(tile.index, tuning.easing));
+                }
+            }
+        });
+
+        ui.collapsing("Palette", |ui| {
+            let mut chosen = None;
+            egui::ComboBox::from_label("palette").selected_text(mods.palette_name(&settings)).show_ui(ui, |ui| {
+                for palette in ALL_PALETTES {
+                    if ui.selectable_label(settings.theme.is_empty() && settings.palette == palette, palette.name()).clicked() {
+                        chosen = Some((palette, String::new()));
+                    }
+                }
+                for (name, _) in mods.themes.iter() {
+                    if ui.selectable_label(settings.theme == *name, name.as_str()).clicked() {
+                        chosen = Some((Palette::Standard, name.clone()));
+                    }
+                }
+            });
+            if let Some((palette, theme)) = chosen {
+                settings.palette = palette;
+                settings.theme = theme;
+            }
+
+            let mut high_contrast = settings.high_contrast;
+            if ui.checkbox(&mut high_contrast, "high contrast").changed() {
+                settings.high_contrast = high_contrast;
+            }
+
+            // a theme is edited where the mods folder put it, the settings pick it up again from there
+            if let Some(theme) = mods.themes.iter().position(|(name, _)| *name == settings.theme) {
+                let mut colours = mods.themes[theme].1;
+                let mut changed = false;
+                changed |= colour_edit(ui, "tile", &mut colours.tile);
+                changed |= colour_edit(ui, "required", &mut colours.required);
+                changed |= colour_edit(ui, "pangram", &mut colours.pangram);
+                changed |= colour_edit(ui, "accepted", &mut colours.accepted);
+                changed |= colour_edit(ui, "rejected", &mut colours.rejected);
+                changed |= colour_edit(ui, "background", &mut colours.background);
+                changed |= ui.checkbox(&mut colours.marks, "marks").changed();
+                if changed {
+                    mods.themes[theme].1 = colours;
+                    settings.set_changed();
+                }
+            }
+        });
+    });
+}
+
+fn colour_edit(ui: &mut egui::Ui, label: &str, colour: &mut Color) -> bool {
+    let mut rgb = [colour.r(), colour.g(), colour.b()];
+    let changed = ui.horizontal(|ui| {
+        let changed = ui.color_edit_button_rgb(&mut rgb).changed();
+        ui.label(label);
+        changed
+    }).inner;
+
+    if changed {
+        *colour = Color::rgb(rgb[0], rgb[1], rgb[2]);
+    }
+    changed
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use std::{fs, path::{Path, PathBuf}};
+use wordgame::daily;
+use wordgame::puzzle_file::PuzzleFile;
+use wordgame::words::GameState;
+
+use crate::{setup_goals, AppState, DailyBoard};
+
+// Puzzles in and out as JSON (see wordgame::puzzle_file for the format): --import FILE plays the
+// board in the file, with its own answers if it has them, and --export FILE writes each board out
+// as it starts, answers and all, for other tools to pick up. Dropping a puzzle file on the window
+// plays it too, see drop_files.rs.
+pub struct InterchangePlugin;
+
+impl Plugin for InterchangePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing).with_system(export_board.after(setup_goals)));
+    }
+}
+
+// the puzzle from --import, until its board is set up
+#[derive(Resource)]
+pub struct ImportedPuzzle(pub Option<PuzzleFile>);
+
+#[derive(Resource)]
+pub struct ExportPath(pub PathBuf);
+
+pub fn load_puzzle_file(path: &Path) -> Result<PuzzleFile, String> {
+    let contents = fs::read_to_string(path).map_err(|err| format!("Could not read puzzle {}: {}", path.display(), err))?;
+    PuzzleFile::parse(&contents)
+}
+
+fn export_board(export_path: Res<ExportPath>, gamestate: Res<GameState>, daily_board: Res<DailyBoard>) {
+    let path = &export_path.0;
+    let file = PuzzleFile::from_board(&gamestate, daily_board.0.map(daily::date_string));
+    match fs::write(path, file.to_json()) {
+        Ok(()) => info!("exported the board to {}", path.display()),
+        Err(err) => warn!("Could not export the board to {}: {}", path.display(), err),
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use wordgame::words::GameState;
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::loading::DictionarySource;
+use crate::settings::Settings;
+use crate::{record_correct_words, setup_goals, AppState, BoardEntity, GameRng, TILE_RADIUS};
+
+// Kids mode, switched on in the settings for each profile: a word list of everyday words with the grown up ones
+// taken out, three letter words allowed, warmer colours (see palette.rs) and a hint that always
+// points at a word still to find. The word list only changes by loading it again, so switching
+// kids mode on or off goes back through the loading screen to a new board.
+pub struct KidsPlugin;
+
+impl Plugin for KidsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KidsHint>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_kids_hint.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(reload_for_kids_mode)
+                .with_system(pick_kids_hint.after(record_correct_words))
+                .with_system(show_kids_hint.after(pick_kids_hint)));
+    }
+}
+
+// how much of the hinted word is given away
+const HINT_LETTERS: usize = 2;
+
+#[derive(Resource, Default)]
+struct KidsHint {
+    word: Option<String>,
+}
+
+#[derive(Component)]
+struct KidsHintText {}
+
+// settings are changed from their own screen, so this is seen once back on the board, and another
+// transition may already be queued that frame, in which case it's tried again next frame
+fn reload_for_kids_mode(settings: Res<Settings>, source: Res<DictionarySource>, mut state: ResMut<State<AppState>>) {
+    if settings.kids_mode() != source.kids && state.replace(AppState::Loading).is_ok() {
+        info!("kids mode turned {}, loading the word list again", if settings.kids_mode() { "on" } else { "off" });
+    }
+}
+
+fn spawn_kids_hint(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>, layout: Res<Layout>) {
+    if !settings.kids_mode() {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hints),
+        ..default()
+    }).insert(BoardEntity {}).insert(KidsHintText {}).insert(LayoutAnchor { slot: LayoutSlot::Hints });
+}
+
+// a new word once the hinted one is found, or when the board changes under it
+fn pick_kids_hint(settings: Res<Settings>, gamestate: Res<GameState>, mut game_rng: ResMut<GameRng>, mut hint: ResMut<KidsHint>) {
+    if !settings.kids_mode() || !gamestate.is_changed() {
+        return;
+    }
+
+    let still_hidden = |word: &String| gamestate.answers.contains(word) && !gamestate.is_found(word);
+    if hint.word.as_ref().is_some_and(still_hidden) {
+        return;
+    }
+
+    let unfound: Vec<&String> = gamestate.answers.iter().filter(|answer| !gamestate.is_found(answer)).collect();
+    hint.word = unfound.choose(&mut game_rng.0).map(|word| (*word).clone());
+}
+
+fn show_kids_hint(hint: Res<KidsHint>, gamestate: Res<GameState>, mut texts: Query<&mut Text, With<KidsHintText>>) {
+    if !hint.is_changed() && !gamestate.is_changed() {
+        return;
+    }
+
+    let left = gamestate.answers.iter().filter(|answer| !gamestate.is_found(answer)).count();
+    let line = match &hint.word {
+        Some(word) => format!("{} {} left to find\nTry a {} letter word starting {}",
+                              left, if left == 1 { "word" } else { "words" },
+                              word.len(), word[..HINT_LETTERS].to_ascii_uppercase()),
+        None => String::from("You found every word!"),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = line.clone();
+    }
+}
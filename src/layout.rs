@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use bevy::window::{WindowMode, WindowResized};
+
+use crate::settings::Settings;
+use crate::touch::touch_world_position;
+use crate::{cursor_world_position, guess_word, AppState, BOARD_CENTER, TILE_RADIUS};
+
+// Where the board and the texts around it go for the shape of the window. Wide windows get the
+// board on the left with the found words beside it. Tall ones, like a phone held upright, get
+// bigger tiles low down and the found words folded away at the top until they're tapped.
+// The camera zooms out whenever the window is smaller than the layout was made for, with the UI
+// shrinking to match, and desktop windows can't be made smaller than MIN_WINDOW_SIZE.
+// F11 or Alt+Enter switches fullscreen, which is kept in the settings.
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Layout>()
+            .init_resource::<FoundListExpanded>()
+            .add_system(choose_layout)
+            .add_system(apply_layout.after(choose_layout))
+            .add_system(fit_camera.after(choose_layout))
+            .add_system(toggle_fullscreen.before(guess_word))
+            .add_system(apply_window_mode.after(toggle_fullscreen))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(toggle_found_list));
+    }
+}
+
+// below this the zoomed out text is too small to read
+pub const MIN_WINDOW_SIZE: Vec2 = Vec2::new(480., 480.);
+// buttons and menus shrink with the board, but no further than this
+const MIN_UI_SCALE: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LayoutSlot {
+    Board,     // the tiles with the guess and hint above them
+    FoundList, // top of the found words list
+    Hud,       // turn, clock and score readouts for the modes that have them
+    Hints,     // under the HUD, for hints about words still to find
+    Scoring,   // between the HUD and the hints, for the optional scoring rules
+}
+
+// moved to its slot whenever the layout changes
+#[derive(Component)]
+pub struct LayoutAnchor {
+    pub slot: LayoutSlot,
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct Layout {
+    pub portrait: bool,
+    pub window_size: Vec2,
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout { portrait: false, window_size: Vec2::new(1280., 720.) }
+    }
+}
+
+// only matters where the list folds, see Layout::found_list_folds
+#[derive(Resource, Default)]
+pub struct FoundListExpanded(pub bool);
+
+impl Layout {
+    // the window size everything is placed for, smaller windows zoom out to fit it
+    pub fn design_size(&self) -> Vec2 {
+        if self.portrait { Vec2::new(720., 1280.) } else { Vec2::new(1280., 720.) }
+    }
+
+    // bigger tiles are easier to hit with a finger
+    pub fn scale(&self) -> f32 {
+        if self.portrait { 1.25 } else { 1. }
+    }
+
+    pub fn tile_radius(&self) -> f32 {
+        TILE_RADIUS * self.scale()
+    }
+
+    pub fn found_list_folds(&self) -> bool {
+        self.portrait
+    }
+
+    // found words that fit between the top of the list and the bottom of the layout, header included
+    pub fn found_list_rows(&self) -> usize {
+        if self.portrait { 18 } else { 12 }
+    }
+
+    // how far the camera zooms out so the whole design size is on screen
+    pub fn zoom(&self) -> f32 {
+        let design = self.design_size();
+        (design.x / self.window_size.x).max(design.y / self.window_size.y).max(1.)
+    }
+
+    pub fn position(&self, slot: LayoutSlot) -> Vec3 {
+        match (self.portrait, slot) {
+            (false, LayoutSlot::Board) => BOARD_CENTER,
+            (false, LayoutSlot::FoundList) => BOARD_CENTER + Vec3::new(6. * TILE_RADIUS, 4.2 * TILE_RADIUS, 1.),
+            (false, LayoutSlot::Hud) => BOARD_CENTER + Vec3::new(-6. * TILE_RADIUS, 4.2 * TILE_RADIUS, 1.),
+            (false, LayoutSlot::Hints) => BOARD_CENTER + Vec3::new(-6. * TILE_RADIUS, -1.5 * TILE_RADIUS, 1.),
+            (false, LayoutSlot::Scoring) => BOARD_CENTER + Vec3::new(-6. * TILE_RADIUS, 1.5 * TILE_RADIUS, 1.),
+            // low enough to reach with a thumb, high enough to clear the buttons along the bottom
+            (true, LayoutSlot::Board) => Vec3::new(0., -160., 0.),
+            (true, LayoutSlot::FoundList) => Vec3::new(180., 610., 1.),
+            (true, LayoutSlot::Hud) => Vec3::new(-180., 610., 1.),
+            (true, LayoutSlot::Hints) => Vec3::new(0., -450., 1.),
+            (true, LayoutSlot::Scoring) => Vec3::new(0., 380., 1.),
+        }
+    }
+
+    pub fn transform(&self, slot: LayoutSlot) -> Transform {
+        Transform::from_translation(self.position(slot)).with_scale(Vec3::splat(self.scale()))
+    }
+
+    pub fn button_padding(&self) -> UiRect {
+        if self.portrait {
+            UiRect::new(Val::Px(20.), Val::Px(20.), Val::Px(12.), Val::Px(12.))
+        }
+        else {
+            UiRect::new(Val::Px(12.), Val::Px(12.), Val::Px(4.), Val::Px(4.))
+        }
+    }
+
+    // a row of buttons with the padding above, for stacking rows along the bottom
+    pub fn button_row_height(&self) -> f32 {
+        if self.portrait { 56. } else { 40. }
+    }
+}
+
+// any resize counts as a change, so everything anchored is placed again for the new size
+fn choose_layout(mut ev_resized: EventReader<WindowResized>, mut layout: ResMut<Layout>) {
+    // minimising reports a zero sized window, which has no layout worth switching to
+    if let Some(ev) = ev_resized.iter().rev().find(|ev| ev.width > 0. && ev.height > 0.) {
+        let portrait = ev.height > ev.width;
+        if layout.portrait != portrait {
+            info!("switching to {} layout", if portrait { "portrait" } else { "landscape" });
+        }
+        *layout = Layout { portrait, window_size: Vec2::new(ev.width, ev.height) };
+    }
+}
+
+fn apply_layout(layout: Res<Layout>, mut anchors: Query<(&LayoutAnchor, &mut Transform)>) {
+    if !layout.is_changed() {
+        return;
+    }
+
+    for (anchor, mut transform) in anchors.iter_mut() {
+        *transform = layout.transform(anchor.slot);
+    }
+}
+
+// zooms out rather than cropping when the window is smaller than the layout, and shrinks the UI
+// by as much so the button rows stay clear of the tiles (high contrast makes it bigger again)
+fn fit_camera(layout: Res<Layout>,
+              settings: Res<Settings>,
+              mut ui_scale: ResMut<UiScale>,
+              mut projections: Query<&mut OrthographicProjection>,
+            ) {
+    if !layout.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    let zoom = layout.zoom();
+    for mut projection in projections.iter_mut() {
+        projection.scale = zoom;
+    }
+    ui_scale.scale = ((1. / zoom).max(MIN_UI_SCALE) * settings.colours().text_scale()) as f64;
+}
+
+fn toggle_fullscreen(mut keys: ResMut<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    let alt = keys.any_pressed([KeyCode::LAlt, KeyCode::RAlt]);
+    if keys.just_pressed(KeyCode::F11) || (alt && keys.just_pressed(KeyCode::Return)) {
+        // Alt+Enter isn't a guess
+        keys.clear_just_pressed(KeyCode::Return);
+        settings.fullscreen = !settings.fullscreen;
+    }
+}
+
+// also puts the window into the saved mode once the settings have loaded
+fn apply_window_mode(settings: Res<Settings>, mut windows: ResMut<Windows>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let mode = if settings.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed };
+    if let Some(window) = windows.get_primary_mut() {
+        if window.mode() != mode {
+            window.set_mode(mode);
+        }
+    }
+}
+
+fn toggle_found_list(layout: Res<Layout>,
+                     mut expanded: ResMut<FoundListExpanded>,
+                     windows: Res<Windows>,
+                     touches: Res<Touches>,
+                     mouse: Res<Input<MouseButton>>,
+                     cameras: Query<(&Camera, &GlobalTransform)>,
+                    ) {
+    if !layout.found_list_folds() {
+        return;
+    }
+
+    let (camera, camera_transform) = cameras.single();
+    let mut taps: Vec<Vec2> = touches.iter_just_released()
+        .filter_map(|touch| touch_world_position(&windows, camera, camera_transform, touch.position()))
+        .collect();
+    if mouse.just_pressed(MouseButton::Left) {
+        taps.extend(cursor_world_position(&windows, camera, camera_transform));
+    }
+
+    // the header line hangs down from the top of the list
+    let header = layout.position(LayoutSlot::FoundList).truncate();
+    let radius = layout.tile_radius();
+    let on_header = taps.iter().any(|tap| (tap.x - header.x).abs() < 2.5 * radius && tap.y < header.y && tap.y > header.y - 0.7 * radius);
+    if on_header {
+        expanded.0 = !expanded.0;
+    }
+}
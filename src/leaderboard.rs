@@ -0,0 +1,264 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use wordgame::daily;
+use wordgame::words::GameState;
+
+use crate::focus::Focusable;
+use crate::golf::Golf;
+use crate::palette::Overlay;
+use crate::settings::Settings;
+use crate::{setup_goals, AppState, BoardEntity, DailyBoard, GameMode};
+
+// The daily leaderboard, on Ctrl+L or the button shown on daily boards. Opening it sends the
+// current score first if the player has turned submission on, then shows the day's top scores.
+// The server is whatever leaderboard_url in settings.ron points at, which needs to answer
+//   POST {url}/daily/{date}                 with a ScoreSubmission body
+//   GET  {url}/daily/{date}?player={name}   with a DailyLeaderboard body
+// Golf on the daily board has a board of its own under {url}/golf/{date}, the same requests with
+// the guesses taken in place of the score, lowest first. Only a holed out round is sent.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_leaderboard_button.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(open_leaderboard))
+            .add_system_set(SystemSet::on_enter(AppState::Leaderboard)
+                .with_system(spawn_leaderboard_screen)
+                .with_system(start_leaderboard_request))
+            .add_system_set(SystemSet::on_update(AppState::Leaderboard)
+                .with_system(poll_leaderboard_request)
+                .with_system(close_leaderboard))
+            .add_system_set(SystemSet::on_exit(AppState::Leaderboard).with_system(despawn_leaderboard_screen));
+    }
+}
+
+const TOP_SCORES_SHOWN: usize = 10;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize)]
+struct ScoreSubmission {
+    player: String,
+    score: u32,
+    words: usize,
+    seconds: u32,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct LeaderboardEntry {
+    player: String,
+    score: u32,
+    words: usize,
+    seconds: u32,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DailyLeaderboard {
+    top: Vec<LeaderboardEntry>,
+    placement: Option<u32>, // only there when the request named a player who has submitted today
+    players: u32,
+}
+
+#[derive(Resource)]
+struct LeaderboardRequest(Task<Result<DailyLeaderboard, String>>);
+
+#[derive(Component)]
+struct LeaderboardButton {}
+
+#[derive(Component)]
+struct LeaderboardRoot {}
+
+#[derive(Component)]
+struct LeaderboardText {}
+
+fn spawn_leaderboard_button(mut commands: Commands, asset_server: Res<AssetServer>, daily_board: Res<DailyBoard>) {
+    if daily_board.0.is_none() {
+        return;
+    }
+
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { right: Val::Px(12.), bottom: Val::Px(12.), ..default() },
+            padding: UiRect::new(Val::Px(12.), Val::Px(12.), Val::Px(4.), Val::Px(4.)),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(LeaderboardButton {}).insert(Focusable { state: AppState::Playing, order: 30 }).with_children(|button| {
+        button.spawn(TextBundle::from_section("Leaderboard", button_style.clone()));
+    });
+}
+
+fn open_leaderboard(keys: Res<Input<KeyCode>>,
+                    buttons: Query<&Interaction, (Changed<Interaction>, With<LeaderboardButton>)>,
+                    daily_board: Res<DailyBoard>,
+                    mut state: ResMut<State<AppState>>,
+                ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let clicked = buttons.iter().any(|interaction| *interaction == Interaction::Clicked);
+
+    if daily_board.0.is_some() && ((ctrl && keys.just_pressed(KeyCode::L)) || clicked) {
+        if let Err(err) = state.push(AppState::Leaderboard) {
+            debug!("not opening the leaderboard, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+// the golf board is kept apart, its scores run the other way
+fn board_path(mode: GameMode) -> &'static str {
+    if mode == GameMode::Golf { "golf" } else { "daily" }
+}
+
+fn spawn_leaderboard_screen(mut commands: Commands, asset_server: Res<AssetServer>, daily_board: Res<DailyBoard>, mode: Res<GameMode>) {
+    let font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let body_style = TextStyle { font: font.clone(), font_size: 32., color: Color::WHITE };
+    let help_style = TextStyle { font: font.clone(), font_size: 24., color: Color::GRAY };
+    let date = daily_board.0.map(daily::date_string).unwrap_or_default();
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(LeaderboardRoot {}).insert(Overlay {}).with_children(|parent| {
+        let title = if *mode == GameMode::Golf { format!("Daily golf {}", date) } else { format!("Daily puzzle {}", date) };
+        parent.spawn(TextBundle::from_section(title, title_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+
+        parent.spawn(TextBundle::from_section("Fetching scores...", body_style.clone())).insert(LeaderboardText {});
+
+        parent.spawn(TextBundle::from_section("Esc or Enter to go back", help_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+fn start_leaderboard_request(mut commands: Commands,
+                             settings: Res<Settings>,
+                             gamestate: Res<GameState>,
+                             daily_board: Res<DailyBoard>,
+                             mode: Res<GameMode>,
+                             golf: Res<Golf>,
+                            ) {
+    let url = format!("{}/{}/{}", settings.leaderboard_url.trim_end_matches('/'), board_path(*mode), daily::date_string(daily_board.0.unwrap_or_default()));
+    let configured = !settings.leaderboard_url.is_empty();
+    let player = if settings.submit_daily_scores { Some(settings.player_name.clone()) } else { None };
+    let submit = *mode != GameMode::Golf || golf.holed;
+    let submission = ScoreSubmission {
+        player: settings.player_name.clone(),
+        score: if *mode == GameMode::Golf { golf.strokes } else { gamestate.score() },
+        words: gamestate.correct_words.len(),
+        seconds: gamestate.time_played as u32,
+    };
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        if !configured {
+            return Err(String::from("No leaderboard set up, add a leaderboard_url to settings.ron"));
+        }
+
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+
+        // only players who opted in are sent or looked up by name
+        if player.is_some() && submit {
+            agent.post(&url).send_json(&submission).map_err(|err| format!("Could not submit score: {}", err))?;
+        }
+
+        let mut request = agent.get(&url);
+        if let Some(player) = &player {
+            request = request.query("player", player);
+        }
+        request.call()
+            .map_err(|err| format!("Could not fetch scores: {}", err))?
+            .into_json::<DailyLeaderboard>()
+            .map_err(|err| format!("Leaderboard sent something unexpected: {}", err))
+    });
+
+    commands.insert_resource(LeaderboardRequest(task));
+}
+
+fn format_time(seconds: u32) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn leaderboard_text(leaderboard: &DailyLeaderboard, submitted: bool, golf: bool) -> String {
+    let mut text = String::new();
+
+    if leaderboard.top.is_empty() {
+        text.push_str("Nobody has a score yet today");
+    }
+    let unit = if golf { "guesses" } else { "points" };
+    for (i, entry) in leaderboard.top.iter().take(TOP_SCORES_SHOWN).enumerate() {
+        text.push_str(&format!("{:>2}. {}  {} {}  {} words  {}\n", i + 1, entry.player, entry.score, unit, entry.words, format_time(entry.seconds)));
+    }
+
+    match leaderboard.placement {
+        Some(placement) => text.push_str(&format!("\nYou are #{} of {}", placement, leaderboard.players)),
+        None if !submitted => text.push_str("\nTurn on \"Submit daily scores\" in settings to join in"),
+        None => {}
+    }
+
+    text
+}
+
+fn poll_leaderboard_request(mut commands: Commands,
+                            request: Option<ResMut<LeaderboardRequest>>,
+                            settings: Res<Settings>,
+                            mode: Res<GameMode>,
+                            mut texts: Query<&mut Text, With<LeaderboardText>>,
+                        ) {
+    let mut request = match request {
+        Some(request) => request,
+        None => return,
+    };
+
+    if let Some(result) = future::block_on(future::poll_once(&mut request.0)) {
+        let message = match result {
+            Ok(leaderboard) => leaderboard_text(&leaderboard, settings.submit_daily_scores, *mode == GameMode::Golf),
+            Err(message) => {
+                warn!("{}", message);
+                message
+            }
+        };
+
+        for mut text in texts.iter_mut() {
+            text.sections[0].value = message.clone();
+        }
+        commands.remove_resource::<LeaderboardRequest>();
+    }
+}
+
+fn close_leaderboard(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::Return]) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.pop() {
+            debug!("not closing the leaderboard, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+// an unfinished request is dropped along with its task
+fn despawn_leaderboard_screen(mut commands: Commands, screens: Query<Entity, With<LeaderboardRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<LeaderboardRequest>();
+}
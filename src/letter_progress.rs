@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use wordgame::words::GameState;
+
+use crate::settings::Settings;
+use crate::{record_correct_words, relabel_board, AppState, GameMode, TileLabel};
+
+// Quiet progress on the board itself: once every answer starting with a letter has been found,
+// that tile's letter fades back. Letters that start no answers never fade, so nothing is given
+// away before it's been earned. Turned off with "Finished letters" in the settings.
+pub struct LetterProgressPlugin;
+
+impl Plugin for LetterProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Playing)
+            .with_system(fade_finished_letters.after(record_correct_words).after(relabel_board)));
+    }
+}
+
+const FINISHED_ALPHA: f32 = 0.3;
+
+fn is_finished(letter: char, gamestate: &GameState) -> bool {
+    let letter = letter.to_ascii_lowercase();
+    let mut starting = gamestate.answers.iter().filter(|answer| answer.starts_with(letter)).peekable();
+    starting.peek().is_some() && starting.all(|answer| gamestate.is_found(answer))
+}
+
+// split screen boards each have their own letters and found words, so they're left alone
+fn fade_finished_letters(gamestate: Res<GameState>,
+                         settings: Res<Settings>,
+                         mode: Res<GameMode>,
+                         added: Query<(), Added<TileLabel>>,
+                         mut labels: Query<(&TileLabel, &mut Text)>,
+                        ) {
+    if *mode == GameMode::SplitScreen || (!gamestate.is_changed() && !settings.is_changed() && added.is_empty()) {
+        return;
+    }
+
+    let letters = gamestate.target_string.as_bytes();
+    for (label, mut text) in labels.iter_mut() {
+        let finished = settings.letter_progress && letters.get(label.index).is_some_and(|letter| is_finished(*letter as char, &gamestate));
+        let alpha = if finished { FINISHED_ALPHA } else { 1. };
+        if text.sections[0].style.color.a() != alpha {
+            text.sections[0].style.color.set_a(alpha);
+        }
+    }
+}
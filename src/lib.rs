@@ -0,0 +1,10 @@
+pub mod challenge;
+pub mod daily;
+pub mod definitions;
+pub mod frequency;
+pub mod packs;
+pub mod printable;
+pub mod protocol;
+pub mod puzzle_code;
+pub mod puzzle_file;
+pub mod words;
@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+#[cfg(feature = "web")]
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadState, LoadedAsset};
+#[cfg(feature = "web")]
+use bevy::reflect::TypeUuid;
+#[cfg(not(feature = "web"))]
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+#[cfg(not(feature = "web"))]
+use futures_lite::future;
+use std::{fs, path::PathBuf};
+#[cfg(not(feature = "web"))]
+use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+use wordgame::words::WordList;
+
+use crate::AppState;
+#[cfg(not(feature = "web"))]
+use crate::mods::InstalledMods;
+use crate::recovery::{open_recovery_dialog, RecoveryAction, RecoveryChosenEvent, RecoveryDialog, RecoveryKind};
+use crate::settings::Settings;
+
+// Parses the word list on a background thread while a loading screen is up. The browser has no
+// threads to spare, so there the list is downloaded as an asset and parsed once it arrives.
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(feature = "web")]
+        app.add_asset::<WordListText>()
+            .init_asset_loader::<WordListTextLoader>();
+
+        app.init_resource::<AfterLoading>()
+            .add_system_set(SystemSet::on_enter(AppState::Loading)
+                .with_system(start_loading_words)
+                .with_system(setup_loading_screen))
+            .add_system_set(SystemSet::on_update(AppState::Loading).with_system(poll_loading_words))
+            .add_system_set(SystemSet::on_resume(AppState::Loading)
+                .with_system(apply_dictionary_recovery)
+                .with_system(start_loading_words.after(apply_dictionary_recovery)))
+            .add_system_set(SystemSet::on_exit(AppState::Loading).with_system(cleanup_loading_screen));
+    }
+}
+
+// where the game goes once the words are in, online games wait in the lobby first
+#[derive(Resource)]
+pub struct AfterLoading(pub AppState);
+
+impl Default for AfterLoading {
+    fn default() -> AfterLoading {
+        AfterLoading(AppState::Playing)
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct DictionarySource {
+    pub path: Option<PathBuf>,     // None uses the word list built into the binary
+    pub kids: bool,                // which built in list, set from the settings as loading starts
+    pub mod_path: Option<PathBuf>, // one from the mods folder picked in the settings, the command line's wins
+}
+
+impl DictionarySource {
+    fn read(&self) -> Result<String, String> {
+        match self.path.as_ref().or(self.mod_path.as_ref()) {
+            Some(path) => fs::read_to_string(path).map_err(|err| format!("Could not read word list {}: {}", path.display(), err)),
+            #[cfg(not(feature = "web"))]
+            None if self.kids => Ok(String::from(include_str!("../assets/words/kids_words.txt"))),
+            #[cfg(not(feature = "web"))]
+            None => Ok(String::from(include_str!("../assets/words/dict_words.txt"))),
+            // kept out of the download, start_loading_words fetches it instead
+            #[cfg(feature = "web")]
+            None => Err(String::from("The built in word list is loaded as an asset in the browser")),
+        }
+    }
+
+    pub fn load(&self, progress: impl FnMut(f32)) -> Result<WordList, String> {
+        DictionarySource::parse(&self.read()?, progress)
+    }
+
+    fn parse(contents: &str, progress: impl FnMut(f32)) -> Result<WordList, String> {
+        let wordlist = WordList::parse_with_progress(contents, progress);
+
+        // every board is built from a pangram, so a list without any can't make a puzzle
+        if wordlist.potential_pangrams.is_empty() {
+            Err(String::from("Word list has no words with exactly seven different letters to build a board from"))
+        }
+        else {
+            Ok(wordlist)
+        }
+    }
+}
+
+#[cfg(not(feature = "web"))]
+#[derive(Resource)]
+struct LoadingWords {
+    task: Task<Result<WordList, String>>,
+    progress: Arc<AtomicUsize>, // thousandths of the file parsed so far
+}
+
+#[cfg(feature = "web")]
+const WORDS_ASSET: &str = "words/dict_words.txt";
+#[cfg(feature = "web")]
+const KIDS_WORDS_ASSET: &str = "words/kids_words.txt";
+
+#[cfg(feature = "web")]
+#[derive(Resource)]
+struct LoadingWords {
+    handle: Handle<WordListText>,
+    path: &'static str,
+}
+
+// a word list file, as downloaded
+#[cfg(feature = "web")]
+#[derive(TypeUuid)]
+#[uuid = "6f0c3a52-9e1d-4b7a-8c2e-5d41a9b0f317"]
+struct WordListText(String);
+
+#[cfg(feature = "web")]
+#[derive(Default)]
+struct WordListTextLoader;
+
+#[cfg(feature = "web")]
+impl AssetLoader for WordListTextLoader {
+    fn load<'a>(&'a self, bytes: &'a [u8], load_context: &'a mut LoadContext) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let contents = String::from_utf8(bytes.to_vec())?;
+            load_context.set_default_asset(LoadedAsset::new(WordListText(contents)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["txt"]
+    }
+}
+
+#[derive(Component)]
+struct LoadingText {}
+
+fn log_loaded(wordlist: &WordList) {
+    info!("loaded {} words, {} potential pangrams", wordlist.all_valid_words.len(), wordlist.potential_pangrams.len());
+}
+
+// blocking version for when there is no window to keep responsive, or to ask what to do on failure
+pub fn setup_word_list(mut wordlist: ResMut<WordList>, source: Res<DictionarySource>) {
+    *wordlist = source.load(|_| {}).unwrap_or_else(|message| {
+        error!("{}, falling back to the built in word list", message);
+        DictionarySource { path: None, kids: source.kids, mod_path: None }.load(|_| {}).unwrap()
+    });
+    log_loaded(&wordlist);
+}
+
+#[cfg(not(feature = "web"))]
+fn start_loading_words(mut commands: Commands,
+                       mut source: ResMut<DictionarySource>,
+                       settings: Res<Settings>,
+                       mods: Res<InstalledMods>,
+                    ) {
+    source.kids = settings.kids_mode();
+    source.mod_path = mods.word_list_path(&settings.word_list);
+    let source = source.clone();
+    let progress = Arc::new(AtomicUsize::new(0));
+    let task_progress = progress.clone();
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        source.load(|fraction| {
+            task_progress.store((fraction * 1000.) as usize, Ordering::Relaxed);
+        })
+    });
+
+    commands.insert_resource(LoadingWords { task, progress });
+}
+
+#[cfg(feature = "web")]
+fn start_loading_words(mut commands: Commands, asset_server: Res<AssetServer>, mut source: ResMut<DictionarySource>, settings: Res<Settings>) {
+    source.kids = settings.kids_mode();
+    let path = if source.kids { KIDS_WORDS_ASSET } else { WORDS_ASSET };
+    commands.insert_resource(LoadingWords { handle: asset_server.load(path), path });
+}
+
+fn setup_loading_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: 40.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("Loading words", info_text_style).with_alignment(TextAlignment::CENTER),
+        ..default()
+    }).insert(LoadingText {});
+}
+
+// another transition may already be queued this frame, in which case the result waits for next frame
+fn finish_loading(finished: &mut Option<Result<WordList, String>>,
+                  commands: &mut Commands,
+                  wordlist: &mut WordList,
+                  state: &mut State<AppState>,
+                  source: &DictionarySource,
+                  after_loading: &AfterLoading,
+                ) {
+    match finished.take() {
+        Some(Ok(parsed)) => match state.set(after_loading.0) {
+            Ok(()) => {
+                *wordlist = parsed;
+                log_loaded(wordlist);
+            }
+            Err(_) => *finished = Some(Ok(parsed)),
+        },
+        Some(Err(message)) => {
+            let mut actions = vec![RecoveryAction::Retry];
+            if source.path.is_some() || source.mod_path.is_some() {
+                actions.push(RecoveryAction::UseDefaults);
+            }
+            let dialog = RecoveryDialog { kind: RecoveryKind::Dictionary, message: message.clone(), actions };
+            if open_recovery_dialog(commands, state, dialog).is_err() {
+                *finished = Some(Err(message));
+            }
+        }
+        None => {}
+    }
+}
+
+#[cfg(not(feature = "web"))]
+fn poll_loading_words(mut commands: Commands,
+                      loading: Option<ResMut<LoadingWords>>,
+                      mut wordlist: ResMut<WordList>,
+                      mut state: ResMut<State<AppState>>,
+                      source: Res<DictionarySource>,
+                      after_loading: Res<AfterLoading>,
+                      mut loading_text: Query<&mut Text, With<LoadingText>>,
+                      mut finished: Local<Option<Result<WordList, String>>>,
+                    ) {
+    if finished.is_none() {
+        // the task resource is inserted by a command, so it may not exist on the very first frame
+        let mut loading = match loading {
+            Some(loading) => loading,
+            None => return,
+        };
+
+        if let Some(result) = future::block_on(future::poll_once(&mut loading.task)) {
+            commands.remove_resource::<LoadingWords>();
+            *finished = Some(result);
+        }
+        else {
+            let message = format!("Loading words {}%", loading.progress.load(Ordering::Relaxed) / 10);
+            for mut text in loading_text.iter_mut() {
+                if text.sections[0].value != message {
+                    text.sections[0].value = message.clone();
+                }
+            }
+            return;
+        }
+    }
+
+    finish_loading(&mut finished, &mut commands, &mut wordlist, &mut state, &source, &after_loading);
+}
+
+#[cfg(feature = "web")]
+fn poll_loading_words(mut commands: Commands,
+                      loading: Option<Res<LoadingWords>>,
+                      asset_server: Res<AssetServer>,
+                      texts: Res<Assets<WordListText>>,
+                      mut wordlist: ResMut<WordList>,
+                      mut state: ResMut<State<AppState>>,
+                      source: Res<DictionarySource>,
+                      after_loading: Res<AfterLoading>,
+                      mut finished: Local<Option<Result<WordList, String>>>,
+                    ) {
+    if finished.is_none() {
+        let loading = match loading {
+            Some(loading) => loading,
+            None => return,
+        };
+
+        let result = match asset_server.get_load_state(&loading.handle) {
+            LoadState::Loaded => match texts.get(&loading.handle) {
+                Some(text) => DictionarySource::parse(&text.0, |_| {}),
+                None => return,
+            },
+            LoadState::Failed => Err(format!("Could not download the word list {}", loading.path)),
+            _ => return,
+        };
+
+        commands.remove_resource::<LoadingWords>();
+        *finished = Some(result);
+    }
+
+    finish_loading(&mut finished, &mut commands, &mut wordlist, &mut state, &source, &after_loading);
+}
+
+// the mods folder's list is forgotten in the settings too, or it would be picked again
+fn apply_dictionary_recovery(mut ev_recovery_chosen: EventReader<RecoveryChosenEvent>,
+                             mut source: ResMut<DictionarySource>,
+                             mut settings: ResMut<Settings>,
+                            ) {
+    for ev in ev_recovery_chosen.iter() {
+        if ev.kind == RecoveryKind::Dictionary && ev.action == RecoveryAction::UseDefaults {
+            source.path = None;
+            source.mod_path = None;
+            settings.word_list.clear();
+        }
+    }
+}
+
+fn cleanup_loading_screen(mut commands: Commands, loading_text: Query<Entity, With<LoadingText>>) {
+    for entity in loading_text.iter() {
+        commands.entity(entity).despawn();
+    }
+}
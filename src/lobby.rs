@@ -0,0 +1,300 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+
+use crate::loading::AfterLoading;
+use crate::net::{NetInbox, NetMessage, NetPeer, NetRole, PeerConnectedEvent};
+use crate::palette::Overlay;
+use crate::{AppState, GameMode, GameRng};
+
+// The lobby both players wait in before an online game, once the words have loaded. The host's
+// room code stands for its address and port, so --join can take the code in place of an address.
+// The host picks co-op or race (M) and the seed the board is picked from (S for another), and
+// each player readies up with Enter, changing either setting unreadies everyone. Once both are
+// ready a short countdown runs on both machines, and each picks the same board from the agreed
+// seed. Esc leaves the lobby to play alone, as if nobody had connected yet.
+pub struct LobbyPlugin;
+
+impl Plugin for LobbyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AfterLoading(AppState::Lobby))
+            .init_resource::<Lobby>()
+            .add_system(greet_peer)
+            .add_system_set(SystemSet::on_enter(AppState::Lobby).with_system(spawn_lobby))
+            .add_system_set(SystemSet::on_update(AppState::Lobby)
+                .with_system(change_lobby)
+                .with_system(receive_lobby_messages)
+                .with_system(count_down.after(change_lobby).after(receive_lobby_messages))
+                .with_system(show_lobby.after(count_down)))
+            .add_system_set(SystemSet::on_exit(AppState::Lobby).with_system(despawn_lobby));
+    }
+}
+
+const COUNTDOWN_SECONDS: f32 = 3.;
+const ROOM_CODE_LETTERS: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ"; // Crockford's, no I, L, O or U to misread
+
+#[derive(Resource)]
+struct Lobby {
+    seed: u64,
+    mode: GameMode, // co-op or race
+    ready: [bool; 2], // by player, the host is player one
+    countdown: Option<f32>,
+    local_address: Option<Ipv4Addr>, // the host's, looked up once on entering the lobby
+}
+
+impl Default for Lobby {
+    fn default() -> Lobby {
+        Lobby { seed: rand::random(), mode: GameMode::Coop, ready: [false; 2], countdown: None, local_address: None }
+    }
+}
+
+#[derive(Component)]
+struct LobbyRoot {}
+
+#[derive(Component)]
+struct LobbyText {}
+
+// ten letters for the four bytes of an IPv4 address and two of the port, in two groups of five
+pub fn room_code(addr: SocketAddrV4) -> String {
+    let bits = addr.ip().octets().iter().fold(0_u64, |bits, octet| bits << 8 | *octet as u64) << 16 | addr.port() as u64;
+    let letters: String = (0..10).rev().map(|i| ROOM_CODE_LETTERS[(bits >> (i * 5) & 31) as usize] as char).collect();
+    format!("{}-{}", &letters[..5], &letters[5..])
+}
+
+pub fn room_address(code: &str) -> Option<SocketAddrV4> {
+    if code.len() != 11 || code.as_bytes()[5] != b'-' {
+        return None;
+    }
+    let letters = code[..5].bytes().chain(code[6..].bytes()).map(|letter| letter.to_ascii_uppercase());
+
+    let mut bits = 0_u64;
+    for letter in letters {
+        bits = bits << 5 | ROOM_CODE_LETTERS.iter().position(|known| *known == letter)? as u64;
+    }
+    let ip = (bits >> 16) as u32;
+    Some(SocketAddrV4::new(Ipv4Addr::from(ip), bits as u16))
+}
+
+// the address the machine would reach the outside from, connecting a UDP socket sends nothing
+fn local_address() -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    match socket.local_addr().ok()?.ip() {
+        std::net::IpAddr::V4(ip) => Some(ip),
+        std::net::IpAddr::V6(_) => None,
+    }
+}
+
+fn local_player(role: &NetRole) -> usize {
+    if matches!(role, NetRole::Host { .. }) { 0 } else { 1 }
+}
+
+// whoever joins is told what the host has picked so far, unless the host has gone off to play
+// alone, then they're sent that board instead
+fn greet_peer(mut ev_peer_connected: EventReader<PeerConnectedEvent>,
+              lobby: Res<Lobby>,
+              peer: Option<ResMut<NetPeer>>,
+              state: Res<State<AppState>>,
+            ) {
+    let mut peer = match peer {
+        Some(peer) if matches!(state.current(), AppState::Loading | AppState::Lobby) => peer,
+        _ => return,
+    };
+
+    for _ev in ev_peer_connected.iter() {
+        peer.send(&NetMessage::Lobby { seed: lobby.seed, mode: lobby.mode.code_index() });
+        peer.send(&NetMessage::Ready { ready: lobby.ready[0] });
+    }
+}
+
+fn spawn_lobby(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, role: Res<NetRole>, mut lobby: ResMut<Lobby>) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let body_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 36., color: Color::WHITE };
+
+    // the host's choice from the command line, when it's one that's played online
+    if matches!(*mode, GameMode::Coop | GameMode::Race) {
+        lobby.mode = *mode;
+    }
+    if matches!(*role, NetRole::Host { .. }) {
+        lobby.local_address = local_address();
+    }
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(LobbyRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Lobby", title_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section("", body_style).with_text_alignment(TextAlignment::CENTER)).insert(LobbyText {});
+    });
+}
+
+fn change_lobby(mut keys: ResMut<Input<KeyCode>>,
+                role: Res<NetRole>,
+                mut lobby: ResMut<Lobby>,
+                mut after_loading: ResMut<AfterLoading>,
+                peer: Option<ResMut<NetPeer>>,
+                mut state: ResMut<State<AppState>>,
+            ) {
+    if keys.just_pressed(KeyCode::Escape) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        match state.set(AppState::Playing) {
+            Ok(()) => after_loading.0 = AppState::Playing,
+            Err(err) => debug!("not leaving the lobby, the state is already changing: {:?}", err),
+        }
+        return;
+    }
+    if lobby.countdown.is_some() {
+        return;
+    }
+
+    let host = matches!(*role, NetRole::Host { .. });
+    let mut settings_changed = false;
+    if host && keys.just_pressed(KeyCode::M) {
+        lobby.mode = if lobby.mode == GameMode::Coop { GameMode::Race } else { GameMode::Coop };
+        settings_changed = true;
+    }
+    if host && keys.just_pressed(KeyCode::S) {
+        lobby.seed = rand::random();
+        settings_changed = true;
+    }
+
+    let mut peer = peer;
+    if settings_changed {
+        lobby.ready = [false; 2];
+        if let Some(peer) = peer.as_mut() {
+            peer.send(&NetMessage::Lobby { seed: lobby.seed, mode: lobby.mode.code_index() });
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        keys.clear_just_pressed(KeyCode::Return);
+        let player = local_player(&role);
+        lobby.ready[player] = !lobby.ready[player];
+        if let Some(peer) = peer.as_mut() {
+            peer.send(&NetMessage::Ready { ready: lobby.ready[player] });
+        }
+    }
+
+    // the host starts the countdown for both, so they begin together
+    if host && lobby.ready == [true; 2] {
+        if let Some(peer) = peer.as_mut() {
+            peer.send(&NetMessage::Start);
+            lobby.countdown = Some(COUNTDOWN_SECONDS);
+        }
+    }
+}
+
+fn receive_lobby_messages(mut inbox: ResMut<NetInbox>,
+                          role: Res<NetRole>,
+                          mut lobby: ResMut<Lobby>,
+                          mut after_loading: ResMut<AfterLoading>,
+                          mut state: ResMut<State<AppState>>,
+                        ) {
+    // a host already playing sends its board straight away, join_board picks it up, a relay
+    // sends one as the countdown starts and it's picked up once the countdown is over
+    if inbox.has_new_board() && lobby.countdown.is_none() {
+        // another transition may already be queued this frame, in which case try again next frame
+        if state.set(AppState::Playing).is_ok() {
+            after_loading.0 = AppState::Playing;
+        }
+        return;
+    }
+
+    let other = 1 - local_player(&role);
+    for message in inbox.take(|message| matches!(message, NetMessage::Lobby { .. } | NetMessage::Ready { .. } | NetMessage::Start)) {
+        match message {
+            NetMessage::Lobby { seed, mode } => {
+                lobby.seed = seed;
+                lobby.mode = GameMode::from_code_index(mode).unwrap_or(GameMode::Coop);
+                lobby.ready = [false; 2];
+            }
+            NetMessage::Ready { ready } => lobby.ready[other] = ready,
+            NetMessage::Start => lobby.countdown = Some(COUNTDOWN_SECONDS),
+            _ => {}
+        }
+    }
+}
+
+// both sides pick the board from the same seed, and the joiner keeps it when the host's arrives
+fn count_down(time: Res<Time>,
+              mut lobby: ResMut<Lobby>,
+              mut mode: ResMut<GameMode>,
+              mut game_rng: ResMut<GameRng>,
+              mut after_loading: ResMut<AfterLoading>,
+              mut state: ResMut<State<AppState>>,
+            ) {
+    let seconds_left = match lobby.countdown {
+        Some(seconds_left) => seconds_left - time.delta_seconds(),
+        None => return,
+    };
+    lobby.countdown = Some(seconds_left);
+    if seconds_left > 0. {
+        return;
+    }
+    // another transition may already be queued this frame, in which case the countdown stays
+    // run out and this is tried again next frame
+    if state.set(AppState::Playing).is_err() {
+        return;
+    }
+
+    info!("starting {:?} from seed {}", lobby.mode, lobby.seed);
+    *mode = lobby.mode;
+    game_rng.0 = StdRng::seed_from_u64(lobby.seed);
+    after_loading.0 = AppState::Playing;
+}
+
+fn show_lobby(role: Res<NetRole>, lobby: Res<Lobby>, peer: Option<Res<NetPeer>>, mut texts: Query<&mut Text, With<LobbyText>>) {
+    let host = matches!(*role, NetRole::Host { .. });
+    let mut lines = Vec::new();
+
+    match &*role {
+        NetRole::Host { port } => match lobby.local_address {
+            Some(ip) => lines.push(format!("Room code {}, give it to the other player", room_code(SocketAddrV4::new(ip, *port)))),
+            None => lines.push(format!("Hosting on port {}", port)),
+        },
+        NetRole::Join { addr } | NetRole::Spectate { addr } => lines.push(format!("Room at {}", addr)),
+    }
+    let mode = if lobby.mode == GameMode::Coop { "Co-op" } else { "Race" };
+    lines.push(format!("{}, board seed {}", mode, lobby.seed));
+    lines.push(String::new());
+
+    let ready = |player: usize| if lobby.ready[player] { "ready" } else { "not ready" };
+    lines.push(format!("Player 1 (host), {}", ready(0)));
+    lines.push(match peer.is_some() {
+        true => format!("Player 2, {}", ready(1)),
+        false if host => String::from("Player 2, waiting for them to join"),
+        false => String::from("Player 2, joining..."),
+    });
+    lines.push(String::new());
+
+    lines.push(match lobby.countdown {
+        Some(seconds_left) => format!("Starting in {}", seconds_left.max(0.).ceil() as u32),
+        None if host => String::from("Enter to ready up, M for co-op or race, S for another seed, Esc to play alone"),
+        None => String::from("Enter to ready up, Esc to play alone"),
+    });
+
+    let text_value = lines.join("\n");
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != text_value {
+            text.sections[0].value = text_value.clone();
+        }
+    }
+}
+
+fn despawn_lobby(mut commands: Commands, screens: Query<Entity, With<LobbyRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
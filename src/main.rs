@@ -1,53 +1,547 @@
+// systems take each resource and query they use as an argument, and queries spell out their filters
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::app::ScheduleRunnerSettings;
 use bevy::prelude::*;
+use bevy::window::WindowResizeConstraints;
 use bevy_prototype_lyon::prelude::*;
-use std::{fs};
-use rand::seq::SliceRandom;
+use clap::{Parser, ValueEnum};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::collections::HashMap;
+use wordgame::daily;
+use wordgame::frequency::WordFrequency;
+use wordgame::puzzle_code::PuzzleCode;
+use wordgame::words::*;
+
+use crate::board_layout::BoardLayout;
+use crate::bonus::BonusRound;
+use crate::cooldown::GuessCooldown;
+use crate::layout::{FoundListExpanded, Layout, LayoutAnchor, LayoutSlot};
+use crate::palette::{Colours, PaletteTile, RequiredMarker, REQUIRED_LETTER_SIZE};
+use crate::settings::Settings;
+use crate::tween::{Easing, Track, Tween, TweenFinishedEvent};
+
+mod achievement_popup;
+mod achievements;
+#[cfg(feature = "audio")]
+mod audio;
+mod blind;
+mod board_layout;
+mod bonus;
+mod camera_shake;
+mod captions;
+mod celebration;
+mod challenge_menu;
+#[cfg(feature = "cheats")]
+mod cheats;
+mod cli;
+mod code_menu;
+mod combo;
+#[cfg(feature = "dev")]
+mod console;
+mod cooldown;
+mod crash;
+#[cfg(feature = "online")]
+mod coop;
+mod daily_history;
+mod drop_files;
+mod endless;
+#[cfg(feature = "particles")]
+mod fireworks;
+mod focus;
+mod ghost;
+mod golf;
+#[cfg(not(feature = "web"))]
+mod haptics;
+mod hints;
+mod hotseat;
+mod interchange;
+mod kids;
+mod layout;
+mod letter_progress;
+#[cfg(feature = "online")]
+mod leaderboard;
+mod loading;
+#[cfg(feature = "online")]
+mod lobby;
+mod marathon;
+mod mega;
+mod mods;
+#[cfg(feature = "audio")]
+mod music;
+#[cfg(feature = "online")]
+mod net;
+#[cfg(feature = "notifications")]
+mod notifications;
+mod pack_menu;
+mod palette;
+mod pause;
+#[cfg(feature = "online")]
+mod race;
+mod recovery;
+mod rotation;
+mod scoring;
+#[cfg(feature = "screen_reader")]
+mod screen_reader;
+mod settings;
+mod settings_menu;
+#[cfg(not(feature = "web"))]
+mod share_image;
+#[cfg(not(feature = "web"))]
+mod simulate;
+#[cfg(feature = "online")]
+mod spectate;
+mod speedrun;
+mod split_screen;
+mod stats;
+mod stats_screen;
+#[cfg(feature = "status_server")]
+mod status_server;
+#[cfg(feature = "steam")]
+mod steam;
+mod storage;
+mod streak;
+mod streamer;
+mod sync;
+mod summary;
+#[cfg(feature = "online")]
+mod telemetry;
+mod time_attack;
+mod touch;
+mod tutorial;
+mod tween;
+#[cfg(feature = "online")]
+mod twitch;
+#[cfg(feature = "online")]
+mod updates;
+mod versus;
+#[cfg(feature = "web")]
+mod web;
+mod word_echo;
+#[cfg(feature = "dev")]
+mod inspector;
 
+// the browser build is `cargo build --target wasm32-unknown-unknown --no-default-features --features web,audio,particles`,
+// see web.rs for the rest
+#[cfg(all(target_arch = "wasm32", not(feature = "web")))]
+compile_error!("building for the browser needs the web feature");
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+compile_error!("the web feature is only for wasm32 builds");
+#[cfg(all(feature = "web", feature = "online"))]
+compile_error!("online play uses plain sockets and isn't available in the browser");
+#[cfg(all(feature = "web", feature = "cloud_sync"))]
+compile_error!("cloud sync uses blocking requests and isn't available in the browser");
+#[cfg(all(feature = "web", feature = "steam"))]
+compile_error!("Steam needs the desktop client and isn't available in the browser");
+#[cfg(all(feature = "web", feature = "status_server"))]
+compile_error!("the status endpoint listens on a socket and isn't available in the browser");
+
+// phones start here, through the entry points bevy_main adds for Android and iOS
+#[bevy_main]
 fn main() {
-    App::new()
-        .insert_resource(Msaa { samples: 4 })
-        .insert_resource(WordList { all_valid_words: Vec::new(), potential_pangrams: Vec::new() })
-        .insert_resource(GameState { target_string: String::new(), target_bits: 0, required_letter: ' ', required_bit: 0, correct_words: Vec::new() })
-        .add_event::<LetterAcceptedEvent>()
-        .add_event::<WordAcceptedEvent>()
-        .add_event::<WordRejectedEvent>()
-        .add_plugins(DefaultPlugins)
-        .add_plugin(ShapePlugin)
-        .add_startup_system(setup_word_list)
-        .add_startup_system(setup_goals.after(setup_word_list))
-        .add_startup_system(setup_shapes.after(setup_goals))
-        .add_system(chose_letter)
-        .add_system(add_letter)
-        .add_system(guess_word)
-        .add_system(wrong_word_hint)
-        .add_system(show_correct_words)
-        .add_system(squish_effects)
-        .run();
+    let args = cli::Args::parse();
+
+    #[cfg(not(feature = "web"))]
+    if let Some(boards) = args.simulate {
+        simulate::run(&loading::DictionarySource { path: args.dictionary.clone(), kids: false, mod_path: None }, args.seed, boards);
+        return;
+    }
+
+    // a challenge brings its own board along
+    let mut next_puzzle = NextPuzzle(args.code);
+    let mut active_challenge = challenge_menu::ActiveChallenge(None);
+    if let Some(path) = &args.challenge {
+        match challenge_menu::load_challenge(path) {
+            Ok(challenge) => {
+                next_puzzle.0 = challenge.puzzle().ok();
+                active_challenge.0 = Some(challenge);
+            }
+            Err(message) => eprintln!("{}", message),
+        }
+    }
+
+    // and so does an imported puzzle, maybe with its own answers
+    let mut imported_puzzle = interchange::ImportedPuzzle(None);
+    if let Some(path) = &args.import {
+        match interchange::load_puzzle_file(path) {
+            Ok(file) => {
+                next_puzzle.0 = file.puzzle().ok();
+                imported_puzzle.0 = Some(file);
+            }
+            Err(message) => eprintln!("{}", message),
+        }
+    }
+
+    let mut app = App::new();
+    app.insert_resource(WordList { all_valid_words: Vec::new(), potential_pangrams: Vec::new() })
+        .init_resource::<GameState>()
+        .insert_resource(loading::DictionarySource { path: args.dictionary.clone(), kids: false, mod_path: None })
+        .insert_resource(GameRng::new(args.seed))
+        .insert_resource(next_puzzle)
+        .insert_resource(active_challenge)
+        .insert_resource(imported_puzzle)
+        .init_resource::<DailyBoard>()
+        .init_resource::<ChatCredits>()
+        .insert_resource(marathon::MarathonRules { boards: args.marathon_boards, target_rank: args.marathon_rank })
+        .insert_resource(time_attack::TimeAttackRules { words_per_board: args.time_attack_words })
+        .insert_resource(endless::EndlessRules { target_rank: args.endless_rank })
+        .insert_resource(cooldown::CooldownRules { seconds: args.guess_cooldown })
+        .insert_resource(streak::StreakRules { enabled: args.streak_multiplier })
+        .insert_resource(combo::ComboRules { enabled: args.combo })
+        .insert_resource(ghost::GhostRules { enabled: args.ghost })
+        .insert_resource(speedrun::SpeedrunRules { enabled: args.speedrun })
+        .insert_resource(versus::BotRules { level: args.bot_level })
+        .insert_resource(rotation::RotationRules { minutes: args.rotate_required })
+        .insert_resource(RarityRules { rarity_modes: args.rarity_scoring.clone() })
+        .init_resource::<scoring::ScoringConfig>()
+        .init_resource::<WordValidators>()
+        .insert_resource(args.mode);
+
+    if args.headless {
+        app.insert_resource(ScheduleRunnerSettings::run_once())
+            .add_plugins(MinimalPlugins)
+            .add_plugin(args.log_plugin())
+            .add_startup_system(loading::setup_word_list)
+            .add_startup_system(setup_goals.after(loading::setup_word_list));
+    }
+    else {
+        app.insert_resource(Msaa { samples: 4 })
+            .add_event::<LetterAcceptedEvent>()
+            .add_event::<LetterRefusedEvent>()
+            .add_event::<WordAcceptedEvent>()
+            .add_event::<WordRejectedEvent>()
+            .add_event::<GuessErasedEvent>()
+            .add_event::<TileHoveredEvent>()
+            .add_event::<GuessActionEvent>()
+            .add_event::<BoardRefreshedEvent>()
+            .add_event::<SpeakBoardEvent>()
+            .add_plugins(DefaultPlugins
+                .set(WindowPlugin {
+                    window: WindowDescriptor {
+                        width: args.width.max(layout::MIN_WINDOW_SIZE.x),
+                        height: args.height.max(layout::MIN_WINDOW_SIZE.y),
+                        transparent: args.streamer == Some(streamer::StreamerBackground::Transparent),
+                        resize_constraints: WindowResizeConstraints {
+                            min_width: layout::MIN_WINDOW_SIZE.x,
+                            min_height: layout::MIN_WINDOW_SIZE.y,
+                            ..default()
+                        },
+                        #[cfg(feature = "web")]
+                        canvas: Some(String::from(web::CANVAS_SELECTOR)),
+                        #[cfg(feature = "web")]
+                        fit_canvas_to_parent: true,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .set(args.log_plugin()))
+            .add_plugin(ShapePlugin)
+            .add_plugin(layout::LayoutPlugin)
+            .add_plugin(loading::LoadingPlugin)
+            .add_plugin(recovery::RecoveryPlugin)
+            .add_plugin(settings::SettingsPlugin)
+            .add_plugin(sync::SyncPlugin)
+            .add_plugin(settings_menu::SettingsMenuPlugin)
+            .add_plugin(palette::PalettePlugin)
+            .add_plugin(mods::ModsPlugin)
+            .add_plugin(crash::CrashPlugin)
+            .add_plugin(tutorial::TutorialPlugin)
+            .add_plugin(tween::TweenPlugin)
+            .add_plugin(focus::FocusPlugin)
+            .add_plugin(achievements::AchievementsPlugin)
+            .add_plugin(achievement_popup::AchievementPopupPlugin)
+            .add_plugin(stats::StatsPlugin)
+            .add_plugin(stats_screen::StatsScreenPlugin)
+            .add_plugin(summary::SummaryPlugin)
+            .add_plugin(daily_history::DailyHistoryPlugin)
+            .add_plugin(captions::CaptionsPlugin)
+            .add_plugin(bonus::BonusPlugin)
+            .add_plugin(kids::KidsPlugin)
+            .add_plugin(hints::HintsPlugin)
+            .add_plugin(letter_progress::LetterProgressPlugin)
+            .add_plugin(word_echo::WordEchoPlugin)
+            .add_plugin(camera_shake::CameraShakePlugin)
+            .add_plugin(code_menu::CodeMenuPlugin)
+            .add_plugin(drop_files::DropFilesPlugin)
+            .add_plugin(challenge_menu::ChallengeMenuPlugin)
+            .add_plugin(pack_menu::PackMenuPlugin)
+            .add_plugin(scoring::ScoringPlugin)
+            .add_plugin(hotseat::HotseatPlugin)
+            .add_plugin(marathon::MarathonPlugin)
+            .add_plugin(time_attack::TimeAttackPlugin)
+            .add_plugin(endless::EndlessPlugin)
+            .add_plugin(blind::BlindPlugin)
+            .add_plugin(cooldown::CooldownPlugin)
+            .add_plugin(streak::StreakPlugin)
+            .add_plugin(combo::ComboPlugin)
+            .add_plugin(ghost::GhostPlugin)
+            .add_plugin(versus::VersusPlugin)
+            .add_plugin(mega::MegaPlugin)
+            .add_plugin(golf::GolfPlugin)
+            .add_plugin(speedrun::SpeedrunPlugin)
+            .add_plugin(rotation::RotationPlugin)
+            .add_plugin(celebration::CelebrationPlugin)
+            .add_plugin(pause::PausePlugin)
+            .add_plugin(split_screen::SplitScreenPlugin)
+            .add_plugin(touch::TouchPlugin)
+            .add_state(AppState::Loading)
+            .add_startup_system(setup_camera)
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(setup_goals)
+                .with_system(setup_shapes.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(chose_letter)
+                .with_system(hover_tiles)
+                .with_system(add_letter)
+                .with_system(guess_word)
+                .with_system(show_guess.after(add_letter).after(guess_word))
+                .with_system(clear_rejected_guess)
+                .with_system(wrong_word_hint)
+                .with_system(record_correct_words)
+                .with_system(show_correct_words.after(record_correct_words))
+                .with_system(relabel_board)
+                .with_system(swap_labels.after(relabel_board))
+                .with_system(tick_time_played)
+                .with_system(show_session_clock.after(tick_time_played))
+                .with_system(record_rank_times.after(record_correct_words)))
+            .add_system_set(SystemSet::on_exit(AppState::Playing).with_system(clear_board))
+            .add_system_set(SystemSet::on_update(AppState::NewBoard).with_system(enter_new_board));
+
+        #[cfg(feature = "online")]
+        app.add_plugin(leaderboard::LeaderboardPlugin)
+            .add_plugin(telemetry::TelemetryPlugin)
+            .add_plugin(updates::UpdatesPlugin);
+
+        #[cfg(feature = "online")]
+        if let Some(role) = args.net_role() {
+            // a spectator only watches, neither mode's side of the game is played there
+            let spectating = matches!(role, net::NetRole::Spectate { .. });
+            app.insert_resource(role)
+                .add_plugin(net::NetPlugin)
+                .add_plugin(spectate::SpectatePlugin);
+            if !spectating {
+                app.add_plugin(coop::CoopPlugin)
+                    .add_plugin(race::RacePlugin)
+                    .add_plugin(lobby::LobbyPlugin);
+            }
+        }
+
+        #[cfg(feature = "online")]
+        if let Some(channel) = args.twitch_channel.clone() {
+            app.insert_resource(twitch::TwitchChannel(channel))
+                .add_plugin(twitch::TwitchPlugin);
+        }
+
+        if let Some(background) = args.streamer {
+            app.insert_resource(background)
+                .add_plugin(streamer::StreamerPlugin);
+        }
+
+        if let Some(path) = &args.export {
+            app.insert_resource(interchange::ExportPath(path.clone()))
+                .add_plugin(interchange::InterchangePlugin);
+        }
+
+        #[cfg(feature = "audio")]
+        app.add_plugin(audio::SoundEffectsPlugin)
+            .add_plugin(music::MusicPlugin);
+
+        #[cfg(feature = "particles")]
+        app.add_plugin(fireworks::FireworksPlugin);
+
+        #[cfg(not(feature = "web"))]
+        app.add_plugin(haptics::HapticsPlugin);
+
+        #[cfg(feature = "steam")]
+        app.add_plugin(steam::SteamPlugin);
+
+        #[cfg(feature = "notifications")]
+        app.add_plugin(notifications::NotificationsPlugin);
+
+        #[cfg(feature = "screen_reader")]
+        app.add_plugin(screen_reader::ScreenReaderPlugin);
+
+        #[cfg(feature = "status_server")]
+        app.insert_resource(status_server::StatusPort(args.status_port))
+            .add_plugin(status_server::StatusServerPlugin);
+
+        #[cfg(feature = "cheats")]
+        app.add_plugin(cheats::CheatsPlugin);
+
+        // the console draws with the inspector's egui
+        #[cfg(feature = "dev")]
+        app.add_plugin(inspector::InspectorPlugin)
+            .add_plugin(console::ConsolePlugin);
+
+        if args.mode == GameMode::Blind && !app.world.contains_resource::<blind::LetterVoice>() {
+            eprintln!("Blind mode needs the screen reader, build with --features screen_reader and check a speech engine is installed");
+            return;
+        }
+    }
+
+    app.run();
+}
+
+#[derive(Resource, ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum GameMode {
+    Classic,
+    Hotseat,     // two players taking turns at one keyboard
+    Daily,       // the same board for everyone, changing at midnight UTC
+    Coop,        // two players on different machines sharing one board
+    Race,        // two players on different machines racing on the same board
+    SplitScreen, // two boards side by side, keyboard against gamepad
+    Marathon,    // a run of boards against one shared clock
+    TimeAttack,  // beat the clock, every few words swaps in a new board and adds time
+    Endless,     // new boards for as long as you like, each one raising a score multiplier
+    Blind,       // the letters are only read out, never shown
+    Versus,      // against a bot on the same board, see versus.rs
+    Mega,        // experimental, nineteen tiles and two required letters, see mega.rs
+    Golf,        // the daily board in as few guesses as possible, see golf.rs
+}
+
+impl GameMode {
+    fn player_count(&self) -> usize {
+        match self {
+            GameMode::Hotseat | GameMode::Coop => 2,
+            _ => 1,
+        }
+    }
+
+    // puzzle codes store the mode by position, so new modes go on the end
+    fn code_index(&self) -> u8 {
+        GameMode::value_variants().iter().position(|mode| mode == self).unwrap() as u8
+    }
+
+    fn from_code_index(index: u8) -> Option<GameMode> {
+        GameMode::value_variants().get(index as usize).copied()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum AppState {
+    Loading,
+    Playing,
+    Recovery,
+    Settings,
+    PuzzleCode,
+    NewBoard, // passed through on the way to a fresh board, see NextPuzzle
+    ChallengeResult,
+    PackMenu,
+    Summary,
+    Yesterday,
+    Celebration,
+    Paused,
+    Stats,
+    DroppedFile,
+    VersusOver,
+    #[cfg(feature = "online")]
+    Leaderboard,
+    #[cfg(feature = "online")]
+    RaceOver,
+    #[cfg(feature = "online")]
+    Lobby,
 }
 
 #[derive(Resource)]
-struct WordList {
-    all_valid_words: Vec<String>,
-    potential_pangrams: Vec<String>, // Will be a subset of all words since these are also valid
+struct GameRng(StdRng);
+
+impl GameRng {
+    fn new(seed: Option<u64>) -> GameRng {
+        match seed {
+            Some(seed) => GameRng(StdRng::seed_from_u64(seed)),
+            None => GameRng(StdRng::from_entropy()),
+        }
+    }
 }
 
+// the board to play next instead of a random one, from --code or the puzzle code screen
+#[derive(Resource)]
+struct NextPuzzle(Option<PuzzleCode>);
+
+// the day number when the current board is that day's daily puzzle
+#[derive(Resource, Default)]
+struct DailyBoard(Option<u64>);
+
+// who in Twitch chat found each word, by the word, see twitch.rs
+#[derive(Resource, Default)]
+struct ChatCredits(HashMap<String, String>);
+
+// the modes played with rarity scoring, from --rarity-scoring, see GameState::weigh_by_rarity
 #[derive(Resource)]
-struct GameState {
-    target_string: String,
-    target_bits: u32,
-    required_letter: char,
-    required_bit: u32,
-    correct_words: Vec<String>,
+struct RarityRules {
+    rarity_modes: Vec<GameMode>,
+}
+
+// a mega board doesn't fit, so encoding its code gives an error rather than some other board
+fn current_puzzle_code(gamestate: &GameState, mode: GameMode) -> PuzzleCode {
+    PuzzleCode { letter_bits: gamestate.target_bits, required_letter: gamestate.required_letter, mode: Some(mode.code_index()) }
 }
 
+// everything spawned for one board, cleared when the board is left for good
+#[derive(Component)]
+struct BoardEntity {}
+
 #[derive(Component)]
 struct LetterTile {
     letter: char,
+    index: usize, // into the board's target_string, 0 is the middle
+}
+
+// the letter drawn on a tile, by the same index
+#[derive(Component)]
+struct TileLabel {
+    index: usize,
 }
 
 #[derive(Component)]
 struct TriedWord {
     current: String,
+    rejected: Option<RejectReason>, // the word just turned down, left up while it flashes
+    required_letters: usize,
+    off_board: usize,
+}
+
+// the counts behind the guess line's preview are kept a letter at a time, the board only changes
+// under a cleared guess
+impl TriedWord {
+    fn push(&mut self, letter: char, gamestate: &GameState) {
+        let letter = letter.to_ascii_uppercase();
+        if letter == gamestate.required_letter.to_ascii_uppercase() {
+            self.required_letters += 1;
+        }
+        if !on_board(letter, gamestate) {
+            self.off_board += 1;
+        }
+        self.current.push(letter);
+    }
+
+    fn pop(&mut self, gamestate: &GameState) {
+        if let Some(letter) = self.current.pop() {
+            if letter == gamestate.required_letter.to_ascii_uppercase() {
+                self.required_letters -= 1;
+            }
+            if !on_board(letter, gamestate) {
+                self.off_board -= 1;
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.current.clear();
+        self.rejected = None;
+        self.required_letters = 0;
+        self.off_board = 0;
+    }
+
+    // long enough, using the required letter and nothing else off the board, so only the word
+    // list is left to say no
+    fn ready(&self, gamestate: &GameState) -> bool {
+        self.current.len() >= gamestate.min_word_length && self.required_letters > 0 && self.off_board == 0
+    }
+}
+
+fn on_board(letter: char, gamestate: &GameState) -> bool {
+    gamestate.target_string.to_ascii_uppercase().contains(letter.to_ascii_uppercase())
 }
 
 #[derive(Component)]
@@ -57,31 +551,30 @@ struct CorrectWordsList {}
 struct HintText {}
 
 #[derive(Component)]
-struct SquishEffect {
-    attack_duration: f32,
-    sustain_duration: f32,
-    decay_duration: f32,
-    elapsed: f32,
-    total_time: f32,
-    base_scale: Vec3,
-    squish_multiplier: Vec3,
-}
+struct SessionClock {}
 
-impl SquishEffect {
-    fn new(base_scale: Vec3, squish_multiplier: Vec3, attack_duration: f32, sustain_duration: f32, decay_duration: f32) -> SquishEffect {
-        SquishEffect { attack_duration, sustain_duration, decay_duration, elapsed: 0., total_time: attack_duration + sustain_duration + decay_duration, base_scale, squish_multiplier }
-    }
+// a tile pressed straight in, then eased back out to full size
+const SQUISH_SCALE: f32 = 0.8;
+const SQUISH_RETURN_SECONDS: f32 = 0.15;
 
-    fn reset(&mut self) {
-        self.elapsed = 0.0;
-    }
+fn tile_squish(scale: f32, duration: f32, easing: Easing) -> Tween {
+    Tween::new("squish")
+        .then(0., Easing::Linear, [Track::Scale(Vec3::ONE, Vec3::splat(scale))])
+        .then(duration, easing, [Track::Scale(Vec3::splat(scale), Vec3::ONE)])
 }
 
+// the required tile springs back harder than the rest
+fn squish_easing(index: usize, easing: Easing) -> Easing {
+    if index == 0 { Easing::ElasticOut } else { easing }
+}
 
 struct LetterAcceptedEvent {
     letter: char,
 }
 
+// a letter not on the board, typed with strict entry on
+struct LetterRefusedEvent {}
+
 struct WordAcceptedEvent {
     word: String,
     pangram: bool,
@@ -89,343 +582,771 @@ struct WordAcceptedEvent {
 
 struct WordRejectedEvent {
     word: String,
-    reason: String,
+    reason: RejectReason,
 }
 
-fn get_spacings(sides: usize, radius: f32, face_index: usize) -> (f32, f32) {
-    let interval = (face_index as f32) * f32::to_radians(360.) / (sides as f32);
-    (f32::sin(interval) * radius * 2., f32::cos(interval) * radius * 2.)
+// only the sounds and the screen reader tell a clear from an erase
+struct GuessErasedEvent {
+    #[cfg(any(feature = "audio", feature = "screen_reader"))]
+    whole_word: bool,
 }
 
-fn alphabet_index(letter: u8) -> usize {
-    if letter >= 'A' as u8 && letter <= 'Z' as u8 {
-        letter as usize - 'A' as usize
-    }
-    else if letter >= 'a' as u8 && letter <= 'z' as u8 {
-        letter as usize - 'a' as usize   
-    }
-    else {
-        panic!("not a letter");
-    }
-}
+// the board was swapped for a new one in place, see refresh_board
+struct BoardRefreshedEvent {}
 
-fn word_to_bits(word: &str) -> u32 {
-    let mut val = 0;
+// read the board's letters out loud, for blind mode
+struct SpeakBoardEvent {}
 
-    for c in word.as_bytes() {
-        val |= 1 << alphabet_index(*c);
-    }
+struct TileHoveredEvent {
+    #[cfg(feature = "audio")]
+    letter: char,
+}
 
-    val
+// the guess line keys, for input that has no keyboard
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GuessAction {
+    Submit,
+    Erase,
+    Clear,
 }
 
-fn is_valid_word(word: &str) -> bool {
-    if word.len() < 4 {
-        return false;
-    }
-    
-    for c in word.as_bytes() {
-        if !c.is_ascii_alphabetic() {
-            return false;
-        }
-    }
-    return true;
+struct GuessActionEvent {
+    action: GuessAction,
 }
 
-fn bits_to_letters(bits: u32) -> String {
-    let mut s = String::new();
+const TILE_RADIUS: f32 = 80.;
+const BOARD_CENTER: Vec3 = Vec3::new(-80., -40., 0.);
 
-    for c in 'A'..'Z' {
-        if bits & 1 << alphabet_index(c as u8) != 0 {
-            s.push(c);
+fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn setup_goals(wordlist: Res<WordList>,
+               mut gamestate: ResMut<GameState>,
+               mut game_rng: ResMut<GameRng>,
+               mut mode: ResMut<GameMode>,
+               mut next_puzzle: ResMut<NextPuzzle>,
+               mut daily_board: ResMut<DailyBoard>,
+               mut imported_puzzle: ResMut<interchange::ImportedPuzzle>,
+               validators: Res<WordValidators>,
+               rarity: Res<RarityRules>,
+               scoring: Res<scoring::ScoringConfig>,
+               settings: Option<Res<Settings>>,       // not there when running headless
+               frequency: Option<Res<WordFrequency>>, // nor this
+               voice: Option<Res<blind::LetterVoice>>,
+            ) {
+    // an imported puzzle with its own answers doesn't need the word list to have a pangram for it
+    let curated = imported_puzzle.0.take().filter(|file| !file.answers.is_empty());
+    let code = next_puzzle.0.take().filter(|code| curated.is_some() || match code.check_playable(&wordlist) {
+        Ok(()) => true,
+        Err(message) => {
+            warn!("{}, picking a random board instead", message);
+            false
         }
+    });
+    if let Some(code_mode) = code.and_then(|code| code.mode).and_then(GameMode::from_code_index) {
+        *mode = code_mode;
+    }
+    if *mode == GameMode::Blind && voice.is_none() {
+        warn!("Blind mode needs the screen reader to read out the letters, playing classic instead");
+        *mode = GameMode::Classic;
     }
 
-    s
-}
-
-fn setup_word_list(mut wordlist: ResMut<WordList>) {
-    let file_contents = include_str!("../assets/words/dict_words.txt");
+    let kids_mode = settings.is_some_and(|settings| settings.kids_mode());
+    let min_word_length = if kids_mode { KIDS_MIN_WORD_LENGTH } else { MIN_WORD_LENGTH };
+    *gamestate = GameState { player_count: mode.player_count(), min_word_length, scoring: scoring.for_mode(*mode), ..default() };
 
-    for word in file_contents.split_whitespace() {
-        if is_valid_word(word) {
-            wordlist.all_valid_words.push(String::from(word));
+    // a daily board is picked from its own seed so everyone gets the same one, golf is played on it too
+    let daily = matches!(*mode, GameMode::Daily | GameMode::Golf);
+    daily_board.0 = if code.is_none() && daily { Some(daily::today()) } else { None };
+    let mut daily_rng = daily_board.0.map(|day| StdRng::seed_from_u64(daily::daily_seed(day)));
+    let rng = match daily_rng.as_mut() {
+        Some(daily_rng) => daily_rng,
+        None => &mut game_rng.0,
+    };
+    if *mode == GameMode::Mega {
+        mega::pick_mega_board(&mut gamestate, &wordlist, rng);
+    }
+    else {
+        pick_board(&mut gamestate, &wordlist, rng, code);
+    }
+    if let Some(file) = curated {
+        gamestate.answers = file.answers;
+        gamestate.curated_answers = true;
+    }
+    validators.filter_answers(&mut gamestate);
+    if let Some(frequency) = frequency.filter(|_| rarity.rarity_modes.contains(&mode)) {
+        gamestate.weigh_by_rarity(&frequency);
+    }
 
-            if word_to_bits(word).count_ones() == 7 {
-                wordlist.potential_pangrams.push(String::from(word));
-            }
-        }
+    match current_puzzle_code(&gamestate, *mode).encode() {
+        Ok(code) => info!("puzzle code is {}", code),
+        Err(message) => info!("no puzzle code for this board: {}", message),
+    }
+    if let Some(day) = daily_board.0 {
+        info!("this is the daily puzzle for {}", daily::date_string(day));
     }
 }
 
-fn setup_goals(wordlist: Res<WordList>, mut gamestate: ResMut<GameState>) {
-    let target_pangram = wordlist.potential_pangrams.choose(&mut rand::thread_rng()).unwrap().to_uppercase();
-    gamestate.target_bits = word_to_bits(target_pangram.as_str());
-    gamestate.target_string = bits_to_letters(gamestate.target_bits);
+// the letters, required letter and answers, from the code when there is one
+fn pick_board(gamestate: &mut GameState, wordlist: &WordList, rng: &mut StdRng, code: Option<PuzzleCode>) {
+    match code {
+        Some(code) => {
+            gamestate.target_bits = code.letter_bits;
+            gamestate.target_string = bits_to_letters(gamestate.target_bits);
+        }
+        None => {
+            let target_pangram = wordlist.potential_pangrams.choose(rng).unwrap().to_uppercase();
+            gamestate.target_bits = word_to_bits(target_pangram.as_str());
+            gamestate.target_string = bits_to_letters(gamestate.target_bits);
 
-    //println!("target pangram is {0}, target letters are {1}", target_pangram, gamestate.target_string);
+            // spoils the puzzle, so only shown when asked for with --verbose or RUST_LOG
+            debug!(target: "wordgame::spoiler", "target pangram is {}, target letters are {}", target_pangram, gamestate.target_string);
+        }
+    }
 
     unsafe {
         let letters = gamestate.target_string.as_bytes_mut();
-        letters.shuffle(&mut rand::thread_rng());
+        letters.shuffle(rng);
+
+        // the required letter goes in the middle tile, which is always the first
+        if let Some(code) = code {
+            let required = letters.iter().position(|letter| *letter as char == code.required_letter).unwrap();
+            letters.swap(0, required);
+        }
         gamestate.required_letter = letters[0] as char;
     }
-    gamestate.required_bit = (1 as u32) << alphabet_index(gamestate.required_letter as u8);
+    gamestate.required_bit = 1_u32 << alphabet_index(gamestate.required_letter as u8);
+    gamestate.answers = find_answers(wordlist, gamestate.target_bits, gamestate.required_bit, gamestate.min_word_length, None);
 
-    println!("target string is shuffled to {}", gamestate.target_string);
+    info!("board letters are {}, required letter is {}", gamestate.target_string, gamestate.required_letter);
+    debug!(target: "wordgame::spoiler", "board has {} answers", gamestate.answers.len());
 }
 
-fn check_word(word: &str, gamestate: &GameState, wordlist: &WordList) -> (bool, String, bool) {
-    if word.len() < 4 {
-        (false, String::from("is too short!"), false)
-    }
-    else {
-        let word_bits = word_to_bits(word);
-        if (word_bits & gamestate.required_bit != 0) && ((word_bits ^ gamestate.target_bits) & word_bits == 0) {
-            if wordlist.all_valid_words.contains(&String::from(word.to_ascii_lowercase())) {
-                if gamestate.correct_words.contains(&String::from(word.to_ascii_lowercase())) {
-                    (false, String::from("was already found"), false)
-                }
-                else {
-                    (true, String::from("hap :)"), (word_bits.count_ones() == 7))
-                }
-            }
-            else {
-                (false, String::from("is not in word list"), false)
-            }
-        }
-        else {
-            (false, String::from("does not use required letters"), false)
-        }
+// swaps a fresh random board in under the tiles already on screen, for modes that move straight
+// on without leaving the board; the score and found words start again
+fn refresh_board(gamestate: &mut GameState,
+                 wordlist: &WordList,
+                 validators: &WordValidators,
+                 frequency: &WordFrequency,
+                 game_rng: &mut GameRng,
+                 ev_board_refreshed: &mut EventWriter<BoardRefreshedEvent>,
+               ) {
+    let weighted = gamestate.rarity_bonus.is_some();
+    *gamestate = GameState {
+        player_count: gamestate.player_count,
+        min_word_length: gamestate.min_word_length,
+        scoring: gamestate.scoring.clone(),
+        ..default()
+    };
+    pick_board(gamestate, wordlist, &mut game_rng.0, None);
+    validators.filter_answers(gamestate);
+    if weighted {
+        gamestate.weigh_by_rarity(frequency);
     }
+    ev_board_refreshed.send(BoardRefreshedEvent {});
 }
 
-fn setup_shapes(mut commands: Commands, asset_server: Res<AssetServer>, gamestate: Res<GameState>) {
-    let center = Vec3::new(-80., -40., 0.);
-    let sides = 6;
-    let spacing = 0.;
-    let radius = 80.;
-    let center_color = Color::CYAN;
-    let petal_color = Color::ALICE_BLUE;
-    let line_width = 8.0;
+// the seven tiles and their letters, the first letter the required one, returned in the same order
+// spawned as children of board, around its origin as board_layout places them; each letter (and
+// the required marker) is a child of its tile, so anything moving or scaling a tile takes its letter along
+fn spawn_tiles(commands: &mut Commands,
+               asset_server: &AssetServer,
+               letters: &[u8],
+               board: Entity,
+               radius: f32,
+               colours: Colours,
+               board_layout: BoardLayout,
+               easing: Easing,
+            ) -> Vec<Entity> {
+    let center_color = colours.required();
+    let petal_color = colours.tile();
+    let line_width = colours.outline_width(radius);
 
-    let shape = shapes::RegularPolygon {
-        sides,
-        feature: shapes::RegularPolygonFeature::Radius(radius),
-        ..shapes::RegularPolygon::default()
-    };
+    // the mega board brings its own layout and a second required letter
+    let (board_layout, required_tiles) = if letters.len() > 7 { (BoardLayout::TwoRings, mega::MEGA_REQUIRED) } else { (board_layout, 1) };
 
-    let bold_font = asset_server.load("fonts/BarlowCondensed-Bold.ttf");
-    let narrow_font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
     let tiles_text_style = TextStyle {
-        font: bold_font.clone(),
+        font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"),
         font_size: radius,
         color: Color::BLACK,
     };
+    let text_alignment = TextAlignment::CENTER;
+
+    let mut tiles = Vec::new();
+    for (i, letter) in letters.iter().enumerate() {
+        let color = if i < required_tiles { center_color } else { petal_color };
+
+        let tile = commands.spawn(board_layout.tile_bundle(
+            radius,
+            DrawMode::Outlined {
+                fill_mode: FillMode::color(color),
+                outline_mode: StrokeMode::new(Color::BLACK, line_width),
+            },
+            Transform::from_translation(board_layout.tile_offset(i, radius)),
+        )).insert(BoardEntity {}).insert(tile_squish(SQUISH_SCALE, SQUISH_RETURN_SECONDS, squish_easing(i, easing))).insert(PaletteTile { required: i < required_tiles, radius }).id();
+        let label = commands.spawn(Text2dBundle{
+            text: Text::from_section(*letter as char, tiles_text_style.clone()).with_alignment(text_alignment),
+            transform: Transform::from_translation(Vec3::new(0., 0., 1.)),
+            ..default()
+        }).insert(BoardEntity {}).insert(TileLabel { index: i }).id();
+
+        commands.entity(tile).add_child(label);
+        commands.entity(board).add_child(tile);
+        tiles.push(tile);
+    }
+
+    for tile in tiles.iter().take(required_tiles) {
+        let marker = commands.spawn(GeometryBuilder::build_as(
+            &shapes::Circle { radius: radius * 0.75, center: Vec2::ZERO },
+            DrawMode::Stroke(StrokeMode::new(Color::BLACK, line_width / 2.)),
+            Transform::from_translation(Vec3::new(0., 0., 0.5)),
+        )).insert(BoardEntity {}).insert(RequiredMarker {}).insert(Visibility { is_visible: colours.marks() }).id();
+        commands.entity(*tile).add_child(marker);
+    }
+
+    tiles
+}
+
+fn setup_shapes(mut commands: Commands,
+                asset_server: Res<AssetServer>,
+                gamestate: Res<GameState>,
+                mode: Res<GameMode>,
+                layout: Res<Layout>,
+                settings: Res<Settings>,
+            ) {
+    // split screen lays out its own pair of boards
+    if *mode == GameMode::SplitScreen {
+        return;
+    }
+
+    // everything is placed at TILE_RADIUS around the board and scaled to the layout's tile size
+    let radius = TILE_RADIUS;
+    let board = commands.spawn(SpatialBundle::from_transform(layout.transform(LayoutSlot::Board)))
+        .insert(BoardEntity {})
+        .insert(LayoutAnchor { slot: LayoutSlot::Board })
+        .id();
+
+    let bold_font = asset_server.load("fonts/BarlowCondensed-Bold.ttf");
+    let narrow_font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
     let word_text_style = TextStyle {
         font: bold_font.clone(),
-        font_size: radius,
+        font_size: radius * settings.colours().text_scale(),
         color: Color::WHITE,
     };
     let info_text_style = TextStyle {
         font: narrow_font.clone(),
-        font_size: radius / 2.,
+        font_size: radius / 2. * settings.colours().text_scale(),
         color: Color::WHITE,
     };
-    let text_alignment = TextAlignment::CENTER;
 
     let letters = gamestate.target_string.as_bytes();
+    let tile_radius = if *mode == GameMode::Mega { radius * mega::MEGA_TILE_SCALE } else { radius };
+    let tiles = spawn_tiles(&mut commands, &asset_server, letters, board, tile_radius, settings.colours(), settings.board_layout, Easing::BackOut);
+    for (index, (tile, letter)) in tiles.iter().zip(letters).enumerate() {
+        commands.entity(*tile).insert(LetterTile { letter: *letter as char, index });
+    }
 
-    commands.spawn(Camera2dBundle::default());
-    commands.spawn(GeometryBuilder::build_as(
-        &shape,
-        DrawMode::Outlined {
-            fill_mode: FillMode::color(center_color),
-            outline_mode: StrokeMode::new(Color::BLACK, line_width),
-        },
-        Transform::from_translation(center),
-    )).insert(LetterTile {
-        letter: letters[0] as char
-    }).insert(SquishEffect::new(Vec3::ONE, Vec3::splat(0.8), 0.0, 0.0, 0.1));
-    commands.spawn(Text2dBundle{
-        text: Text::from_section(letters[0] as char, tiles_text_style.clone()).with_alignment(text_alignment),
-        transform: Transform::from_translation(center + Vec3::new(0., 0., 1.)),
-        ..default()
-    });
-
-    
-    commands.spawn(Text2dBundle{
+    let guess = commands.spawn(Text2dBundle{
         text: Text::from_section("_", word_text_style.clone()).with_alignment(TextAlignment::CENTER),
-        transform: Transform::from_translation(center + Vec3::new(0., 4.2 * radius, 1.)),
+        transform: Transform::from_translation(Vec3::new(0., 4.2 * radius, 1.)),
         ..default()
-    }).insert(TriedWord {
+    }).insert(BoardEntity {}).insert(TriedWord {
         current: String::new(),
-    });
+        rejected: None,
+        required_letters: 0,
+        off_board: 0,
+    }).id();
 
-    commands.spawn(Text2dBundle{
+    let hint = commands.spawn(Text2dBundle{
         text: Text::from_section("", info_text_style.clone()).with_alignment(TextAlignment::CENTER),
-        transform: Transform::from_translation(center + Vec3::new(0., 3.6 * radius, 1.)),
+        transform: Transform::from_translation(Vec3::new(0., 3.6 * radius, 1.)),
+        ..default()
+    }).insert(BoardEntity {}).insert(HintText {}).id();
+
+    // kept small and grey under the tiles, it's there to glance at rather than race
+    let clock = commands.spawn(Text2dBundle{
+        text: Text::from_section("", TextStyle { font_size: radius / 3., color: Color::GRAY, ..info_text_style.clone() }).with_alignment(TextAlignment::CENTER),
+        transform: Transform::from_translation(Vec3::new(0., -3.4 * radius, 1.)),
         ..default()
-    }).insert(HintText {});
+    }).insert(BoardEntity {}).insert(SessionClock {}).id();
+
+    commands.entity(board).push_children(&[guess, hint, clock]);
 
     commands.spawn(Text2dBundle{
         text: Text::from_section("Found Words: 0", info_text_style.clone()).with_alignment(TextAlignment::TOP_CENTER),
-        transform: Transform::from_translation(center + Vec3::new(6. * radius, 4.2 * radius, 1.)),
+        transform: layout.transform(LayoutSlot::FoundList),
         ..default()
-    }).insert(CorrectWordsList { });
+    }).insert(BoardEntity {}).insert(CorrectWordsList { }).insert(LayoutAnchor { slot: LayoutSlot::FoundList });
+}
 
-    for i in 0..sides {
-        let (x_space, y_space) = get_spacings(sides, radius + spacing, i);
-        commands.spawn(GeometryBuilder::build_as(
-            &shape,
-            DrawMode::Outlined {
-                fill_mode: FillMode::color(petal_color),
-                outline_mode: StrokeMode::new(Color::BLACK, line_width),
-            },
-            Transform::from_translation(center + Vec3::new(x_space, y_space, 0.0)),
-        )).insert(LetterTile {
-            letter: letters[i + 1] as char
-        }).insert(SquishEffect::new(Vec3::ONE, Vec3::splat(0.8), 0.0, 0.0, 0.1));
-        commands.spawn(Text2dBundle{
-            text: Text::from_section(letters[i + 1] as char, tiles_text_style.clone()).with_alignment(text_alignment),
-            transform: Transform::from_translation(center + Vec3::new(x_space, y_space, 1.)),
-            ..default()
-        });
+fn enter_new_board(mut state: ResMut<State<AppState>>) {
+    if let Err(err) = state.set(AppState::Playing) {
+        debug!("not starting the new board yet, the state is already changing: {:?}", err);
+    }
+}
+
+const RELABEL_OUT: &str = "relabel out";
+
+// the letters typed and clicked change straight away, the labels shrink away first and swap in
+// swap_labels once they're gone
+fn relabel_board(mut commands: Commands,
+                 mut ev_board_refreshed: EventReader<BoardRefreshedEvent>,
+                 gamestate: Res<GameState>,
+                 mut tiles: Query<&mut LetterTile>,
+                 labels: Query<Entity, With<TileLabel>>,
+                 mut hints: Query<&mut Text, With<HintText>>,
+                 mut word_guess: Query<&mut TriedWord>,
+                ) {
+    if ev_board_refreshed.iter().count() == 0 {
+        return;
+    }
+
+    let letters = gamestate.target_string.as_bytes();
+    for mut tile in tiles.iter_mut() {
+        tile.letter = letters[tile.index] as char;
+    }
+    for label in labels.iter() {
+        commands.entity(label).insert(Tween::new(RELABEL_OUT).then(0.12, Easing::CubicOut, [Track::Scale(Vec3::ONE, Vec3::ZERO)]));
+    }
+    for mut text in hints.iter_mut() {
+        text.sections[0].value.clear();
+    }
+    for mut tried_word in word_guess.iter_mut() {
+        tried_word.clear();
     }
-    
 }
 
+// the new letters drop into place one after another, flashing white as they land
+fn swap_labels(mut commands: Commands,
+               mut ev_tween_finished: EventReader<TweenFinishedEvent>,
+               gamestate: Res<GameState>,
+               mut labels: Query<(&TileLabel, &mut Text)>,
+            ) {
+    let letters = gamestate.target_string.as_bytes();
+    for ev in ev_tween_finished.iter().filter(|ev| ev.tag == RELABEL_OUT) {
+        let (label, mut text) = match labels.get_mut(ev.entity) {
+            Ok(label) => label,
+            Err(_) => continue,
+        };
+
+        text.sections[0].value = String::from(letters[label.index] as char);
+        let drop = Vec3::new(0., TILE_RADIUS / 3., 1.);
+        commands.entity(ev.entity).insert(Tween::new("relabel in")
+            .with_delay(0.04 * label.index as f32)
+            .then(0.3, Easing::BackOut, [
+                Track::Scale(Vec3::ZERO, Vec3::ONE),
+                Track::Translation(drop, Vec3::new(0., 0., 1.)),
+                Track::Color(Color::WHITE, Color::BLACK),
+            ]));
+    }
+}
+
+fn clear_board(mut commands: Commands, board: Query<Entity, With<BoardEntity>>) {
+    for entity in board.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// strict entry only lets the board's letters into the guess and buzzes at the rest, free entry
+// takes any letter and leaves show_guess to grey out the ones that can't be used. Blind mode takes
+// any letter too, the buzz would give the board away
 fn chose_letter(mut char_evr: EventReader<ReceivedCharacter>,
+                settings: Res<Settings>,
+                mode: Res<GameMode>,
                 mut ev_letter_accepted: EventWriter<LetterAcceptedEvent>,
-                mut letter_tiles: Query<(&LetterTile, &mut SquishEffect)>) {
+                mut ev_letter_refused: EventWriter<LetterRefusedEvent>,
+                mut letter_tiles: Query<(&LetterTile, &mut Tween)>) {
     for ev in char_evr.iter() {
-        for (tile, mut squish) in letter_tiles.iter_mut() {
-            if ev.char.to_ascii_uppercase() == tile.letter.to_ascii_uppercase() {
-                ev_letter_accepted.send(LetterAcceptedEvent { letter: ev.char });
-                squish.reset();
-                break;
-            }
+        if !ev.char.is_ascii_alphabetic() {
+            continue;
         }
-    }
-}
 
-fn add_letter(mut word_guess: Query<(&mut Text, &mut TriedWord)>, 
-              mut ev_letter_accepted: EventReader<LetterAcceptedEvent>,
-              game_state: Res<GameState>,) {
-    for ev in ev_letter_accepted.iter() {
-        let (mut text, mut tried_word) = word_guess.get_single_mut().unwrap();
-        tried_word.current.push(ev.letter.to_ascii_uppercase());
-        let mut style = text.sections[0].style.clone();
-        if ev.letter.to_ascii_uppercase() == game_state.required_letter {
-            style.color = Color::CYAN;
+        let tile = letter_tiles.iter_mut().find(|(tile, _)| ev.char.eq_ignore_ascii_case(&tile.letter));
+        if let Some((_, mut squish)) = tile {
+            ev_letter_accepted.send(LetterAcceptedEvent { letter: ev.char });
+            squish.restart();
+        }
+        else if settings.free_entry || *mode == GameMode::Blind {
+            ev_letter_accepted.send(LetterAcceptedEvent { letter: ev.char });
         }
         else {
-            style.color = Color::WHITE;
+            ev_letter_refused.send(LetterRefusedEvent {});
+        }
+    }
+}
+
+fn cursor_world_position(windows: &Windows, camera: &Camera, camera_transform: &GlobalTransform) -> Option<Vec2> {
+    let cursor = windows.get_primary()?.cursor_position()?;
+    camera.viewport_to_world(camera_transform, cursor).map(|ray| ray.origin.truncate())
+}
+
+fn hover_tiles(windows: Res<Windows>,
+               layout: Res<Layout>,
+               cameras: Query<(&Camera, &GlobalTransform)>,
+               tiles: Query<(Entity, &LetterTile, &GlobalTransform)>,
+               mut hovered: Local<Option<Entity>>,
+               mut ev_tile_hovered: EventWriter<TileHoveredEvent>,
+            ) {
+    let (camera, camera_transform) = cameras.single();
+    let cursor = cursor_world_position(&windows, camera, camera_transform);
+
+    // the nearest, as the mega board's tiles are closer together than a tile radius
+    let under_cursor = cursor.and_then(|cursor| {
+        tiles.iter()
+            .map(|tile| (tile.2.translation().truncate().distance(cursor), tile))
+            .filter(|(distance, _)| *distance < layout.tile_radius())
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, tile)| tile)
+    });
+
+    let entity = under_cursor.map(|(entity, _, _)| entity);
+    if entity != *hovered {
+        #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+        if let Some((_, tile, _)) = under_cursor {
+            ev_tile_hovered.send(TileHoveredEvent {
+                #[cfg(feature = "audio")]
+                letter: tile.letter,
+            });
         }
-        if text.sections[0].value == "_" {
-            text.sections.clear();
+        *hovered = entity;
+    }
+}
+
+fn add_letter(mut word_guess: Query<&mut TriedWord>,
+              gamestate: Res<GameState>,
+              mut ev_letter_accepted: EventReader<LetterAcceptedEvent>,) {
+    for ev in ev_letter_accepted.iter() {
+        // spectators have no guess line, see spectate.rs
+        let mut tried_word = match word_guess.get_single_mut() {
+            Ok(tried_word) => tried_word,
+            Err(_) => return,
+        };
+        if tried_word.rejected.is_some() {
+            tried_word.clear();
         }
-        text.sections.push(TextSection { value: ev.letter.to_ascii_uppercase().to_string(), style });
-        //println!("got a letter! {0} Word so far is {1}", ev.letter, tried_word.current);
+        tried_word.push(ev.letter, &gamestate);
+        trace!("got a letter! {0} Word so far is {1}", ev.letter, tried_word.current);
     }
 }
 
-fn guess_word(mut word_guess: Query<(&mut Text, &mut TriedWord)>,
+fn guess_word(mut word_guess: Query<&mut TriedWord>,
               gamestate: Res<GameState>,
-              wordlist: Res<WordList>, 
+              wordlist: Res<WordList>,
+              validators: Res<WordValidators>,
+              bonus: Res<BonusRound>,
+              cooldown: Res<GuessCooldown>,
               keys: Res<Input<KeyCode>>,
               mut ev_word_accepted: EventWriter<WordAcceptedEvent>,
               mut ev_word_rejected: EventWriter<WordRejectedEvent>,
+              mut ev_guess_erased: EventWriter<GuessErasedEvent>,
+              mut ev_guess_action: EventReader<GuessActionEvent>,
             ) {
-    let (mut text, mut tried_word) = word_guess.get_single_mut().unwrap();
+    let actions: Vec<GuessAction> = ev_guess_action.iter().map(|ev| ev.action).collect();
+
+    // split screen has no main guess line
+    let mut tried_word = match word_guess.get_single_mut() {
+        Ok(tried_word) => tried_word,
+        Err(_) => return,
+    };
 
-    if keys.just_pressed(KeyCode::Return) {
-        let (correct, reason, pangram) = check_word(&tried_word.current.as_str(), &gamestate, &wordlist);
+    let submitted = keys.just_pressed(KeyCode::Return) || actions.contains(&GuessAction::Submit);
+    let erased = keys.just_pressed(KeyCode::Back) || actions.contains(&GuessAction::Erase);
+    let cleared = keys.just_pressed(KeyCode::Delete) || actions.contains(&GuessAction::Clear);
+
+    // the rejected word is only up while it flashes, anything pressed meanwhile starts from empty
+    if tried_word.rejected.is_some() && (submitted || erased || cleared) {
+        tried_word.clear();
+        return;
+    }
+
+    // locked out for a moment after too many misses, the guess waits for the cooldown to end
+    if submitted && !cooldown.active() {
+        // the bonus round only takes the word being unscrambled
+        let bonus_words = bonus.word_list();
+        let wordlist = bonus_words.as_ref().unwrap_or(&wordlist);
+        let checked = validators.check_word(tried_word.current.as_str(), &gamestate, wordlist);
         
-        if correct {
-            ev_word_accepted.send(WordAcceptedEvent{ word: tried_word.current.clone(), pangram });
+        debug!("guessed {}: {:?}", tried_word.current, checked);
+        match checked {
+            Ok(pangram) => {
+                ev_word_accepted.send(WordAcceptedEvent{ word: tried_word.current.clone(), pangram });
+                tried_word.clear();
+            }
+            Err(reason) => {
+                ev_word_rejected.send(WordRejectedEvent { word: tried_word.current.clone(), reason });
+                tried_word.rejected = Some(reason);
+            }
         }
-        else {
-            ev_word_rejected.send(WordRejectedEvent { word: tried_word.current.clone(), reason: reason });
+        return;
+    }
+    
+    if erased && !tried_word.current.is_empty() {
+        tried_word.pop(&gamestate);
+        ev_guess_erased.send(GuessErasedEvent {
+            #[cfg(any(feature = "audio", feature = "screen_reader"))]
+            whole_word: false,
+        });
+    }
+
+    if cleared && !tried_word.current.is_empty() {
+        tried_word.clear();
+        ev_guess_erased.send(GuessErasedEvent {
+            #[cfg(any(feature = "audio", feature = "screen_reader"))]
+            whole_word: true,
+        });
+    }
+}
+
+const REJECT_FLASH: &str = "reject flash";
+const REJECT_FLASH_SECONDS: f32 = 0.4;
+
+// the word flashes and fades whatever was wrong with it, and the letter mistakes move as well: a
+// missing required letter swells the word, a letter off the board (or used too often) makes it wobble
+fn reject_flash(reason: RejectReason, color: Color) -> Tween {
+    let motion = match reason {
+        RejectReason::MissingRequired => Some((Vec3::splat(1.25), Easing::BackOut)),
+        RejectReason::OffBoardLetter | RejectReason::OutOfTiles => Some((Vec3::new(1.15, 0.85, 1.), Easing::ElasticOut)),
+        _ => None,
+    };
+
+    let mut tracks = vec![Track::Alpha(1., 0.3)];
+    if let Some((scale, _)) = motion {
+        tracks.push(Track::Scale(scale, Vec3::ONE));
+    }
+    Tween::new(REJECT_FLASH)
+        .then(0., Easing::Linear, [Track::Color(color, color)])
+        .then(REJECT_FLASH_SECONDS, motion.map(|(_, easing)| easing).unwrap_or(Easing::Linear), tracks)
+}
+
+// rebuilds the guess line from TriedWord, only when it or the palette has actually changed. Letters
+// not on the board are greyed (but not in blind mode, where that would give them away), the letter
+// count follows the guess, green once it's long enough, and a dot follows that when the guess
+// passes all of the board's rules. A rejected word flashes red where it was typed, and is cleared
+// in clear_rejected_guess after
+fn show_guess(mut commands: Commands,
+              mut word_guess: Query<(Entity, &mut Text, &mut Transform, &TriedWord, ChangeTrackers<TriedWord>)>,
+              gamestate: Res<GameState>,
+              settings: Res<Settings>,
+              mode: Res<GameMode>,
+            ) {
+    let colours = settings.colours();
+    for (entity, mut text, mut transform, tried_word, tracker) in word_guess.iter_mut() {
+        if !tracker.is_changed() && !settings.is_changed() {
+            continue;
+        }
+
+        match tried_word.rejected {
+            Some(reason) => {
+                commands.entity(entity).insert(reject_flash(reason, colours.rejected_flash()));
+            }
+            None => {
+                commands.entity(entity).remove::<Tween>();
+                transform.scale = Vec3::ONE;
+            }
+        }
+
+        let mut style = text.sections[0].style.clone();
+        style.color = Color::WHITE;
+        style.font_size = TILE_RADIUS * colours.text_scale();
+
+        if tried_word.current.is_empty() {
+            text.sections = vec![TextSection::new("_", style)];
         }
+        else {
+            text.sections = tried_word.current.chars().map(|letter| {
+                let mut style = style.clone();
+                if letter == gamestate.required_letter {
+                    style.color = colours.required();
+                    if colours.marks() {
+                        style.font_size *= REQUIRED_LETTER_SIZE;
+                    }
+                }
+                else if *mode != GameMode::Blind && !on_board(letter, &gamestate) {
+                    style.color = Color::GRAY;
+                }
+                TextSection::new(letter.to_string(), style)
+            }).collect();
 
-        text.sections[0].value = String::from("_");
-        text.sections[0].style.color = Color::WHITE;
-        text.sections.resize(1, TextSection::default());
-        tried_word.current = String::new();
+            let length = tried_word.current.len();
+            let mut count_style = style.clone();
+            count_style.font_size *= 0.5;
+            count_style.color = if length >= gamestate.min_word_length { colours.long_enough() } else { Color::GRAY };
+            text.sections.push(TextSection::new(format!(" ({})", length), count_style));
+
+            // a quiet dot once the word could be an answer, left off while it's flashing red
+            if tried_word.ready(&gamestate) && tried_word.rejected.is_none() {
+                let mut style = style.clone();
+                style.color = *colours.accepted().set_a(0.6);
+                style.font_size *= 0.5;
+                text.sections.push(TextSection::new(" \u{2022}", style));
+            }
+        }
     }
-    
-    if keys.just_pressed(KeyCode::Back) {
-        tried_word.current.pop();
-        if text.sections.len() == 1 {
-            text.sections[0].value = String::from("_");
-            text.sections[0].style.color = Color::WHITE;
-        } else {
-            text.sections.pop();
+}
+
+fn clear_rejected_guess(mut ev_tween_finished: EventReader<TweenFinishedEvent>, mut word_guess: Query<&mut TriedWord>) {
+    for ev in ev_tween_finished.iter().filter(|ev| ev.tag == REJECT_FLASH) {
+        if let Ok(mut tried_word) = word_guess.get_mut(ev.entity) {
+            if tried_word.rejected.is_some() {
+                tried_word.clear();
+            }
         }
     }
 }
 
 fn wrong_word_hint(mut ev_word_rejected: EventReader<WordRejectedEvent>,
-                   mut hint_text: Query<(&mut Text, &mut HintText)>,) {
+                   mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                   mut hint_text: Query<(&mut Text, &mut HintText)>,
+                   settings: Res<Settings>,
+                ) {
+    let colours = settings.colours();
     for ev in ev_word_rejected.iter() {
         for (mut text, _hint) in hint_text.iter_mut() {
-            text.sections[0].value = ev.word.clone() + " " + ev.reason.as_str();
+            let mark = if colours.marks() { "x " } else { "" };
+            text.sections[0].value = format!("{}{} {}", mark, ev.word, ev.reason);
+            text.sections[0].style.color = colours.rejected();
+        }
+    }
+
+    // the standard palette leaves accepted words to the found list
+    for ev in ev_word_accepted.iter() {
+        if !colours.marks() {
+            continue;
+        }
+        for (mut text, _hint) in hint_text.iter_mut() {
+            text.sections[0].value = String::from("+ ") + ev.word.as_str();
+            text.sections[0].style.color = colours.accepted();
         }
     }
 }
 
-fn show_correct_words(mut ev_word_accepted: EventReader<WordAcceptedEvent>,
-                      mut word_list: Query<(&mut Text, &CorrectWordsList)>,
-                      mut gamestate: ResMut<GameState>,
+fn record_correct_words(mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                        mut gamestate: ResMut<GameState>,
+                        bonus: Res<BonusRound>,
                     ) {
     for ev in ev_word_accepted.iter() {
-        for (mut text, _word_list) in word_list.iter_mut() {
-            let mut style = text.sections[0].style.clone();
-            if ev.pangram {
-                style.color = Color::CYAN;
-            }
-            let word = String::from("\n") + ev.word.as_str();
-            text.sections.push(TextSection::new(word, style));
-            gamestate.correct_words.push(ev.word.clone().to_ascii_lowercase());
-
-            text.sections[0].value = format!("Found Words: {}", gamestate.correct_words.len());
+        // bonus words only earn their partial credit, see bonus.rs
+        if bonus.active {
+            continue;
         }
+
+        let player = gamestate.current_player;
+        gamestate.correct_words.push(FoundWord { word: ev.word.clone().to_ascii_lowercase(), player });
     }
 }
 
-fn squish_effects(mut squishees: Query<(&mut Transform, &mut SquishEffect)>, time: Res<Time>) {
-    for (mut transform, mut squish) in squishees.iter_mut() {
-        if squish.elapsed < squish.total_time {
-            squish.elapsed += time.delta_seconds();
-            let mut adj_elapsed = squish.elapsed;
-            if adj_elapsed > squish.attack_duration {
-                adj_elapsed -= squish.attack_duration;
+// rebuilds the found words list from GameState, only when it has actually changed
+fn show_correct_words(mut word_list: Query<&mut Text, With<CorrectWordsList>>,
+                      gamestate: Res<GameState>,
+                      layout: Res<Layout>,
+                      expanded: Res<FoundListExpanded>,
+                      settings: Res<Settings>,
+                      frequency: Res<WordFrequency>,
+                      credits: Res<ChatCredits>,
+                    ) {
+    if !gamestate.is_changed() && !layout.is_changed() && !expanded.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let folded = layout.found_list_folds() && !expanded.0;
+    let colours = settings.colours();
 
-                if adj_elapsed > squish.sustain_duration {
-                    adj_elapsed -= squish.sustain_duration;
+    for mut text in word_list.iter_mut() {
+        let mut style = text.sections[0].style.clone();
+        style.color = Color::WHITE;
+        style.font_size = TILE_RADIUS / 2. * colours.text_scale();
 
-                    if adj_elapsed > squish.decay_duration {
-                        // animation finished, reset scale
-                        transform.scale = squish.base_scale;
-                    }
-                    else {
-                        // in decay phase, do lerp out
-                        let t = adj_elapsed / squish.decay_duration;
-                        transform.scale = squish.base_scale.lerp(squish.squish_multiplier, 1.0 - t);
-                    }
+        let mut sections = vec![TextSection::new(format!("Found Words: {}", gamestate.correct_words.len()), style.clone())];
+        if folded {
+            sections[0].value.push_str(" (show)");
+            text.sections = sections;
+            continue;
+        }
+
+        // the newest words when there are more than fit above the bottom of the window
+        let rows = ((layout.found_list_rows() as f32 / colours.text_scale()) as usize).max(2) - 1;
+        let skipped = if gamestate.correct_words.len() > rows { gamestate.correct_words.len() + 1 - rows } else { 0 };
+        if skipped > 0 {
+            let mut style = style.clone();
+            style.color = Color::GRAY;
+            sections.push(TextSection::new(format!("\n...and {} earlier", skipped), style));
+        }
+
+        for found in gamestate.correct_words.iter().skip(skipped) {
+            let mut style = style.clone();
+            let mut line = String::from("\n") + found.word.to_ascii_uppercase().as_str();
+
+            // with several players the colour says who found it, so pangrams get a star instead
+            if gamestate.player_count > 1 {
+                style.color = hotseat::player_color(found.player);
+                if is_pangram(&found.word) {
+                    line.push_str(" *");
                 }
-                else {
-                    // in sustain phase, hold
-                    transform.scale = squish.base_scale * squish.squish_multiplier;
+            }
+            else if is_pangram(&found.word) {
+                style.color = colours.pangram();
+                if colours.marks() {
+                    line.push_str(" *");
                 }
             }
-            else {
-                // in attack phase, do lerp in
-                let t = adj_elapsed / squish.attack_duration;
-                transform.scale = squish.base_scale.lerp(squish.squish_multiplier, t);
+            sections.push(TextSection::new(line, style.clone()));
+
+            // how obscure it is and who in chat found it, quieter than the word itself
+            let mut tag_style = style;
+            tag_style.color = Color::GRAY;
+            tag_style.font_size *= 0.6;
+            let mut tag = format!("  {}", frequency.rate(&found.word).name().to_ascii_lowercase());
+            if let Some(chatter) = credits.0.get(&found.word) {
+                tag.push_str(&format!(", from {}", chatter));
             }
-            
+            sections.push(TextSection::new(tag, tag_style));
         }
+        text.sections = sections;
     }
-} 
\ No newline at end of file
+}
+
+fn tick_time_played(mut gamestate: ResMut<GameState>, time: Res<Time>) {
+    // every frame would otherwise count as a change and rebuild everything watching GameState
+    gamestate.bypass_change_detection().time_played += time.delta_seconds();
+}
+
+fn format_clock(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// the pace is over the whole board so far, not just the words since the last one
+fn pace(gamestate: &GameState) -> Option<String> {
+    let words = gamestate.correct_words.len();
+    if words == 0 || gamestate.time_played < 1. {
+        return None;
+    }
+    let per_minute = words as f32 / (gamestate.time_played / 60.);
+    Some(format!("{:.0}s a word, {:.1} words a minute", gamestate.time_played / words as f32, per_minute))
+}
+
+fn show_session_clock(gamestate: Res<GameState>, settings: Res<Settings>, mut clocks: Query<&mut Text, With<SessionClock>>) {
+    let mut clock = format_clock(gamestate.time_played);
+    if let Some(pace) = pace(&gamestate).filter(|_| settings.pace) {
+        clock = format!("{}   {}", clock, pace);
+    }
+    for mut text in clocks.iter_mut() {
+        if text.sections[0].value != clock {
+            text.sections[0].value = clock.clone();
+        }
+    }
+}
+
+// a rank can be passed more than one at a time, they all get the same time; one lost again to
+// hints keeps its first time
+fn record_rank_times(mut gamestate: ResMut<GameState>) {
+    if !gamestate.is_changed() {
+        return;
+    }
+
+    let next = gamestate.rank_times.last().map_or(1, |(rank, _)| rank + 1);
+    let time_played = gamestate.time_played;
+    for rank in next..=gamestate.rank() {
+        gamestate.rank_times.push((rank, time_played));
+    }
+}
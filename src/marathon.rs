@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use wordgame::words::{GameState, RANKS};
+
+use crate::celebration::check_genius;
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::{guess_word, record_correct_words, setup_goals, AppState, BoardEntity, GameMode, TILE_RADIUS};
+
+// A run of boards one after another against a single bank of time. Reaching the target rank on a
+// board banks its score and a little more time and moves straight on to the next one, and the run
+// is over when every board is cleared or the time runs out, whichever comes first. The clock only
+// runs while a board is being played, menus pause it.
+pub struct MarathonPlugin;
+
+impl Plugin for MarathonPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Marathon>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(start_marathon.after(setup_goals))
+                .with_system(spawn_marathon_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(restart_marathon.before(guess_word))
+                .with_system(tick_marathon)
+                .with_system(advance_marathon.after(record_correct_words).after(tick_marathon).before(check_genius))
+                .with_system(show_marathon_hud.after(advance_marathon)));
+    }
+}
+
+const MARATHON_START_SECONDS: f32 = 4. * 60.;
+const MARATHON_BOARD_SECONDS: f32 = 60.; // added for each board cleared
+
+// from the command line
+#[derive(Resource)]
+pub struct MarathonRules {
+    pub boards: u32,
+    pub target_rank: usize, // an index into RANKS
+}
+
+#[derive(Resource, Default)]
+struct Marathon {
+    started: bool,
+    finished: bool,
+    boards_cleared: u32,
+    banked_score: u32, // from the boards already left behind
+    seconds_left: f32,
+}
+
+impl Marathon {
+    fn running(&self) -> bool {
+        self.started && !self.finished
+    }
+}
+
+#[derive(Component)]
+struct MarathonHud {}
+
+// the run carries on through each new board, only the first one of a run starts it
+fn start_marathon(mut marathon: ResMut<Marathon>, mode: Res<GameMode>, rules: Res<MarathonRules>) {
+    if *mode != GameMode::Marathon {
+        *marathon = Marathon::default();
+        return;
+    }
+    if marathon.started {
+        return;
+    }
+
+    *marathon = Marathon { started: true, seconds_left: MARATHON_START_SECONDS, ..default() };
+    info!("marathon started, {} boards to {}", rules.boards, RANKS[rules.target_rank].0);
+}
+
+fn spawn_marathon_hud(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if *mode != GameMode::Marathon {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(MarathonHud {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
+
+// once it's over Enter starts another run, there's nothing left to guess for
+fn restart_marathon(mut keys: ResMut<Input<KeyCode>>,
+                    mode: Res<GameMode>,
+                    mut marathon: ResMut<Marathon>,
+                    mut state: ResMut<State<AppState>>,
+                ) {
+    if *mode != GameMode::Marathon || !marathon.finished || !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    keys.clear_just_pressed(KeyCode::Return);
+    match state.set(AppState::NewBoard) {
+        Ok(()) => *marathon = Marathon::default(),
+        Err(err) => debug!("not restarting the marathon, the state is already changing: {:?}", err),
+    }
+}
+
+fn tick_marathon(mut marathon: ResMut<Marathon>, gamestate: Res<GameState>, time: Res<Time>) {
+    if !marathon.running() {
+        return;
+    }
+
+    marathon.seconds_left -= time.delta_seconds();
+    if marathon.seconds_left <= 0. {
+        // whatever was found on the unfinished board still counts
+        marathon.seconds_left = 0.;
+        marathon.finished = true;
+        marathon.banked_score += gamestate.score();
+        info!("marathon out of time after {} boards, {} points", marathon.boards_cleared, marathon.banked_score);
+    }
+}
+
+fn advance_marathon(mut marathon: ResMut<Marathon>,
+                    rules: Res<MarathonRules>,
+                    gamestate: Res<GameState>,
+                    mut state: ResMut<State<AppState>>,
+                    mut retry: Local<bool>,
+                ) {
+    let retrying = std::mem::take(&mut *retry);
+    if !marathon.running() || !(gamestate.is_changed() || retrying) || gamestate.rank() < rules.target_rank {
+        return;
+    }
+    // even the lowest rank needs a word, or a fresh board would be cleared straight away
+    if gamestate.correct_words.is_empty() {
+        return;
+    }
+
+    // another transition may already be queued this frame, in which case the board is left for next frame
+    let last_board = marathon.boards_cleared + 1 >= rules.boards;
+    if !last_board && state.set(AppState::NewBoard).is_err() {
+        *retry = true;
+        return;
+    }
+
+    marathon.banked_score += gamestate.score();
+    marathon.boards_cleared += 1;
+    if last_board {
+        marathon.finished = true;
+        info!("marathon finished with {:.0} seconds to spare, {} points", marathon.seconds_left, marathon.banked_score);
+    }
+    else {
+        marathon.seconds_left += MARATHON_BOARD_SECONDS;
+    }
+}
+
+fn format_clock(seconds: f32) -> String {
+    let seconds = seconds.max(0.).ceil() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn show_marathon_hud(marathon: Res<Marathon>,
+                     rules: Res<MarathonRules>,
+                     gamestate: Res<GameState>,
+                     mut huds: Query<&mut Text, With<MarathonHud>>,
+                    ) {
+    let status = if marathon.finished {
+        format!("Marathon over, {} of {} boards cleared\nTotal {}\nEnter for another run",
+                marathon.boards_cleared, rules.boards, marathon.banked_score)
+    }
+    else {
+        format!("Board {} of {}, {} left\nTotal {}\n{} to go on, now {}",
+                marathon.boards_cleared + 1, rules.boards, format_clock(marathon.seconds_left),
+                marathon.banked_score + gamestate.score(),
+                RANKS[rules.target_rank].0, RANKS[gamestate.rank()].0)
+    };
+
+    for mut text in huds.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom};
+use wordgame::words::{alphabet_index, bits_to_letters, find_answers, word_to_bits, GameState, WordList};
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::{setup_goals, AppState, BoardEntity, GameMode, TILE_RADIUS};
+
+// The mega board, an experiment: nineteen tiles in two rings round the middle instead of seven.
+// No word has twelve different letters to build a board around, so the letters of two pangrams
+// are cut down to twelve, two of them required (every word needs both), and the seven tiles left
+// over double up the most common letters. A tile can only be used once in a word, so it's the
+// doubles that let a word have two Es. Puzzle codes only hold seven letter boards, so a code for a
+// mega board just starts a fresh one.
+pub struct MegaPlugin;
+
+impl Plugin for MegaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_mega_hud.after(setup_goals)));
+    }
+}
+
+pub const MEGA_REQUIRED: usize = 2; // the first tiles of the board, the middle and the top of the inner ring
+pub const MEGA_TILE_SCALE: f32 = 0.55; // both rings fit where the flower goes
+const MEGA_LETTERS: usize = 12;
+const MEGA_TILES: usize = 19;
+const MIN_ANSWERS: usize = 30;
+const MAX_ATTEMPTS: usize = 50; // after that the last board picked will do
+const COMMON_LETTERS: &[u8] = b"EAIORSTNL"; // doubled up on the spare tiles, most common first
+
+#[derive(Component)]
+struct MegaHud {}
+
+pub fn pick_mega_board(gamestate: &mut GameState, wordlist: &WordList, rng: &mut StdRng) {
+    for _ in 0..MAX_ATTEMPTS {
+        pick_mega_letters(gamestate, wordlist, rng);
+        if gamestate.answers.len() >= MIN_ANSWERS {
+            break;
+        }
+    }
+
+    info!("mega board letters are {}, required letters are {}", gamestate.target_string, bits_to_letters(gamestate.required_bit));
+    debug!(target: "wordgame::spoiler", "mega board has {} answers", gamestate.answers.len());
+}
+
+fn pick_mega_letters(gamestate: &mut GameState, wordlist: &WordList, rng: &mut StdRng) {
+    let first = word_to_bits(wordlist.potential_pangrams.choose(rng).unwrap());
+    let second = word_to_bits(wordlist.potential_pangrams.choose(rng).unwrap());
+
+    // both required letters come from the one pangram, so at least one word has them together
+    let mut required = bits_to_letters(first).into_bytes();
+    required.shuffle(rng);
+    required.truncate(MEGA_REQUIRED);
+
+    let mut others: Vec<u8> = bits_to_letters(first | second).bytes().filter(|letter| !required.contains(letter)).collect();
+    others.shuffle(rng);
+    others.truncate(MEGA_LETTERS - MEGA_REQUIRED);
+    // two pangrams sharing most of their letters don't make twelve between them
+    for letter in COMMON_LETTERS {
+        if others.len() + MEGA_REQUIRED < MEGA_LETTERS && !others.contains(letter) && !required.contains(letter) {
+            others.push(*letter);
+        }
+    }
+
+    let doubled: Vec<u8> = COMMON_LETTERS.iter().filter(|letter| others.contains(*letter) || required.contains(*letter)).copied().collect();
+    let spares = MEGA_TILES - MEGA_REQUIRED - others.len();
+    others.extend(doubled.iter().cycle().take(spares));
+    others.shuffle(rng);
+
+    let tiles: Vec<u8> = required.iter().chain(others.iter()).copied().collect();
+    let mut limits = [0; 26];
+    for letter in tiles.iter() {
+        limits[alphabet_index(*letter)] += 1;
+    }
+
+    gamestate.target_string = String::from_utf8(tiles).unwrap();
+    gamestate.target_bits = word_to_bits(&gamestate.target_string);
+    gamestate.required_letter = required[0] as char;
+    gamestate.required_bit = word_to_bits(&String::from_utf8(required).unwrap());
+    gamestate.letter_limits = Some(limits);
+    gamestate.answers = find_answers(wordlist, gamestate.target_bits, gamestate.required_bit, gamestate.min_word_length, Some(&limits));
+}
+
+fn spawn_mega_hud(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, gamestate: Res<GameState>, layout: Res<Layout>) {
+    if *mode != GameMode::Mega {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    let required: Vec<String> = bits_to_letters(gamestate.required_bit).chars().map(String::from).collect();
+    let rules = format!("Mega board, every word needs {}\nEach tile only once a word", required.join(" and "));
+    commands.spawn(Text2dBundle{
+        text: Text::from_section(rules, info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(MegaHud {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
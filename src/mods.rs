@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+#[cfg(not(feature = "web"))]
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::loading::DictionarySource;
+use crate::palette::{Palette, PaletteTile, ThemeColours, ALL_PALETTES};
+use crate::settings::Settings;
+use crate::AppState;
+
+// Themes, tile skins and word lists dropped into the mods folder next to the settings, looked
+// through once at startup and offered in the settings alongside the built in ones:
+//   mods/themes/NAME.theme.ron  colours for the board, picked with the palettes
+//   mods/skins/NAME.png         a picture drawn on every tile, under the letter
+//   mods/words/NAME.txt         a word list in the same format as the built in one
+// A theme is a ThemeFile, each colour an (r, g, b) from 0 to 1:
+//   (tile: (0.95, 0.9, 0.8), required: (0.9, 0.5, 0.2), pangram: (0.9, 0.5, 0.2),
+//    accepted: (0.3, 0.8, 0.3), rejected: (0.8, 0.2, 0.2), background: (0.1, 0.1, 0.15), marks: false)
+// A broken theme is left out with a warning. Things are picked by name, so a mod that's been
+// taken away falls back to the built in one. The browser build has no folder to look in.
+pub struct ModsPlugin;
+
+impl Plugin for ModsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(scan_mods())
+            .add_system(resolve_theme)
+            .add_system(skin_tiles.after(resolve_theme))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(reload_for_word_list));
+    }
+}
+
+#[cfg(not(feature = "web"))]
+const MODS_FOLDER: &str = "mods";
+const SKIN_SIZE: f32 = 1.6; // of the tile radius, inside the outline
+
+#[cfg(not(feature = "web"))]
+#[derive(Deserialize)]
+struct ThemeFile {
+    tile: (f32, f32, f32),
+    required: (f32, f32, f32),
+    pangram: (f32, f32, f32),
+    accepted: (f32, f32, f32),
+    rejected: (f32, f32, f32),
+    background: (f32, f32, f32),
+    #[serde(default)]
+    marks: bool,
+}
+
+#[cfg(not(feature = "web"))]
+impl ThemeFile {
+    fn colours(&self) -> ThemeColours {
+        let rgb = |(r, g, b): (f32, f32, f32)| Color::rgb(r, g, b);
+        ThemeColours {
+            tile: rgb(self.tile),
+            required: rgb(self.required),
+            pangram: rgb(self.pangram),
+            accepted: rgb(self.accepted),
+            rejected: rgb(self.rejected),
+            background: rgb(self.background),
+            marks: self.marks,
+        }
+    }
+}
+
+// by name, sorted so the settings step through them in the same order every time
+#[derive(Resource, Default)]
+pub struct InstalledMods {
+    pub themes: Vec<(String, ThemeColours)>,
+    pub skins: Vec<(String, PathBuf)>,
+    pub word_lists: Vec<(String, PathBuf)>,
+}
+
+impl InstalledMods {
+    // the built in palettes, then the themes, wrapping round
+    pub fn step_palette(&self, settings: &mut Settings, direction: f32) {
+        let count = ALL_PALETTES.len() + self.themes.len();
+        let index = match self.themes.iter().position(|(name, _)| *name == settings.theme) {
+            Some(theme) => ALL_PALETTES.len() + theme,
+            None => ALL_PALETTES.iter().position(|palette| *palette == settings.palette).unwrap_or(0),
+        };
+
+        let index = if direction < 0. { (index + count - 1) % count } else { (index + 1) % count };
+        if index < ALL_PALETTES.len() {
+            settings.palette = ALL_PALETTES[index];
+            settings.theme.clear();
+        }
+        else {
+            settings.palette = Palette::Standard;
+            settings.theme = self.themes[index - ALL_PALETTES.len()].0.clone();
+        }
+    }
+
+    pub fn palette_name(&self, settings: &Settings) -> String {
+        match self.themes.iter().find(|(name, _)| *name == settings.theme) {
+            Some((name, _)) => name.clone(),
+            None => String::from(settings.palette.name()),
+        }
+    }
+
+    fn theme(&self, name: &str) -> Option<ThemeColours> {
+        self.themes.iter().find(|(theme, _)| theme == name).map(|(_, colours)| *colours)
+    }
+
+    pub fn skin_path(&self, name: &str) -> Option<PathBuf> {
+        find_path(&self.skins, name)
+    }
+
+    pub fn word_list_path(&self, name: &str) -> Option<PathBuf> {
+        find_path(&self.word_lists, name)
+    }
+}
+
+fn find_path(mods: &[(String, PathBuf)], name: &str) -> Option<PathBuf> {
+    mods.iter().find(|(found, _)| found == name).map(|(_, path)| path.clone())
+}
+
+// for the settings rows, "" is none and comes before the mods
+pub fn step_name(mods: &[(String, PathBuf)], current: &str, direction: f32) -> String {
+    let count = mods.len() + 1;
+    let index = mods.iter().position(|(name, _)| name == current).map_or(0, |index| index + 1);
+    let index = if direction < 0. { (index + count - 1) % count } else { (index + 1) % count };
+    if index == 0 { String::new() } else { mods[index - 1].0.clone() }
+}
+
+pub fn mod_name(mods: &[(String, PathBuf)], current: &str, none: &str) -> String {
+    match mods.iter().find(|(name, _)| name == current) {
+        Some((name, _)) => name.clone(),
+        None => String::from(none),
+    }
+}
+
+#[cfg(not(feature = "web"))]
+fn scan_mods() -> InstalledMods {
+    let folder = crate::storage::path(MODS_FOLDER);
+    if !folder.exists() {
+        return InstalledMods::default();
+    }
+
+    let mut themes = Vec::new();
+    for (name, path) in list_folder(&folder.join("themes"), ".theme.ron") {
+        let theme = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| ron::from_str::<ThemeFile>(&contents).map_err(|err| err.to_string()));
+        match theme {
+            Ok(theme) => themes.push((name, theme.colours())),
+            Err(err) => warn!("Could not load the theme {}: {}", path.display(), err),
+        }
+    }
+
+    let mods = InstalledMods {
+        themes,
+        skins: list_folder(&folder.join("skins"), ".png"),
+        word_lists: list_folder(&folder.join("words"), ".txt"),
+    };
+    info!("{} themes, {} tile skins and {} word lists in {}", mods.themes.len(), mods.skins.len(), mods.word_lists.len(), folder.display());
+    mods
+}
+
+#[cfg(feature = "web")]
+fn scan_mods() -> InstalledMods {
+    InstalledMods::default()
+}
+
+// the files ending with the extension, named without it
+#[cfg(not(feature = "web"))]
+fn list_folder(folder: &std::path::Path, extension: &str) -> Vec<(String, PathBuf)> {
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut found: Vec<(String, PathBuf)> = entries.filter_map(|entry| {
+        let path = entry.ok()?.path();
+        let name = path.file_name()?.to_str()?.strip_suffix(extension)?.to_string();
+        Some((name, path))
+    }).collect();
+    found.sort();
+    found
+}
+
+// only written when it changes, so the palette isn't put back on every frame
+fn resolve_theme(mods: Res<InstalledMods>, mut settings: ResMut<Settings>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let theme = mods.theme(&settings.theme);
+    if settings.theme_colours != theme {
+        settings.theme_colours = theme;
+    }
+}
+
+#[derive(Component)]
+struct TileSkin {}
+
+// skins go on as tiles are spawned, and all come off and back on when the settings change
+fn skin_tiles(mut commands: Commands,
+              asset_server: Res<AssetServer>,
+              mods: Res<InstalledMods>,
+              settings: Res<Settings>,
+              tiles: Query<(Entity, &PaletteTile)>,
+              new_tiles: Query<Entity, Added<PaletteTile>>,
+              skins: Query<Entity, With<TileSkin>>,
+            ) {
+    if !settings.is_changed() && new_tiles.is_empty() {
+        return;
+    }
+
+    for skin in skins.iter() {
+        commands.entity(skin).despawn_recursive();
+    }
+
+    let path = match mods.skin_path(&settings.tile_skin) {
+        Some(path) => path,
+        None => return,
+    };
+    let texture: Handle<Image> = asset_server.load(path);
+    let colours = settings.colours();
+    for (entity, tile) in tiles.iter() {
+        // between the tile and the letter, with the required ring on top
+        let skin = commands.spawn(SpriteBundle {
+            texture: texture.clone(),
+            sprite: Sprite {
+                color: if tile.required { colours.required() } else { Color::WHITE },
+                custom_size: Some(Vec2::splat(tile.radius * SKIN_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0., 0., 0.25)),
+            ..default()
+        }).insert(TileSkin {}).id();
+        commands.entity(entity).add_child(skin);
+    }
+}
+
+// like kids mode, a new word list only comes in through the loading screen, and one given on the
+// command line or dropped on the window wins over the settings. Another transition may already be
+// queued the frame it's seen, in which case it's tried again next frame
+fn reload_for_word_list(settings: Res<Settings>,
+                        mods: Res<InstalledMods>,
+                        source: Res<DictionarySource>,
+                        mut state: ResMut<State<AppState>>,
+                    ) {
+    // kids mode reloads too, and the loading screen picks up the word list on its way
+    if source.path.is_some() || settings.kids_mode() != source.kids {
+        return;
+    }
+
+    let path = mods.word_list_path(&settings.word_list);
+    if path != source.mod_path && state.replace(AppState::Loading).is_ok() {
+        info!("word list changed to {}, loading it", mod_name(&mods.word_lists, &settings.word_list, "the built in one"));
+    }
+}
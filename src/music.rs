@@ -0,0 +1,133 @@
+use bevy::audio::AudioSink;
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::settings::Settings;
+use crate::time_attack::TimeAttack;
+use crate::WordAcceptedEvent;
+
+// Background music, a shuffled playlist crossfading from one track into the next, played faster
+// through time attack's lightning round
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_music)
+            .add_system(update_music);
+    }
+}
+
+// sinks can't tell us when a track has ended, so the lengths are kept alongside the files
+const PLAYLIST: [(&str, f32); 3] = [
+    ("audio/music/morning.wav", 22.),
+    ("audio/music/study.wav", 24.),
+    ("audio/music/breeze.wav", 20.),
+];
+const CROSSFADE_SECONDS: f32 = 3.;
+const FANFARE_SECONDS: f32 = 2.;
+const FANFARE_DIM: f32 = 0.25; // fraction of the music volume left while the pangram fanfare plays
+const LIGHTNING_SPEED: f32 = 1.5;
+
+struct PlayingTrack {
+    sink: Handle<AudioSink>,
+    elapsed: f32,
+    duration: f32,
+}
+
+#[derive(Resource)]
+struct MusicPlayer {
+    tracks: Vec<Handle<AudioSource>>,
+    order: Vec<usize>,
+    position: usize,
+    current: Option<PlayingTrack>,
+    fading_out: Option<PlayingTrack>,
+    dim_remaining: f32,
+}
+
+impl MusicPlayer {
+    fn advance(&mut self) {
+        self.position += 1;
+        if self.position >= self.order.len() {
+            // reshuffle for the next pass, without playing the same track twice in a row
+            let last = self.order[self.order.len() - 1];
+            self.order.shuffle(&mut rand::thread_rng());
+            if self.order[0] == last {
+                let end = self.order.len() - 1;
+                self.order.swap(0, end);
+            }
+            self.position = 0;
+        }
+    }
+}
+
+fn load_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut order: Vec<usize> = (0..PLAYLIST.len()).collect();
+    order.shuffle(&mut rand::thread_rng());
+
+    commands.insert_resource(MusicPlayer {
+        tracks: PLAYLIST.iter().map(|(path, _)| asset_server.load(*path)).collect(),
+        order,
+        position: 0,
+        current: None,
+        fading_out: None,
+        dim_remaining: 0.,
+    });
+}
+
+fn update_music(audio: Res<Audio>,
+                sinks: Res<Assets<AudioSink>>,
+                settings: Res<Settings>,
+                time: Res<Time>,
+                time_attack: Res<TimeAttack>,
+                mut player: ResMut<MusicPlayer>,
+                mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+            ) {
+    let player = &mut *player;
+    // the track lengths are at normal speed, so a sped up track runs through them faster
+    let speed = if time_attack.in_lightning_round() { LIGHTNING_SPEED } else { 1. };
+    let delta = time.delta_seconds() * speed;
+
+    for ev in ev_word_accepted.iter() {
+        if ev.pangram {
+            player.dim_remaining = FANFARE_SECONDS;
+        }
+    }
+    player.dim_remaining = (player.dim_remaining - time.delta_seconds()).max(0.);
+    let volume = if player.dim_remaining > 0. { settings.effective_music_volume() * FANFARE_DIM } else { settings.effective_music_volume() };
+
+    let needs_next = match &player.current {
+        Some(track) => track.elapsed >= track.duration - CROSSFADE_SECONDS,
+        None => true,
+    };
+    if needs_next {
+        if let Some(finished) = player.fading_out.take() {
+            if let Some(sink) = sinks.get(&finished.sink) {
+                sink.stop();
+            }
+        }
+        if player.current.is_some() {
+            player.advance();
+        }
+        player.fading_out = player.current.take();
+
+        let index = player.order[player.position];
+        let sink = sinks.get_handle(audio.play_with_settings(player.tracks[index].clone(), PlaybackSettings::ONCE.with_volume(0.)));
+        player.current = Some(PlayingTrack { sink, elapsed: 0., duration: PLAYLIST[index].1 });
+    }
+
+    if let Some(track) = &mut player.current {
+        track.elapsed += delta;
+        if let Some(sink) = sinks.get(&track.sink) {
+            sink.set_volume(volume * (track.elapsed / CROSSFADE_SECONDS).min(1.));
+            sink.set_speed(speed);
+        }
+    }
+
+    if let Some(track) = &mut player.fading_out {
+        track.elapsed += delta;
+        if let Some(sink) = sinks.get(&track.sink) {
+            sink.set_volume(volume * ((track.duration - track.elapsed) / CROSSFADE_SECONDS).clamp(0., 1.));
+            sink.set_speed(speed);
+        }
+    }
+}
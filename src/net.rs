@@ -0,0 +1,399 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+pub use wordgame::protocol::NetMessage;
+use wordgame::puzzle_code::PuzzleCode;
+use wordgame::words::{GameState, WordList};
+
+use crate::recovery::{RecoveryAction, RecoveryChosenEvent, RecoveryDialog, RecoveryKind, RecoveryQueue};
+use crate::{current_puzzle_code, setup_goals, AppState, GameMode, NextPuzzle};
+
+// A direct connection to one other player, for the modes that play across two machines.
+// One side hosts (--host) and the other joins (--join with the host's room code or address),
+// after that both ends are the same, and both wait in the lobby until they're ready, see
+// lobby.rs. Anyone else can watch with --spectate ADDR, see spectate.rs. Messages are RON, one
+// per line, over a plain TCP stream, and whatever arrives waits in the NetInbox until the mode
+// that understands it takes it out. What can't be written straight away waits in the peer's
+// outgoing buffer for the next frame, so a line is never cut short.
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetInbox>()
+            .add_event::<PeerConnectedEvent>()
+            .add_startup_system(start_networking)
+            .add_system(apply_connection_recovery)
+            .add_system(finish_connecting)
+            .add_system(accept_peer)
+            .add_system(receive_messages)
+            .add_system(flush_messages)
+            .add_system(share_board_on_connect)
+            .add_system(show_connection_status)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(share_new_board.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(join_board));
+    }
+}
+
+pub const DEFAULT_PORT: u16 = 7878;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_BUFFERED: usize = 64 * 1024; // either way, a peer this far behind or this far into one line has gone wrong
+
+#[derive(Resource, Clone, Debug)]
+pub enum NetRole {
+    Host { port: u16 },
+    Join { addr: String },
+    Spectate { addr: String },
+}
+
+// the host is always player one and whoever joins player two
+#[derive(Resource)]
+pub struct NetPeer {
+    stream: TcpStream,
+    received: Vec<u8>,
+    sending: Vec<u8>,
+    gone: bool,
+    pub local_player: usize,
+}
+
+impl NetPeer {
+    pub fn new(stream: TcpStream, local_player: usize) -> Result<NetPeer, String> {
+        stream.set_nonblocking(true).map_err(|err| err.to_string())?;
+        stream.set_nodelay(true).map_err(|err| err.to_string())?;
+        Ok(NetPeer { stream, received: Vec::new(), sending: Vec::new(), gone: false, local_player })
+    }
+
+    // false once the other end has gone, the NetPeer resource is dropped by flush_messages then
+    pub fn send(&mut self, message: &NetMessage) -> bool {
+        let mut line = ron::to_string(message).unwrap();
+        line.push('\n');
+        self.sending.extend_from_slice(line.as_bytes());
+        self.flush()
+    }
+
+    // writes as much of the outgoing buffer as the stream takes without blocking
+    pub fn flush(&mut self) -> bool {
+        while !self.gone && !self.sending.is_empty() {
+            match self.stream.write(&self.sending) {
+                Ok(0) => self.gone = true,
+                Ok(count) => {
+                    self.sending.drain(..count);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    warn!("could not send: {}", err);
+                    self.gone = true;
+                }
+            }
+        }
+
+        if self.sending.len() > MAX_BUFFERED {
+            warn!("the other end has stopped reading");
+            self.gone = true;
+        }
+        !self.gone
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct NetInbox {
+    messages: Vec<NetMessage>,
+}
+
+impl NetInbox {
+    // only messages about the current board, anything after a new board waits until it's up
+    pub fn take(&mut self, mut wanted: impl FnMut(&NetMessage) -> bool) -> Vec<NetMessage> {
+        let mut board_at = self.messages.iter().position(|message| matches!(message, NetMessage::Board { .. })).unwrap_or(self.messages.len());
+        let mut taken = Vec::new();
+
+        let mut i = 0;
+        while i < board_at {
+            if wanted(&self.messages[i]) {
+                taken.push(self.messages.remove(i));
+                board_at -= 1;
+            }
+            else {
+                i += 1;
+            }
+        }
+
+        taken
+    }
+
+    pub fn has_new_board(&self) -> bool {
+        self.messages.iter().any(|message| matches!(message, NetMessage::Board { .. }))
+    }
+}
+
+// sent on the host when someone joins, so the modes can bring them up to date
+pub struct PeerConnectedEvent;
+
+#[derive(Resource)]
+struct NetListener(TcpListener);
+
+#[derive(Resource)]
+struct Connecting(Task<Result<TcpStream, String>>);
+
+#[derive(Component)]
+struct ConnectionStatus {}
+
+fn connection_failed(message: String) -> RecoveryDialog {
+    RecoveryDialog {
+        kind: RecoveryKind::Connection,
+        message,
+        actions: vec![RecoveryAction::Retry, RecoveryAction::Continue],
+    }
+}
+
+fn connect(commands: &mut Commands, role: &NetRole, recovery: &mut RecoveryQueue) {
+    match role.clone() {
+        NetRole::Host { port } => match TcpListener::bind(("0.0.0.0", port)).and_then(|listener| {
+            listener.set_nonblocking(true)?;
+            Ok(listener)
+        }) {
+            Ok(listener) => {
+                info!("waiting for another player on port {}", port);
+                commands.insert_resource(NetListener(listener));
+            }
+            Err(err) => recovery.push(connection_failed(format!("Could not host on port {}: {}", port, err))),
+        },
+        NetRole::Join { addr } | NetRole::Spectate { addr } => {
+            // resolving and connecting can both take a while, so neither happens on the main thread
+            let task = AsyncComputeTaskPool::get().spawn(async move {
+                let resolved: Vec<SocketAddr> = addr.to_socket_addrs().map_err(|err| format!("Could not find {}: {}", addr, err))?.collect();
+                let target = resolved.first().ok_or(format!("Could not find {}", addr))?;
+                TcpStream::connect_timeout(target, CONNECT_TIMEOUT).map_err(|err| format!("Could not join {}: {}", addr, err))
+            });
+            commands.insert_resource(Connecting(task));
+        }
+    }
+}
+
+fn start_networking(mut commands: Commands,
+                    asset_server: Res<AssetServer>,
+                    role: Res<NetRole>,
+                    mut recovery: ResMut<RecoveryQueue>,
+                ) {
+    commands.spawn(TextBundle::from_section("", TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: 24.,
+        color: Color::GRAY,
+    }).with_style(Style {
+        position_type: PositionType::Absolute,
+        position: UiRect { right: Val::Px(12.), top: Val::Px(12.), ..default() },
+        ..default()
+    })).insert(ConnectionStatus {});
+
+    connect(&mut commands, &role, &mut recovery);
+}
+
+fn apply_connection_recovery(mut commands: Commands,
+                             mut ev_recovery_chosen: EventReader<RecoveryChosenEvent>,
+                             role: Res<NetRole>,
+                             mut recovery: ResMut<RecoveryQueue>,
+                            ) {
+    for ev in ev_recovery_chosen.iter() {
+        if ev.kind == RecoveryKind::Connection && ev.action == RecoveryAction::Retry {
+            connect(&mut commands, &role, &mut recovery);
+        }
+    }
+}
+
+fn finish_connecting(mut commands: Commands,
+                     connecting: Option<ResMut<Connecting>>,
+                     mut recovery: ResMut<RecoveryQueue>,
+                    ) {
+    let mut connecting = match connecting {
+        Some(connecting) => connecting,
+        None => return,
+    };
+
+    if let Some(result) = future::block_on(future::poll_once(&mut connecting.0)) {
+        commands.remove_resource::<Connecting>();
+        match result.and_then(|stream| NetPeer::new(stream, 1)) {
+            Ok(peer) => {
+                info!("joined game at {}", peer.stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_default());
+                commands.insert_resource(peer);
+            }
+            Err(message) => recovery.push(connection_failed(message)),
+        }
+    }
+}
+
+fn accept_peer(mut commands: Commands,
+               listener: Option<Res<NetListener>>,
+               peer: Option<Res<NetPeer>>,
+               mut ev_peer_connected: EventWriter<PeerConnectedEvent>,
+            ) {
+    let listener = match listener {
+        Some(listener) => listener,
+        None => return,
+    };
+
+    // one other player at a time, anyone else waits in the backlog until they leave
+    if peer.is_some() {
+        return;
+    }
+
+    match listener.0.accept() {
+        Ok((stream, addr)) => match NetPeer::new(stream, 0) {
+            Ok(peer) => {
+                info!("{} joined", addr);
+                commands.insert_resource(peer);
+                ev_peer_connected.send(PeerConnectedEvent);
+            }
+            Err(err) => warn!("could not set up connection from {}: {}", addr, err),
+        },
+        Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+        Err(err) => warn!("could not accept connection: {}", err),
+    }
+}
+
+fn receive_messages(mut commands: Commands, peer: Option<ResMut<NetPeer>>, mut inbox: ResMut<NetInbox>) {
+    let mut peer = match peer {
+        Some(peer) => peer,
+        None => return,
+    };
+
+    let mut buffer = [0; 4096];
+    let mut disconnected = false;
+    loop {
+        match peer.stream.read(&mut buffer) {
+            Ok(0) => {
+                disconnected = true;
+                break;
+            }
+            Ok(count) => peer.received.extend_from_slice(&buffer[..count]),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(err) => {
+                warn!("connection lost: {}", err);
+                disconnected = true;
+                break;
+            }
+        }
+    }
+
+    while let Some(end) = peer.received.iter().position(|byte| *byte == b'\n') {
+        let line: Vec<u8> = peer.received.drain(..=end).collect();
+        match std::str::from_utf8(&line).map_err(|err| err.to_string()).and_then(|line| ron::from_str(line).map_err(|err| err.to_string())) {
+            Ok(message) => inbox.messages.push(message),
+            Err(err) => warn!("ignoring a message that couldn't be read: {}", err),
+        }
+    }
+
+    // what's left is the start of a line still coming
+    if peer.received.len() > MAX_BUFFERED {
+        warn!("dropping a connection sending a message too long to read");
+        disconnected = true;
+    }
+
+    if disconnected {
+        info!("the other player left");
+        commands.remove_resource::<NetPeer>();
+    }
+}
+
+fn flush_messages(mut commands: Commands, peer: Option<ResMut<NetPeer>>) {
+    if let Some(mut peer) = peer {
+        if !peer.flush() {
+            info!("the other player left");
+            commands.remove_resource::<NetPeer>();
+        }
+    }
+}
+
+fn show_connection_status(role: Res<NetRole>,
+                          peer: Option<Res<NetPeer>>,
+                          connecting: Option<Res<Connecting>>,
+                          mut texts: Query<&mut Text, With<ConnectionStatus>>,
+                        ) {
+    let status = match (&*role, peer.is_some(), connecting.is_some()) {
+        (NetRole::Spectate { .. }, true, _) => String::from("Watching"),
+        (_, true, _) => String::from("Connected"),
+        (NetRole::Host { port }, false, _) => format!("Waiting for a player on port {}", port),
+        (NetRole::Join { addr }, false, true) => format!("Joining {}...", addr),
+        (NetRole::Spectate { addr }, false, true) => format!("Connecting to {} to watch...", addr),
+        (NetRole::Join { .. } | NetRole::Spectate { .. }, false, false) => String::from("Not connected"),
+    };
+
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
+
+fn send_board(peer: &mut NetPeer, gamestate: &GameState, mode: GameMode) {
+    match current_puzzle_code(gamestate, mode).encode() {
+        Ok(code) => {
+            peer.send(&NetMessage::Board { code });
+        }
+        Err(message) => warn!("could not share the board: {}", message),
+    }
+}
+
+pub fn share_board_on_connect(mut ev_peer_connected: EventReader<PeerConnectedEvent>,
+                              peer: Option<ResMut<NetPeer>>,
+                              gamestate: Res<GameState>,
+                              mode: Res<GameMode>,
+                              state: Res<State<AppState>>,
+                            ) {
+    // a race starts on a fresh board instead, which is shared once it's been set up, and the
+    // first board isn't there to share before loading and the lobby are over
+    if *mode == GameMode::Race || matches!(state.current(), AppState::Loading | AppState::Lobby) {
+        return;
+    }
+
+    if let Some(mut peer) = peer {
+        for _ev in ev_peer_connected.iter() {
+            send_board(&mut peer, &gamestate, *mode);
+        }
+    }
+}
+
+// the host picks every board, so a new one there is passed straight on
+fn share_new_board(role: Res<NetRole>, peer: Option<ResMut<NetPeer>>, gamestate: Res<GameState>, mode: Res<GameMode>) {
+    if let (NetRole::Host { .. }, Some(mut peer)) = (&*role, peer) {
+        send_board(&mut peer, &gamestate, *mode);
+    }
+}
+
+fn join_board(mut inbox: ResMut<NetInbox>,
+              gamestate: Res<GameState>,
+              wordlist: Res<WordList>,
+              mode: Res<GameMode>,
+              mut next_puzzle: ResMut<NextPuzzle>,
+              mut state: ResMut<State<AppState>>,
+            ) {
+    let found = inbox.messages.iter().enumerate().find_map(|(at, message)| match message {
+        NetMessage::Board { code } => Some((at, code.clone())),
+        _ => None,
+    });
+    let (board_at, code) = match found {
+        Some(found) => found,
+        None => return,
+    };
+
+    // already picked from the seed agreed in the lobby
+    if current_puzzle_code(&gamestate, *mode).encode().as_ref() != Ok(&code) {
+        // a board the host's word list has and this one doesn't can't be played here
+        match PuzzleCode::decode(&code).and_then(|decoded| decoded.check_playable(&wordlist).map(|()| decoded)) {
+            Ok(decoded) => {
+                // another transition may already be queued this frame, in which case the board
+                // waits in the inbox until next frame
+                if state.set(AppState::NewBoard).is_err() {
+                    return;
+                }
+                info!("playing the host's board {}", code);
+                next_puzzle.0 = Some(decoded);
+            }
+            Err(message) => warn!("Could not play the host's board {}: {}", code, message),
+        }
+    }
+
+    // anything left over from before was about the old board
+    inbox.messages.drain(..=board_at);
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use notify_rust::Notification;
+use std::time::{SystemTime, UNIX_EPOCH};
+use wordgame::daily;
+
+use crate::daily_history::DailyHistory;
+use crate::settings::Settings;
+
+// Desktop notifications in builds with the notifications feature, for players who turn
+// daily_notifications on in the settings file: one when the next daily puzzle comes out while the
+// game is open, minimised or not, and with daily_reminder set, one at that hour (UTC, like the
+// boards themselves) if the day's puzzle hasn't been played yet. Each is sent at most once a day.
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(check_daily_puzzle);
+    }
+}
+
+const CHECK_SECONDS: f32 = 60.;
+
+#[derive(Default)]
+struct Notified {
+    since: f32,
+    day: Option<u64>, // the day the game was on at the last check
+    reminded: Option<u64>,
+}
+
+fn notify(body: &str) {
+    let shown = Notification::new()
+        .appname("Spelling Tiles")
+        .summary("Spelling Tiles")
+        .body(body)
+        .show();
+    if let Err(err) = shown {
+        warn!("couldn't show a notification: {}", err);
+    }
+}
+
+fn hour_of_day() -> u32 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    ((now.as_secs() / (60 * 60)) % 24) as u32
+}
+
+fn check_daily_puzzle(time: Res<Time>, settings: Res<Settings>, history: Res<DailyHistory>, mut notified: Local<Notified>) {
+    notified.since += time.delta_seconds();
+    if notified.since < CHECK_SECONDS && notified.day.is_some() {
+        return;
+    }
+    notified.since = 0.;
+
+    // the first check only notes the day, the puzzle already out when the game started isn't news
+    let today = daily::today();
+    let new_day = notified.day.is_some_and(|day| day != today);
+    notified.day = Some(today);
+    if !settings.daily_notifications || history.played(today) {
+        return;
+    }
+
+    if new_day {
+        notify(&format!("The daily puzzle for {} is ready", daily::date_string(today)));
+    }
+    else if settings.daily_reminder.is_some_and(|hour| hour_of_day() >= hour) && notified.reminded != Some(today) {
+        notified.reminded = Some(today);
+        notify("Today's daily puzzle is still waiting for you");
+    }
+}
@@ -0,0 +1,340 @@
+use bevy::prelude::*;
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::reflect::TypeUuid;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wordgame::packs::PuzzlePack;
+use wordgame::words::{GameState, WordList, RANKS};
+
+use crate::challenge_menu::ActiveChallenge;
+use crate::focus::Focusable;
+use crate::layout::Layout;
+use crate::palette::Overlay;
+use crate::storage;
+use crate::{record_correct_words, setup_goals, AppState, BoardEntity, NextPuzzle};
+
+// Hand-picked boards in themed packs (see wordgame::packs for the file format). A button on the
+// board (or Ctrl+P) opens a screen listing every pack and its boards, and picking one plays it.
+// Reaching the top rank on a pack board marks it solved, kept in packs.ron next to the settings.
+pub struct PackMenuPlugin;
+
+impl Plugin for PackMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PackAsset>()
+            .init_asset_loader::<PackAssetLoader>()
+            .init_resource::<PackProgress>()
+            .init_resource::<CurrentPackBoard>()
+            .add_startup_system(load_packs)
+            .add_startup_system(load_pack_progress)
+            .add_system(save_pack_progress)
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(start_pack_board.after(setup_goals))
+                .with_system(spawn_packs_button.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(open_pack_menu)
+                .with_system(check_pack_board.after(record_correct_words)))
+            .add_system_set(SystemSet::on_enter(AppState::PackMenu).with_system(spawn_pack_menu))
+            .add_system_set(SystemSet::on_update(AppState::PackMenu).with_system(choose_pack_board))
+            .add_system_set(SystemSet::on_exit(AppState::PackMenu).with_system(despawn_pack_menu));
+    }
+}
+
+pub const PACK_PROGRESS_FILE: &str = "packs.ron";
+const BUILT_IN_PACKS: [&str; 2] = ["packs/animals.pack.ron", "packs/kitchen.pack.ron"];
+
+// a pack file, checked as it loads
+#[derive(TypeUuid)]
+#[uuid = "b3e2d7c4-51a8-4f0e-9c6d-2a7f18e4c903"]
+struct PackAsset(PuzzlePack);
+
+#[derive(Default)]
+struct PackAssetLoader;
+
+impl AssetLoader for PackAssetLoader {
+    fn load<'a>(&'a self, bytes: &'a [u8], load_context: &'a mut LoadContext) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let contents = String::from_utf8(bytes.to_vec())?;
+            let pack = PuzzlePack::parse(&contents).map_err(bevy::asset::Error::msg)?;
+            load_context.set_default_asset(LoadedAsset::new(PackAsset(pack)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pack.ron"]
+    }
+}
+
+#[derive(Resource, Default)]
+struct PuzzlePacks {
+    handles: Vec<Handle<PackAsset>>,
+}
+
+// solved boards by pack title, so reordering the boards of a pack loses its progress
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PackProgress {
+    solved: BTreeMap<String, Vec<usize>>,
+}
+
+impl PackProgress {
+    fn is_solved(&self, title: &str, board: usize) -> bool {
+        self.solved.get(title).is_some_and(|boards| boards.contains(&board))
+    }
+
+    fn solved_count(&self, pack: &PuzzlePack) -> usize {
+        (0..pack.boards.len()).filter(|board| self.is_solved(&pack.title, *board)).count()
+    }
+}
+
+// the pack and board being played, chosen is set by the menu for the board it starts
+#[derive(Resource, Default)]
+struct CurrentPackBoard {
+    chosen: Option<(String, usize)>,
+    playing: Option<(String, usize)>,
+}
+
+#[derive(Component)]
+struct PacksButton {}
+
+#[derive(Component)]
+struct PackMenuRoot {}
+
+#[derive(Component)]
+struct PackBoardButton {
+    pack: usize,
+    board: usize,
+}
+
+#[derive(Component)]
+struct PackMenuError {}
+
+fn load_packs(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handles = BUILT_IN_PACKS.iter().map(|path| asset_server.load(*path)).collect();
+    commands.insert_resource(PuzzlePacks { handles });
+}
+
+// a broken or unwritable file only costs the record, so it's a warning rather than a dialog
+fn load_pack_progress(mut progress: ResMut<PackProgress>) {
+    let loaded = storage::read(PACK_PROGRESS_FILE).and_then(|contents| match contents {
+        Some(contents) => ron::from_str(&contents).map_err(|err| format!("Puzzle pack progress {} is corrupt: {}", storage::location(PACK_PROGRESS_FILE), err)),
+        None => Ok(PackProgress::default()),
+    });
+
+    match loaded {
+        Ok(loaded) => *progress = loaded,
+        Err(message) => warn!("{}", message),
+    }
+}
+
+fn save_pack_progress(progress: Res<PackProgress>) {
+    if !progress.is_changed() || progress.is_added() {
+        return;
+    }
+
+    let saved = ron::ser::to_string_pretty(&*progress, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| storage::write(PACK_PROGRESS_FILE, &contents));
+    if let Err(message) = saved {
+        warn!("{}", message);
+    }
+}
+
+// any other way onto a new board leaves the pack behind
+fn start_pack_board(mut current: ResMut<CurrentPackBoard>) {
+    current.playing = current.chosen.take();
+    if let Some((title, board)) = &current.playing {
+        info!("playing board {} of puzzle pack \"{}\"", board + 1, title);
+    }
+}
+
+fn spawn_packs_button(mut commands: Commands, asset_server: Res<AssetServer>, layout: Res<Layout>) {
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+
+    // above the puzzle code buttons
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(16.), bottom: Val::Px(12. + layout.button_row_height()), ..default() },
+            padding: layout.button_padding(),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(PacksButton {}).insert(Focusable { state: AppState::Playing, order: 12 }).with_children(|button| {
+        button.spawn(TextBundle::from_section("Puzzle packs", button_style.clone()));
+    });
+}
+
+fn open_pack_menu(keys: Res<Input<KeyCode>>,
+                  buttons: Query<&Interaction, (Changed<Interaction>, With<PacksButton>)>,
+                  mut state: ResMut<State<AppState>>,
+                ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if (ctrl && keys.just_pressed(KeyCode::P)) || buttons.iter().any(|interaction| *interaction == Interaction::Clicked) {
+        if let Err(err) = state.push(AppState::PackMenu) {
+            debug!("not opening the puzzle packs, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn check_pack_board(gamestate: Res<GameState>, current: Res<CurrentPackBoard>, mut progress: ResMut<PackProgress>) {
+    let (title, board) = match &current.playing {
+        Some(playing) => playing,
+        None => return,
+    };
+    if !gamestate.is_changed() || gamestate.rank() < RANKS.len() - 1 || progress.is_solved(title, *board) {
+        return;
+    }
+
+    info!("solved board {} of puzzle pack \"{}\"", board + 1, title);
+    let solved = progress.solved.entry(title.clone()).or_default();
+    solved.push(*board);
+    solved.sort();
+}
+
+fn spawn_pack_menu(mut commands: Commands,
+                   asset_server: Res<AssetServer>,
+                   packs: Res<PuzzlePacks>,
+                   pack_assets: Res<Assets<PackAsset>>,
+                   progress: Res<PackProgress>,
+                   layout: Res<Layout>,
+                ) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let pack_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 32., color: Color::WHITE };
+    let help_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::GRAY };
+    let error_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::ORANGE_RED };
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+
+    // packs that failed to load have already said why in the log
+    let loaded: Vec<&PuzzlePack> = packs.handles.iter().filter_map(|handle| pack_assets.get(handle)).map(|asset| &asset.0).collect();
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(PackMenuRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Puzzle packs", title_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+
+        if loaded.is_empty() {
+            parent.spawn(TextBundle::from_section("No puzzle packs found", pack_style.clone()));
+        }
+
+        let mut order = 0;
+        for (pack_index, pack) in loaded.iter().enumerate() {
+            let heading = format!("{}  {}/{} solved", pack.title, progress.solved_count(pack), pack.boards.len());
+            parent.spawn(TextBundle::from_section(heading, pack_style.clone()).with_style(Style {
+                margin: UiRect { top: Val::Px(12.), ..default() },
+                ..default()
+            }));
+            if !pack.description.is_empty() {
+                parent.spawn(TextBundle::from_section(pack.description.clone(), help_style.clone()));
+            }
+
+            parent.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            }).with_children(|row| {
+                for board in 0..pack.boards.len() {
+                    // solved boards are greyed out, and the count above says how many
+                    let background = if progress.is_solved(&pack.title, board) { Color::GRAY } else { Color::ALICE_BLUE };
+                    row.spawn(ButtonBundle {
+                        style: Style {
+                            margin: UiRect::all(Val::Px(4.)),
+                            padding: layout.button_padding(),
+                            ..default()
+                        },
+                        background_color: background.into(),
+                        ..default()
+                    }).insert(PackBoardButton { pack: pack_index, board }).insert(Focusable { state: AppState::PackMenu, order }).with_children(|button| {
+                        button.spawn(TextBundle::from_section(format!("{}", board + 1), button_style.clone()));
+                    });
+                    order += 1;
+                }
+            });
+        }
+
+        parent.spawn(TextBundle::from_section("", error_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(12.)),
+            ..default()
+        })).insert(PackMenuError {});
+
+        parent.spawn(TextBundle::from_section("Pick a board to play it, reach Genius to mark it solved, Esc to go back", help_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+fn choose_pack_board(mut keys: ResMut<Input<KeyCode>>,
+                     buttons: Query<(&Interaction, &PackBoardButton), Changed<Interaction>>,
+                     packs: Res<PuzzlePacks>,
+                     pack_assets: Res<Assets<PackAsset>>,
+                     wordlist: Res<WordList>,
+                     mut current: ResMut<CurrentPackBoard>,
+                     mut next_puzzle: ResMut<NextPuzzle>,
+                     mut active_challenge: ResMut<ActiveChallenge>,
+                     mut state: ResMut<State<AppState>>,
+                     mut error_text: Query<&mut Text, With<PackMenuError>>,
+                ) {
+    if keys.just_pressed(KeyCode::Escape) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        if let Err(err) = state.pop() {
+            debug!("not closing the puzzle packs, the state is already changing: {:?}", err);
+        }
+        return;
+    }
+
+    let chosen = buttons.iter().find(|(interaction, _)| **interaction == Interaction::Clicked).map(|(_, button)| button);
+    let button = match chosen {
+        Some(button) => button,
+        None => return,
+    };
+
+    // numbered the same way as the menu, which skipped any that didn't load
+    let loaded: Vec<&PuzzlePack> = packs.handles.iter().filter_map(|handle| pack_assets.get(handle)).map(|asset| &asset.0).collect();
+    let pack = match loaded.get(button.pack) {
+        Some(pack) => pack,
+        None => return,
+    };
+
+    // a pack written for a different word list can name boards this one can't make
+    let puzzle = pack.boards[button.board].puzzle().and_then(|code| code.check_playable(&wordlist).map(|()| code));
+    match puzzle {
+        Ok(code) => match state.replace(AppState::Playing) {
+            Ok(()) => {
+                next_puzzle.0 = Some(code);
+                active_challenge.0 = None;
+                current.chosen = Some((pack.title.clone(), button.board));
+            }
+            Err(err) => debug!("not starting the pack board, the state is already changing: {:?}", err),
+        },
+        Err(message) => {
+            for mut text in error_text.iter_mut() {
+                text.sections[0].value = message.clone();
+            }
+        }
+    }
+}
+
+fn despawn_pack_menu(mut commands: Commands, screens: Query<Entity, With<PackMenuRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::puzzle_code::PuzzleCode;
+use crate::words::word_to_bits;
+
+// A named set of hand-picked boards, like "Animals week". Packs are RON files in assets/packs:
+//
+//     (
+//         title: "Animals week",
+//         description: "One animal a day, spelled out in the pangram",
+//         boards: [(letters: "ELEPHANT", required: 'P'), ...],
+//     )
+//
+// The letters can be any word using the board's seven letters, usually its pangram, which keeps
+// the file readable, and the required letter has to be one of them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PuzzlePack {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub boards: Vec<PackBoard>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PackBoard {
+    pub letters: String,
+    pub required: char,
+}
+
+impl PackBoard {
+    pub fn puzzle(&self) -> Result<PuzzleCode, String> {
+        if self.letters.is_empty() || !self.letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("'{}' isn't a word", self.letters));
+        }
+
+        let letter_bits = word_to_bits(&self.letters);
+        if letter_bits.count_ones() != 7 {
+            return Err(format!("'{}' doesn't have exactly seven different letters", self.letters));
+        }
+        if !self.letters.to_ascii_uppercase().contains(self.required.to_ascii_uppercase()) {
+            return Err(format!("'{}' isn't one of the letters of '{}'", self.required, self.letters));
+        }
+
+        Ok(PuzzleCode { letter_bits, required_letter: self.required.to_ascii_uppercase(), mode: None })
+    }
+}
+
+impl PuzzlePack {
+    // every board is checked up front, so one bad line doesn't turn up halfway through the pack
+    pub fn parse(text: &str) -> Result<PuzzlePack, String> {
+        let pack: PuzzlePack = ron::from_str(text).map_err(|err| format!("That puzzle pack is damaged: {}", err))?;
+        if pack.boards.is_empty() {
+            return Err(format!("Puzzle pack \"{}\" has no boards", pack.title));
+        }
+        for (i, board) in pack.boards.iter().enumerate() {
+            board.puzzle().map_err(|message| format!("Board {} of \"{}\": {}", i + 1, pack.title, message))?;
+        }
+        Ok(pack)
+    }
+}
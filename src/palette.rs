@@ -0,0 +1,283 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+use crate::{HintText, TILE_RADIUS};
+
+// Tile and feedback colours, picked in the settings. The colourblind palettes keep their colours
+// apart for that kind of colour vision and also never leave the colour to carry a meaning alone:
+// the required tile gets a ring, required letters in the guess are drawn larger, pangrams in the
+// found list get a star and the hint line marks words with + or x.
+// High contrast is a separate switch that works with any palette: pure black, white and yellow,
+// thick outlines, bigger text and solid backgrounds behind the menus. Kids mode warms up the
+// standard palette and leaves the others alone. A theme from the mods folder (see mods.rs) is
+// picked along with the palettes and stands in for their colours.
+pub struct PalettePlugin;
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_palette)
+            .add_system(colour_overlays);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Palette {
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+pub const ALL_PALETTES: [Palette; 4] = [Palette::Standard, Palette::Deuteranopia, Palette::Protanopia, Palette::Tritanopia];
+
+// how much bigger required letters are in the guess when the palette marks them
+pub const REQUIRED_LETTER_SIZE: f32 = 1.3;
+
+impl Palette {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::Deuteranopia => "Deuteranopia",
+            Palette::Protanopia => "Protanopia",
+            Palette::Tritanopia => "Tritanopia",
+        }
+    }
+
+    // adds the shapes and marks on top of the colours
+    pub fn marks(&self) -> bool {
+        *self != Palette::Standard
+    }
+
+    pub fn tile(&self) -> Color {
+        match self {
+            Palette::Standard => Color::ALICE_BLUE,
+            _ => Color::rgb(0.95, 0.95, 0.95),
+        }
+    }
+
+    // the required tile and required letters in the guess
+    pub fn required(&self) -> Color {
+        match self {
+            Palette::Standard => Color::CYAN,
+            Palette::Deuteranopia => Color::rgb(0.9, 0.62, 0.),
+            Palette::Protanopia => Color::rgb(0.94, 0.89, 0.26),
+            Palette::Tritanopia => Color::rgb(0.84, 0.37, 0.),
+        }
+    }
+
+    pub fn pangram(&self) -> Color {
+        match self {
+            Palette::Standard => Color::CYAN,
+            Palette::Deuteranopia | Palette::Protanopia => Color::rgb(0.34, 0.71, 0.91),
+            Palette::Tritanopia => Color::rgb(0., 0.62, 0.45),
+        }
+    }
+
+    pub fn accepted(&self) -> Color {
+        match self {
+            Palette::Standard => Color::WHITE,
+            Palette::Deuteranopia | Palette::Protanopia => Color::rgb(0.34, 0.71, 0.91),
+            Palette::Tritanopia => Color::rgb(0., 0.62, 0.45),
+        }
+    }
+
+    pub fn rejected(&self) -> Color {
+        match self {
+            Palette::Standard => Color::WHITE,
+            Palette::Deuteranopia => Color::rgb(0.8, 0.47, 0.65),
+            Palette::Protanopia => Color::rgb(0.9, 0.62, 0.),
+            Palette::Tritanopia => Color::rgb(0.8, 0.47, 0.65),
+        }
+    }
+
+    // the guess line flashing a rejected word, standard red where the hint line stays white
+    pub fn rejected_flash(&self) -> Color {
+        match self {
+            Palette::Standard => Color::rgb(0.93, 0.26, 0.26),
+            _ => self.rejected(),
+        }
+    }
+
+    // the letter count by the guess once there are enough letters for a word
+    pub fn long_enough(&self) -> Color {
+        match self {
+            Palette::Standard => Color::rgb(0.4, 0.86, 0.4),
+            _ => self.accepted(),
+        }
+    }
+}
+
+const HIGH_CONTRAST_TEXT_SCALE: f32 = 1.25;
+
+// a theme's colours, the ones it doesn't give follow from these
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ThemeColours {
+    pub tile: Color,
+    pub required: Color,
+    pub pangram: Color,
+    pub accepted: Color,
+    pub rejected: Color,
+    pub background: Color,
+    pub marks: bool, // the ring and other marks the colourblind palettes add
+}
+
+// the palette as drawn, after high contrast has had its say
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Colours {
+    pub palette: Palette,
+    pub high_contrast: bool,
+    pub kids: bool, // warmer and brighter, only over the standard palette so the others still do their job
+    pub theme: Option<ThemeColours>,
+}
+
+impl Colours {
+    pub fn marks(&self) -> bool {
+        self.high_contrast || self.theme.map_or(self.palette.marks(), |theme| theme.marks)
+    }
+
+    fn kids_theme(&self) -> bool {
+        self.kids && self.palette == Palette::Standard && !self.high_contrast && self.theme.is_none()
+    }
+
+    pub fn tile(&self) -> Color {
+        if self.high_contrast {
+            Color::WHITE
+        }
+        else if let Some(theme) = self.theme {
+            theme.tile
+        }
+        else if self.kids_theme() {
+            Color::rgb(1., 0.93, 0.6)
+        }
+        else {
+            self.palette.tile()
+        }
+    }
+
+    pub fn required(&self) -> Color {
+        if self.high_contrast {
+            Color::YELLOW
+        }
+        else if let Some(theme) = self.theme {
+            theme.required
+        }
+        else if self.kids_theme() {
+            Color::rgb(1., 0.6, 0.45)
+        }
+        else {
+            self.palette.required()
+        }
+    }
+
+    pub fn pangram(&self) -> Color {
+        if self.high_contrast { Color::YELLOW } else { self.theme.map_or_else(|| self.palette.pangram(), |theme| theme.pangram) }
+    }
+
+    pub fn accepted(&self) -> Color {
+        if self.high_contrast { Color::WHITE } else { self.theme.map_or_else(|| self.palette.accepted(), |theme| theme.accepted) }
+    }
+
+    pub fn rejected(&self) -> Color {
+        if self.high_contrast { Color::YELLOW } else { self.theme.map_or_else(|| self.palette.rejected(), |theme| theme.rejected) }
+    }
+
+    pub fn rejected_flash(&self) -> Color {
+        if self.high_contrast { Color::YELLOW } else { self.theme.map_or_else(|| self.palette.rejected_flash(), |theme| theme.rejected) }
+    }
+
+    pub fn long_enough(&self) -> Color {
+        if self.high_contrast { Color::WHITE } else { self.theme.map_or_else(|| self.palette.long_enough(), |theme| theme.accepted) }
+    }
+
+    pub fn outline_width(&self, radius: f32) -> f32 {
+        if self.high_contrast {
+            radius / 5.
+        }
+        else if self.kids_theme() {
+            radius / 7.
+        }
+        else {
+            radius / 10.
+        }
+    }
+
+    // applied to the board text, and to the UI through the layout's UI scale
+    pub fn text_scale(&self) -> f32 {
+        if self.high_contrast { HIGH_CONTRAST_TEXT_SCALE } else { 1. }
+    }
+
+    // behind the menus and dialogs, the board shows through unless that would get in the way
+    pub fn overlay(&self) -> Color {
+        if self.high_contrast { Color::BLACK } else { Color::rgba(0., 0., 0., 0.85) }
+    }
+
+    pub fn background(&self) -> Color {
+        if self.high_contrast {
+            Color::BLACK
+        }
+        else if let Some(theme) = self.theme {
+            theme.background
+        }
+        else if self.kids_theme() {
+            Color::rgb(0.2, 0.35, 0.6)
+        }
+        else {
+            ClearColor::default().0
+        }
+    }
+}
+
+// a tile coloured by the palette, the required one is the middle of the board
+#[derive(Component)]
+pub struct PaletteTile {
+    pub required: bool,
+    pub radius: f32,
+}
+
+// the ring on the required tile, only shown by palettes with marks
+#[derive(Component)]
+pub struct RequiredMarker {}
+
+// the full screen backdrop of a menu or dialog
+#[derive(Component)]
+pub struct Overlay {}
+
+// recolours boards already on screen when the palette is changed from the settings
+pub fn apply_palette(settings: Res<Settings>,
+                 mut clear_color: ResMut<ClearColor>,
+                 mut tiles: Query<(&PaletteTile, &mut DrawMode)>,
+                 mut markers: Query<&mut Visibility, With<RequiredMarker>>,
+                 mut hints: Query<&mut Text, With<HintText>>,
+                ) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let colours = settings.colours();
+    clear_color.0 = colours.background();
+    for (tile, mut draw_mode) in tiles.iter_mut() {
+        if let DrawMode::Outlined { ref mut fill_mode, ref mut outline_mode } = *draw_mode {
+            fill_mode.color = if tile.required { colours.required() } else { colours.tile() };
+            outline_mode.options.line_width = colours.outline_width(tile.radius);
+        }
+    }
+    for mut visibility in markers.iter_mut() {
+        visibility.is_visible = colours.marks();
+    }
+    for mut text in hints.iter_mut() {
+        text.sections[0].style.font_size = TILE_RADIUS / 2. * colours.text_scale();
+    }
+}
+
+fn colour_overlays(settings: Res<Settings>,
+                   mut overlays: Query<(&mut BackgroundColor, ChangeTrackers<Overlay>), With<Overlay>>,
+                ) {
+    for (mut background, tracker) in overlays.iter_mut() {
+        if tracker.is_added() || settings.is_changed() {
+            background.0 = settings.colours().overlay();
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use bevy::input::touch::TouchInput;
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+
+use crate::palette::Overlay;
+use crate::settings::Settings;
+use crate::{AppState, GameMode};
+
+// Leaving the window pauses the board: every clock in the game only runs while a board is being
+// played, so covering it with the pause screen stops the round timer, combos and the time played
+// together. Coming back to the window carries on, as do Esc and Enter. Timed and daily boards
+// also pause after the settings' idle minutes without any input at all, so the time they record
+// is time spent playing. The race isn't paused, the other player's clock would keep going anyway.
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PauseReason::FocusLost)
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(pause_on_focus_loss)
+                .with_system(pause_when_idle))
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(spawn_pause_screen))
+            .add_system_set(SystemSet::on_update(AppState::Paused).with_system(resume))
+            .add_system_set(SystemSet::on_exit(AppState::Paused).with_system(despawn_pause_screen));
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+enum PauseReason {
+    FocusLost,
+    Idle,
+}
+
+#[derive(Component)]
+struct PauseRoot {}
+
+fn pause(reason: PauseReason, state: &mut State<AppState>, pause_reason: &mut PauseReason) {
+    match state.push(AppState::Paused) {
+        Ok(()) => *pause_reason = reason,
+        Err(err) => debug!("not pausing, the state is already changing: {:?}", err),
+    }
+}
+
+fn pause_on_focus_loss(mut ev_focused: EventReader<WindowFocused>,
+                       mode: Res<GameMode>,
+                       mut state: ResMut<State<AppState>>,
+                       mut pause_reason: ResMut<PauseReason>,
+                    ) {
+    let lost = ev_focused.iter().any(|ev| !ev.focused);
+    if lost && *mode != GameMode::Race {
+        pause(PauseReason::FocusLost, &mut state, &mut pause_reason);
+    }
+}
+
+fn timed(mode: GameMode) -> bool {
+    matches!(mode, GameMode::Daily | GameMode::Marathon | GameMode::TimeAttack | GameMode::Versus)
+}
+
+// anything at all counts as input, a mouse moved over the board as much as a letter typed
+fn pause_when_idle(time: Res<Time>,
+                   settings: Res<Settings>,
+                   mode: Res<GameMode>,
+                   keys: Res<Input<KeyCode>>,
+                   mouse_buttons: Res<Input<MouseButton>>,
+                   gamepad_buttons: Res<Input<GamepadButton>>,
+                   mut ev_cursor_moved: EventReader<CursorMoved>,
+                   mut ev_touch: EventReader<TouchInput>,
+                   mut idle: Local<f32>,
+                   mut state: ResMut<State<AppState>>,
+                   mut pause_reason: ResMut<PauseReason>,
+                ) {
+    let moved = ev_cursor_moved.iter().count() > 0 || ev_touch.iter().count() > 0;
+    let pressed = keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some();
+    if moved || pressed || settings.idle_pause_minutes == 0 || !timed(*mode) {
+        *idle = 0.;
+        return;
+    }
+
+    *idle += time.delta_seconds();
+    if *idle >= settings.idle_pause_minutes as f32 * 60. {
+        info!("no input for {} minutes, pausing", settings.idle_pause_minutes);
+        *idle = 0.;
+        pause(PauseReason::Idle, &mut state, &mut pause_reason);
+    }
+}
+
+fn spawn_pause_screen(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>, pause_reason: Res<PauseReason>) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let help_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::GRAY };
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(PauseRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Paused", title_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        let help = match *pause_reason {
+            PauseReason::FocusLost => String::from("The clock is stopped until you're back, or press Esc or Enter"),
+            PauseReason::Idle => format!("Nothing happened for {} minutes, so the clock is stopped. Esc or Enter to carry on", settings.idle_pause_minutes),
+        };
+        parent.spawn(TextBundle::from_section(help, help_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+fn resume(mut keys: ResMut<Input<KeyCode>>, mut ev_focused: EventReader<WindowFocused>, mut state: ResMut<State<AppState>>) {
+    let focused = ev_focused.iter().any(|ev| ev.focused);
+    if focused || keys.any_just_pressed([KeyCode::Escape, KeyCode::Return]) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.pop() {
+            debug!("not resuming, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn despawn_pause_screen(mut commands: Commands, screens: Query<Entity, With<PauseRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
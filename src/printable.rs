@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use crate::words::{is_pangram, GameState};
+
+// A board on an A4 page as SVG, for handing to someone away from a screen: the seven tiles with
+// the required letter in the middle, how many words there are of each length, and a blank line
+// for each to write in. Browsers print SVG as is, so nothing past the format is needed.
+
+const PAGE_WIDTH: f32 = 210.; // millimetres, which the viewBox is in
+const PAGE_HEIGHT: f32 = 297.;
+const TILE_RADIUS: f32 = 16.;
+const BOARD_CENTER: (f32, f32) = (105., 90.);
+const GRID_TOP: f32 = 160.;
+const GRID_COLUMNS: usize = 3;
+const MAX_ROW_HEIGHT: f32 = 9.;
+
+// flat sides top and bottom, like the tiles on screen
+fn hexagon_points(center: (f32, f32), radius: f32) -> String {
+    let corners: Vec<String> = (0..6).map(|i| {
+        let angle = (i as f32 * 60_f32).to_radians();
+        format!("{:.2},{:.2}", center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+    }).collect();
+    corners.join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+pub fn puzzle_sheet(gamestate: &GameState, title: &str) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">\n",
+        w = PAGE_WIDTH,
+        h = PAGE_HEIGHT,
+    );
+    svg.push_str(&format!("<text x=\"105\" y=\"25\" font-size=\"10\" text-anchor=\"middle\">{}</text>\n", escape(title)));
+    svg.push_str(&format!(
+        "<text x=\"105\" y=\"35\" font-size=\"5\" text-anchor=\"middle\">Words of {} letters or more using the middle letter, letters can be used again</text>\n",
+        gamestate.min_word_length,
+    ));
+
+    // the same spacing as the board on screen, the required letter in the middle
+    for (i, letter) in gamestate.target_string.chars().enumerate() {
+        let center = if i == 0 {
+            BOARD_CENTER
+        }
+        else {
+            let angle = ((i - 1) as f32 * 60.).to_radians();
+            (BOARD_CENTER.0 + angle.sin() * TILE_RADIUS * 2., BOARD_CENTER.1 - angle.cos() * TILE_RADIUS * 2.)
+        };
+        let fill = if i == 0 { "#d0d0d0" } else { "white" };
+
+        svg.push_str(&format!("<polygon points=\"{}\" fill=\"{}\" stroke=\"black\" stroke-width=\"0.6\"/>\n", hexagon_points(center, TILE_RADIUS), fill));
+        svg.push_str(&format!(
+            "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"14\" font-weight=\"bold\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+            center.0, center.1, letter,
+        ));
+    }
+
+    let mut lengths = BTreeMap::new();
+    for answer in gamestate.answers.iter() {
+        *lengths.entry(answer.len()).or_insert(0) += 1;
+    }
+    let counts: Vec<String> = lengths.iter().map(|(length, count)| format!("{} of {}", count, length)).collect();
+    let pangrams = gamestate.answers.iter().filter(|answer| is_pangram(answer)).count();
+    svg.push_str(&format!(
+        "<text x=\"105\" y=\"{}\" font-size=\"5\" text-anchor=\"middle\">{} words ({} letters), {} using every letter</text>\n",
+        GRID_TOP - 8., gamestate.answers.len(), counts.join(", "), pangrams,
+    ));
+
+    // one line per answer, squeezed together when there are a lot of them
+    let rows = gamestate.answers.len().div_ceil(GRID_COLUMNS);
+    let row_height = ((PAGE_HEIGHT - GRID_TOP - 15.) / rows.max(1) as f32).min(MAX_ROW_HEIGHT);
+    let column_width = (PAGE_WIDTH - 30.) / GRID_COLUMNS as f32;
+    for index in 0..gamestate.answers.len() {
+        let x = 15. + (index % GRID_COLUMNS) as f32 * column_width;
+        let y = GRID_TOP + (index / GRID_COLUMNS + 1) as f32 * row_height;
+        svg.push_str(&format!(
+            "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"gray\" stroke-width=\"0.3\"/>\n",
+            x + 4., y, x + column_width - 4., y,
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+// What the game sends over the network, shared with the relay server (src/bin/relay.rs) so the
+// two can't drift apart. Each message goes as RON on a line of its own.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum NetMessage {
+    Board { code: String },                // host to joiner, the board both play on
+    Found { word: String, player: usize }, // a word one side has had accepted
+    Score { score: u32, words: usize },    // race progress, without giving the words away
+    Finished { score: u32, words: usize }, // a race result once that side's clock runs out
+    Lobby { seed: u64, mode: u8 },         // host to joiner, the lobby's settings, the mode as in a PuzzleCode
+    Ready { ready: bool },                 // either way, that side's readiness in the lobby
+    Start,                                 // host to joiner, both are ready and the countdown has begun
+}
@@ -0,0 +1,150 @@
+use std::str::FromStr;
+
+use crate::words::{alphabet_index, bits_to_letters, word_to_bits, WordList};
+
+// A board packed into eight characters that can be read out or pasted, like "4C7X-9QHT".
+// The 40 bits are the letters (26), which of the seven is required (3), the mode (5, zero
+// for none) and a check value (6) so a mistyped character is caught rather than giving
+// some other board. Modes are indexes below 31.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PuzzleCode {
+    pub letter_bits: u32,
+    pub required_letter: char,
+    pub mode: Option<u8>,
+}
+
+// Crockford's base 32, no I, L, O or U so the code can't be misread
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CODE_LENGTH: usize = 8;
+const CHECK_MODULUS: u64 = 61;
+
+impl PuzzleCode {
+    // only a seven letter board with its required letter among them fits in a code
+    pub fn encode(&self) -> Result<String, String> {
+        let letters = bits_to_letters(self.letter_bits);
+        if self.letter_bits.count_ones() != 7 {
+            return Err(format!("Only seven letter boards have puzzle codes, not {}", letters));
+        }
+        let required_index = letters.find(self.required_letter.to_ascii_uppercase())
+            .ok_or(format!("The required letter {} isn't one of {}", self.required_letter, letters))? as u64;
+        let mode = self.mode.map_or(0, |mode| mode as u64 + 1);
+
+        let value = (self.letter_bits as u64) | (required_index << 26) | (mode << 29);
+        let mut packed = (value << 6) | (value % CHECK_MODULUS);
+
+        let mut code = Vec::new();
+        for _ in 0..CODE_LENGTH {
+            code.push(ALPHABET[(packed & 31) as usize]);
+            packed >>= 5;
+        }
+        code.reverse();
+        code.insert(CODE_LENGTH / 2, b'-');
+
+        Ok(String::from_utf8(code).unwrap())
+    }
+
+    pub fn decode(code: &str) -> Result<PuzzleCode, String> {
+        let mut packed: u64 = 0;
+        let mut length = 0;
+
+        for c in code.chars().filter(|c| !c.is_whitespace() && *c != '-') {
+            // the usual misreadings of the letters left out of the alphabet
+            let c = match c.to_ascii_uppercase() {
+                'O' => '0',
+                'I' | 'L' => '1',
+                c => c,
+            };
+            let digit = ALPHABET.iter().position(|a| *a as char == c).ok_or(format!("'{}' can't be part of a puzzle code", c))?;
+            packed = (packed << 5) | digit as u64;
+            length += 1;
+        }
+
+        if length != CODE_LENGTH {
+            return Err(format!("Puzzle codes are {} characters long", CODE_LENGTH));
+        }
+
+        let value = packed >> 6;
+        if packed & 63 != value % CHECK_MODULUS {
+            return Err(String::from("That puzzle code has a typo in it"));
+        }
+
+        let letter_bits = (value & ((1 << 26) - 1)) as u32;
+        let required_index = ((value >> 26) & 7) as usize;
+        let mode = (value >> 29) as u8;
+
+        let letters = bits_to_letters(letter_bits);
+        if letter_bits.count_ones() != 7 || required_index >= letters.len() {
+            return Err(String::from("That puzzle code isn't for a seven letter board"));
+        }
+
+        Ok(PuzzleCode {
+            letter_bits,
+            required_letter: letters.as_bytes()[required_index] as char,
+            mode: if mode == 0 { None } else { Some(mode - 1) },
+        })
+    }
+
+    // a code can be well formed and still name letters this word list has no pangram for
+    pub fn check_playable(&self, wordlist: &WordList) -> Result<(), String> {
+        let has_pangram = wordlist.potential_pangrams.iter().any(|word| word_to_bits(word) == self.letter_bits);
+
+        if has_pangram {
+            Ok(())
+        }
+        else {
+            Err(format!("The current word list has no pangram using {}", bits_to_letters(self.letter_bits)))
+        }
+    }
+
+    pub fn required_bit(&self) -> u32 {
+        1 << alphabet_index(self.required_letter as u8)
+    }
+}
+
+impl FromStr for PuzzleCode {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<PuzzleCode, String> {
+        PuzzleCode::decode(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for (word, required, mode) in [("planted", 'P', None), ("zealots", 'Z', Some(3)), ("jukebox", 'X', Some(30))] {
+            let code = PuzzleCode { letter_bits: word_to_bits(word), required_letter: required, mode };
+            let encoded = code.encode().unwrap();
+            assert_eq!(encoded.len(), CODE_LENGTH + 1);
+            assert_eq!(PuzzleCode::decode(&encoded), Ok(code));
+            assert_eq!(PuzzleCode::decode(&encoded.to_ascii_lowercase().replace('-', " ")), Ok(code));
+        }
+    }
+
+    #[test]
+    fn lowercase_required_letter() {
+        let code = PuzzleCode { letter_bits: word_to_bits("zealots"), required_letter: 'z', mode: None };
+        assert_eq!(PuzzleCode::decode(&code.encode().unwrap()).map(|code| code.required_letter), Ok('Z'));
+    }
+
+    #[test]
+    fn encode_turns_down_boards_without_codes() {
+        let off_board = PuzzleCode { letter_bits: word_to_bits("planted"), required_letter: 'Z', mode: None };
+        assert!(off_board.encode().is_err());
+        let mega = PuzzleCode { letter_bits: word_to_bits("planted") | word_to_bits("bogus"), required_letter: 'A', mode: None };
+        assert!(mega.encode().is_err());
+    }
+
+    #[test]
+    fn decode_catches_typos() {
+        let encoded = PuzzleCode { letter_bits: word_to_bits("zealots"), required_letter: 'E', mode: None }.encode().unwrap();
+        let mut typo = encoded.into_bytes();
+        typo[0] = if typo[0] == b'7' { b'8' } else { b'7' };
+        assert!(PuzzleCode::decode(std::str::from_utf8(&typo).unwrap()).is_err());
+        assert!(PuzzleCode::decode("4C7X-9QH").is_err());
+        assert!(PuzzleCode::decode("4C7X-9QHU").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::packs::PackBoard;
+use crate::puzzle_code::PuzzleCode;
+use crate::words::{word_to_bits, GameState};
+
+// One board as JSON, for trading puzzles with other tools and other spelling bee games:
+//
+//     {
+//       "format": 1,
+//       "letters": "ELEPHANT",
+//       "required": "P",
+//       "date": "2024-03-01",
+//       "answers": ["elephant", "happen", "plan"],
+//       "metadata": { "author": "Sam", "source": "puzzle club" }
+//     }
+//
+// letters is any word using the board's seven letters (or just the seven), and required has to
+// be one of them. The rest can be left out: date is only a label, "YYYY-MM-DD" for daily boards;
+// answers, when given, are the only words the board accepts rather than everything in the word
+// list, and have to fit the board; metadata is free text passed along untouched. format goes up
+// when a change would trip up older readers, and newer files are refused.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PuzzleFile {
+    pub format: u32,
+    pub letters: String,
+    pub required: char,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+pub const FORMAT_VERSION: u32 = 1;
+
+impl PuzzleFile {
+    // the board and everything its word list accepts on it
+    pub fn from_board(gamestate: &GameState, date: Option<String>) -> PuzzleFile {
+        PuzzleFile {
+            format: FORMAT_VERSION,
+            letters: gamestate.target_string.clone(),
+            required: gamestate.required_letter,
+            date,
+            answers: gamestate.answers.clone(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    pub fn puzzle(&self) -> Result<PuzzleCode, String> {
+        PackBoard { letters: self.letters.clone(), required: self.required }.puzzle()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    // answers come back lowercase, like the word list, so they can be checked against guesses as is
+    pub fn parse(text: &str) -> Result<PuzzleFile, String> {
+        let mut file: PuzzleFile = serde_json::from_str(text).map_err(|err| format!("That puzzle file is damaged: {}", err))?;
+        if file.format > FORMAT_VERSION {
+            return Err(format!("That puzzle file is format {}, this version only reads up to {}", file.format, FORMAT_VERSION));
+        }
+
+        let code = file.puzzle()?;
+        for answer in file.answers.iter_mut() {
+            *answer = answer.to_ascii_lowercase();
+            if answer.is_empty() || !answer.bytes().all(|c| c.is_ascii_lowercase()) {
+                return Err(format!("'{}' isn't a word", answer));
+            }
+            let bits = word_to_bits(answer);
+            if bits & !code.letter_bits != 0 || bits & code.required_bit() == 0 {
+                return Err(format!("'{}' can't be made on that board", answer));
+            }
+        }
+        Ok(file)
+    }
+}
@@ -0,0 +1,280 @@
+use bevy::prelude::*;
+use wordgame::words::GameState;
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::net::{NetInbox, NetMessage, NetPeer, NetRole, PeerConnectedEvent};
+use crate::palette::Overlay;
+use crate::{record_correct_words, setup_goals, AppState, BoardEntity, GameMode, NextPuzzle, TILE_RADIUS};
+
+// Two connected players get the same fresh board and five minutes. Each side sends its score
+// and word count as they change (never the words), and when the clock runs out both show who won.
+// The host can start a rematch from the results.
+pub struct RacePlugin;
+
+impl Plugin for RacePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Race>()
+            .add_system(restart_race_on_connect)
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(start_race.after(setup_goals))
+                .with_system(spawn_race_clock.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(send_race_score.after(record_correct_words))
+                .with_system(receive_race_scores)
+                .with_system(finish_race.after(send_race_score).after(receive_race_scores))
+                .with_system(show_race_clock.after(finish_race)))
+            .add_system_set(SystemSet::on_enter(AppState::RaceOver).with_system(spawn_race_results))
+            .add_system_set(SystemSet::on_update(AppState::RaceOver)
+                .with_system(receive_race_scores)
+                .with_system(show_race_results.after(receive_race_scores))
+                .with_system(close_race_results))
+            .add_system_set(SystemSet::on_exit(AppState::RaceOver).with_system(despawn_race_results));
+    }
+}
+
+const RACE_SECONDS: f64 = 5. * 60.;
+
+#[derive(Resource, Default)]
+struct Race {
+    running: bool,
+    ends_at: f64, // by the app clock, which keeps going while menus are open
+    opponent_score: u32,
+    opponent_words: usize,
+    opponent_finished: bool,
+}
+
+#[derive(Component)]
+struct RaceClock {}
+
+#[derive(Component)]
+struct RaceResultsRoot {}
+
+#[derive(Component)]
+struct RaceResultsText {}
+
+// the host throws away whatever it was practising on so both start the same board together
+fn restart_race_on_connect(mut ev_peer_connected: EventReader<PeerConnectedEvent>,
+                           mode: Res<GameMode>,
+                           mut next_puzzle: ResMut<NextPuzzle>,
+                           mut state: ResMut<State<AppState>>,
+                           mut restarting: Local<bool>,
+                        ) {
+    if ev_peer_connected.iter().count() > 0 && *mode == GameMode::Race {
+        *restarting = true;
+    }
+
+    if !*restarting {
+        return;
+    }
+
+    // still loading or in the lobby means the first board hasn't been made yet, and it will be
+    // shared when it is. Another transition may already be queued this frame, in which case try
+    // again next frame
+    if matches!(state.current(), AppState::Loading | AppState::Lobby) {
+        *restarting = false;
+    }
+    else if state.replace(AppState::NewBoard).is_ok() {
+        next_puzzle.0 = None;
+        *restarting = false;
+    }
+}
+
+fn start_race(mut race: ResMut<Race>, mode: Res<GameMode>, peer: Option<Res<NetPeer>>, time: Res<Time>) {
+    *race = Race::default();
+
+    if *mode == GameMode::Race && peer.is_some() {
+        race.running = true;
+        race.ends_at = time.elapsed_seconds_f64() + RACE_SECONDS;
+        info!("race started");
+    }
+}
+
+fn spawn_race_clock(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if *mode != GameMode::Race {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(RaceClock {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
+
+fn send_race_score(race: Res<Race>, gamestate: Res<GameState>, peer: Option<ResMut<NetPeer>>) {
+    if let Some(mut peer) = peer {
+        if race.running && gamestate.is_changed() {
+            peer.send(&NetMessage::Score { score: gamestate.score(), words: gamestate.correct_words.len() });
+        }
+    }
+}
+
+fn receive_race_scores(mut inbox: ResMut<NetInbox>, mut race: ResMut<Race>) {
+    for message in inbox.take(|message| matches!(message, NetMessage::Score { .. } | NetMessage::Finished { .. })) {
+        match message {
+            NetMessage::Score { score, words } => {
+                race.opponent_score = score;
+                race.opponent_words = words;
+            }
+            NetMessage::Finished { score, words } => {
+                race.opponent_score = score;
+                race.opponent_words = words;
+                race.opponent_finished = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn finish_race(mut race: ResMut<Race>,
+               gamestate: Res<GameState>,
+               peer: Option<ResMut<NetPeer>>,
+               time: Res<Time>,
+               mut state: ResMut<State<AppState>>,
+            ) {
+    if !race.running || time.elapsed_seconds_f64() < race.ends_at {
+        return;
+    }
+
+    // another transition may already be queued this frame, in which case try again next frame
+    if state.push(AppState::RaceOver).is_err() {
+        return;
+    }
+    race.running = false;
+    if let Some(mut peer) = peer {
+        peer.send(&NetMessage::Finished { score: gamestate.score(), words: gamestate.correct_words.len() });
+    }
+}
+
+fn format_clock(seconds: f64) -> String {
+    let seconds = seconds.max(0.).ceil() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn show_race_clock(race: Res<Race>,
+                   gamestate: Res<GameState>,
+                   peer: Option<Res<NetPeer>>,
+                   time: Res<Time>,
+                   mut clocks: Query<&mut Text, With<RaceClock>>,
+                ) {
+    let status = if race.running {
+        format!("{}\nYou: {} ({} words)\nThem: {} ({} words)",
+                format_clock(race.ends_at - time.elapsed_seconds_f64()),
+                gamestate.score(), gamestate.correct_words.len(),
+                race.opponent_score, race.opponent_words)
+    }
+    else if peer.is_none() {
+        String::from("Waiting for an opponent\nthis board is just for practice")
+    }
+    else {
+        String::from("Race over")
+    };
+
+    for mut text in clocks.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
+
+fn spawn_race_results(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
+    let body_style = TextStyle { font: font.clone(), font_size: 40., color: Color::WHITE };
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(RaceResultsRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("", body_style.clone())).insert(RaceResultsText {});
+    });
+}
+
+fn show_race_results(race: Res<Race>,
+                     gamestate: Res<GameState>,
+                     role: Res<NetRole>,
+                     peer: Option<Res<NetPeer>>,
+                     mut texts: Query<&mut Text, With<RaceResultsText>>,
+                    ) {
+    let score = gamestate.score();
+
+    // the opponent's last score only counts once they've finished too, or have gone
+    let outcome = if !race.opponent_finished && peer.is_some() {
+        "Waiting for the other player's final score..."
+    }
+    else if score > race.opponent_score {
+        "You win!"
+    }
+    else if score < race.opponent_score {
+        "They win"
+    }
+    else {
+        "It's a draw"
+    };
+
+    let next = match *role {
+        NetRole::Host { .. } => "Enter for a rematch, Esc to look over the board",
+        NetRole::Join { .. } | NetRole::Spectate { .. } => "Esc to look over the board, the host can start a rematch",
+    };
+
+    let results = format!("{}\n\nYou: {} points, {} words\nThem: {} points, {} words\n\n{}",
+                          outcome, score, gamestate.correct_words.len(), race.opponent_score, race.opponent_words, next);
+
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != results {
+            text.sections[0].value = results.clone();
+        }
+    }
+}
+
+fn close_race_results(mut keys: ResMut<Input<KeyCode>>,
+                      role: Res<NetRole>,
+                      inbox: Res<NetInbox>,
+                      mut next_puzzle: ResMut<NextPuzzle>,
+                      mut state: ResMut<State<AppState>>,
+                      mut closing: Local<Option<bool>>,
+                    ) {
+    if keys.just_pressed(KeyCode::Return) && matches!(*role, NetRole::Host { .. }) {
+        keys.clear_just_pressed(KeyCode::Return);
+        *closing = Some(true);
+    }
+    // back to the board, where the host's new board gets picked up
+    else if keys.just_pressed(KeyCode::Escape) || inbox.has_new_board() {
+        keys.clear_just_pressed(KeyCode::Escape);
+        *closing = Some(false);
+    }
+
+    // whether it's a rematch is kept until the screen has actually closed, another transition may
+    // already be queued this frame, in which case try again next frame
+    let rematch = match *closing {
+        Some(rematch) => rematch,
+        None => return,
+    };
+    let closed = if rematch { state.replace(AppState::NewBoard).is_ok() } else { state.pop().is_ok() };
+    if closed {
+        if rematch {
+            next_puzzle.0 = None;
+        }
+        *closing = None;
+    }
+}
+
+fn despawn_race_results(mut commands: Commands, screens: Query<Entity, With<RaceResultsRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
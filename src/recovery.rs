@@ -0,0 +1,193 @@
+use bevy::ecs::schedule::StateError;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::focus::Focusable;
+use crate::palette::Overlay;
+use crate::AppState;
+
+// When something the game depends on fails (a word list that won't load, later a corrupt file)
+// the subsystem opens a dialog here instead of panicking, then reacts to whichever action the
+// player picks. The dialog is pushed on top of the current state so a running board survives it.
+// The board left behind by a crash is offered back through here too, see crash.rs.
+pub struct RecoveryPlugin;
+
+impl Plugin for RecoveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RecoveryChosenEvent>()
+            .init_resource::<RecoveryQueue>()
+            .add_system_to_stage(CoreStage::PostUpdate, open_queued_recovery)
+            .add_system_set(SystemSet::on_enter(AppState::Recovery).with_system(spawn_recovery_dialog))
+            .add_system_set(SystemSet::on_update(AppState::Recovery).with_system(choose_recovery_action))
+            .add_system_set(SystemSet::on_exit(AppState::Recovery).with_system(despawn_recovery_dialog));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecoveryKind {
+    Dictionary,
+    SettingsLoad,
+    SettingsSave,
+    #[cfg(feature = "online")]
+    Connection,
+    Crash,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecoveryAction {
+    Retry,
+    ResetFile,
+    UseDefaults,
+    Continue,
+    Restore,
+}
+
+impl RecoveryAction {
+    fn label(&self) -> &'static str {
+        match self {
+            RecoveryAction::Retry => "Retry",
+            RecoveryAction::ResetFile => "Reset file",
+            RecoveryAction::UseDefaults => "Continue with defaults",
+            RecoveryAction::Continue => "Continue",
+            RecoveryAction::Restore => "Restore the board",
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct RecoveryDialog {
+    pub kind: RecoveryKind,
+    pub message: String,
+    pub actions: Vec<RecoveryAction>,
+}
+
+// sent once the dialog has closed, the subsystem that opened it decides what the action means
+pub struct RecoveryChosenEvent {
+    pub kind: RecoveryKind,
+    pub action: RecoveryAction,
+}
+
+// for the system that owns the current state, opens the dialog straight away unless another
+// transition is already queued this frame, in which case the caller tries again next frame
+pub fn open_recovery_dialog(commands: &mut Commands, state: &mut State<AppState>, dialog: RecoveryDialog) -> Result<(), StateError> {
+    state.push(AppState::Recovery)?;
+    error!("{}", dialog.message);
+    commands.insert_resource(dialog);
+    Ok(())
+}
+
+// for everything else, dialogs wait here until the board is up and nothing else is changing state
+#[derive(Resource, Default)]
+pub struct RecoveryQueue {
+    pending: VecDeque<RecoveryDialog>,
+}
+
+impl RecoveryQueue {
+    pub fn push(&mut self, dialog: RecoveryDialog) {
+        error!("{}", dialog.message);
+        self.pending.push_back(dialog);
+    }
+}
+
+fn open_queued_recovery(mut commands: Commands,
+                        mut state: ResMut<State<AppState>>,
+                        mut queue: ResMut<RecoveryQueue>,
+                        open_dialog: Option<Res<RecoveryDialog>>,
+                    ) {
+    if open_dialog.is_some() || queue.pending.is_empty() || *state.current() != AppState::Playing {
+        return;
+    }
+
+    // another transition may already be queued this frame, in which case try again next frame
+    if state.push(AppState::Recovery).is_ok() {
+        commands.insert_resource(queue.pending.pop_front().unwrap());
+    }
+}
+
+#[derive(Component)]
+struct RecoveryDialogRoot {}
+
+#[derive(Component)]
+struct RecoveryButton {
+    action: RecoveryAction,
+}
+
+const NUMBER_KEYS: [KeyCode; 3] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+
+fn spawn_recovery_dialog(mut commands: Commands, asset_server: Res<AssetServer>, dialog: Res<RecoveryDialog>) {
+    let font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
+    let message_style = TextStyle { font: font.clone(), font_size: 36., color: Color::WHITE };
+    let button_style = TextStyle { font: font.clone(), font_size: 30., color: Color::BLACK };
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(100),
+        ..default()
+    }).insert(RecoveryDialogRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(dialog.message.clone(), message_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            max_size: Size::new(Val::Percent(80.), Val::Auto),
+            ..default()
+        }));
+
+        for (i, action) in dialog.actions.iter().enumerate() {
+            parent.spawn(ButtonBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(8.)),
+                    padding: UiRect::new(Val::Px(24.), Val::Px(24.), Val::Px(8.), Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::ALICE_BLUE.into(),
+                ..default()
+            }).insert(RecoveryButton { action: *action }).insert(Focusable { state: AppState::Recovery, order: i as u32 }).with_children(|button| {
+                button.spawn(TextBundle::from_section(format!("[{}] {}", i + 1, action.label()), button_style.clone()));
+            });
+        }
+    });
+}
+
+fn choose_recovery_action(mut commands: Commands,
+                          mut state: ResMut<State<AppState>>,
+                          dialog: Res<RecoveryDialog>,
+                          keys: Res<Input<KeyCode>>,
+                          buttons: Query<(&Interaction, &RecoveryButton), Changed<Interaction>>,
+                          mut ev_recovery_chosen: EventWriter<RecoveryChosenEvent>,
+                        ) {
+    let mut chosen = None;
+
+    for (i, action) in dialog.actions.iter().enumerate() {
+        if i < NUMBER_KEYS.len() && keys.just_pressed(NUMBER_KEYS[i]) {
+            chosen = Some(*action);
+        }
+    }
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction == Interaction::Clicked {
+            chosen = Some(button.action);
+        }
+    }
+
+    if let Some(action) = chosen {
+        if let Err(err) = state.pop() {
+            debug!("not closing the recovery dialog, the state is already changing: {:?}", err);
+            return;
+        }
+        info!("recovering from {:?} failure with {:?}", dialog.kind, action);
+        ev_recovery_chosen.send(RecoveryChosenEvent { kind: dialog.kind, action });
+        commands.remove_resource::<RecoveryDialog>();
+    }
+}
+
+fn despawn_recovery_dialog(mut commands: Commands, dialogs: Query<Entity, With<RecoveryDialogRoot>>) {
+    for entity in dialogs.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
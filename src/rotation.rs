@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use rand::Rng;
+use wordgame::words::{alphabet_index, find_answers, GameState, WordList, WordValidators};
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::tween::{Easing, Track, Tween};
+use crate::{format_clock, guess_word, setup_goals, AppState, BoardEntity, BoardRefreshedEvent, GameMode, GameRng, HintText, LetterTile, SpeakBoardEvent, TileLabel, TriedWord, TILE_RADIUS};
+
+// The rotating required letter from --rotate-required: every few minutes the middle tile swaps
+// letters with one of the others, and that becomes the letter every word needs. The answers are
+// worked out again for the new letter, keeping the words already found so their points stand,
+// and hints for words that no longer count are dropped. The time to the next swap shows under the
+// board and counts down loudly for the last few seconds. Boards with fixed answers (imported
+// puzzles and the mega board) and split screen keep their letter.
+pub struct RotationPlugin;
+
+impl Plugin for RotationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rotation>()
+            .add_event::<RequiredRotatedEvent>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(reset_rotation.after(setup_goals))
+                .with_system(spawn_rotation_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(reset_rotation_on_refresh)
+                .with_system(rotate_required.after(reset_rotation_on_refresh).after(guess_word))
+                .with_system(show_rotation.after(rotate_required)));
+    }
+}
+
+const WARNING_SECONDS: f32 = 10.;
+
+// from the command line
+#[derive(Resource)]
+pub struct RotationRules {
+    pub minutes: Option<f32>,
+}
+
+#[derive(Resource, Default)]
+struct Rotation {
+    seconds_left: f32,
+}
+
+pub struct RequiredRotatedEvent {}
+
+#[derive(Component)]
+struct RotationText {}
+
+// seconds between swaps, if this board has them
+fn rotation_seconds(rules: &RotationRules, mode: GameMode, gamestate: &GameState) -> Option<f32> {
+    match rules.minutes {
+        Some(minutes) if mode != GameMode::SplitScreen && !gamestate.curated_answers && gamestate.letter_limits.is_none() => Some(minutes * 60.),
+        _ => None,
+    }
+}
+
+fn reset_rotation(rules: Res<RotationRules>, mode: Res<GameMode>, gamestate: Res<GameState>, mut rotation: ResMut<Rotation>) {
+    rotation.seconds_left = rotation_seconds(&rules, *mode, &gamestate).unwrap_or(0.);
+}
+
+fn reset_rotation_on_refresh(mut ev_board_refreshed: EventReader<BoardRefreshedEvent>,
+                             rules: Res<RotationRules>,
+                             mode: Res<GameMode>,
+                             gamestate: Res<GameState>,
+                             mut rotation: ResMut<Rotation>,
+                            ) {
+    if ev_board_refreshed.iter().count() > 0 {
+        rotation.seconds_left = rotation_seconds(&rules, *mode, &gamestate).unwrap_or(0.);
+    }
+}
+
+// under the ghost's line, which is under the session clock
+fn spawn_rotation_hud(mut commands: Commands,
+                      asset_server: Res<AssetServer>,
+                      rules: Res<RotationRules>,
+                      mode: Res<GameMode>,
+                      gamestate: Res<GameState>,
+                      layout: Res<Layout>,
+                    ) {
+    if rotation_seconds(&rules, *mode, &gamestate).is_none() {
+        return;
+    }
+
+    let rotation_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 3.,
+        color: Color::GRAY,
+    };
+
+    commands.spawn(SpatialBundle::from_transform(layout.transform(LayoutSlot::Board)))
+        .insert(BoardEntity {})
+        .insert(LayoutAnchor { slot: LayoutSlot::Board })
+        .with_children(|hud| {
+            hud.spawn(Text2dBundle{
+                text: Text::from_section("", rotation_text_style).with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(Vec3::new(0., -4.2 * TILE_RADIUS, 1.)),
+                ..default()
+            }).insert(RotationText {});
+        });
+}
+
+// the new letter pops into the middle tile and the old one into the tile it came from
+fn rotate_required(mut commands: Commands,
+                   time: Res<Time>,
+                   rules: Res<RotationRules>,
+                   mode: Res<GameMode>,
+                   (wordlist, validators): (Res<WordList>, Res<WordValidators>),
+                   mut gamestate: ResMut<GameState>,
+                   mut game_rng: ResMut<GameRng>,
+                   mut rotation: ResMut<Rotation>,
+                   mut tiles: Query<(&mut LetterTile, &mut Tween)>,
+                   mut labels: Query<(Entity, &TileLabel, &mut Text), Without<HintText>>,
+                   mut hints: Query<&mut Text, With<HintText>>,
+                   mut word_guess: Query<&mut TriedWord>,
+                   mut ev_rotated: EventWriter<RequiredRotatedEvent>,
+                   mut ev_speak_board: EventWriter<SpeakBoardEvent>,
+                ) {
+    let seconds = match rotation_seconds(&rules, *mode, &gamestate) {
+        Some(seconds) => seconds,
+        None => return,
+    };
+    rotation.seconds_left -= time.delta_seconds();
+    if rotation.seconds_left > 0. {
+        return;
+    }
+    rotation.seconds_left = seconds;
+
+    let mut letters = gamestate.target_string.clone().into_bytes();
+    let swap = game_rng.0.gen_range(1..letters.len());
+    letters.swap(0, swap);
+    let required = letters[0] as char;
+    gamestate.target_string = String::from_utf8(letters.clone()).unwrap();
+    gamestate.required_letter = required;
+    gamestate.required_bit = 1 << alphabet_index(letters[0]);
+
+    gamestate.answers = find_answers(&wordlist, gamestate.target_bits, gamestate.required_bit, gamestate.min_word_length, None);
+    validators.filter_answers(&mut gamestate);
+    let missing: Vec<String> = gamestate.correct_words.iter()
+        .map(|found| found.word.clone())
+        .filter(|word| !gamestate.answers.contains(word))
+        .collect();
+    gamestate.answers.extend(missing);
+
+    for (mut tile, mut squish) in tiles.iter_mut().filter(|(tile, _)| tile.index == 0 || tile.index == swap) {
+        tile.letter = letters[tile.index] as char;
+        squish.restart();
+    }
+    for (entity, label, mut text) in labels.iter_mut().filter(|(_, label, _)| label.index == 0 || label.index == swap) {
+        text.sections[0].value = String::from(letters[label.index] as char);
+        commands.entity(entity).insert(Tween::new("rotate in").then(0.3, Easing::BackOut, [Track::Scale(Vec3::ZERO, Vec3::ONE)]));
+    }
+    for mut tried_word in word_guess.iter_mut() {
+        tried_word.required_letters = tried_word.current.chars().filter(|letter| *letter == required).count();
+    }
+    for mut text in hints.iter_mut() {
+        text.sections[0].value = format!("The required letter is now {}", required);
+    }
+
+    info!("required letter rotated to {}, {} answers", required, gamestate.answers.len());
+    ev_rotated.send(RequiredRotatedEvent {});
+    if *mode == GameMode::Blind {
+        ev_speak_board.send(SpeakBoardEvent {});
+    }
+}
+
+fn show_rotation(rotation: Res<Rotation>, mut texts: Query<&mut Text, With<RotationText>>) {
+    let (value, color) = if rotation.seconds_left <= WARNING_SECONDS {
+        (format!("New required letter in {:.0}!", rotation.seconds_left.ceil()), Color::WHITE)
+    }
+    else {
+        (format!("Required letter changes in {}", format_clock(rotation.seconds_left)), Color::GRAY)
+    };
+
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+            text.sections[0].style.color = color;
+        }
+    }
+}
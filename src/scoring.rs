@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::reflect::TypeUuid;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use wordgame::words::{GameState, ScoringRules};
+
+use crate::GameMode;
+
+// How words are scored comes from default.scoring.ron in the assets: the rules every board is
+// scored by, and any a mode plays by instead, by its --mode name, e.g.
+//   (rules: (pangram_bonus: 10), modes: {"time-attack": (points_per_letter: 2)})
+// Anything left out of a set of rules keeps its usual value, see wordgame::words::ScoringRules.
+// The rules are taken up as a board starts, until the file has loaded (and when running
+// headless) the usual rules apply.
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ScoringAsset>()
+            .init_asset_loader::<ScoringAssetLoader>()
+            .add_startup_system(load_scoring)
+            .add_system(apply_scoring);
+    }
+}
+
+const SCORING_ASSET: &str = "default.scoring.ron";
+
+#[derive(Resource, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    rules: ScoringRules,
+    modes: BTreeMap<String, ScoringRules>,
+}
+
+impl ScoringConfig {
+    pub fn for_mode(&self, mode: GameMode) -> ScoringRules {
+        mode.to_possible_value()
+            .and_then(|value| self.modes.get(value.get_name()).cloned())
+            .unwrap_or_else(|| self.rules.clone())
+    }
+}
+
+#[derive(TypeUuid)]
+#[uuid = "6f1c9e2a-84d3-4b57-a0e8-3c25d9b71f46"]
+struct ScoringAsset(ScoringConfig);
+
+#[derive(Default)]
+struct ScoringAssetLoader;
+
+impl AssetLoader for ScoringAssetLoader {
+    fn load<'a>(&'a self, bytes: &'a [u8], load_context: &'a mut LoadContext) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let config = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(ScoringAsset(config)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scoring.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct ScoringHandle(Handle<ScoringAsset>);
+
+fn load_scoring(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ScoringHandle(asset_server.load(SCORING_ASSET)));
+}
+
+// the board already up when the file arrives is scored by it too
+fn apply_scoring(mut ev_asset: EventReader<AssetEvent<ScoringAsset>>,
+                 scoring: Res<ScoringHandle>,
+                 assets: Res<Assets<ScoringAsset>>,
+                 mode: Res<GameMode>,
+                 mut config: ResMut<ScoringConfig>,
+                 mut gamestate: ResMut<GameState>,
+                ) {
+    for ev in ev_asset.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = ev {
+            if *handle != scoring.0 {
+                continue;
+            }
+            if let Some(asset) = assets.get(handle) {
+                *config = asset.0.clone();
+                gamestate.scoring = config.for_mode(*mode);
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use tts::Tts;
+use wordgame::words::GameState;
+
+use crate::blind::LetterVoice;
+use crate::{AppState, BoardRefreshedEvent, GuessErasedEvent, LetterAcceptedEvent, SpeakBoardEvent, TriedWord, WordAcceptedEvent, WordRejectedEvent};
+
+// Speaks the game through the platform's screen reader or speech engine (the screen_reader
+// feature): the letters when a board starts, each letter typed, and whether a guess was taken.
+// F1 reads the board again, F2 the current guess and F3 the found words. Blind mode asks for the
+// board to be read out too, and is only playable when this has a voice.
+// bevy 0.9 has no AccessKit support, so this stands in until bevy_a11y can expose the board,
+// guess, hint and found list as proper accessibility nodes.
+pub struct ScreenReaderPlugin;
+
+impl Plugin for ScreenReaderPlugin {
+    fn build(&self, app: &mut App) {
+        let tts = match Tts::default() {
+            Ok(tts) => tts,
+            Err(err) => {
+                warn!("No screen reader or speech available, nothing will be read out: {}", err);
+                return;
+            }
+        };
+
+        app.insert_non_send_resource(ScreenReader { tts })
+            .insert_resource(LetterVoice {})
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(announce_board))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(announce_guess_events)
+                .with_system(announce_refreshed_board)
+                .with_system(announce_spoken_board)
+                .with_system(read_on_request));
+    }
+}
+
+struct ScreenReader {
+    tts: Tts,
+}
+
+impl ScreenReader {
+    // interrupting keeps up with fast typing rather than queueing a backlog of letters
+    fn say(&mut self, text: &str, interrupt: bool) {
+        if let Err(err) = self.tts.speak(text, interrupt) {
+            warn!("Could not read out \"{}\": {}", text, err);
+        }
+    }
+}
+
+// letters one by one so the speech engine doesn't try to pronounce them as a word
+fn spell(word: &str) -> String {
+    word.chars().map(|letter| letter.to_ascii_uppercase().to_string()).collect::<Vec<String>>().join(" ")
+}
+
+fn board_description(gamestate: &GameState) -> String {
+    let others: String = gamestate.target_string.chars().filter(|letter| *letter != gamestate.required_letter).collect();
+    format!("Required letter {}. Other letters {}.", gamestate.required_letter.to_ascii_uppercase(), spell(&others))
+}
+
+fn found_description(gamestate: &GameState) -> String {
+    if gamestate.correct_words.is_empty() {
+        return String::from("No words found yet.");
+    }
+
+    let words: Vec<&str> = gamestate.correct_words.iter().map(|found| found.word.as_str()).collect();
+    format!("{} words found, {} points: {}.", words.len(), gamestate.score(), words.join(", "))
+}
+
+fn announce_board(mut reader: NonSendMut<ScreenReader>, gamestate: Res<GameState>) {
+    reader.say(&board_description(&gamestate), true);
+}
+
+fn announce_refreshed_board(mut reader: NonSendMut<ScreenReader>, mut ev_board_refreshed: EventReader<BoardRefreshedEvent>, gamestate: Res<GameState>) {
+    if ev_board_refreshed.iter().count() > 0 {
+        reader.say(&format!("New board. {}", board_description(&gamestate)), false);
+    }
+}
+
+fn announce_spoken_board(mut reader: NonSendMut<ScreenReader>, mut ev_speak_board: EventReader<SpeakBoardEvent>, gamestate: Res<GameState>) {
+    if ev_speak_board.iter().count() > 0 {
+        reader.say(&board_description(&gamestate), true);
+    }
+}
+
+fn announce_guess_events(mut reader: NonSendMut<ScreenReader>,
+                         gamestate: Res<GameState>,
+                         mut ev_letter_accepted: EventReader<LetterAcceptedEvent>,
+                         mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                         mut ev_word_rejected: EventReader<WordRejectedEvent>,
+                         mut ev_guess_erased: EventReader<GuessErasedEvent>,
+                        ) {
+    for ev in ev_letter_accepted.iter() {
+        reader.say(&ev.letter.to_ascii_uppercase().to_string(), true);
+    }
+    for ev in ev_guess_erased.iter() {
+        reader.say(if ev.whole_word { "Cleared" } else { "Erased" }, true);
+    }
+    for ev in ev_word_accepted.iter() {
+        let pangram = if ev.pangram { ", pangram" } else { "" };
+        reader.say(&format!("{} accepted{}, {} points", ev.word, pangram, gamestate.word_points(&ev.word.to_ascii_lowercase())), true);
+    }
+    for ev in ev_word_rejected.iter() {
+        reader.say(&format!("{} {}", ev.word, ev.reason), true);
+    }
+}
+
+fn read_on_request(mut reader: NonSendMut<ScreenReader>,
+                   keys: Res<Input<KeyCode>>,
+                   gamestate: Res<GameState>,
+                   word_guess: Query<&TriedWord>,
+                ) {
+    if keys.just_pressed(KeyCode::F1) {
+        reader.say(&board_description(&gamestate), true);
+    }
+    if keys.just_pressed(KeyCode::F2) {
+        let guess = word_guess.get_single().map(|tried_word| tried_word.current.clone()).unwrap_or_default();
+        if guess.is_empty() {
+            reader.say("Guess is empty", true);
+        }
+        else {
+            reader.say(&format!("Guess {}", spell(&guess)), true);
+        }
+    }
+    if keys.just_pressed(KeyCode::F3) {
+        reader.say(&found_description(&gamestate), true);
+    }
+}
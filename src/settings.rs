@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::board_layout::BoardLayout;
+use crate::palette::{Colours, Palette, ThemeColours};
+use crate::recovery::{RecoveryAction, RecoveryChosenEvent, RecoveryDialog, RecoveryKind, RecoveryQueue};
+use crate::storage;
+
+// Player preferences, saved to settings.ron in the platform config directory (or browser storage
+// on the web) whenever they change
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_startup_system(load_settings)
+            .add_system(apply_settings_recovery)
+            .add_system(save_settings.after(apply_settings_recovery));
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub muted: bool, // silences everything without touching the volumes
+    pub hover_sounds: bool,
+    pub letter_sounds: bool,
+    pub erase_sounds: bool,
+    pub submit_daily_scores: bool, // nothing is sent to the leaderboard unless this is turned on
+    pub leaderboard_url: String,   // only set by editing the file, empty for no leaderboard
+    pub telemetry: bool,           // anonymous usage data, nothing is recorded or sent unless this is turned on
+    pub telemetry_url: String,     // only set by editing the file, empty keeps the usage data queued
+    pub check_updates: bool,       // the release feed is only asked once this is turned on
+    pub update_feed_url: String,   // only set by editing the file
+    pub player_name: String, // also the profile in use
+    pub profiles: BTreeMap<String, Profile>, // by player name, more are only added by editing the file
+    pub fullscreen: bool,
+    pub palette: Palette,
+    pub board_layout: BoardLayout, // takes effect from the next board
+    pub high_contrast: bool,
+    pub reduced_motion: bool, // anything that moves just to look lively snaps to its end state
+    pub captions: bool,
+    pub haptics: bool, // gamepad rumble and phone vibration, where there's any
+    pub letter_progress: bool, // fade the letters whose words are all found
+    pub free_entry: bool, // any letter can be typed into the guess, not just the board's
+    pub idle_pause_minutes: u32, // 0 never pauses a timed or daily board for want of input
+    pub pace: bool, // seconds a word and words a minute next to the clock under the board
+    pub daily_notifications: bool, // only set by editing the file, and only in builds with notifications
+    pub daily_reminder: Option<u32>, // hour of the day, UTC like the daily boards, to nag if it's not done
+    pub theme: String,     // from the mods folder by name, empty for the palette's own colours
+    pub tile_skin: String, // from the mods folder by name, empty for plain tiles
+    pub word_list: String, // from the mods folder by name, empty for the built in list
+    pub tutorial_done: bool, // finished or skipped, turned off again to replay it on the next board
+    #[serde(skip)]
+    pub theme_colours: Option<ThemeColours>, // the theme's, once the mods folder has been looked through
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            sfx_volume: 0.8,
+            music_volume: 0.5,
+            muted: false,
+            hover_sounds: true,
+            letter_sounds: true,
+            erase_sounds: true,
+            submit_daily_scores: false,
+            leaderboard_url: String::new(),
+            telemetry: false,
+            telemetry_url: String::new(),
+            check_updates: false,
+            update_feed_url: String::from("https://api.github.com/repos/jakemcleman/bevy_spelling_tiles/releases/latest"),
+            player_name: String::from("Player"),
+            profiles: BTreeMap::new(),
+            fullscreen: false,
+            palette: Palette::Standard,
+            board_layout: BoardLayout::Flower,
+            high_contrast: false,
+            reduced_motion: false,
+            captions: false,
+            haptics: true,
+            letter_progress: true,
+            free_entry: false,
+            idle_pause_minutes: 5,
+            pace: false,
+            daily_notifications: false,
+            daily_reminder: None,
+            theme: String::new(),
+            tile_skin: String::new(),
+            word_list: String::new(),
+            tutorial_done: false,
+            theme_colours: None,
+        }
+    }
+}
+
+// What each player sharing the game picks for themselves, so a child's profile can keep kids mode on
+// without it being on for everyone
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Profile {
+    pub kids_mode: bool, // the kids word list, three letter words, hints and brighter colours
+}
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+impl Settings {
+    #[cfg(feature = "audio")]
+    pub fn effective_sfx_volume(&self) -> f32 {
+        if self.muted { 0. } else { self.sfx_volume }
+    }
+
+    #[cfg(feature = "audio")]
+    pub fn effective_music_volume(&self) -> f32 {
+        if self.muted { 0. } else { self.music_volume }
+    }
+
+    pub fn colours(&self) -> Colours {
+        Colours { palette: self.palette, high_contrast: self.high_contrast, kids: self.kids_mode(), theme: self.theme_colours }
+    }
+
+    // a player without a profile yet gets the defaults
+    pub fn profile(&self) -> Profile {
+        self.profiles.get(&self.player_name).cloned().unwrap_or_default()
+    }
+
+    pub fn profile_mut(&mut self) -> &mut Profile {
+        self.profiles.entry(self.player_name.clone()).or_default()
+    }
+
+    pub fn kids_mode(&self) -> bool {
+        self.profile().kids_mode
+    }
+
+    // through the profiles in the file by name, wrapping round, with the current player among them
+    pub fn step_profile(&mut self, direction: f32) {
+        let mut names: Vec<&String> = self.profiles.keys().collect();
+        if !self.profiles.contains_key(&self.player_name) {
+            names.push(&self.player_name);
+            names.sort();
+        }
+
+        let index = names.iter().position(|name| **name == self.player_name).unwrap_or(0);
+        let count = names.len();
+        let next = names[if direction < 0. { (index + count - 1) % count } else { (index + 1) % count }].clone();
+        self.player_name = next;
+    }
+
+    fn load() -> Result<Settings, String> {
+        match storage::read(SETTINGS_FILE)? {
+            Some(contents) => ron::from_str(&contents).map_err(|err| format!("Settings file {} is corrupt: {}", storage::location(SETTINGS_FILE), err)),
+            None => Ok(Settings::default()),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        storage::write(SETTINGS_FILE, &contents)
+    }
+}
+
+fn load_failed_dialog(message: String) -> RecoveryDialog {
+    RecoveryDialog {
+        kind: RecoveryKind::SettingsLoad,
+        message,
+        actions: vec![RecoveryAction::Retry, RecoveryAction::ResetFile, RecoveryAction::UseDefaults],
+    }
+}
+
+fn load_settings(mut settings: ResMut<Settings>, mut recovery: ResMut<RecoveryQueue>) {
+    match Settings::load() {
+        Ok(loaded) => *settings = loaded,
+        Err(message) => recovery.push(load_failed_dialog(message)),
+    }
+}
+
+fn save_settings(settings: Res<Settings>, mut recovery: ResMut<RecoveryQueue>) {
+    // loading the file counts as a change, but there's no point writing it straight back
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+
+    if let Err(message) = settings.save() {
+        recovery.push(RecoveryDialog {
+            kind: RecoveryKind::SettingsSave,
+            message,
+            actions: vec![RecoveryAction::Retry, RecoveryAction::Continue],
+        });
+    }
+}
+
+fn apply_settings_recovery(mut ev_recovery_chosen: EventReader<RecoveryChosenEvent>,
+                           mut settings: ResMut<Settings>,
+                           mut recovery: ResMut<RecoveryQueue>,
+                        ) {
+    for ev in ev_recovery_chosen.iter() {
+        match (ev.kind, ev.action) {
+            (RecoveryKind::SettingsLoad, RecoveryAction::Retry) => match Settings::load() {
+                Ok(loaded) => *settings = loaded,
+                Err(message) => recovery.push(load_failed_dialog(message)),
+            },
+            // overwriting the broken file happens through the normal save on change
+            (RecoveryKind::SettingsLoad, RecoveryAction::ResetFile) => *settings = Settings::default(),
+            (RecoveryKind::SettingsSave, RecoveryAction::Retry) => settings.set_changed(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use bevy::prelude::*;
+
+use crate::focus::Focus;
+use crate::mods::{mod_name, step_name, InstalledMods};
+use crate::palette::Overlay;
+use crate::settings::Settings;
+use crate::AppState;
+
+// Escape from the board opens the settings, arrow keys or the -/+ buttons change them
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsSelection>()
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(open_settings_menu))
+            .add_system_set(SystemSet::on_enter(AppState::Settings).with_system(spawn_settings_menu))
+            .add_system_set(SystemSet::on_update(AppState::Settings)
+                .with_system(navigate_settings_menu)
+                .with_system(click_settings_buttons)
+                .with_system(show_settings_values.after(navigate_settings_menu).after(click_settings_buttons))
+                .with_system(focus_selected_row.after(navigate_settings_menu).after(click_settings_buttons)))
+            .add_system_set(SystemSet::on_exit(AppState::Settings).with_system(despawn_settings_menu));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SettingsRow {
+    EffectsVolume,
+    MusicVolume,
+    HoverSounds,
+    LetterSounds,
+    EraseSounds,
+    SubmitScores,
+    Telemetry,
+    CheckUpdates,
+    Fullscreen,
+    Palette,
+    TileSkin,
+    BoardLayout,
+    HighContrast,
+    ReducedMotion,
+    Captions,
+    Haptics,
+    Profile,
+    KidsMode,
+    WordList,
+    LetterProgress,
+    LetterEntry,
+    IdlePause,
+    Pace,
+    Tutorial,
+}
+
+const ROWS: [SettingsRow; 24] = [
+    SettingsRow::EffectsVolume,
+    SettingsRow::MusicVolume,
+    SettingsRow::HoverSounds,
+    SettingsRow::LetterSounds,
+    SettingsRow::EraseSounds,
+    SettingsRow::SubmitScores,
+    SettingsRow::Telemetry,
+    SettingsRow::CheckUpdates,
+    SettingsRow::Fullscreen,
+    SettingsRow::Palette,
+    SettingsRow::TileSkin,
+    SettingsRow::BoardLayout,
+    SettingsRow::HighContrast,
+    SettingsRow::ReducedMotion,
+    SettingsRow::Captions,
+    SettingsRow::Haptics,
+    SettingsRow::Profile,
+    SettingsRow::KidsMode,
+    SettingsRow::WordList,
+    SettingsRow::LetterProgress,
+    SettingsRow::LetterEntry,
+    SettingsRow::IdlePause,
+    SettingsRow::Pace,
+    SettingsRow::Tutorial,
+];
+const VOLUME_STEP: f32 = 0.1;
+
+impl SettingsRow {
+    fn label(&self) -> &'static str {
+        match self {
+            SettingsRow::EffectsVolume => "Effects volume",
+            SettingsRow::MusicVolume => "Music volume",
+            SettingsRow::HoverSounds => "Tile hover sounds",
+            SettingsRow::LetterSounds => "Letter sounds",
+            SettingsRow::EraseSounds => "Erase sounds",
+            SettingsRow::SubmitScores => "Submit daily scores",
+            SettingsRow::Telemetry => "Anonymous usage data",
+            SettingsRow::CheckUpdates => "Check for updates",
+            SettingsRow::Fullscreen => "Fullscreen (F11)",
+            SettingsRow::Palette => "Colour palette",
+            SettingsRow::TileSkin => "Tile skin",
+            SettingsRow::BoardLayout => "Board layout",
+            SettingsRow::HighContrast => "High contrast",
+            SettingsRow::ReducedMotion => "Reduced motion",
+            SettingsRow::Captions => "Event captions",
+            SettingsRow::Haptics => "Vibration",
+            SettingsRow::Profile => "Profile",
+            SettingsRow::KidsMode => "Kids mode",
+            SettingsRow::WordList => "Word list",
+            SettingsRow::LetterProgress => "Finished letters",
+            SettingsRow::LetterEntry => "Letter entry",
+            SettingsRow::IdlePause => "Pause when idle",
+            SettingsRow::Pace => "Show pace",
+            SettingsRow::Tutorial => "Tutorial",
+        }
+    }
+
+    // the mods folder's themes, skins and word lists are stepped through along with the built in ones
+    fn value(&self, settings: &Settings, mods: &InstalledMods) -> String {
+        match self {
+            SettingsRow::EffectsVolume => percent(settings.sfx_volume),
+            SettingsRow::MusicVolume => percent(settings.music_volume),
+            SettingsRow::HoverSounds => on_off(settings.hover_sounds),
+            SettingsRow::LetterSounds => on_off(settings.letter_sounds),
+            SettingsRow::EraseSounds => on_off(settings.erase_sounds),
+            SettingsRow::SubmitScores => on_off(settings.submit_daily_scores),
+            SettingsRow::Telemetry => on_off(settings.telemetry),
+            SettingsRow::CheckUpdates => on_off(settings.check_updates),
+            SettingsRow::Fullscreen => on_off(settings.fullscreen),
+            SettingsRow::Palette => mods.palette_name(settings),
+            SettingsRow::TileSkin => mod_name(&mods.skins, &settings.tile_skin, "None"),
+            SettingsRow::BoardLayout => String::from(settings.board_layout.name()),
+            SettingsRow::HighContrast => on_off(settings.high_contrast),
+            SettingsRow::ReducedMotion => on_off(settings.reduced_motion),
+            SettingsRow::Captions => on_off(settings.captions),
+            SettingsRow::Haptics => on_off(settings.haptics),
+            SettingsRow::Profile => settings.player_name.clone(),
+            SettingsRow::KidsMode => on_off(settings.kids_mode()),
+            SettingsRow::WordList => mod_name(&mods.word_lists, &settings.word_list, "Built in"),
+            SettingsRow::LetterProgress => on_off(settings.letter_progress),
+            SettingsRow::LetterEntry => String::from(if settings.free_entry { "Free" } else { "Strict" }),
+            SettingsRow::IdlePause => match settings.idle_pause_minutes {
+                0 => String::from("Never"),
+                minutes => format!("After {} min", minutes),
+            },
+            SettingsRow::Pace => on_off(settings.pace),
+            SettingsRow::Tutorial => String::from(if settings.tutorial_done { "Done" } else { "Next board" }),
+        }
+    }
+
+    fn adjust(&self, settings: &mut Settings, mods: &InstalledMods, direction: f32) {
+        match self {
+            SettingsRow::EffectsVolume => settings.sfx_volume = step_volume(settings.sfx_volume, direction),
+            SettingsRow::MusicVolume => settings.music_volume = step_volume(settings.music_volume, direction),
+            // either direction flips a toggle
+            SettingsRow::HoverSounds => settings.hover_sounds = !settings.hover_sounds,
+            SettingsRow::LetterSounds => settings.letter_sounds = !settings.letter_sounds,
+            SettingsRow::EraseSounds => settings.erase_sounds = !settings.erase_sounds,
+            SettingsRow::SubmitScores => settings.submit_daily_scores = !settings.submit_daily_scores,
+            SettingsRow::Telemetry => settings.telemetry = !settings.telemetry,
+            SettingsRow::CheckUpdates => settings.check_updates = !settings.check_updates,
+            SettingsRow::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            SettingsRow::Palette => mods.step_palette(settings, direction),
+            SettingsRow::TileSkin => settings.tile_skin = step_name(&mods.skins, &settings.tile_skin, direction),
+            SettingsRow::BoardLayout => settings.board_layout = settings.board_layout.step(direction),
+            SettingsRow::HighContrast => settings.high_contrast = !settings.high_contrast,
+            SettingsRow::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            SettingsRow::Captions => settings.captions = !settings.captions,
+            SettingsRow::Haptics => settings.haptics = !settings.haptics,
+            SettingsRow::Profile => settings.step_profile(direction),
+            // for the profile in use, see Settings::profiles
+            SettingsRow::KidsMode => settings.profile_mut().kids_mode = !settings.kids_mode(),
+            SettingsRow::WordList => settings.word_list = step_name(&mods.word_lists, &settings.word_list, direction),
+            SettingsRow::LetterProgress => settings.letter_progress = !settings.letter_progress,
+            SettingsRow::LetterEntry => settings.free_entry = !settings.free_entry,
+            SettingsRow::IdlePause => settings.idle_pause_minutes = step_idle_pause(settings.idle_pause_minutes, direction),
+            SettingsRow::Pace => settings.pace = !settings.pace,
+            SettingsRow::Tutorial => settings.tutorial_done = !settings.tutorial_done,
+        }
+    }
+}
+
+fn percent(volume: f32) -> String {
+    format!("{}%", (volume * 100.).round())
+}
+
+fn on_off(enabled: bool) -> String {
+    String::from(if enabled { "On" } else { "Off" })
+}
+
+// snapped to whole steps so repeated presses don't drift
+fn step_volume(volume: f32, direction: f32) -> f32 {
+    (((volume / VOLUME_STEP).round() + direction) * VOLUME_STEP).clamp(0., 1.)
+}
+
+// minutes, wrapping round like the palettes, anything hand edited into the file steps from Never
+const IDLE_PAUSE_STEPS: [u32; 4] = [0, 2, 5, 10];
+
+fn step_idle_pause(minutes: u32, direction: f32) -> u32 {
+    let index = IDLE_PAUSE_STEPS.iter().position(|step| *step == minutes).unwrap_or(0);
+    let count = IDLE_PAUSE_STEPS.len();
+    IDLE_PAUSE_STEPS[if direction < 0. { (index + count - 1) % count } else { (index + 1) % count }]
+}
+
+#[derive(Resource, Default)]
+struct SettingsSelection(usize);
+
+#[derive(Component)]
+struct SettingsMenuRoot {}
+
+#[derive(Component)]
+struct SettingsRowText {
+    row: usize,
+}
+
+// the whole line, which carries the focus ring when selected
+#[derive(Component)]
+struct SettingsRowLine {
+    row: usize,
+}
+
+#[derive(Component)]
+struct SettingsAdjustButton {
+    row: usize,
+    direction: f32,
+}
+
+fn row_text(row: usize, settings: &Settings, mods: &InstalledMods) -> String {
+    format!("{}  {}", ROWS[row].label(), ROWS[row].value(settings, mods))
+}
+
+fn row_color(row: usize, selection: &SettingsSelection) -> Color {
+    if row == selection.0 { Color::CYAN } else { Color::WHITE }
+}
+
+fn open_settings_menu(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        // otherwise the menu sees the same press this frame and closes straight away
+        keys.clear_just_pressed(KeyCode::Escape);
+        if let Err(err) = state.push(AppState::Settings) {
+            debug!("not opening the settings, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn spawn_settings_menu(mut commands: Commands,
+                       asset_server: Res<AssetServer>,
+                       settings: Res<Settings>,
+                       mods: Res<InstalledMods>,
+                       selection: Res<SettingsSelection>,
+                    ) {
+    let font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let row_style = TextStyle { font: font.clone(), font_size: 36., color: Color::WHITE };
+    let button_style = TextStyle { font: font.clone(), font_size: 36., color: Color::BLACK };
+    let help_style = TextStyle { font: font.clone(), font_size: 24., color: Color::GRAY };
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(SettingsMenuRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Settings", title_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+
+        for row in 0..ROWS.len() {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::all(Val::Px(6.)),
+                    ..default()
+                },
+                ..default()
+            }).insert(SettingsRowLine { row }).with_children(|line| {
+                for direction in [-1., 1.] {
+                    let mut button = line.spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(40.), Val::Px(40.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: Color::ALICE_BLUE.into(),
+                        ..default()
+                    });
+                    button.insert(SettingsAdjustButton { row, direction }).with_children(|button| {
+                        button.spawn(TextBundle::from_section(if direction < 0. { "-" } else { "+" }, button_style.clone()));
+                    });
+
+                    if direction < 0. {
+                        let mut style = row_style.clone();
+                        style.color = row_color(row, &selection);
+                        line.spawn(TextBundle::from_section(row_text(row, &settings, &mods), style).with_style(Style {
+                            size: Size::new(Val::Px(320.), Val::Auto),
+                            margin: UiRect::horizontal(Val::Px(16.)),
+                            ..default()
+                        })).insert(SettingsRowText { row });
+                    }
+                }
+            });
+        }
+
+        parent.spawn(TextBundle::from_section("Up/Down or Tab to choose, Left/Right or Enter to change, Esc to go back", help_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+fn navigate_settings_menu(mut keys: ResMut<Input<KeyCode>>,
+                          mut state: ResMut<State<AppState>>,
+                          mut selection: ResMut<SettingsSelection>,
+                          mut settings: ResMut<Settings>,
+                          mods: Res<InstalledMods>,
+                        ) {
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if keys.just_pressed(KeyCode::Up) || (shift && keys.just_pressed(KeyCode::Tab)) {
+        selection.0 = (selection.0 + ROWS.len() - 1) % ROWS.len();
+    }
+    if keys.just_pressed(KeyCode::Down) || (!shift && keys.just_pressed(KeyCode::Tab)) {
+        selection.0 = (selection.0 + 1) % ROWS.len();
+    }
+    if keys.just_pressed(KeyCode::Left) {
+        ROWS[selection.0].adjust(&mut settings, &mods, -1.);
+    }
+    if keys.any_just_pressed([KeyCode::Right, KeyCode::Return, KeyCode::Space]) {
+        ROWS[selection.0].adjust(&mut settings, &mods, 1.);
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        if let Err(err) = state.pop() {
+            debug!("not closing the settings, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn click_settings_buttons(buttons: Query<(&Interaction, &SettingsAdjustButton), Changed<Interaction>>,
+                          mut selection: ResMut<SettingsSelection>,
+                          mut settings: ResMut<Settings>,
+                          mods: Res<InstalledMods>,
+                        ) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction == Interaction::Clicked {
+            selection.0 = button.row;
+            ROWS[button.row].adjust(&mut settings, &mods, button.direction);
+        }
+    }
+}
+
+fn show_settings_values(settings: Res<Settings>,
+                        mods: Res<InstalledMods>,
+                        selection: Res<SettingsSelection>,
+                        mut rows: Query<(&mut Text, &SettingsRowText)>,
+                    ) {
+    if !settings.is_changed() && !selection.is_changed() {
+        return;
+    }
+
+    for (mut text, row_text_marker) in rows.iter_mut() {
+        text.sections[0].value = row_text(row_text_marker.row, &settings, &mods);
+        text.sections[0].style.color = row_color(row_text_marker.row, &selection);
+    }
+}
+
+fn focus_selected_row(selection: Res<SettingsSelection>,
+                      lines: Query<(Entity, &SettingsRowLine)>,
+                      mut focus: ResMut<Focus>,
+                    ) {
+    let selected = lines.iter().find(|(_, line)| line.row == selection.0).map(|(entity, _)| entity);
+    if selected.is_some() && focus.entity != selected {
+        focus.entity = selected;
+    }
+}
+
+fn despawn_settings_menu(mut commands: Commands, menus: Query<Entity, With<SettingsMenuRoot>>) {
+    for entity in menus.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,176 @@
+use ab_glyph::{point, Font, FontRef, ScaleFont};
+use bevy::prelude::*;
+use directories::UserDirs;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, Rgba, RgbaImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+use wordgame::words::{GameState, RANKS};
+
+use crate::palette::Colours;
+
+// The board drawn out to a PNG for sharing, the letters on their tiles with the date and, if
+// wanted, the score under them. It's drawn on the CPU into an image of its own rather than read
+// back from the screen, so it looks the same whatever the window size or whatever is open on top.
+// Saved to Downloads next to challenge files, as are printable sheets. The reveal GIF is drawn the
+// same way, frame by frame: the board with its points counting up, then the rank it reached.
+
+const IMAGE_SIZE: (u32, u32) = (640, 760);
+const RADIUS: f32 = 80.;
+const OUTLINE: f32 = 4.;
+const BOARD_CENTER: (f32, f32) = (320., 380.);
+const REVEAL_FRAMES: u32 = 24; // counting the points up
+const REVEAL_HOLD_FRAMES: usize = 20; // on the rank before it loops
+const REVEAL_FRAME_MS: u32 = 80;
+const GIF_SPEED: i32 = 10; // colour quantising, 1 is best and slowest, 30 fastest
+
+fn rgba(color: Color) -> Rgba<u8> {
+    let [r, g, b, a] = color.as_rgba_f32();
+    Rgba([(r * 255.) as u8, (g * 255.) as u8, (b * 255.) as u8, (a * 255.) as u8])
+}
+
+fn blend(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, coverage: f32) {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+        return;
+    }
+
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        let under = pixel.0[channel] as f32;
+        pixel.0[channel] = (under + (color.0[channel] as f32 - under) * coverage.clamp(0., 1.)) as u8;
+    }
+}
+
+// flat sides top and bottom, like the tiles on screen
+fn fill_hexagon(image: &mut RgbaImage, center: (f32, f32), radius: f32, color: Rgba<u8>) {
+    let half_height = radius * 3_f32.sqrt() / 2.;
+    for y in (center.1 - half_height) as i32..=(center.1 + half_height) as i32 {
+        for x in (center.0 - radius) as i32..=(center.0 + radius) as i32 {
+            let (dx, dy) = ((x as f32 - center.0).abs(), (y as f32 - center.1).abs());
+            if dy <= half_height && 3_f32.sqrt() * dx + dy <= 3_f32.sqrt() * radius {
+                blend(image, x, y, color, 1.);
+            }
+        }
+    }
+}
+
+// one line, centred on the point
+fn draw_text(image: &mut RgbaImage, font: &FontRef, text: &str, size: f32, center: (f32, f32), color: Rgba<u8>) {
+    let scaled = font.as_scaled(size);
+    let width: f32 = text.chars().map(|c| scaled.h_advance(font.glyph_id(c))).sum();
+    let baseline = center.1 + (scaled.ascent() + scaled.descent()) / 2.;
+
+    let mut x = center.0 - width / 2.;
+    for c in text.chars() {
+        let glyph = scaled.scaled_glyph(c);
+        let advance = scaled.h_advance(glyph.id);
+        let glyph = ab_glyph::Glyph { position: point(x, baseline), ..glyph };
+        if let Some(outline) = font.outline_glyph(glyph) {
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| blend(image, bounds.min.x as i32 + gx as i32, bounds.min.y as i32 + gy as i32, color, coverage));
+        }
+        x += advance;
+    }
+}
+
+pub fn render_board(gamestate: &GameState, colours: Colours, date: &str, score: bool) -> RgbaImage {
+    let bold = FontRef::try_from_slice(include_bytes!("../assets/fonts/BarlowCondensed-Bold.ttf")).unwrap();
+    let regular = FontRef::try_from_slice(include_bytes!("../assets/fonts/BarlowCondensed-Regular.ttf")).unwrap();
+    let white = rgba(Color::WHITE);
+    let black = rgba(Color::BLACK);
+
+    let mut image = RgbaImage::from_pixel(IMAGE_SIZE.0, IMAGE_SIZE.1, rgba(*colours.background().set_a(1.)));
+    draw_text(&mut image, &bold, "Spelling Tiles", 56., (BOARD_CENTER.0, 50.), white);
+    draw_text(&mut image, &regular, date, 32., (BOARD_CENTER.0, 100.), rgba(Color::GRAY));
+
+    // the same spacing as the board on screen, the required letter in the middle
+    for (i, letter) in gamestate.target_string.chars().enumerate() {
+        let center = if i == 0 {
+            BOARD_CENTER
+        }
+        else {
+            let angle = ((i - 1) as f32 * 60.).to_radians();
+            (BOARD_CENTER.0 + angle.sin() * RADIUS * 2., BOARD_CENTER.1 - angle.cos() * RADIUS * 2.)
+        };
+        let color = if i == 0 { colours.required() } else { colours.tile() };
+
+        fill_hexagon(&mut image, center, RADIUS, black);
+        fill_hexagon(&mut image, center, RADIUS - OUTLINE, rgba(color));
+        draw_text(&mut image, &bold, &letter.to_string(), RADIUS, center, black);
+    }
+
+    if score {
+        let line = format!("{} points, {} words, {}", gamestate.score(), gamestate.correct_words.len(), RANKS[gamestate.rank()].0);
+        draw_text(&mut image, &regular, &line, 40., (BOARD_CENTER.0, IMAGE_SIZE.1 as f32 - 70.), white);
+    }
+
+    image
+}
+
+// eased the same way as a CubicOut tween, quick at first and slowing into the final score
+pub fn render_reveal(gamestate: &GameState, colours: Colours, date: &str) -> Vec<RgbaImage> {
+    let bold = FontRef::try_from_slice(include_bytes!("../assets/fonts/BarlowCondensed-Bold.ttf")).unwrap();
+    let regular = FontRef::try_from_slice(include_bytes!("../assets/fonts/BarlowCondensed-Regular.ttf")).unwrap();
+    let white = rgba(Color::WHITE);
+    let board = render_board(gamestate, colours, date, false);
+    let score_at = (BOARD_CENTER.0, IMAGE_SIZE.1 as f32 - 70.);
+
+    let mut frames = Vec::new();
+    for frame in 1..=REVEAL_FRAMES {
+        let t = frame as f32 / REVEAL_FRAMES as f32;
+        let eased = 1. - (1. - t).powi(3);
+        let points = (gamestate.score() as f32 * eased).round() as u32;
+        let words = (gamestate.correct_words.len() as f32 * eased).round() as usize;
+
+        let mut image = board.clone();
+        draw_text(&mut image, &regular, &format!("{} points, {} words", points, words), 40., score_at, white);
+        frames.push(image);
+    }
+
+    let mut last = board;
+    draw_text(&mut last, &regular, &format!("{} points, {} words", gamestate.score(), gamestate.correct_words.len()), 40., score_at, white);
+    draw_text(&mut last, &bold, RANKS[gamestate.rank()].0, 56., (BOARD_CENTER.0, IMAGE_SIZE.1 as f32 - 120.), rgba(colours.pangram()));
+    frames.extend(std::iter::repeat_n(last, REVEAL_HOLD_FRAMES));
+    frames
+}
+
+fn download_path(file_name: &str) -> PathBuf {
+    let dir = UserDirs::new()
+        .and_then(|dirs| dirs.download_dir().map(Path::to_path_buf))
+        .unwrap_or_default();
+    dir.join(file_name)
+}
+
+// gives back where it was saved
+pub fn save_board_image(image: &RgbaImage, name: &str) -> Result<String, String> {
+    let path = download_path(&format!("{}.png", name));
+    image.save(&path).map_err(|err| format!("Could not save {}: {}", path.display(), err))?;
+    Ok(path.display().to_string())
+}
+
+// loops forever, the way GIFs are shared
+pub fn save_reveal_gif(frames: Vec<RgbaImage>, name: &str) -> Result<String, String> {
+    let path = download_path(&format!("{}.gif", name));
+    let failed = |err: String| format!("Could not save {}: {}", path.display(), err);
+    let file = fs::File::create(&path).map_err(|err| failed(err.to_string()))?;
+
+    let mut encoder = GifEncoder::new_with_speed(file, GIF_SPEED);
+    encoder.set_repeat(Repeat::Infinite).map_err(|err| failed(err.to_string()))?;
+    encoder.encode_frames(frames.into_iter().map(|image| Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(REVEAL_FRAME_MS, 1))))
+        .map_err(|err| failed(err.to_string()))?;
+    Ok(path.display().to_string())
+}
+
+// LiveSplit splits from speedrun.rs, with the rest of the downloads
+pub fn save_splits(lss: &str, name: &str) -> Result<String, String> {
+    let path = download_path(&format!("{}.lss", name));
+    fs::write(&path, lss).map_err(|err| format!("Could not save {}: {}", path.display(), err))?;
+    Ok(path.display().to_string())
+}
+
+// the sheet from wordgame::printable, beside the pictures
+pub fn save_printable_sheet(svg: &str, name: &str) -> Result<String, String> {
+    let path = download_path(&format!("{}.svg", name));
+    fs::write(&path, svg).map_err(|err| format!("Could not save {}: {}", path.display(), err))?;
+    Ok(path.display().to_string())
+}
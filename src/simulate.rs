@@ -0,0 +1,106 @@
+use rand::Rng;
+use wordgame::puzzle_code::PuzzleCode;
+use wordgame::words::{is_pangram, FoundWord, GameState, WordList, RANKS};
+
+use crate::loading::DictionarySource;
+use crate::{pick_board, GameRng};
+
+// --simulate N: picks N boards the way the game does, without a window, and plays each with a
+// greedy solver that always goes for the best word left. What comes out is for tuning word lists
+// and the board picking: how many answers and points boards have, how many words it takes to
+// climb the ranks at best, and the boards too thin or too crowded to be much fun, with their
+// puzzle codes so they can be looked at. The same --seed gives the same boards.
+
+// outside these a board is listed as degenerate
+const FEWEST_GOOD_ANSWERS: usize = 10;
+const MOST_GOOD_ANSWERS: usize = 150;
+const DEGENERATE_LISTED: usize = 20; // the rest are only counted
+
+struct BoardResult {
+    code: PuzzleCode,
+    answers: usize,
+    max_score: u32,
+    pangrams: usize,
+    words_to_rank: Vec<usize>, // by rank, greedily
+}
+
+pub fn run(source: &DictionarySource, seed: Option<u64>, boards: usize) {
+    let wordlist = match source.load(|_| {}) {
+        Ok(wordlist) => wordlist,
+        Err(message) => {
+            eprintln!("{}", message);
+            return;
+        }
+    };
+
+    // a seed is picked even without one, so an odd run can be repeated
+    let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = GameRng::new(Some(seed));
+    println!("Simulating {} boards from seed {}, {} words and {} possible pangrams in the list",
+             boards, seed, wordlist.all_valid_words.len(), wordlist.potential_pangrams.len());
+
+    let results: Vec<BoardResult> = (0..boards).map(|_| play_board(&wordlist, &mut rng)).collect();
+    if results.is_empty() {
+        return;
+    }
+
+    println!();
+    print_spread("Answers", results.iter().map(|result| result.answers as f32).collect());
+    print_spread("Max score", results.iter().map(|result| result.max_score as f32).collect());
+    print_spread("Pangrams", results.iter().map(|result| result.pangrams as f32).collect());
+
+    println!();
+    println!("Words to reach each rank, playing the best word first:");
+    for (rank, (name, _)) in RANKS.iter().enumerate().skip(1) {
+        print_spread(name, results.iter().map(|result| result.words_to_rank[rank] as f32).collect());
+    }
+
+    let degenerate: Vec<&BoardResult> = results.iter()
+        .filter(|result| result.answers < FEWEST_GOOD_ANSWERS || result.answers > MOST_GOOD_ANSWERS)
+        .collect();
+    println!();
+    println!("{} degenerate boards ({:.1}%), fewer than {} or more than {} answers",
+             degenerate.len(), degenerate.len() as f32 * 100. / results.len() as f32, FEWEST_GOOD_ANSWERS, MOST_GOOD_ANSWERS);
+    for result in degenerate.iter().take(DEGENERATE_LISTED) {
+        let code = result.code.encode().unwrap_or_else(|message| message);
+        println!("  {} {} required, {} answers, {} points", code, result.code.required_letter, result.answers, result.max_score);
+    }
+    if degenerate.len() > DEGENERATE_LISTED {
+        println!("  and {} more", degenerate.len() - DEGENERATE_LISTED);
+    }
+}
+
+fn play_board(wordlist: &WordList, rng: &mut GameRng) -> BoardResult {
+    let mut gamestate = GameState::default();
+    pick_board(&mut gamestate, wordlist, &mut rng.0, None);
+
+    let mut best_first = gamestate.answers.clone();
+    best_first.sort_by_key(|answer| std::cmp::Reverse(gamestate.word_points(answer)));
+
+    // every rank is reached with all the answers found, so each gets filled in on the way
+    let mut words_to_rank = vec![0; RANKS.len()];
+    for (count, answer) in best_first.iter().enumerate() {
+        gamestate.correct_words.push(FoundWord { word: answer.clone(), player: 0 });
+        for rank in words_to_rank.iter_mut().take(gamestate.rank() + 1).skip(1) {
+            if *rank == 0 {
+                *rank = count + 1;
+            }
+        }
+    }
+
+    BoardResult {
+        code: PuzzleCode { letter_bits: gamestate.target_bits, required_letter: gamestate.required_letter, mode: None },
+        answers: gamestate.answers.len(),
+        max_score: gamestate.max_score(),
+        pangrams: gamestate.answers.iter().filter(|answer| is_pangram(answer)).count(),
+        words_to_rank,
+    }
+}
+
+fn print_spread(name: &str, mut values: Vec<f32>) {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |percent: usize| values[(values.len() - 1) * percent / 100];
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    println!("{:>12}  min {:>5}  10% {:>5}  median {:>5}  90% {:>5}  max {:>5}  mean {:>7.1}",
+             name, percentile(0), percentile(10), percentile(50), percentile(90), percentile(100), mean);
+}
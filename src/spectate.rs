@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use wordgame::words::{FoundWord, GameState};
+
+use crate::net::{NetInbox, NetMessage, NetPeer, NetRole};
+use crate::{chose_letter, current_puzzle_code, AppState, GameMode, HintText, TriedWord};
+
+// Watching a game from another machine, with --spectate ADDR. The host takes spectators on the
+// port after its own, and sends each one the board and every word found on it as it's found, by
+// either player. A spectator's board follows along without a guess line, and typing does nothing.
+// Spectators never send anything back, the players don't know they're there.
+pub struct SpectatePlugin;
+
+impl Plugin for SpectatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Spectators>()
+            .add_startup_system(listen_for_spectators)
+            .add_system(accept_spectators)
+            .add_system(update_spectators.after(accept_spectators))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(ignore_typing.before(chose_letter))
+                .with_system(remove_guess_line)
+                .with_system(watch_found_words));
+    }
+}
+
+pub const SPECTATOR_PORT_OFFSET: u16 = 1;
+
+#[derive(Resource)]
+struct SpectatorListener(TcpListener);
+
+struct Spectator {
+    peer: NetPeer,
+    board: String, // the code of the board it was last sent
+    sent: usize,   // of that board's found words
+}
+
+#[derive(Resource, Default)]
+struct Spectators(Vec<Spectator>);
+
+// spectators are a nice extra, a port that's taken only means nobody can watch
+fn listen_for_spectators(mut commands: Commands, role: Res<NetRole>) {
+    let port = match *role {
+        NetRole::Host { port } => port + SPECTATOR_PORT_OFFSET,
+        _ => return,
+    };
+
+    match TcpListener::bind(("0.0.0.0", port)).and_then(|listener| {
+        listener.set_nonblocking(true)?;
+        Ok(listener)
+    }) {
+        Ok(listener) => {
+            info!("spectators can watch on port {}", port);
+            commands.insert_resource(SpectatorListener(listener));
+        }
+        Err(err) => warn!("Could not take spectators on port {}: {}", port, err),
+    }
+}
+
+fn accept_spectators(listener: Option<Res<SpectatorListener>>, mut spectators: ResMut<Spectators>) {
+    let listener = match listener {
+        Some(listener) => listener,
+        None => return,
+    };
+
+    loop {
+        match listener.0.accept() {
+            Ok((stream, addr)) => match NetPeer::new(stream, 0) {
+                Ok(peer) => {
+                    info!("{} is watching", addr);
+                    spectators.0.push(Spectator { peer, board: String::new(), sent: 0 });
+                }
+                Err(err) => warn!("could not set up connection from {}: {}", addr, err),
+            },
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(err) => {
+                warn!("could not accept spectator: {}", err);
+                break;
+            }
+        }
+    }
+}
+
+// a new board (or one joined late) is sent whole, after that only the words found since
+fn update_spectators(gamestate: Res<GameState>, mode: Res<GameMode>, mut spectators: ResMut<Spectators>) {
+    // nothing to watch until the players are out of the lobby
+    if spectators.0.is_empty() || gamestate.target_string.is_empty() {
+        return;
+    }
+
+    // a board without a code can't be shown to them
+    let code = match current_puzzle_code(&gamestate, *mode).encode() {
+        Ok(code) => code,
+        Err(_) => return,
+    };
+    spectators.0.retain_mut(|spectator| {
+        let mut connected = true;
+        if spectator.board != code {
+            connected &= spectator.peer.send(&NetMessage::Board { code: code.clone() });
+            spectator.board = code.clone();
+            spectator.sent = 0;
+        }
+        for found in gamestate.correct_words.iter().skip(spectator.sent) {
+            connected &= spectator.peer.send(&NetMessage::Found { word: found.word.clone(), player: found.player });
+        }
+        spectator.sent = gamestate.correct_words.len();
+        connected &= spectator.peer.flush();
+
+        if !connected {
+            info!("a spectator left");
+        }
+        connected
+    });
+}
+
+fn ignore_typing(role: Res<NetRole>, mut chars: ResMut<Events<ReceivedCharacter>>) {
+    if matches!(*role, NetRole::Spectate { .. }) {
+        chars.clear();
+    }
+}
+
+// the guess line is spawned with the board, so it's caught here once it exists
+fn remove_guess_line(mut commands: Commands, role: Res<NetRole>, guesses: Query<Entity, With<TriedWord>>) {
+    if !matches!(*role, NetRole::Spectate { .. }) {
+        return;
+    }
+
+    for entity in guesses.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn watch_found_words(role: Res<NetRole>,
+                     mut inbox: ResMut<NetInbox>,
+                     mut gamestate: ResMut<GameState>,
+                     mut hint_text: Query<&mut Text, With<HintText>>,
+                    ) {
+    if !matches!(*role, NetRole::Spectate { .. }) {
+        return;
+    }
+
+    for message in inbox.take(|message| matches!(message, NetMessage::Found { .. })) {
+        if let NetMessage::Found { word, player } = message {
+            if gamestate.is_found(&word) {
+                continue;
+            }
+
+            let found = if gamestate.player_count > 1 { format!("Player {} found {}", player + 1, word.to_ascii_uppercase()) } else { format!("Found {}", word.to_ascii_uppercase()) };
+            for mut text in hint_text.iter_mut() {
+                text.sections[0].value = found.clone();
+            }
+            gamestate.correct_words.push(FoundWord { word, player });
+        }
+    }
+}
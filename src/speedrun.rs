@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use wordgame::words::{GameState, RANKS};
+
+#[cfg(not(feature = "web"))]
+use crate::share_image;
+use crate::{record_rank_times, setup_goals, AppState, BoardEntity};
+
+// The speedrun overlay from --speedrun: the board's clock to the millisecond in the top right
+// corner, with a split for each rank as it's reached, showing the time to it and how long it took
+// from the one before. The clock is the board's time played, the same one the ranks are timed
+// on, so it stops while a menu is open. On desktop Ctrl+X saves the splits as a LiveSplit file
+// (.lss) in the downloads folder, which most other split tools can read in too.
+pub struct SpeedrunPlugin;
+
+impl Plugin for SpeedrunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_speedrun_overlay.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(show_speedrun.after(record_rank_times)));
+
+        #[cfg(not(feature = "web"))]
+        app.add_system_set(SystemSet::on_update(AppState::Playing).with_system(export_splits.after(show_speedrun)));
+    }
+}
+
+// from the command line
+#[derive(Resource)]
+pub struct SpeedrunRules {
+    pub enabled: bool,
+}
+
+#[derive(Component)]
+struct SpeedrunOverlay {}
+
+fn format_precise(seconds: f32) -> String {
+    let millis = (seconds.max(0.) * 1000.).round() as u32;
+    format!("{}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
+
+// under the connection status, when there is one
+fn spawn_speedrun_overlay(mut commands: Commands, asset_server: Res<AssetServer>, rules: Res<SpeedrunRules>) {
+    if !rules.enabled {
+        return;
+    }
+
+    let clock_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 40., color: Color::WHITE };
+    let splits_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::WHITE };
+    let message_style = TextStyle { color: Color::GRAY, ..splits_style.clone() };
+
+    commands.spawn(TextBundle::from_sections([
+        TextSection::new("", clock_style),
+        TextSection::new("", splits_style),
+        TextSection::new("", message_style),
+    ]).with_text_alignment(TextAlignment::TOP_RIGHT).with_style(Style {
+        position_type: PositionType::Absolute,
+        position: UiRect { right: Val::Px(12.), top: Val::Px(48.), ..default() },
+        ..default()
+    })).insert(BoardEntity {}).insert(SpeedrunOverlay {});
+}
+
+// each reached rank with its time and segment, then the one still to come
+fn splits_text(gamestate: &GameState) -> String {
+    let mut text = String::new();
+    let mut previous = 0.;
+    for (rank, time) in gamestate.rank_times.iter() {
+        text.push_str(&format!("\n{}  {}  +{}", RANKS[*rank].0, format_precise(*time), format_precise(time - previous)));
+        previous = *time;
+    }
+
+    let next = gamestate.rank_times.last().map_or(1, |(rank, _)| rank + 1);
+    if let Some((name, _)) = RANKS.get(next) {
+        text.push_str(&format!("\nNext: {}", name));
+    }
+    text
+}
+
+fn show_speedrun(gamestate: Res<GameState>, mut overlays: Query<&mut Text, With<SpeedrunOverlay>>) {
+    for mut text in overlays.iter_mut() {
+        text.sections[0].value = format_precise(gamestate.time_played);
+        let splits = splits_text(&gamestate);
+        if text.sections[1].value != splits {
+            text.sections[1].value = splits;
+        }
+    }
+}
+
+// LiveSplit's own layout for a time, to the ten millionth of a second
+#[cfg(not(feature = "web"))]
+fn livesplit_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.) as f64;
+    let whole = seconds as u64;
+    format!("{:02}:{:02}:{:02}.{:07}", whole / 3600, whole / 60 % 60, whole % 60, ((seconds - whole as f64) * 1e7).round() as u64)
+}
+
+// one segment for every rank past the first, the ones not reached yet left without a time so
+// the file can still be run against
+#[cfg(not(feature = "web"))]
+fn livesplit_splits(gamestate: &GameState) -> String {
+    let mut segments = String::new();
+    let mut previous = 0.;
+    for (rank, (name, _)) in RANKS.iter().enumerate().skip(1) {
+        let reached = gamestate.rank_times.iter().find(|(reached, _)| *reached == rank).map(|(_, time)| *time);
+        let (split, best) = match reached {
+            Some(time) => {
+                let segment = time - previous;
+                previous = time;
+                (format!("<RealTime>{}</RealTime>", livesplit_time(time)), format!("<RealTime>{}</RealTime>", livesplit_time(segment)))
+            }
+            None => (String::new(), String::new()),
+        };
+        segments.push_str(&format!(concat!(
+            "    <Segment>\n",
+            "      <Name>{}</Name>\n",
+            "      <Icon />\n",
+            "      <SplitTimes>\n",
+            "        <SplitTime name=\"Personal Best\">{}</SplitTime>\n",
+            "      </SplitTimes>\n",
+            "      <BestSegmentTime>{}</BestSegmentTime>\n",
+            "      <SegmentHistory />\n",
+            "    </Segment>\n"), name, split, best));
+    }
+
+    format!(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<Run version=\"1.7.0\">\n",
+        "  <GameIcon />\n",
+        "  <GameName>Spelling Tiles</GameName>\n",
+        "  <CategoryName>{}</CategoryName>\n",
+        "  <Offset>00:00:00</Offset>\n",
+        "  <AttemptCount>1</AttemptCount>\n",
+        "  <AttemptHistory />\n",
+        "  <Segments>\n",
+        "{}",
+        "  </Segments>\n",
+        "  <AutoSplitterSettings />\n",
+        "</Run>\n"), gamestate.target_string, segments)
+}
+
+#[cfg(not(feature = "web"))]
+fn export_splits(keys: Res<Input<KeyCode>>,
+                 rules: Res<SpeedrunRules>,
+                 gamestate: Res<GameState>,
+                 mut overlays: Query<&mut Text, With<SpeedrunOverlay>>,
+                ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if !(rules.enabled && ctrl && keys.just_pressed(KeyCode::X)) {
+        return;
+    }
+
+    let message = match share_image::save_splits(&livesplit_splits(&gamestate), &format!("spelling-tiles-{}", gamestate.target_string)) {
+        Ok(path) => format!("\nSplits saved to {}", path),
+        Err(message) => {
+            warn!("{}", message);
+            format!("\n{}", message)
+        }
+    };
+
+    for mut text in overlays.iter_mut() {
+        text.sections[2].value = message.clone();
+    }
+}
@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use wordgame::words::{FoundWord, GameState, WordList, WordValidators};
+
+use crate::settings::Settings;
+use crate::tween::{Easing, Tween};
+use crate::{setup_goals, spawn_tiles, AppState, BoardEntity, GameMode};
+
+// Two copies of the board side by side for two people on one screen, each with its own guess,
+// found words, score and clock. The left board is typed on the keyboard, the right one played
+// with the first gamepad: the d-pad picks a tile, A adds it, B erases, Y clears and X guesses.
+// A board's clock stops once it has found every answer.
+pub struct SplitScreenPlugin;
+
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SplitBoards>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(setup_split_boards.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(keyboard_side_input)
+                .with_system(gamepad_side_input)
+                .with_system(tick_side_clocks)
+                .with_system(show_sides.after(keyboard_side_input).after(gamepad_side_input))
+                .with_system(show_side_clocks.after(tick_side_clocks))
+                .with_system(highlight_selected_tile.after(gamepad_side_input)));
+    }
+}
+
+const SIDE_RADIUS: f32 = 48.;
+const SIDE_CENTERS: [Vec3; 2] = [Vec3::new(-320., -60., 0.), Vec3::new(320., -60., 0.)];
+const KEYBOARD_SIDE: usize = 0;
+const GAMEPAD_SIDE: usize = 1;
+const SELECTED_OUTLINE: Color = Color::ORANGE;
+
+struct Side {
+    state: GameState, // a copy of the shared board with this side's own found words
+    guess: String,
+    hint: String,
+    selected: usize, // the tile the gamepad is on
+}
+
+impl Side {
+    fn finished(&self) -> bool {
+        self.state.correct_words.len() >= self.state.answers.len()
+    }
+
+    fn submit(&mut self, player: usize, wordlist: &WordList, validators: &WordValidators) {
+        let guess = std::mem::take(&mut self.guess);
+        match validators.check_word(&guess, &self.state, wordlist) {
+            Ok(_pangram) => {
+                let word = guess.to_ascii_lowercase();
+                self.hint = format!("{} +{}", guess, self.state.scoring.word_score(&word));
+                self.state.correct_words.push(FoundWord { word, player });
+            }
+            Err(reason) => self.hint = format!("{} {}", guess, reason),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct SplitBoards {
+    sides: Vec<Side>,
+}
+
+#[derive(Component)]
+struct SideTile {
+    side: usize,
+    index: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SideTextKind {
+    Guess,
+    Hint,
+    Status,
+    Found,
+}
+
+#[derive(Component)]
+struct SideText {
+    side: usize,
+    kind: SideTextKind,
+}
+
+fn setup_split_boards(mut commands: Commands,
+                      asset_server: Res<AssetServer>,
+                      gamestate: Res<GameState>,
+                      mode: Res<GameMode>,
+                      settings: Res<Settings>,
+                      mut boards: ResMut<SplitBoards>,
+                    ) {
+    boards.sides.clear();
+    if *mode != GameMode::SplitScreen {
+        return;
+    }
+
+    let bold_font = asset_server.load("fonts/BarlowCondensed-Bold.ttf");
+    let narrow_font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
+    let word_text_style = TextStyle { font: bold_font.clone(), font_size: SIDE_RADIUS, color: Color::WHITE };
+    let info_text_style = TextStyle { font: narrow_font.clone(), font_size: SIDE_RADIUS / 2., color: Color::WHITE };
+    let letters = gamestate.target_string.as_bytes();
+
+    for (side, center) in SIDE_CENTERS.iter().enumerate() {
+        boards.sides.push(Side { state: gamestate.clone(), guess: String::new(), hint: String::new(), selected: 0 });
+
+        let board = commands.spawn(SpatialBundle::from_transform(Transform::from_translation(*center))).insert(BoardEntity {}).id();
+        // the small boards settle without overshooting, two boards bouncing side by side is a lot
+        let tiles = spawn_tiles(&mut commands, &asset_server, letters, board, SIDE_RADIUS, settings.colours(), settings.board_layout, Easing::CubicOut);
+        for (index, tile) in tiles.iter().enumerate() {
+            commands.entity(*tile).insert(SideTile { side, index });
+        }
+
+        // the found words go on the outside edge so the two boards don't crowd each other
+        let outward = if side == KEYBOARD_SIDE { -1. } else { 1. };
+        let texts = [
+            (SideTextKind::Status, Vec3::new(0., 5.4 * SIDE_RADIUS, 1.), &info_text_style, TextAlignment::CENTER),
+            (SideTextKind::Guess, Vec3::new(0., 4.4 * SIDE_RADIUS, 1.), &word_text_style, TextAlignment::CENTER),
+            (SideTextKind::Hint, Vec3::new(0., 3.6 * SIDE_RADIUS, 1.), &info_text_style, TextAlignment::CENTER),
+            (SideTextKind::Found, Vec3::new(outward * 4.5 * SIDE_RADIUS, 5.4 * SIDE_RADIUS, 1.), &info_text_style, TextAlignment::TOP_CENTER),
+        ];
+
+        for (kind, offset, style, alignment) in texts {
+            commands.spawn(Text2dBundle{
+                text: Text::from_section("", style.clone()).with_alignment(alignment),
+                transform: Transform::from_translation(*center + offset),
+                ..default()
+            }).insert(BoardEntity {}).insert(SideText { side, kind });
+        }
+    }
+}
+
+fn squish_side_tile(tiles: &mut Query<(&SideTile, &mut Tween)>, side: usize, index: usize) {
+    for (tile, mut squish) in tiles.iter_mut() {
+        if tile.side == side && tile.index == index {
+            squish.restart();
+        }
+    }
+}
+
+fn keyboard_side_input(mut char_evr: EventReader<ReceivedCharacter>,
+                       keys: Res<Input<KeyCode>>,
+                       wordlist: Res<WordList>,
+                       validators: Res<WordValidators>,
+                       mut boards: ResMut<SplitBoards>,
+                       mut tiles: Query<(&SideTile, &mut Tween)>,
+                    ) {
+    // only touch the boards when there's input, so their texts aren't rebuilt every frame
+    if char_evr.is_empty() && !keys.any_just_pressed([KeyCode::Return, KeyCode::Back, KeyCode::Delete]) {
+        return;
+    }
+
+    let side = match boards.sides.get_mut(KEYBOARD_SIDE) {
+        Some(side) => side,
+        None => return,
+    };
+
+    for ev in char_evr.iter() {
+        let letter = ev.char.to_ascii_uppercase();
+        if let Some(index) = side.state.target_string.find(letter) {
+            side.guess.push(letter);
+            squish_side_tile(&mut tiles, KEYBOARD_SIDE, index);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        side.submit(KEYBOARD_SIDE, &wordlist, &validators);
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        side.guess.pop();
+    }
+    if keys.just_pressed(KeyCode::Delete) {
+        side.guess.clear();
+    }
+}
+
+fn gamepad_side_input(gamepads: Res<Gamepads>,
+                      buttons: Res<Input<GamepadButton>>,
+                      wordlist: Res<WordList>,
+                      validators: Res<WordValidators>,
+                      mut boards: ResMut<SplitBoards>,
+                      mut tiles: Query<(&SideTile, &mut Tween)>,
+                    ) {
+    if buttons.get_just_pressed().len() == 0 {
+        return;
+    }
+
+    let (side, gamepad) = match (boards.sides.get_mut(GAMEPAD_SIDE), gamepads.iter().next()) {
+        (Some(side), Some(gamepad)) => (side, gamepad),
+        _ => return,
+    };
+    let pressed = |button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+    let tile_count = side.state.target_string.len();
+
+    if pressed(GamepadButtonType::DPadRight) || pressed(GamepadButtonType::DPadUp) {
+        side.selected = (side.selected + 1) % tile_count;
+    }
+    if pressed(GamepadButtonType::DPadLeft) || pressed(GamepadButtonType::DPadDown) {
+        side.selected = (side.selected + tile_count - 1) % tile_count;
+    }
+    if pressed(GamepadButtonType::South) {
+        let letter = side.state.target_string.as_bytes()[side.selected] as char;
+        side.guess.push(letter);
+        squish_side_tile(&mut tiles, GAMEPAD_SIDE, side.selected);
+    }
+    if pressed(GamepadButtonType::East) {
+        side.guess.pop();
+    }
+    if pressed(GamepadButtonType::North) {
+        side.guess.clear();
+    }
+    if pressed(GamepadButtonType::West) {
+        side.submit(GAMEPAD_SIDE, &wordlist, &validators);
+    }
+}
+
+fn tick_side_clocks(mut boards: ResMut<SplitBoards>, time: Res<Time>) {
+    // like the main board's clock, ticking shouldn't count as a change to everything watching
+    for side in boards.bypass_change_detection().sides.iter_mut() {
+        if !side.finished() {
+            side.state.time_played += time.delta_seconds();
+        }
+    }
+}
+
+fn show_sides(boards: Res<SplitBoards>, mut texts: Query<(&mut Text, &SideText)>) {
+    if !boards.is_changed() {
+        return;
+    }
+
+    for (mut text, side_text) in texts.iter_mut() {
+        let side = match boards.sides.get(side_text.side) {
+            Some(side) => side,
+            None => continue,
+        };
+
+        match side_text.kind {
+            SideTextKind::Guess => text.sections[0].value = if side.guess.is_empty() { String::from("_") } else { side.guess.clone() },
+            SideTextKind::Hint => text.sections[0].value = side.hint.clone(),
+            SideTextKind::Found => {
+                let mut found = format!("Found Words: {}", side.state.correct_words.len());
+                for word in side.state.correct_words.iter() {
+                    found.push('\n');
+                    found.push_str(&word.word.to_ascii_uppercase());
+                }
+                text.sections[0].value = found;
+            }
+            SideTextKind::Status => {}
+        }
+    }
+}
+
+fn show_side_clocks(boards: Res<SplitBoards>, gamepads: Res<Gamepads>, mut texts: Query<(&mut Text, &SideText)>) {
+    for (mut text, side_text) in texts.iter_mut().filter(|(_, side_text)| side_text.kind == SideTextKind::Status) {
+        let side = match boards.sides.get(side_text.side) {
+            Some(side) => side,
+            None => continue,
+        };
+
+        let seconds = side.state.time_played as u32;
+        let mut status = format!("Player {}   {} points   {}:{:02}", side_text.side + 1, side.state.score(), seconds / 60, seconds % 60);
+        if side_text.side == GAMEPAD_SIDE && gamepads.iter().next().is_none() {
+            status = format!("Player {}, connect a gamepad to play", side_text.side + 1);
+        }
+        else if side.finished() {
+            status.push_str("   all found!");
+        }
+
+        if text.sections[0].value != status {
+            text.sections[0].value = status;
+        }
+    }
+}
+
+fn highlight_selected_tile(boards: Res<SplitBoards>, mut tiles: Query<(&SideTile, &mut DrawMode)>) {
+    if !boards.is_changed() {
+        return;
+    }
+
+    let selected = match boards.sides.get(GAMEPAD_SIDE) {
+        Some(side) => side.selected,
+        None => return,
+    };
+
+    for (tile, mut draw_mode) in tiles.iter_mut().filter(|(tile, _)| tile.side == GAMEPAD_SIDE) {
+        if let DrawMode::Outlined { ref mut outline_mode, .. } = *draw_mode {
+            outline_mode.color = if tile.index == selected { SELECTED_OUTLINE } else { Color::BLACK };
+        }
+    }
+}
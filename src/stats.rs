@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wordgame::frequency::{Obscurity, WordFrequency};
+use wordgame::words::GameState;
+
+use crate::storage;
+use crate::{record_correct_words, record_rank_times, AppState, WordAcceptedEvent};
+
+// Running totals across every board ever played, the fastest time to each rank and the pace words
+// are found at, kept in stats.ron next to the settings and shown on the board summary.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Stats>()
+            .insert_resource(WordFrequency::parse(include_str!("../assets/words/mit_10000_words.txt")))
+            .add_startup_system(load_stats)
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(count_found_words.after(record_correct_words))
+                .with_system(record_pace.after(record_correct_words))
+                .with_system(record_fastest_ranks.after(record_rank_times)))
+            .add_system(save_stats);
+    }
+}
+
+pub const STATS_FILE: &str = "stats.ron";
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub rare_words_found: u32,
+    pub fastest_ranks: BTreeMap<usize, f32>, // by index into RANKS, the least time played to reach it
+    pub pace_words: u32,
+    pub pace_seconds: f32, // time played up to each of the pace words, since the word before
+    pub letters_used: BTreeMap<char, u32>, // every letter of every word found, lowercase
+}
+
+impl Stats {
+    pub fn seconds_per_word(&self) -> Option<f32> {
+        if self.pace_words == 0 { None } else { Some(self.pace_seconds / self.pace_words as f32) }
+    }
+}
+
+// a broken or unwritable file only costs the record, so it's a warning rather than a dialog
+fn load_stats(mut stats: ResMut<Stats>) {
+    let loaded = storage::read(STATS_FILE).and_then(|contents| match contents {
+        Some(contents) => ron::from_str(&contents).map_err(|err| format!("Stats file {} is corrupt: {}", storage::location(STATS_FILE), err)),
+        None => Ok(Stats::default()),
+    });
+
+    match loaded {
+        Ok(loaded) => *stats = loaded,
+        Err(message) => warn!("{}", message),
+    }
+}
+
+fn save_stats(stats: Res<Stats>) {
+    if !stats.is_changed() || stats.is_added() {
+        return;
+    }
+
+    let saved = ron::ser::to_string_pretty(&*stats, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| storage::write(STATS_FILE, &contents));
+    if let Err(message) = saved {
+        warn!("{}", message);
+    }
+}
+
+// only words that made it into the found list, bonus round answers don't count
+fn count_found_words(mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                    gamestate: Res<GameState>,
+                    frequency: Res<WordFrequency>,
+                    mut stats: ResMut<Stats>,
+                ) {
+    for ev in ev_word_accepted.iter() {
+        let word = ev.word.to_ascii_lowercase();
+        if !gamestate.is_found(&word) {
+            continue;
+        }
+        if frequency.rate(&word) == Obscurity::Rare {
+            stats.rare_words_found += 1;
+        }
+        for letter in word.chars() {
+            *stats.letters_used.entry(letter).or_insert(0) += 1;
+        }
+    }
+}
+
+// the time taken over each word, counted as each is found so the stats aren't saved every frame
+fn record_pace(mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+               gamestate: Res<GameState>,
+               mut last_word: Local<f32>,
+               mut stats: ResMut<Stats>,
+            ) {
+    // a new board starts its clock from nothing
+    if gamestate.time_played < *last_word {
+        *last_word = 0.;
+    }
+
+    for ev in ev_word_accepted.iter() {
+        if gamestate.is_found(&ev.word.to_ascii_lowercase()) {
+            stats.pace_words += 1;
+            stats.pace_seconds += gamestate.time_played - *last_word;
+            *last_word = gamestate.time_played;
+        }
+    }
+}
+
+// only touches the stats for a new record, the board changes all the time
+fn record_fastest_ranks(gamestate: Res<GameState>, mut stats: ResMut<Stats>) {
+    if !gamestate.is_changed() {
+        return;
+    }
+
+    for (rank, time) in gamestate.rank_times.iter() {
+        if stats.fastest_ranks.get(rank).is_none_or(|fastest| time < fastest) {
+            stats.fastest_ranks.insert(*rank, *time);
+        }
+    }
+}
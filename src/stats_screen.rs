@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use wordgame::words::RANKS;
+
+use crate::focus::Focusable;
+use crate::layout::Layout;
+use crate::palette::Overlay;
+use crate::stats::Stats;
+use crate::{format_clock, setup_goals, AppState, BoardEntity, DailyBoard};
+
+// The lifetime stats on a screen of their own, opened with the Stats button or Ctrl+T: the totals
+// and records from stats.ron, and a bar for each letter of the alphabet showing how often it's
+// turned up in the words found. Esc or Enter goes back to the board.
+pub struct StatsScreenPlugin;
+
+impl Plugin for StatsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_stats_button.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(open_stats))
+            .add_system_set(SystemSet::on_enter(AppState::Stats).with_system(spawn_stats))
+            .add_system_set(SystemSet::on_update(AppState::Stats).with_system(close_stats))
+            .add_system_set(SystemSet::on_exit(AppState::Stats).with_system(despawn_stats));
+    }
+}
+
+const BAR_HEIGHT: f32 = 200.; // for the most used letter, the rest are in proportion
+const BAR_WIDTH: f32 = 18.;
+
+#[derive(Component)]
+struct StatsButton {}
+
+#[derive(Component)]
+struct StatsRoot {}
+
+fn spawn_stats_button(mut commands: Commands, asset_server: Res<AssetServer>, daily_board: Res<DailyBoard>, layout: Res<Layout>) {
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+
+    // on top of the right hand column, which has the yesterday button in it on a daily board
+    let row = if daily_board.0.is_some() { 4. } else { 3. };
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { right: Val::Px(12.), bottom: Val::Px(12. + row * layout.button_row_height()), ..default() },
+            padding: layout.button_padding(),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(StatsButton {}).insert(Focusable { state: AppState::Playing, order: 36 }).with_children(|button| {
+        button.spawn(TextBundle::from_section("Stats", button_style.clone()));
+    });
+}
+
+fn open_stats(keys: Res<Input<KeyCode>>,
+              buttons: Query<&Interaction, (Changed<Interaction>, With<StatsButton>)>,
+              mut state: ResMut<State<AppState>>,
+            ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if (ctrl && keys.just_pressed(KeyCode::T)) || buttons.iter().any(|interaction| *interaction == Interaction::Clicked) {
+        if let Err(err) = state.push(AppState::Stats) {
+            debug!("not opening the stats, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn spawn_stats(mut commands: Commands, asset_server: Res<AssetServer>, stats: Res<Stats>) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let body_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 36., color: Color::WHITE };
+    let letter_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 20., color: Color::WHITE };
+    let help_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::GRAY };
+
+    let mut lines = vec![format!("Rare words found: {}", stats.rare_words_found)];
+    if let Some(seconds) = stats.seconds_per_word() {
+        lines.push(format!("Pace: {:.0}s a word over {} words", seconds, stats.pace_words));
+    }
+    // the best rank ever reached is the record worth showing
+    if let Some((rank, time)) = stats.fastest_ranks.iter().next_back() {
+        lines.push(format!("Fastest to {}: {}", RANKS[*rank].0, format_clock(*time)));
+    }
+
+    let most_used = stats.letters_used.values().copied().max().unwrap_or(0).max(1);
+    let mut by_use: Vec<(&char, &u32)> = stats.letters_used.iter().collect();
+    by_use.sort_by(|a, b| b.1.cmp(a.1));
+    let favourites: Vec<String> = by_use.iter().take(3).map(|(letter, count)| format!("{} {}", letter.to_ascii_uppercase(), count)).collect();
+    lines.push(if favourites.is_empty() { String::from("No words found yet") } else { format!("Most used letters: {}", favourites.join(", ")) });
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(StatsRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Lifetime stats", title_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section(lines.join("\n"), body_style).with_style(Style {
+            max_size: Size::new(Val::Percent(80.), Val::Auto),
+            ..default()
+        }));
+
+        // a column per letter, the bar sitting on top of its letter
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexEnd,
+                margin: UiRect::all(Val::Px(20.)),
+                ..default()
+            },
+            ..default()
+        }).with_children(|chart| {
+            for letter in 'a'..='z' {
+                let count = stats.letters_used.get(&letter).copied().unwrap_or(0);
+                chart.spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::horizontal(Val::Px(3.)),
+                        ..default()
+                    },
+                    ..default()
+                }).with_children(|column| {
+                    column.spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT * count as f32 / most_used as f32)),
+                            ..default()
+                        },
+                        background_color: Color::ALICE_BLUE.into(),
+                        ..default()
+                    });
+                    column.spawn(TextBundle::from_section(letter.to_ascii_uppercase().to_string(), letter_style.clone()));
+                });
+            }
+        });
+
+        parent.spawn(TextBundle::from_section("Esc or Enter to go back to the board", help_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+fn close_stats(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::Return]) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.pop() {
+            debug!("not closing the stats, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn despawn_stats(mut commands: Commands, screens: Query<Entity, With<StatsRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
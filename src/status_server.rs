@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use serde::Serialize;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+use wordgame::words::{GameState, RANKS};
+
+// A tiny HTTP endpoint on this machine only, for stream overlays and companion apps to show how
+// the board is going: any request to http://127.0.0.1:PORT/ gets the letters, score, rank and
+// words found as JSON, e.g.
+//   {"letters":"TRAINED","required_letter":"A","score":42,"rank":"Good","found":9}
+// The letters are the whole board, the answers never go out. It's a handful of requests a second
+// at most, so they're answered between frames rather than on a thread of their own.
+pub struct StatusServerPlugin;
+
+impl Plugin for StatusServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(start_status_server)
+            .add_system(answer_status_requests);
+    }
+}
+
+pub const DEFAULT_STATUS_PORT: u16 = 7880;
+const READ_TIMEOUT: Duration = Duration::from_millis(50); // a request is a few lines, anything slower is dropped
+
+// from the command line
+#[derive(Resource)]
+pub struct StatusPort(pub u16);
+
+#[derive(Resource)]
+struct StatusListener(TcpListener);
+
+#[derive(Serialize)]
+struct Status<'a> {
+    letters: &'a str,
+    required_letter: char,
+    score: u32,
+    rank: &'a str,
+    found: usize,
+}
+
+// a port already in use only loses the endpoint, so it's a warning rather than a dialog
+fn start_status_server(mut commands: Commands, port: Res<StatusPort>) {
+    let listener = TcpListener::bind(("127.0.0.1", port.0)).and_then(|listener| {
+        listener.set_nonblocking(true)?;
+        Ok(listener)
+    });
+
+    match listener {
+        Ok(listener) => {
+            info!("status at http://127.0.0.1:{}/", port.0);
+            commands.insert_resource(StatusListener(listener));
+        }
+        Err(err) => warn!("Could not serve the status on port {}: {}", port.0, err),
+    }
+}
+
+fn answer_status_requests(listener: Option<Res<StatusListener>>, gamestate: Res<GameState>) {
+    let listener = match listener {
+        Some(listener) => listener,
+        None => return,
+    };
+
+    loop {
+        let mut stream = match listener.0.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) if err.kind() == ErrorKind::WouldBlock => return,
+            Err(err) => {
+                warn!("status request failed: {}", err);
+                return;
+            }
+        };
+
+        // whatever was asked for gets the status, there's only the one thing to serve
+        let mut request = [0; 1024];
+        let read = stream.set_nonblocking(false)
+            .and_then(|_| stream.set_read_timeout(Some(READ_TIMEOUT)))
+            .and_then(|_| stream.read(&mut request));
+        if let Err(err) = read {
+            debug!("status request dropped: {}", err);
+            continue;
+        }
+
+        let status = Status {
+            letters: &gamestate.target_string,
+            required_letter: gamestate.required_letter,
+            score: gamestate.score(),
+            rank: RANKS[gamestate.rank()].0,
+            found: gamestate.correct_words.len(),
+        };
+        let body = serde_json::to_string(&status).unwrap();
+
+        // overlays are usually local web pages, which need the CORS header to read it
+        let response = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                               body.len(), body);
+        if let Err(err) = stream.write_all(response.as_bytes()) {
+            debug!("status response dropped: {}", err);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use steamworks::{CallbackHandle, Client, ClientManager, Leaderboard, LeaderboardDisplayType, LeaderboardSortMethod, SingleClient, UploadScoreMethod, UserStatsReceived};
+use wordgame::daily;
+use wordgame::words::GameState;
+
+use crate::achievements::{Achievement, Achievements};
+use crate::settings::Settings;
+use crate::{DailyBoard, GameMode};
+
+// Steam builds (the steam feature) mirror the achievements onto Steam and put daily scores on a
+// Steam leaderboard per day, as well as the game's own leaderboard. Without Steam running the
+// game carries on as usual. The API names must match the ones set up on the Steamworks site.
+pub struct SteamPlugin;
+
+impl Plugin for SteamPlugin {
+    fn build(&self, app: &mut App) {
+        let (client, single) = match Client::init() {
+            Ok(clients) => clients,
+            Err(err) => {
+                warn!("Steam isn't available, playing without it: {}", err);
+                return;
+            }
+        };
+
+        // nothing can be set until Steam has sent the current stats back
+        let stats_ready = Arc::new(AtomicBool::new(false));
+        let ready = stats_ready.clone();
+        let stats_callback = client.register_callback(move |_: UserStatsReceived| ready.store(true, Ordering::Relaxed));
+        client.user_stats().request_current_stats();
+
+        app.insert_resource(SteamClient { client, stats_ready })
+            .insert_non_send_resource(SteamCallbacks { single, _stats_callback: stats_callback })
+            .init_resource::<DailyLeaderboard>()
+            .add_system(run_steam_callbacks)
+            .add_system(sync_achievements.after(run_steam_callbacks))
+            .add_system(publish_daily_score.after(run_steam_callbacks));
+    }
+}
+
+#[derive(Resource)]
+struct SteamClient {
+    client: Client<ClientManager>,
+    stats_ready: Arc<AtomicBool>,
+}
+
+// the callback handle unregisters when dropped, so it lives as long as the client
+struct SteamCallbacks {
+    single: SingleClient<ClientManager>,
+    _stats_callback: CallbackHandle<ClientManager>,
+}
+
+// the Steam leaderboard for the daily puzzle being played, filled in once Steam finds it
+#[derive(Resource, Default)]
+struct DailyLeaderboard {
+    day: Option<u64>,
+    board: Arc<Mutex<Option<Leaderboard>>>,
+    uploaded_score: u32,
+}
+
+fn steam_name(achievement: Achievement) -> &'static str {
+    match achievement {
+        Achievement::FirstWord => "FIRST_WORD",
+        Achievement::FirstPangram => "FIRST_PANGRAM",
+        Achievement::CenturyBoard => "CENTURY_BOARD",
+        Achievement::AllWords => "ALL_WORDS",
+        Achievement::DailyPuzzle => "DAILY_PUZZLE",
+    }
+}
+
+fn run_steam_callbacks(callbacks: NonSend<SteamCallbacks>) {
+    callbacks.single.run_callbacks();
+}
+
+// everything already unlocked is sent once the stats arrive, in case it was earned offline
+fn sync_achievements(steam: Res<SteamClient>, achievements: Res<Achievements>, mut synced: Local<bool>) {
+    if !steam.stats_ready.load(Ordering::Relaxed) || (*synced && !achievements.is_changed()) {
+        return;
+    }
+    *synced = true;
+
+    let stats = steam.client.user_stats();
+    for achievement in achievements.unlocked.iter() {
+        if stats.achievement(steam_name(*achievement)).set().is_err() {
+            warn!("Steam doesn't know the achievement {}", steam_name(*achievement));
+        }
+    }
+    if stats.store_stats().is_err() {
+        warn!("Could not store achievements on Steam");
+    }
+}
+
+fn publish_daily_score(steam: Res<SteamClient>,
+                       settings: Res<Settings>,
+                       gamestate: Res<GameState>,
+                       daily_board: Res<DailyBoard>,
+                       mode: Res<GameMode>,
+                       mut leaderboard: ResMut<DailyLeaderboard>,
+                    ) {
+    // same opt in as the game's own leaderboard, golf rounds only go on the game's own golf board
+    let day = match daily_board.0 {
+        Some(day) if settings.submit_daily_scores && *mode != GameMode::Golf => day,
+        _ => return,
+    };
+
+    if leaderboard.day != Some(day) {
+        let board = Arc::new(Mutex::new(None));
+        let found = board.clone();
+        let name = format!("daily-{}", daily::date_string(day));
+        steam.client.user_stats().find_or_create_leaderboard(&name, LeaderboardSortMethod::Descending, LeaderboardDisplayType::Numeric, move |result| match result {
+            Ok(board) => *found.lock().unwrap() = board,
+            Err(err) => warn!("Could not find the Steam leaderboard for {}: {}", daily::date_string(day), err),
+        });
+        *leaderboard = DailyLeaderboard { day: Some(day), board, uploaded_score: 0 };
+    }
+
+    let score = gamestate.score();
+    if score <= leaderboard.uploaded_score {
+        return;
+    }
+    let board = match leaderboard.board.lock().unwrap().clone() {
+        Some(board) => board,
+        None => return,
+    };
+
+    steam.client.user_stats().upload_leaderboard_score(&board, UploadScoreMethod::KeepBest, score as i32, &[], |result| {
+        if let Err(err) = result {
+            warn!("Could not send the daily score to Steam: {}", err);
+        }
+    });
+    leaderboard.uploaded_score = score;
+}
@@ -0,0 +1,67 @@
+#[cfg(not(feature = "web"))]
+use directories::ProjectDirs;
+#[cfg(not(feature = "web"))]
+use std::{fs, path::PathBuf};
+
+// The small files kept between runs (settings, achievements), in the platform config directory
+// or, in the browser, localStorage under the same names.
+
+#[cfg(not(feature = "web"))]
+fn saved_path(name: &str) -> PathBuf {
+    match ProjectDirs::from("", "", "wordgame") {
+        Some(dirs) => dirs.config_dir().join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+// for folders kept alongside, like the mods
+#[cfg(not(feature = "web"))]
+pub fn path(name: &str) -> PathBuf {
+    saved_path(name)
+}
+
+// where a file lives, for messages
+#[cfg(not(feature = "web"))]
+pub fn location(name: &str) -> String {
+    saved_path(name).display().to_string()
+}
+
+// None when nothing has been saved yet
+#[cfg(not(feature = "web"))]
+pub fn read(name: &str) -> Result<Option<String>, String> {
+    let path = saved_path(name);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    fs::read_to_string(&path).map(Some).map_err(|err| format!("Could not read {}: {}", path.display(), err))
+}
+
+#[cfg(not(feature = "web"))]
+pub fn write(name: &str, contents: &str) -> Result<(), String> {
+    let path = saved_path(name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Could not create folder {}: {}", dir.display(), err))?;
+    }
+    fs::write(&path, contents).map_err(|err| format!("Could not save {}: {}", path.display(), err))
+}
+
+#[cfg(feature = "web")]
+fn storage_key(name: &str) -> String {
+    format!("wordgame.{}", name)
+}
+
+#[cfg(feature = "web")]
+pub fn location(name: &str) -> String {
+    format!("{} in browser storage", name)
+}
+
+#[cfg(feature = "web")]
+pub fn read(name: &str) -> Result<Option<String>, String> {
+    crate::web::load_item(&storage_key(name))
+}
+
+#[cfg(feature = "web")]
+pub fn write(name: &str, contents: &str) -> Result<(), String> {
+    crate::web::save_item(&storage_key(name), contents)
+}
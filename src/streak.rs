@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use wordgame::words::GameState;
+
+use crate::bonus::BonusRound;
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::{record_correct_words, setup_goals, AppState, BoardEntity, WordAcceptedEvent, WordRejectedEvent, TILE_RADIUS};
+
+// The scoring rule from --streak-multiplier: every few words found in a row add one to a
+// multiplier on the points for the next word, up to a limit (three words and x3 unless the
+// board's ScoringRules say otherwise), and any rejected guess starts the streak over. The points
+// past the word's own score go into the board's bonus score, and the streak is shown under the HUD.
+pub struct StreakPlugin;
+
+impl Plugin for StreakPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Streak>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(reset_streak.after(setup_goals))
+                .with_system(spawn_streak_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(score_streak.after(record_correct_words))
+                .with_system(show_streak.after(score_streak)));
+    }
+}
+
+// from the command line
+#[derive(Resource)]
+pub struct StreakRules {
+    pub enabled: bool,
+}
+
+#[derive(Resource, Default)]
+struct Streak {
+    words: u32, // found in a row
+}
+
+#[derive(Component)]
+struct StreakHud {}
+
+fn reset_streak(mut streak: ResMut<Streak>) {
+    *streak = Streak::default();
+}
+
+fn spawn_streak_hud(mut commands: Commands, asset_server: Res<AssetServer>, rules: Res<StreakRules>, layout: Res<Layout>) {
+    if !rules.enabled {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Scoring),
+        ..default()
+    }).insert(BoardEntity {}).insert(StreakHud {}).insert(LayoutAnchor { slot: LayoutSlot::Scoring });
+}
+
+// a board swapped in under the tiles keeps the streak going, it's the same run of guesses
+fn score_streak(rules: Res<StreakRules>,
+                bonus: Res<BonusRound>,
+                mut streak: ResMut<Streak>,
+                mut gamestate: ResMut<GameState>,
+                mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                mut ev_word_rejected: EventReader<WordRejectedEvent>,
+            ) {
+    if !rules.enabled || bonus.active {
+        return;
+    }
+
+    for ev in ev_word_accepted.iter() {
+        let extra = gamestate.word_points(&ev.word.to_ascii_lowercase()) * (gamestate.scoring.streak_multiplier(streak.words) - 1);
+        if extra > 0 {
+            gamestate.bonus_score += extra;
+            debug!("streak of {}, {} extra points", streak.words, extra);
+        }
+        streak.words += 1;
+    }
+    if ev_word_rejected.iter().count() > 0 {
+        streak.words = 0;
+    }
+}
+
+fn show_streak(streak: Res<Streak>, gamestate: Res<GameState>, mut huds: Query<&mut Text, With<StreakHud>>) {
+    let status = format!("Streak {}, next word x{}", streak.words, gamestate.scoring.streak_multiplier(streak.words));
+    for mut text in huds.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
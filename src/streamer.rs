@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use clap::ValueEnum;
+
+use crate::palette::apply_palette;
+
+// Streamer mode, from --streamer: the board and found list on a plain background for capturing
+// in OBS, either chroma key green to key out or a transparent window for a window capture with
+// alpha. The buttons are hidden, their keyboard shortcuts still work, and the spoiler logging
+// --verbose would turn on stays off, see Args::log_plugin. RUST_LOG still overrides that.
+pub struct StreamerPlugin;
+
+impl Plugin for StreamerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(keep_background.after(apply_palette))
+            .add_system(hide_buttons);
+    }
+}
+
+#[derive(Resource, ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamerBackground {
+    Green,
+    Transparent,
+}
+
+impl StreamerBackground {
+    fn color(&self) -> Color {
+        match self {
+            StreamerBackground::Green => Color::rgb(0., 1., 0.),
+            StreamerBackground::Transparent => Color::NONE,
+        }
+    }
+}
+
+// the palette sets its own background whenever the settings change
+fn keep_background(background: Res<StreamerBackground>, mut clear_color: ResMut<ClearColor>) {
+    if clear_color.0 != background.color() {
+        clear_color.0 = background.color();
+    }
+}
+
+// buttons come and go with the boards and menus, so they're caught here once they exist
+fn hide_buttons(mut buttons: Query<&mut Visibility, With<Button>>) {
+    for mut visibility in buttons.iter_mut() {
+        if visibility.is_visible {
+            visibility.is_visible = false;
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use bevy::prelude::*;
+#[cfg(not(feature = "web"))]
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+#[cfg(not(feature = "web"))]
+use futures_lite::future;
+#[cfg(not(feature = "web"))]
+use wordgame::daily;
+#[cfg(not(feature = "web"))]
+use wordgame::printable;
+use wordgame::frequency::{Obscurity, WordFrequency};
+use wordgame::words::{is_pangram, GameState, RANKS};
+
+use crate::focus::Focusable;
+use crate::layout::Layout;
+use crate::palette::Overlay;
+#[cfg(not(feature = "web"))]
+use crate::settings::Settings;
+#[cfg(not(feature = "web"))]
+use crate::share_image;
+use crate::stats::Stats;
+#[cfg(not(feature = "web"))]
+use crate::DailyBoard;
+use crate::{format_clock, pace, setup_goals, AppState, BoardEntity};
+
+// A look over the board so far: score and rank, how many of the words and pangrams are found,
+// how obscure the found words are, whether any hints were bought and how long each rank took, with
+// the lifetime totals underneath. Opened with the Summary button or Ctrl+S, and closed again with Esc or Enter.
+// On desktop P saves a picture of the board to share, see share_image.rs, G an animated GIF of
+// its score counting up to the rank reached, and E a printable sheet of it, see wordgame::printable.
+pub struct SummaryPlugin;
+
+impl Plugin for SummaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing).with_system(spawn_summary_button.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(open_summary))
+            .add_system_set(SystemSet::on_enter(AppState::Summary).with_system(spawn_summary))
+            .add_system_set(SystemSet::on_update(AppState::Summary).with_system(close_summary))
+            .add_system_set(SystemSet::on_exit(AppState::Summary).with_system(despawn_summary));
+
+        #[cfg(not(feature = "web"))]
+        app.add_system_set(SystemSet::on_update(AppState::Summary)
+            .with_system(share_board)
+            .with_system(start_reveal_gif)
+            .with_system(finish_reveal_gif)
+            .with_system(export_printable))
+            .add_system_set(SystemSet::on_exit(AppState::Summary).with_system(drop_reveal_gif));
+    }
+}
+
+#[cfg(not(feature = "web"))]
+const HELP: &str = "Esc or Enter to go back to the board\nP saves a picture of the board to share, Shift+P leaves the score off\nG saves an animated GIF of the reveal\nE exports a printable sheet to solve on paper";
+#[cfg(feature = "web")]
+const HELP: &str = "Esc or Enter to go back to the board";
+
+// a GIF takes a moment to encode, so it's done off the main thread
+#[cfg(not(feature = "web"))]
+#[derive(Resource)]
+struct RevealGif(Task<Result<String, String>>);
+
+#[derive(Component)]
+struct SummaryButton {}
+
+#[derive(Component)]
+struct SummaryRoot {}
+
+#[derive(Component)]
+struct SummaryMessage {}
+
+fn spawn_summary_button(mut commands: Commands, asset_server: Res<AssetServer>, layout: Res<Layout>) {
+    let button_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::BLACK };
+
+    // above the give up and leaderboard buttons
+    commands.spawn(ButtonBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { right: Val::Px(12.), bottom: Val::Px(12. + 2. * layout.button_row_height()), ..default() },
+            padding: layout.button_padding(),
+            ..default()
+        },
+        background_color: Color::ALICE_BLUE.into(),
+        ..default()
+    }).insert(BoardEntity {}).insert(SummaryButton {}).insert(Focusable { state: AppState::Playing, order: 34 }).with_children(|button| {
+        button.spawn(TextBundle::from_section("Summary", button_style.clone()));
+    });
+}
+
+fn open_summary(keys: Res<Input<KeyCode>>,
+                buttons: Query<&Interaction, (Changed<Interaction>, With<SummaryButton>)>,
+                mut state: ResMut<State<AppState>>,
+            ) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if (ctrl && keys.just_pressed(KeyCode::S)) || buttons.iter().any(|interaction| *interaction == Interaction::Clicked) {
+        if let Err(err) = state.push(AppState::Summary) {
+            debug!("not opening the summary, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn obscurity_line(gamestate: &GameState, frequency: &WordFrequency) -> String {
+    let count = |obscurity: Obscurity| gamestate.correct_words.iter().filter(|found| frequency.rate(&found.word) == obscurity).count();
+    format!("Common {}, uncommon {}, rare {}", count(Obscurity::Common), count(Obscurity::Uncommon), count(Obscurity::Rare))
+}
+
+fn spawn_summary(mut commands: Commands,
+                 asset_server: Res<AssetServer>,
+                 gamestate: Res<GameState>,
+                 frequency: Res<WordFrequency>,
+                 stats: Res<Stats>,
+            ) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let body_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 36., color: Color::WHITE };
+    let help_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::GRAY };
+
+    let pangrams = gamestate.answers.iter().filter(|answer| is_pangram(answer)).count();
+    let pangrams_found = gamestate.correct_words.iter().filter(|found| is_pangram(&found.word)).count();
+    let rare: Vec<String> = gamestate.correct_words.iter()
+        .filter(|found| frequency.rate(&found.word) == Obscurity::Rare)
+        .map(|found| found.word.to_ascii_uppercase())
+        .collect();
+
+    let mut lines = vec![
+        format!("{} points, {}", gamestate.score(), RANKS[gamestate.rank()].0),
+        format!("{} of {} words, {} of {} pangrams", gamestate.correct_words.len(), gamestate.answers.len(), pangrams_found, pangrams),
+        obscurity_line(&gamestate, &frequency),
+    ];
+    if !rare.is_empty() {
+        lines.push(format!("Rare finds: {}", rare.join(", ")));
+    }
+    // purists want to know the points came without help
+    lines.push(match gamestate.hints_used {
+        0 => String::from("No hints used, a clean solve"),
+        1 => String::from("1 hint used"),
+        hints => format!("{} hints used", hints),
+    });
+    lines.push(match pace(&gamestate) {
+        Some(pace) => format!("Time played {}, {}", format_clock(gamestate.time_played), pace),
+        None => format!("Time played {}", format_clock(gamestate.time_played)),
+    });
+    if !gamestate.rank_times.is_empty() {
+        let reached: Vec<String> = gamestate.rank_times.iter().map(|(rank, time)| format!("{} {}", RANKS[*rank].0, format_clock(*time))).collect();
+        lines.push(format!("Reached {}", reached.join(", ")));
+    }
+    lines.push(String::new());
+    lines.push(format!("Rare words found, all time: {}", stats.rare_words_found));
+    if let Some(seconds) = stats.seconds_per_word() {
+        lines.push(format!("Pace, all time: {:.0}s a word", seconds));
+    }
+    // the best time for the best rank on this board
+    let fastest = gamestate.rank_times.last().and_then(|(rank, _)| stats.fastest_ranks.get(rank).map(|time| (rank, time)));
+    if let Some((rank, time)) = fastest {
+        lines.push(format!("Fastest to {}, all time: {}", RANKS[*rank].0, format_clock(*time)));
+    }
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(SummaryRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Board summary", title_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section(lines.join("\n"), body_style).with_style(Style {
+            max_size: Size::new(Val::Percent(80.), Val::Auto),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section(HELP, help_style.clone()).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section("", help_style)).insert(SummaryMessage {});
+    });
+}
+
+#[cfg(not(feature = "web"))]
+fn share_board(keys: Res<Input<KeyCode>>,
+               gamestate: Res<GameState>,
+               daily_board: Res<DailyBoard>,
+               settings: Res<Settings>,
+               mut messages: Query<&mut Text, With<SummaryMessage>>,
+            ) {
+    if !keys.just_pressed(KeyCode::P) {
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let date = daily::date_string(daily_board.0.unwrap_or_else(daily::today));
+    let title = if daily_board.0.is_some() { format!("Daily puzzle {}", date) } else { date.clone() };
+    let image = share_image::render_board(&gamestate, settings.colours(), &title, !shift);
+    let message = match share_image::save_board_image(&image, &format!("spelling-tiles-{}-{}", date, gamestate.target_string)) {
+        Ok(path) => format!("Saved to {}", path),
+        Err(message) => {
+            warn!("{}", message);
+            message
+        }
+    };
+
+    for mut text in messages.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+#[cfg(not(feature = "web"))]
+fn start_reveal_gif(mut commands: Commands,
+                    keys: Res<Input<KeyCode>>,
+                    gamestate: Res<GameState>,
+                    daily_board: Res<DailyBoard>,
+                    settings: Res<Settings>,
+                    encoding: Option<Res<RevealGif>>,
+                    mut messages: Query<&mut Text, With<SummaryMessage>>,
+                ) {
+    if !keys.just_pressed(KeyCode::G) || encoding.is_some() {
+        return;
+    }
+
+    let date = daily::date_string(daily_board.0.unwrap_or_else(daily::today));
+    let title = if daily_board.0.is_some() { format!("Daily puzzle {}", date) } else { date.clone() };
+    let frames = share_image::render_reveal(&gamestate, settings.colours(), &title);
+    let name = format!("spelling-tiles-{}-{}", date, gamestate.target_string);
+    commands.insert_resource(RevealGif(AsyncComputeTaskPool::get().spawn(async move { share_image::save_reveal_gif(frames, &name) })));
+
+    for mut text in messages.iter_mut() {
+        text.sections[0].value = String::from("Saving the GIF...");
+    }
+}
+
+#[cfg(not(feature = "web"))]
+fn finish_reveal_gif(mut commands: Commands, encoding: Option<ResMut<RevealGif>>, mut messages: Query<&mut Text, With<SummaryMessage>>) {
+    let mut encoding = match encoding {
+        Some(encoding) => encoding,
+        None => return,
+    };
+
+    if let Some(result) = future::block_on(future::poll_once(&mut encoding.0)) {
+        let message = match result {
+            Ok(path) => format!("Saved to {}", path),
+            Err(message) => {
+                warn!("{}", message);
+                message
+            }
+        };
+        for mut text in messages.iter_mut() {
+            text.sections[0].value = message.clone();
+        }
+        commands.remove_resource::<RevealGif>();
+    }
+}
+
+// an unfinished GIF is dropped along with its task
+#[cfg(not(feature = "web"))]
+fn drop_reveal_gif(mut commands: Commands) {
+    commands.remove_resource::<RevealGif>();
+}
+
+fn close_summary(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::Return]) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.pop() {
+            debug!("not closing the summary, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+fn despawn_summary(mut commands: Commands, screens: Query<Entity, With<SummaryRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(not(feature = "web"))]
+fn export_printable(keys: Res<Input<KeyCode>>,
+                    gamestate: Res<GameState>,
+                    daily_board: Res<DailyBoard>,
+                    mut messages: Query<&mut Text, With<SummaryMessage>>,
+                ) {
+    if !keys.just_pressed(KeyCode::E) {
+        return;
+    }
+
+    let date = daily::date_string(daily_board.0.unwrap_or_else(daily::today));
+    let title = if daily_board.0.is_some() { format!("Spelling Tiles, daily puzzle {}", date) } else { String::from("Spelling Tiles") };
+    let sheet = printable::puzzle_sheet(&gamestate, &title);
+    let message = match share_image::save_printable_sheet(&sheet, &format!("spelling-tiles-{}-{}", date, gamestate.target_string)) {
+        Ok(path) => format!("Saved to {}", path),
+        Err(message) => {
+            warn!("{}", message);
+            message
+        }
+    };
+
+    for mut text in messages.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use wordgame::words::{GameState, RANKS};
+
+use crate::settings::Settings;
+use crate::storage;
+use crate::{record_correct_words, record_rank_times, setup_goals, AppState, BoardRefreshedEvent, GameMode};
+
+// Anonymous usage events, only once the player has turned them on in the settings: which modes
+// boards are played in, the ranks reached and the boards finished. Nothing names the player, the
+// machine or the words, each event is a mode and a number or two, with the game's version and OS
+// on each batch. They're queued in telemetry_queue.ron and sent a batch at a time, as a POST of
+//   {"version": "0.1.0", "platform": "linux", "events": [{"kind": "RankReached", "mode": "classic", "rank": "Great"}]}
+// to telemetry_url in settings.ron. Offline, or with no url, they wait in the queue up to a cap,
+// and turning the setting off throws the queue away.
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Telemetry::load())
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(record_board_started.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(record_board_refreshed)
+                .with_system(record_progress.after(record_correct_words).after(record_rank_times)))
+            .add_system(forget_without_consent)
+            .add_system(send_batch)
+            .add_system(finish_sending);
+    }
+}
+
+const QUEUE_FILE: &str = "telemetry_queue.ron";
+const MAX_QUEUED: usize = 500; // the oldest go first past this, a long way offline shouldn't fill the disk
+const BATCH_SIZE: usize = 50;
+const SEND_SECONDS: f32 = 60.;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind")]
+enum TelemetryEvent {
+    BoardStarted { mode: String },
+    RankReached { mode: String, rank: String },
+    BoardFinished { mode: String, minutes: u32 },
+}
+
+#[derive(Serialize)]
+struct Batch<'a> {
+    version: &'static str,
+    platform: &'static str,
+    events: &'a [TelemetryEvent],
+}
+
+#[derive(Resource)]
+struct Telemetry {
+    queue: Vec<TelemetryEvent>,
+    seconds_until_send: f32,
+    sending: Option<Task<Result<usize, String>>>, // how many off the front of the queue went
+}
+
+impl Telemetry {
+    // a broken queue is only a few lost events, so it's a warning rather than a dialog
+    fn load() -> Telemetry {
+        let queue = storage::read(QUEUE_FILE).and_then(|contents| match contents {
+            Some(contents) => ron::from_str(&contents).map_err(|err| format!("Telemetry queue {} is corrupt: {}", storage::location(QUEUE_FILE), err)),
+            None => Ok(Vec::new()),
+        });
+
+        let queue = queue.unwrap_or_else(|message| {
+            warn!("{}", message);
+            Vec::new()
+        });
+        Telemetry { queue, seconds_until_send: SEND_SECONDS, sending: None }
+    }
+
+    fn record(&mut self, settings: &Settings, event: TelemetryEvent) {
+        if !settings.telemetry {
+            return;
+        }
+
+        self.queue.push(event);
+        if self.queue.len() > MAX_QUEUED {
+            let extra = self.queue.len() - MAX_QUEUED;
+            self.queue.drain(..extra);
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Err(message) = storage::write(QUEUE_FILE, &ron::to_string(&self.queue).unwrap()) {
+            warn!("{}", message);
+        }
+    }
+}
+
+fn mode_name(mode: GameMode) -> String {
+    format!("{:?}", mode).to_ascii_lowercase()
+}
+
+fn record_board_started(settings: Res<Settings>, mode: Res<GameMode>, mut telemetry: ResMut<Telemetry>) {
+    telemetry.record(&settings, TelemetryEvent::BoardStarted { mode: mode_name(*mode) });
+}
+
+fn record_board_refreshed(mut ev_board_refreshed: EventReader<BoardRefreshedEvent>,
+                          settings: Res<Settings>,
+                          mode: Res<GameMode>,
+                          mut telemetry: ResMut<Telemetry>,
+                        ) {
+    for _ in ev_board_refreshed.iter() {
+        telemetry.record(&settings, TelemetryEvent::BoardStarted { mode: mode_name(*mode) });
+    }
+}
+
+// ranks are counted off rank_times like the hint tokens, which starts over with each board
+fn record_progress(gamestate: Res<GameState>,
+                   settings: Res<Settings>,
+                   mode: Res<GameMode>,
+                   mut telemetry: ResMut<Telemetry>,
+                   mut ranks_seen: Local<usize>,
+                   mut finished: Local<bool>,
+                ) {
+    if gamestate.rank_times.len() < *ranks_seen {
+        *ranks_seen = 0;
+    }
+    for (rank, _) in gamestate.rank_times.iter().skip(*ranks_seen) {
+        telemetry.record(&settings, TelemetryEvent::RankReached { mode: mode_name(*mode), rank: String::from(RANKS[*rank].0) });
+    }
+    *ranks_seen = gamestate.rank_times.len();
+
+    let all_found = !gamestate.answers.is_empty() && gamestate.correct_words.len() >= gamestate.answers.len();
+    if all_found && !*finished {
+        telemetry.record(&settings, TelemetryEvent::BoardFinished { mode: mode_name(*mode), minutes: (gamestate.time_played / 60.) as u32 });
+    }
+    *finished = all_found;
+}
+
+fn forget_without_consent(settings: Res<Settings>, mut telemetry: ResMut<Telemetry>) {
+    if settings.is_changed() && !settings.telemetry && !telemetry.queue.is_empty() {
+        info!("usage data turned off, forgetting {} queued events", telemetry.queue.len());
+        telemetry.queue.clear();
+        telemetry.save();
+    }
+}
+
+fn send_batch(time: Res<Time>, settings: Res<Settings>, mut telemetry: ResMut<Telemetry>) {
+    telemetry.seconds_until_send -= time.delta_seconds();
+    if telemetry.seconds_until_send > 0. || telemetry.sending.is_some() {
+        return;
+    }
+    telemetry.seconds_until_send = SEND_SECONDS;
+
+    if !settings.telemetry || settings.telemetry_url.is_empty() || telemetry.queue.is_empty() {
+        return;
+    }
+
+    let url = settings.telemetry_url.clone();
+    let events: Vec<TelemetryEvent> = telemetry.queue.iter().take(BATCH_SIZE).cloned().collect();
+    telemetry.sending = Some(AsyncComputeTaskPool::get().spawn(async move {
+        let batch = Batch { version: env!("CARGO_PKG_VERSION"), platform: std::env::consts::OS, events: &events };
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        agent.post(&url).send_json(&batch).map_err(|err| format!("Could not send usage data: {}", err))?;
+        Ok(events.len())
+    }));
+}
+
+// a failed batch stays at the front of the queue for next time
+fn finish_sending(settings: Res<Settings>, mut telemetry: ResMut<Telemetry>) {
+    let finished = match telemetry.sending.as_mut() {
+        Some(task) => future::block_on(future::poll_once(task)),
+        None => return,
+    };
+
+    if let Some(result) = finished {
+        telemetry.sending = None;
+        match result {
+            // the queue may have been forgotten while the batch was out
+            Ok(sent) if settings.telemetry => {
+                let sent = sent.min(telemetry.queue.len());
+                telemetry.queue.drain(..sent);
+                telemetry.save();
+            }
+            Ok(_) => {}
+            Err(message) => debug!("{}", message),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use wordgame::frequency::WordFrequency;
+use wordgame::words::{GameState, WordList, WordValidators};
+
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::tween::{Easing, Track, Tween};
+use crate::{guess_word, record_correct_words, refresh_board, setup_goals, AppState, BoardEntity, BoardRefreshedEvent, GameMode, GameRng, WordAcceptedEvent, TILE_RADIUS};
+
+// Racing the clock across as many boards as it takes. Finding a few words on a board, or its
+// pangram, swaps a new board in under the same tiles straight away and puts some time back on
+// the clock. Every board's points count. When the clock first runs out a fresh board comes up for
+// a minute long lightning round, where only four letter words count and they score double, with
+// the HUD in gold and the music sped up. The run is over when that clock runs out too.
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeAttack>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(start_time_attack.after(setup_goals))
+                .with_system(spawn_time_attack_hud.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(restart_time_attack.before(guess_word))
+                .with_system(tick_time_attack.after(record_correct_words))
+                .with_system(next_time_attack_board.after(record_correct_words).after(tick_time_attack))
+                .with_system(show_time_attack_hud.after(next_time_attack_board)));
+    }
+}
+
+const TIME_ATTACK_START_SECONDS: f32 = 90.;
+const TIME_ATTACK_BONUS_SECONDS: f32 = 30.; // for each board moved on from
+const LIGHTNING_SECONDS: f32 = 60.;
+const LIGHTNING_WORD_LENGTH: usize = 4;
+const LIGHTNING_MULTIPLIER: u32 = 2;
+
+// from the command line
+#[derive(Resource)]
+pub struct TimeAttackRules {
+    pub words_per_board: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct TimeAttack {
+    running: bool,
+    lightning: bool, // the lightning round's points go straight into the bank
+    finished: bool,
+    boards: u32, // moved on from, not counting the one being played
+    banked_score: u32,
+    seconds_left: f32,
+}
+
+impl TimeAttack {
+    pub fn in_lightning_round(&self) -> bool {
+        self.running && self.lightning
+    }
+}
+
+#[derive(Component)]
+struct TimeAttackHud {}
+
+fn start_time_attack(mut time_attack: ResMut<TimeAttack>, mode: Res<GameMode>) {
+    *time_attack = TimeAttack::default();
+
+    if *mode == GameMode::TimeAttack {
+        time_attack.running = true;
+        time_attack.seconds_left = TIME_ATTACK_START_SECONDS;
+        info!("time attack started");
+    }
+}
+
+fn spawn_time_attack_hud(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if *mode != GameMode::TimeAttack {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(TimeAttackHud {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
+
+// once time is up Enter starts another run, there's nothing left to guess for
+fn restart_time_attack(mut keys: ResMut<Input<KeyCode>>, time_attack: Res<TimeAttack>, mut state: ResMut<State<AppState>>) {
+    if time_attack.finished && keys.just_pressed(KeyCode::Return) {
+        keys.clear_just_pressed(KeyCode::Return);
+        if let Err(err) = state.set(AppState::NewBoard) {
+            debug!("not restarting time attack, the state is already changing: {:?}", err);
+        }
+    }
+}
+
+// the first time the clock runs out the lightning round starts on a fresh board, with plenty of
+// short words left on it
+fn tick_time_attack(mut time_attack: ResMut<TimeAttack>,
+                    time: Res<Time>,
+                    mut gamestate: ResMut<GameState>,
+                    wordlist: Res<WordList>,
+                    validators: Res<WordValidators>,
+                    frequency: Res<WordFrequency>,
+                    mut game_rng: ResMut<GameRng>,
+                    mut ev_board_refreshed: EventWriter<BoardRefreshedEvent>,
+                ) {
+    if !time_attack.running {
+        return;
+    }
+
+    time_attack.seconds_left -= time.delta_seconds();
+    if time_attack.seconds_left > 0. {
+        return;
+    }
+
+    if time_attack.lightning {
+        time_attack.seconds_left = 0.;
+        time_attack.running = false;
+        time_attack.finished = true;
+        info!("time attack over after {} boards, {} points", time_attack.boards, time_attack.banked_score);
+    }
+    else {
+        time_attack.banked_score += gamestate.score();
+        time_attack.boards += 1;
+        time_attack.lightning = true;
+        time_attack.seconds_left = LIGHTNING_SECONDS;
+        refresh_board(&mut gamestate, &wordlist, &validators, &frequency, &mut game_rng, &mut ev_board_refreshed);
+        info!("lightning round started with {} points banked", time_attack.banked_score);
+    }
+}
+
+fn next_time_attack_board(mut time_attack: ResMut<TimeAttack>,
+                          rules: Res<TimeAttackRules>,
+                          mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                          mut gamestate: ResMut<GameState>,
+                          wordlist: Res<WordList>,
+                          validators: Res<WordValidators>,
+                          frequency: Res<WordFrequency>,
+                          mut game_rng: ResMut<GameRng>,
+                          mut ev_board_refreshed: EventWriter<BoardRefreshedEvent>,
+                        ) {
+    let accepted: Vec<&WordAcceptedEvent> = ev_word_accepted.iter().collect();
+    if !time_attack.running {
+        return;
+    }
+
+    // the board stays put for the lightning round, it only counts the short words
+    if time_attack.lightning {
+        for ev in accepted.iter().filter(|ev| ev.word.len() == LIGHTNING_WORD_LENGTH) {
+            time_attack.banked_score += LIGHTNING_MULTIPLIER * gamestate.word_points(&ev.word.to_ascii_lowercase());
+        }
+        return;
+    }
+
+    let pangram = accepted.iter().any(|ev| ev.pangram);
+    if !pangram && gamestate.correct_words.len() < rules.words_per_board as usize {
+        return;
+    }
+
+    time_attack.banked_score += gamestate.score();
+    time_attack.boards += 1;
+    time_attack.seconds_left += TIME_ATTACK_BONUS_SECONDS;
+    refresh_board(&mut gamestate, &wordlist, &validators, &frequency, &mut game_rng, &mut ev_board_refreshed);
+}
+
+fn format_clock(seconds: f32) -> String {
+    let seconds = seconds.max(0.).ceil() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// the lightning round's HUD goes gold and flashes white every second, the layout owns its scale
+fn show_time_attack_hud(mut commands: Commands,
+                        time_attack: Res<TimeAttack>,
+                        rules: Res<TimeAttackRules>,
+                        gamestate: Res<GameState>,
+                        mut last_second: Local<u32>,
+                        mut huds: Query<(Entity, &mut Text), With<TimeAttackHud>>,
+                    ) {
+    let second = time_attack.seconds_left.max(0.).ceil() as u32;
+    let tick = time_attack.in_lightning_round() && second != *last_second;
+    *last_second = second;
+
+    let color = if time_attack.in_lightning_round() { Color::GOLD } else { Color::WHITE };
+    let status = if time_attack.finished {
+        format!("Time's up! {} points over {} boards\nEnter for another run", time_attack.banked_score, time_attack.boards)
+    }
+    else if time_attack.lightning {
+        format!("LIGHTNING ROUND {}\nTotal {}\nOnly four letter words count, and they score double",
+                format_clock(time_attack.seconds_left), time_attack.banked_score)
+    }
+    else {
+        let words_left = (rules.words_per_board as usize).saturating_sub(gamestate.correct_words.len());
+        format!("{} left\nTotal {}\n{} more {} or a pangram for a new board",
+                format_clock(time_attack.seconds_left), time_attack.banked_score + gamestate.score(),
+                words_left, if words_left == 1 { "word" } else { "words" })
+    };
+
+    for (entity, mut text) in huds.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+            text.sections[0].style.color = color;
+        }
+        if tick {
+            commands.entity(entity).insert(Tween::new("lightning tick").then(0.25, Easing::CubicOut, [Track::Color(Color::WHITE, Color::GOLD)]));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::layout::Layout;
+use crate::tween::Tween;
+use crate::{AppState, GuessAction, GuessActionEvent, LetterAcceptedEvent, LetterTile, TriedWord};
+
+// Playing the main board without a keyboard: tap a tile to add its letter, tap the guess to
+// submit it, swipe left to erase a letter and swipe down to clear the whole guess.
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Playing).with_system(touch_board));
+    }
+}
+
+const SWIPE_DISTANCE: f32 = 60.;
+
+pub fn touch_world_position(windows: &Windows, camera: &Camera, camera_transform: &GlobalTransform, position: Vec2) -> Option<Vec2> {
+    // touches are measured from the top of the window, the cursor and cameras from the bottom
+    let window = windows.get_primary()?;
+    let position = Vec2::new(position.x, window.height() - position.y);
+    camera.viewport_to_world(camera_transform, position).map(|ray| ray.origin.truncate())
+}
+
+fn touch_board(touches: Res<Touches>,
+               windows: Res<Windows>,
+               layout: Res<Layout>,
+               cameras: Query<(&Camera, &GlobalTransform)>,
+               mut tiles: Query<(&LetterTile, &GlobalTransform, &mut Tween)>,
+               guess: Query<&GlobalTransform, With<TriedWord>>,
+               mut ev_letter_accepted: EventWriter<LetterAcceptedEvent>,
+               mut ev_guess_action: EventWriter<GuessActionEvent>,
+            ) {
+    let (camera, camera_transform) = cameras.single();
+    let radius = layout.tile_radius();
+
+    for touch in touches.iter_just_released() {
+        let swipe = touch.distance();
+        if swipe.x < -SWIPE_DISTANCE && swipe.x.abs() > swipe.y.abs() {
+            ev_guess_action.send(GuessActionEvent { action: GuessAction::Erase });
+            continue;
+        }
+        if swipe.y > SWIPE_DISTANCE && swipe.y > swipe.x.abs() {
+            ev_guess_action.send(GuessActionEvent { action: GuessAction::Clear });
+            continue;
+        }
+
+        let position = match touch_world_position(&windows, camera, camera_transform, touch.position()) {
+            Some(position) => position,
+            None => continue,
+        };
+
+        let on_tile = tiles.iter_mut()
+            .map(|tile| (tile.1.translation().truncate().distance(position), tile))
+            .filter(|(distance, _)| *distance < radius)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, tile)| tile);
+        if let Some((tile, _, mut squish)) = on_tile {
+            ev_letter_accepted.send(LetterAcceptedEvent { letter: tile.letter });
+            squish.restart();
+        }
+        else if guess.iter().any(|transform| {
+            let offset = position - transform.translation().truncate();
+            offset.x.abs() < 3. * radius && offset.y.abs() < radius / 2.
+        }) {
+            ev_guess_action.send(GuessActionEvent { action: GuessAction::Submit });
+        }
+    }
+}
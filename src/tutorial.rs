@@ -0,0 +1,220 @@
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use wordgame::puzzle_file::{PuzzleFile, FORMAT_VERSION};
+
+use crate::focus::Focusable;
+use crate::interchange::ImportedPuzzle;
+use crate::settings::Settings;
+use crate::{record_correct_words, setup_goals, AppState, BoardEntity, GameMode, NextPuzzle, WordAcceptedEvent};
+
+// The first board on a first run: a small board with its own handful of answers, and a card at the
+// top saying what to try next, one step at a time, until the pangram's found. Finishing or
+// skipping it marks it done in the settings so it doesn't come back, and turning "Tutorial" back
+// on in the settings plays it again on the next board. Only classic boards are swapped for it, a
+// daily or a code asked for on the command line is played as asked.
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(start_tutorial.before(setup_goals))
+                .with_system(spawn_tutorial_card.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(advance_tutorial.after(record_correct_words))
+                .with_system(leave_tutorial));
+    }
+}
+
+const LETTERS: &str = "PLANTED";
+const REQUIRED: char = 'A';
+const ANSWERS: [&str; 15] = ["plan", "plant", "plane", "planet", "planted", "land", "lane", "late", "plate", "petal", "pedal", "deal", "lead", "tale", "dental"];
+const LONG_WORD: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TutorialStep {
+    FirstWord,
+    LongWord,
+    Pangram,
+    Finished,
+}
+
+impl TutorialStep {
+    fn prompt(&self) -> &'static str {
+        match self {
+            TutorialStep::FirstWord => "Make a word of 4 or more letters that uses the middle letter A, then press Enter",
+            TutorialStep::LongWord => "Longer words are worth more, a point a letter. Try one with 5 or more letters",
+            TutorialStep::Pangram => "A word using all seven letters is a pangram, worth 7 more. Can you find it?",
+            TutorialStep::Finished => "That's all there is to it! Every board has at least one pangram",
+        }
+    }
+
+    fn button(&self) -> &'static str {
+        if *self == TutorialStep::Finished { "Play a real board" } else { "Skip tutorial" }
+    }
+
+    // whether this word moves things along
+    fn done_by(&self, word: &str, pangram: bool) -> bool {
+        match self {
+            TutorialStep::FirstWord => true,
+            TutorialStep::LongWord => word.len() >= LONG_WORD,
+            TutorialStep::Pangram => pangram,
+            TutorialStep::Finished => false,
+        }
+    }
+
+    fn next(&self) -> TutorialStep {
+        match self {
+            TutorialStep::FirstWord => TutorialStep::LongWord,
+            TutorialStep::LongWord => TutorialStep::Pangram,
+            TutorialStep::Pangram | TutorialStep::Finished => TutorialStep::Finished,
+        }
+    }
+}
+
+// None outside the tutorial board
+#[derive(Resource, Default)]
+struct Tutorial {
+    step: Option<TutorialStep>,
+}
+
+#[derive(Component)]
+struct TutorialText {}
+
+#[derive(Component)]
+struct TutorialButton {}
+
+#[derive(Component)]
+struct TutorialButtonLabel {}
+
+fn tutorial_puzzle() -> PuzzleFile {
+    PuzzleFile {
+        format: FORMAT_VERSION,
+        letters: String::from(LETTERS),
+        required: REQUIRED,
+        date: None,
+        answers: ANSWERS.iter().map(|answer| answer.to_string()).collect(),
+        metadata: BTreeMap::new(),
+    }
+}
+
+// any board started while the tutorial's still to do is the tutorial board, unless something else was asked for
+fn start_tutorial(settings: Res<Settings>,
+                  mode: Res<GameMode>,
+                  mut tutorial: ResMut<Tutorial>,
+                  mut next_puzzle: ResMut<NextPuzzle>,
+                  mut imported_puzzle: ResMut<ImportedPuzzle>,
+                ) {
+    tutorial.step = None;
+    if settings.tutorial_done || *mode != GameMode::Classic || next_puzzle.0.is_some() || imported_puzzle.0.is_some() {
+        return;
+    }
+
+    let puzzle = tutorial_puzzle();
+    next_puzzle.0 = puzzle.puzzle().ok();
+    imported_puzzle.0 = Some(puzzle);
+    tutorial.step = Some(TutorialStep::FirstWord);
+    info!("starting the tutorial");
+}
+
+fn spawn_tutorial_card(mut commands: Commands, asset_server: Res<AssetServer>, tutorial: Res<Tutorial>) {
+    let step = match tutorial.step {
+        Some(step) => step,
+        None => return,
+    };
+
+    let font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
+    let prompt_style = TextStyle { font: font.clone(), font_size: 30., color: Color::WHITE };
+    let button_style = TextStyle { font, font_size: 24., color: Color::BLACK };
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(0.), right: Val::Px(0.), top: Val::Px(12.), ..default() },
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ..default()
+    }).insert(BoardEntity {}).with_children(|parent| {
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(12.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.85).into(),
+            ..default()
+        }).with_children(|card| {
+            card.spawn(TextBundle::from_section(step.prompt(), prompt_style)).insert(TutorialText {});
+            card.spawn(ButtonBundle {
+                style: Style {
+                    margin: UiRect::top(Val::Px(8.)),
+                    padding: UiRect::new(Val::Px(10.), Val::Px(10.), Val::Px(4.), Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::ALICE_BLUE.into(),
+                ..default()
+            }).insert(TutorialButton {}).insert(Focusable { state: AppState::Playing, order: 1 }).with_children(|button| {
+                button.spawn(TextBundle::from_section(step.button(), button_style)).insert(TutorialButtonLabel {});
+            });
+        });
+    });
+}
+
+// done is saved as soon as the pangram's found, whether or not the button's pressed after
+fn advance_tutorial(mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                    mut tutorial: ResMut<Tutorial>,
+                    mut settings: ResMut<Settings>,
+                    mut prompts: Query<&mut Text, (With<TutorialText>, Without<TutorialButtonLabel>)>,
+                    mut labels: Query<&mut Text, (With<TutorialButtonLabel>, Without<TutorialText>)>,
+                ) {
+    let mut step = match tutorial.step {
+        Some(step) => step,
+        None => return,
+    };
+
+    for ev in ev_word_accepted.iter() {
+        if step.done_by(&ev.word, ev.pangram) {
+            step = step.next();
+        }
+    }
+    if tutorial.step == Some(step) {
+        return;
+    }
+    tutorial.step = Some(step);
+
+    if step == TutorialStep::Finished {
+        info!("tutorial finished");
+        settings.tutorial_done = true;
+    }
+    for mut text in prompts.iter_mut() {
+        text.sections[0].value = String::from(step.prompt());
+    }
+    for mut text in labels.iter_mut() {
+        text.sections[0].value = String::from(step.button());
+    }
+}
+
+// skipping counts as done too
+fn leave_tutorial(buttons: Query<&Interaction, (Changed<Interaction>, With<TutorialButton>)>,
+                  mut tutorial: ResMut<Tutorial>,
+                  mut settings: ResMut<Settings>,
+                  mut state: ResMut<State<AppState>>,
+                ) {
+    if tutorial.step.is_none() || !buttons.iter().any(|interaction| *interaction == Interaction::Clicked) {
+        return;
+    }
+
+    if let Err(err) = state.set(AppState::NewBoard) {
+        debug!("not leaving the tutorial, the state is already changing: {:?}", err);
+        return;
+    }
+
+    if !settings.tutorial_done {
+        info!("tutorial skipped");
+        settings.tutorial_done = true;
+    }
+    tutorial.step = None;
+}
@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::settings::Settings;
+
+// Small animations on any entity: a Tween is a run of steps played one after another, each
+// moving one or more tracks (scale, translation, colour or alpha) from one value to another along
+// an easing curve. Colours and alphas go to every section of a Text, or the fill of a shape.
+// Finishing sends a TweenFinishedEvent with the tween's tag, for anything that has to happen
+// afterwards, and the entity can be despawned with it. Reduced motion puts scales and translations
+// straight at their ends, colours and alphas still play out since some of them are the feedback.
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TweenFinishedEvent>()
+            .add_system(run_tweens);
+    }
+}
+
+// how far through a step an animation looks, given how far through it is in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    CubicOut,
+    BackOut,    // overshoots a little before settling
+    ElasticOut, // springs past the end a few times
+}
+
+impl Easing {
+    // the curves from easings.net
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Easing::Linear => t,
+            Easing::CubicOut => 1. - (1. - t).powi(3),
+            Easing::BackOut => {
+                let overshoot = 1.70158;
+                1. + (overshoot + 1.) * (t - 1.).powi(3) + overshoot * (t - 1.).powi(2)
+            }
+            Easing::ElasticOut => {
+                if t == 0. || t == 1. {
+                    t
+                }
+                else {
+                    2_f32.powf(-10. * t) * ((t * 10. - 0.75) * std::f32::consts::TAU / 3.).sin() + 1.
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Track {
+    Scale(Vec3, Vec3),
+    Translation(Vec3, Vec3),
+    Color(Color, Color),
+    Alpha(f32, f32),
+}
+
+impl Track {
+    fn moves(&self) -> bool {
+        matches!(self, Track::Scale(..) | Track::Translation(..))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TweenStep {
+    pub tracks: Vec<Track>,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Tween {
+    pub steps: Vec<TweenStep>,
+    pub tag: &'static str, // sent back in the TweenFinishedEvent
+    delay: f32,
+    despawn_when_done: bool,
+    elapsed: f32,
+    finished: bool,
+}
+
+impl Tween {
+    pub fn new(tag: &'static str) -> Tween {
+        Tween { steps: Vec::new(), tag, delay: 0., despawn_when_done: false, elapsed: 0., finished: false }
+    }
+
+    pub fn then(mut self, duration: f32, easing: Easing, tracks: impl Into<Vec<Track>>) -> Tween {
+        self.steps.push(TweenStep { tracks: tracks.into(), duration, easing });
+        self
+    }
+
+    pub fn with_delay(mut self, delay: f32) -> Tween {
+        self.delay = delay;
+        self
+    }
+
+    pub fn despawning(mut self) -> Tween {
+        self.despawn_when_done = true;
+        self
+    }
+
+    // plays again from the start, after the delay
+    pub fn restart(&mut self) {
+        self.elapsed = 0.;
+        self.finished = false;
+    }
+
+    fn duration(&self) -> f32 {
+        self.delay + self.steps.iter().map(|step| step.duration).sum::<f32>()
+    }
+}
+
+pub struct TweenFinishedEvent {
+    pub entity: Entity,
+    pub tag: &'static str,
+}
+
+// colours don't overshoot with the easing, there's nothing past white
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0., 1.);
+    let [from_r, from_g, from_b, from_a] = from.as_rgba_f32();
+    let [to_r, to_g, to_b, to_a] = to.as_rgba_f32();
+    Color::rgba(from_r + (to_r - from_r) * t, from_g + (to_g - from_g) * t, from_b + (to_b - from_b) * t, from_a + (to_a - from_a) * t)
+}
+
+fn apply_track(track: Track, t: f32, transform: &mut Option<Mut<Transform>>, text: &mut Option<Mut<Text>>, draw_mode: &mut Option<Mut<DrawMode>>) {
+    let paint = |color: &mut Color| match track {
+        Track::Color(from, to) => *color = lerp_color(from, to, t),
+        Track::Alpha(from, to) => {
+            color.set_a((from + (to - from) * t).clamp(0., 1.));
+        }
+        _ => {}
+    };
+
+    match track {
+        Track::Scale(from, to) => {
+            if let Some(transform) = transform {
+                transform.scale = from.lerp(to, t);
+            }
+        }
+        Track::Translation(from, to) => {
+            if let Some(transform) = transform {
+                transform.translation = from.lerp(to, t);
+            }
+        }
+        Track::Color(..) | Track::Alpha(..) => {
+            if let Some(text) = text {
+                for section in text.sections.iter_mut() {
+                    paint(&mut section.style.color);
+                }
+            }
+            if let Some(draw_mode) = draw_mode {
+                match &mut **draw_mode {
+                    DrawMode::Fill(fill_mode) | DrawMode::Outlined { fill_mode, .. } => paint(&mut fill_mode.color),
+                    DrawMode::Stroke(_) => {}
+                }
+            }
+        }
+    }
+}
+
+fn run_tweens(mut commands: Commands,
+              time: Res<Time>,
+              settings: Res<Settings>,
+              mut tweens: Query<(Entity, &mut Tween, Option<&mut Transform>, Option<&mut Text>, Option<&mut DrawMode>)>,
+              mut ev_tween_finished: EventWriter<TweenFinishedEvent>,
+            ) {
+    for (entity, mut tween, mut transform, mut text, mut draw_mode) in tweens.iter_mut() {
+        if tween.finished {
+            continue;
+        }
+
+        // a tween that only moves things is over at once, one with colours in it takes its time
+        let only_moves = tween.steps.iter().all(|step| step.tracks.iter().all(Track::moves));
+        tween.elapsed = if settings.reduced_motion && only_moves { tween.duration() } else { tween.elapsed + time.delta_seconds() };
+        let mut step_time = tween.elapsed - tween.delay;
+        if step_time < 0. {
+            continue;
+        }
+
+        if settings.reduced_motion {
+            for track in tween.steps.iter().flat_map(|step| step.tracks.iter()).filter(|track| track.moves()) {
+                apply_track(*track, 1., &mut transform, &mut text, &mut draw_mode);
+            }
+        }
+
+        // every step started so far is applied in order, so one skipped over in a long frame still
+        // leaves its end values behind
+        for step in tween.steps.iter() {
+            let t = if step.duration > 0. { (step_time / step.duration).min(1.) } else { 1. };
+            let eased = step.easing.apply(t);
+            for track in step.tracks.iter().filter(|track| !settings.reduced_motion || !track.moves()) {
+                apply_track(*track, eased, &mut transform, &mut text, &mut draw_mode);
+            }
+
+            step_time -= step.duration;
+            if step_time < 0. {
+                break;
+            }
+        }
+
+        if tween.elapsed >= tween.duration() {
+            tween.finished = true;
+            ev_tween_finished.send(TweenFinishedEvent { entity, tag: tween.tag });
+            if tween.despawn_when_done {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
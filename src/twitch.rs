@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use wordgame::words::{GameState, WordList, WordValidators};
+
+use crate::{guess_word, record_correct_words, setup_goals, AppState, BoardRefreshedEvent, ChatCredits, HintText, WordAcceptedEvent};
+
+// Twitch chat playing along, from --twitch-channel: the game reads the channel's chat anonymously
+// over IRC, and any message that's a single word is tried as a guess on the board. A word chat
+// gets right counts like any other, and the found list says who sent it. Each chatter gets one
+// guess every few seconds and the whole chat a few a second, so a busy channel can't flood the
+// board. Wrong guesses are dropped quietly, there would be far too many to show.
+pub struct TwitchPlugin;
+
+impl Plugin for TwitchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatLimits>()
+            .add_startup_system(connect_to_chat)
+            .add_system(finish_connecting)
+            .add_system(read_chat)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(reset_chat_credits.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(reset_chat_credits_on_refresh)
+                .with_system(guess_from_chat.after(read_chat).after(guess_word).before(record_correct_words)));
+    }
+}
+
+const CHAT_SERVER: &str = "irc.chat.twitch.tv:6667";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const CHATTER_GUESS_SECONDS: f32 = 5.; // between one chatter's guesses
+const CHAT_GUESS_SECONDS: f32 = 0.25; // between any two chat guesses
+const LONGEST_GUESS: usize = 20;
+
+// from the command line, without the #
+#[derive(Resource)]
+pub struct TwitchChannel(pub String);
+
+// the chat connection once it's up, read without blocking like the other player's in net.rs
+#[derive(Resource)]
+struct ChatConnection {
+    stream: TcpStream,
+    received: Vec<u8>,
+    guesses: Vec<(String, String)>, // chatter and word, waiting for the board
+}
+
+impl ChatConnection {
+    fn send(&mut self, line: &str) {
+        if let Err(err) = self.stream.write_all(format!("{}\r\n", line).as_bytes()) {
+            warn!("could not send to Twitch chat: {}", err);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ChatConnecting(Task<Result<TcpStream, String>>);
+
+#[derive(Resource, Default)]
+struct ChatLimits {
+    last_guess: f32,
+    chatters: HashMap<String, f32>, // by name, when each last had a guess tried
+}
+
+fn connect_to_chat(mut commands: Commands) {
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let resolved = CHAT_SERVER.to_socket_addrs().map_err(|err| format!("Could not find Twitch chat: {}", err))?.next();
+        let target = resolved.ok_or(String::from("Could not find Twitch chat"))?;
+        TcpStream::connect_timeout(&target, CONNECT_TIMEOUT).map_err(|err| format!("Could not reach Twitch chat: {}", err))
+    });
+    commands.insert_resource(ChatConnecting(task));
+}
+
+// justinfan names can read any channel without logging in, and never say anything
+fn finish_connecting(mut commands: Commands, connecting: Option<ResMut<ChatConnecting>>, channel: Res<TwitchChannel>) {
+    let mut connecting = match connecting {
+        Some(connecting) => connecting,
+        None => return,
+    };
+
+    if let Some(result) = future::block_on(future::poll_once(&mut connecting.0)) {
+        commands.remove_resource::<ChatConnecting>();
+        let stream = result.and_then(|stream| stream.set_nonblocking(true).map(|_| stream).map_err(|err| err.to_string()));
+        match stream {
+            Ok(stream) => {
+                let mut connection = ChatConnection { stream, received: Vec::new(), guesses: Vec::new() };
+                connection.send(&format!("NICK justinfan{}", rand::random::<u32>() % 100_000));
+                connection.send(&format!("JOIN #{}", channel.0.trim_start_matches('#').to_ascii_lowercase()));
+                info!("reading Twitch chat for #{}", channel.0.trim_start_matches('#'));
+                commands.insert_resource(connection);
+            }
+            Err(message) => warn!("{}, playing without chat", message),
+        }
+    }
+}
+
+// a chat line looks like ":name!name@name.tmi.twitch.tv PRIVMSG #channel :the message"
+fn chat_message(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix(':')?;
+    let (prefix, rest) = rest.split_once(' ')?;
+    let (command, rest) = rest.split_once(' ')?;
+    if command != "PRIVMSG" {
+        return None;
+    }
+    let (_, message) = rest.split_once(" :")?;
+    let chatter = prefix.split('!').next()?;
+    Some((chatter.to_string(), message.trim().to_string()))
+}
+
+// one word of letters and nothing else, chatting around it isn't a guess
+fn chat_guess(message: &str) -> Option<String> {
+    let word = message.trim();
+    if word.is_empty() || word.len() > LONGEST_GUESS || !word.chars().all(|letter| letter.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(word.to_ascii_uppercase())
+}
+
+fn read_chat(mut commands: Commands, connection: Option<ResMut<ChatConnection>>) {
+    let mut connection = match connection {
+        Some(connection) => connection,
+        None => return,
+    };
+
+    let mut buffer = [0; 4096];
+    let mut disconnected = false;
+    loop {
+        match connection.stream.read(&mut buffer) {
+            Ok(0) => {
+                disconnected = true;
+                break;
+            }
+            Ok(count) => connection.received.extend_from_slice(&buffer[..count]),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(err) => {
+                warn!("lost Twitch chat: {}", err);
+                disconnected = true;
+                break;
+            }
+        }
+    }
+
+    while let Some(end) = connection.received.iter().position(|byte| *byte == b'\n') {
+        let line: Vec<u8> = connection.received.drain(..=end).collect();
+        let line = String::from_utf8_lossy(&line).trim_end().to_string();
+        // the server drops anyone who doesn't answer
+        if let Some(server) = line.strip_prefix("PING ") {
+            connection.send(&format!("PONG {}", server));
+        }
+        else if let Some((chatter, word)) = chat_message(&line).and_then(|(chatter, message)| chat_guess(&message).map(|word| (chatter, word))) {
+            connection.guesses.push((chatter, word));
+        }
+    }
+
+    if disconnected {
+        info!("Twitch chat closed");
+        commands.remove_resource::<ChatConnection>();
+    }
+}
+
+fn reset_chat_credits(mut credits: ResMut<ChatCredits>) {
+    credits.0.clear();
+}
+
+fn reset_chat_credits_on_refresh(mut ev_board_refreshed: EventReader<BoardRefreshedEvent>, mut credits: ResMut<ChatCredits>) {
+    if ev_board_refreshed.iter().count() > 0 {
+        credits.0.clear();
+    }
+}
+
+// only right answers make a sound, through the usual accepted event
+fn guess_from_chat(connection: Option<ResMut<ChatConnection>>,
+                   time: Res<Time>,
+                   gamestate: Res<GameState>,
+                   wordlist: Res<WordList>,
+                   validators: Res<WordValidators>,
+                   mut limits: ResMut<ChatLimits>,
+                   mut credits: ResMut<ChatCredits>,
+                   mut hint_text: Query<&mut Text, With<HintText>>,
+                   mut ev_word_accepted: EventWriter<WordAcceptedEvent>,
+                ) {
+    let mut connection = match connection {
+        Some(connection) => connection,
+        None => return,
+    };
+
+    let now = time.elapsed_seconds();
+    for (chatter, word) in connection.guesses.drain(..) {
+        let ready = limits.chatters.get(&chatter).is_none_or(|last| now - last >= CHATTER_GUESS_SECONDS);
+        if !ready || now - limits.last_guess < CHAT_GUESS_SECONDS {
+            continue;
+        }
+        limits.last_guess = now;
+        limits.chatters.insert(chatter.clone(), now);
+
+        // two chatters can send the same word before it's recorded
+        let lowercase = word.to_ascii_lowercase();
+        if credits.0.contains_key(&lowercase) {
+            continue;
+        }
+        if let Ok(pangram) = validators.check_word(&word, &gamestate, &wordlist) {
+            for mut text in hint_text.iter_mut() {
+                text.sections[0].value = format!("{} found {}", chatter, word);
+            }
+            info!("{} found {} in chat", chatter, word);
+            credits.0.insert(lowercase, chatter);
+            ev_word_accepted.send(WordAcceptedEvent { word, pangram });
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use wordgame::daily;
+
+use crate::code_menu::GameClipboard;
+use crate::settings::Settings;
+use crate::storage;
+
+// Looks for a newer release once the player has turned on "Check for updates", and shows a banner
+// across the top with the first line of its changelog and a button to copy its link. The feed is
+// update_feed_url in settings.ron, a GitHub latest release by default, so anything answering
+//   {"tag_name": "v0.2.0", "body": "changelog...", "html_url": "https://..."}
+// will do. The answer is kept in update_check.ron and the feed asked at most once a day (by the
+// daily boards' UTC day). Dismissing the banner keeps it away until the release after that one.
+pub struct UpdatesPlugin;
+
+impl Plugin for UpdatesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(UpdateCheck::load())
+            .add_system(start_update_check)
+            .add_system(finish_update_check)
+            .add_system(show_update_banner.after(finish_update_check))
+            .add_system(click_update_banner);
+    }
+}
+
+const CACHE_FILE: &str = "update_check.ron";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const SUMMARY_LENGTH: usize = 120;
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+struct Release {
+    tag_name: String,
+    body: String,
+    html_url: String,
+}
+
+impl Release {
+    fn version(&self) -> Option<(u32, u32, u32)> {
+        parse_version(self.tag_name.trim_start_matches('v'))
+    }
+
+    // the first line that says something, headings and list marks taken off
+    fn summary(&self) -> String {
+        let line = self.body.lines()
+            .map(|line| line.trim().trim_start_matches(['#', '-', '*']).trim())
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+        if line.chars().count() > SUMMARY_LENGTH {
+            format!("{}...", line.chars().take(SUMMARY_LENGTH).collect::<String>())
+        }
+        else {
+            String::from(line)
+        }
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.split(['.', '-']).map(|part| part.parse::<u32>().ok());
+    Some((parts.next()??, parts.next().flatten().unwrap_or(0), parts.next().flatten().unwrap_or(0)))
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct UpdateCache {
+    checked_day: Option<u64>,
+    latest: Option<Release>,
+    dismissed: String, // the tag of the release whose banner was closed
+}
+
+#[derive(Resource)]
+struct UpdateCheck {
+    cache: UpdateCache,
+    request: Option<Task<Result<Release, String>>>,
+    shown: bool, // once a run
+}
+
+impl UpdateCheck {
+    // a broken cache only means asking the feed again, so it's a warning rather than a dialog
+    fn load() -> UpdateCheck {
+        let cache = storage::read(CACHE_FILE).and_then(|contents| match contents {
+            Some(contents) => ron::from_str(&contents).map_err(|err| format!("Update check {} is corrupt: {}", storage::location(CACHE_FILE), err)),
+            None => Ok(UpdateCache::default()),
+        });
+
+        let cache = cache.unwrap_or_else(|message| {
+            warn!("{}", message);
+            UpdateCache::default()
+        });
+        UpdateCheck { cache, request: None, shown: false }
+    }
+
+    fn save(&self) {
+        if let Err(message) = storage::write(CACHE_FILE, &ron::to_string(&self.cache).unwrap()) {
+            warn!("{}", message);
+        }
+    }
+
+    // the cached release, when it's newer than this build and hasn't been dismissed
+    fn newer_release(&self) -> Option<&Release> {
+        let current = parse_version(env!("CARGO_PKG_VERSION"));
+        self.cache.latest.as_ref()
+            .filter(|release| release.tag_name != self.cache.dismissed)
+            .filter(|release| release.version().is_some() && release.version() > current)
+    }
+}
+
+#[derive(Component)]
+struct UpdateBanner {}
+
+#[derive(Component)]
+struct CopyUpdateLinkButton {}
+
+#[derive(Component)]
+struct CopyUpdateLinkLabel {}
+
+#[derive(Component)]
+struct DismissUpdateButton {}
+
+// runs from the first frame, after the settings have loaded, and again if the setting's turned on later
+fn start_update_check(settings: Res<Settings>, mut check: ResMut<UpdateCheck>) {
+    if !settings.check_updates || check.request.is_some() || check.cache.checked_day == Some(daily::today()) {
+        return;
+    }
+    check.cache.checked_day = Some(daily::today());
+
+    let url = settings.update_feed_url.clone();
+    check.request = Some(AsyncComputeTaskPool::get().spawn(async move {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        let response = agent.get(&url).call().map_err(|err| format!("Could not check for updates: {}", err))?;
+        response.into_json::<Release>().map_err(|err| format!("Could not read the update feed: {}", err))
+    }));
+}
+
+// a failed check waits for tomorrow like a successful one, the feed being down isn't worth more tries
+fn finish_update_check(mut check: ResMut<UpdateCheck>) {
+    let finished = match check.request.as_mut() {
+        Some(task) => future::block_on(future::poll_once(task)),
+        None => return,
+    };
+
+    if let Some(result) = finished {
+        match result {
+            Ok(release) => {
+                info!("latest release is {}", release.tag_name);
+                check.cache.latest = Some(release);
+            }
+            Err(message) => warn!("{}", message),
+        }
+        check.save();
+    }
+}
+
+fn show_update_banner(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>, mut check: ResMut<UpdateCheck>) {
+    if check.shown || !settings.check_updates {
+        return;
+    }
+    let release = match check.newer_release() {
+        Some(release) => release.clone(),
+        None => return,
+    };
+    check.shown = true;
+
+    let font = asset_server.load("fonts/BarlowCondensed-Regular.ttf");
+    let text_style = TextStyle { font: font.clone(), font_size: 24., color: Color::WHITE };
+    let button_style = TextStyle { font, font_size: 24., color: Color::BLACK };
+
+    // above the board, under the menus and dialogs
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect { left: Val::Px(0.), right: Val::Px(0.), top: Val::Px(0.), ..default() },
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(Val::Px(6.)),
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(40),
+        ..default()
+    }).insert(UpdateBanner {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(format!("{} is out: {}", release.tag_name, release.summary()), text_style).with_style(Style {
+            margin: UiRect::horizontal(Val::Px(12.)),
+            ..default()
+        }));
+
+        let button = ButtonBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(4.)),
+                padding: UiRect::new(Val::Px(10.), Val::Px(10.), Val::Px(4.), Val::Px(4.)),
+                ..default()
+            },
+            background_color: Color::ALICE_BLUE.into(),
+            ..default()
+        };
+        parent.spawn(button.clone()).insert(CopyUpdateLinkButton {}).with_children(|button| {
+            button.spawn(TextBundle::from_section("Copy link", button_style.clone())).insert(CopyUpdateLinkLabel {});
+        });
+        parent.spawn(button).insert(DismissUpdateButton {}).with_children(|button| {
+            button.spawn(TextBundle::from_section("Dismiss", button_style.clone()));
+        });
+    });
+}
+
+// where there's no clipboard the link is put on the button instead, to be typed
+fn click_update_banner(mut commands: Commands,
+                       mut check: ResMut<UpdateCheck>,
+                       mut clipboard: NonSendMut<GameClipboard>,
+                       copy_buttons: Query<&Interaction, (Changed<Interaction>, With<CopyUpdateLinkButton>)>,
+                       dismiss_buttons: Query<&Interaction, (Changed<Interaction>, With<DismissUpdateButton>)>,
+                       mut copy_labels: Query<&mut Text, With<CopyUpdateLinkLabel>>,
+                       banners: Query<Entity, With<UpdateBanner>>,
+                    ) {
+    if copy_buttons.iter().any(|interaction| *interaction == Interaction::Clicked) {
+        let url = check.cache.latest.as_ref().map(|release| release.html_url.clone()).unwrap_or_default();
+        let label = match clipboard.copy(&url) {
+            Ok(()) => String::from("Copied"),
+            Err(_) => url,
+        };
+        for mut text in copy_labels.iter_mut() {
+            text.sections[0].value = label.clone();
+        }
+    }
+
+    if dismiss_buttons.iter().any(|interaction| *interaction == Interaction::Clicked) {
+        check.cache.dismissed = check.cache.latest.as_ref().map(|release| release.tag_name.clone()).unwrap_or_default();
+        check.save();
+        for banner in banners.iter() {
+            commands.entity(banner).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use bevy::prelude::*;
+use clap::ValueEnum;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use wordgame::words::GameState;
+
+use crate::celebration::check_genius;
+use crate::layout::{Layout, LayoutAnchor, LayoutSlot};
+use crate::palette::Overlay;
+use crate::{format_clock, record_correct_words, setup_goals, tick_time_played, AppState, BoardEntity, GameMode, GameRng, NextPuzzle, TILE_RADIUS};
+
+// Versus mode: a bot plays the same board for VERSUS_SECONDS of time played, finding words from the
+// answers every so often, how often and how many of them it knows set by --bot-level. Its score
+// and latest words run down a column of their own, and at the end a screen compares the two
+// results, with the words only it found. The bot plays on the board's clock, so it waits while
+// the game is paused or a menu is open.
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bot>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing)
+                .with_system(start_bot.after(setup_goals))
+                .with_system(spawn_rival_column.after(setup_goals)))
+            .add_system_set(SystemSet::on_update(AppState::Playing)
+                .with_system(play_bot.after(tick_time_played))
+                .with_system(finish_versus.after(play_bot).after(record_correct_words).before(check_genius))
+                .with_system(show_rival_column.after(finish_versus)))
+            .add_system_set(SystemSet::on_enter(AppState::VersusOver).with_system(spawn_versus_results))
+            .add_system_set(SystemSet::on_update(AppState::VersusOver).with_system(close_versus_results))
+            .add_system_set(SystemSet::on_exit(AppState::VersusOver).with_system(despawn_versus_results));
+    }
+}
+
+const VERSUS_SECONDS: f32 = 5. * 60.;
+const RIVAL_WORDS_SHOWN: usize = 10;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BotLevel {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl BotLevel {
+    // on average, each gap is somewhere between half and one and a half times this
+    fn seconds_per_word(&self) -> f32 {
+        match self {
+            BotLevel::Easy => 25.,
+            BotLevel::Medium => 12.,
+            BotLevel::Hard => 6.,
+        }
+    }
+
+    // of the board's answers, the rest it never finds
+    fn vocabulary(&self) -> f32 {
+        match self {
+            BotLevel::Easy => 0.35,
+            BotLevel::Medium => 0.6,
+            BotLevel::Hard => 0.9,
+        }
+    }
+}
+
+// from the command line
+#[derive(Resource)]
+pub struct BotRules {
+    pub level: BotLevel,
+}
+
+#[derive(Resource, Default)]
+struct Bot {
+    playing: bool,
+    to_find: Vec<String>, // in the order it'll find them
+    found: Vec<String>,
+    next_word_at: f32, // by the board's time played
+}
+
+impl Bot {
+    // scored the same way as the player, rarity bonus and all
+    fn score(&self, gamestate: &GameState) -> u32 {
+        self.found.iter().map(|word| gamestate.word_points(word)).sum()
+    }
+}
+
+#[derive(Component)]
+struct RivalColumn {}
+
+#[derive(Component)]
+struct VersusResultsRoot {}
+
+fn start_bot(mut bot: ResMut<Bot>, mode: Res<GameMode>, rules: Res<BotRules>, gamestate: Res<GameState>, mut game_rng: ResMut<GameRng>) {
+    *bot = Bot::default();
+    if *mode != GameMode::Versus {
+        return;
+    }
+
+    let mut to_find = gamestate.answers.clone();
+    to_find.shuffle(&mut game_rng.0);
+    to_find.truncate((to_find.len() as f32 * rules.level.vocabulary()).round() as usize);
+
+    let next_word_at = rules.level.seconds_per_word() * game_rng.0.gen_range(0.5..1.5);
+    *bot = Bot { playing: true, to_find, found: Vec::new(), next_word_at };
+    info!("versus started against a {:?} bot knowing {} words", rules.level, bot.to_find.len());
+}
+
+fn play_bot(mut bot: ResMut<Bot>, rules: Res<BotRules>, gamestate: Res<GameState>, mut game_rng: ResMut<GameRng>) {
+    if !bot.playing || gamestate.time_played < bot.next_word_at {
+        return;
+    }
+
+    if let Some(word) = bot.to_find.pop() {
+        debug!(target: "wordgame::spoiler", "bot found {}", word);
+        bot.found.push(word);
+    }
+    bot.next_word_at += rules.level.seconds_per_word() * game_rng.0.gen_range(0.5..1.5);
+}
+
+fn finish_versus(mut bot: ResMut<Bot>, gamestate: Res<GameState>, mut state: ResMut<State<AppState>>) {
+    if !bot.playing || gamestate.time_played < VERSUS_SECONDS {
+        return;
+    }
+
+    // another transition may already be queued this frame, like the idle pause, in which case
+    // try again next frame
+    if state.push(AppState::VersusOver).is_ok() {
+        bot.playing = false;
+        info!("versus over, {} to the bot's {}", gamestate.score(), bot.score(&gamestate));
+    }
+}
+
+fn spawn_rival_column(mut commands: Commands, asset_server: Res<AssetServer>, mode: Res<GameMode>, layout: Res<Layout>) {
+    if *mode != GameMode::Versus {
+        return;
+    }
+
+    let info_text_style = TextStyle {
+        font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"),
+        font_size: TILE_RADIUS / 2.,
+        color: Color::WHITE,
+    };
+
+    commands.spawn(Text2dBundle{
+        text: Text::from_section("", info_text_style).with_alignment(TextAlignment::TOP_CENTER),
+        transform: layout.transform(LayoutSlot::Hud),
+        ..default()
+    }).insert(BoardEntity {}).insert(RivalColumn {}).insert(LayoutAnchor { slot: LayoutSlot::Hud });
+}
+
+// the newest words first
+fn show_rival_column(bot: Res<Bot>,
+                     rules: Res<BotRules>,
+                     gamestate: Res<GameState>,
+                     mut columns: Query<&mut Text, With<RivalColumn>>,
+                    ) {
+    let clock = if bot.playing { format_clock((VERSUS_SECONDS - gamestate.time_played).max(0.)) } else { String::from("Time's up") };
+    let mut lines = vec![
+        clock,
+        format!("You: {} ({} words)", gamestate.score(), gamestate.correct_words.len()),
+        format!("{:?} bot: {} ({} words)", rules.level, bot.score(&gamestate), bot.found.len()),
+    ];
+    lines.extend(bot.found.iter().rev().take(RIVAL_WORDS_SHOWN).map(|word| word.to_ascii_uppercase()));
+
+    let status = lines.join("\n");
+    for mut text in columns.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
+
+fn spawn_versus_results(mut commands: Commands, asset_server: Res<AssetServer>, bot: Res<Bot>, rules: Res<BotRules>, gamestate: Res<GameState>) {
+    let title_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Bold.ttf"), font_size: 60., color: Color::WHITE };
+    let body_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 36., color: Color::WHITE };
+    let help_style = TextStyle { font: asset_server.load("fonts/BarlowCondensed-Regular.ttf"), font_size: 24., color: Color::GRAY };
+
+    let (score, bot_score) = (gamestate.score(), bot.score(&gamestate));
+    let outcome = if score > bot_score {
+        "You win!"
+    }
+    else if score < bot_score {
+        "The bot wins"
+    }
+    else {
+        "It's a draw"
+    };
+
+    let mut lines = vec![
+        format!("You: {} points, {} words", score, gamestate.correct_words.len()),
+        format!("{:?} bot: {} points, {} words", rules.level, bot_score, bot.found.len()),
+    ];
+    let missed: Vec<String> = bot.found.iter().filter(|word| !gamestate.is_found(word)).map(|word| word.to_ascii_uppercase()).collect();
+    if !missed.is_empty() {
+        lines.push(format!("Only the bot found: {}", missed.join(", ")));
+    }
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.85).into(),
+        z_index: ZIndex::Global(50),
+        ..default()
+    }).insert(VersusResultsRoot {}).insert(Overlay {}).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(outcome, title_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section(lines.join("\n"), body_style).with_style(Style {
+            max_size: Size::new(Val::Percent(80.), Val::Auto),
+            ..default()
+        }));
+        parent.spawn(TextBundle::from_section("Enter for a rematch on a new board, Esc to look over this one", help_style).with_style(Style {
+            margin: UiRect::all(Val::Px(20.)),
+            ..default()
+        }));
+    });
+}
+
+// whether it's a rematch is kept until the screen has actually closed
+fn close_versus_results(mut keys: ResMut<Input<KeyCode>>,
+                        mut next_puzzle: ResMut<NextPuzzle>,
+                        mut state: ResMut<State<AppState>>,
+                        mut closing: Local<Option<bool>>,
+                    ) {
+    if keys.just_pressed(KeyCode::Return) {
+        keys.clear_just_pressed(KeyCode::Return);
+        *closing = Some(true);
+    }
+    else if keys.just_pressed(KeyCode::Escape) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        *closing = Some(false);
+    }
+
+    // another transition may already be queued this frame, in which case try again next frame
+    let rematch = match *closing {
+        Some(rematch) => rematch,
+        None => return,
+    };
+    let closed = if rematch { state.replace(AppState::NewBoard).is_ok() } else { state.pop().is_ok() };
+    if closed {
+        if rematch {
+            next_puzzle.0 = None;
+        }
+        *closing = None;
+    }
+}
+
+fn despawn_versus_results(mut commands: Commands, screens: Query<Entity, With<VersusResultsRoot>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
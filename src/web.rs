@@ -0,0 +1,26 @@
+// Running in the browser. Build with
+//   cargo build --release --target wasm32-unknown-unknown --no-default-features --features web,audio,particles
+//   wasm-bindgen --target web --out-dir web/pkg target/wasm32-unknown-unknown/release/wordgame.wasm
+// then serve web/ with a copy of assets/ next to index.html. The word list comes in as an asset
+// (see loading.rs), settings live in localStorage, and the canvas follows the size of the page
+// with layout.rs fitting the board to it.
+
+// the canvas in web/index.html
+pub const CANVAS_SELECTOR: &str = "#wordgame";
+
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .ok_or_else(|| String::from("No browser window"))?
+        .local_storage()
+        .map_err(|err| format!("Browser storage is blocked: {:?}", err))?
+        .ok_or_else(|| String::from("This browser has no local storage"))
+}
+
+// None when nothing has been saved under the key yet
+pub fn load_item(key: &str) -> Result<Option<String>, String> {
+    local_storage()?.get_item(key).map_err(|err| format!("Could not read {} from browser storage: {:?}", key, err))
+}
+
+pub fn save_item(key: &str, value: &str) -> Result<(), String> {
+    local_storage()?.set_item(key, value).map_err(|err| format!("Could not save {} to browser storage: {:?}", key, err))
+}
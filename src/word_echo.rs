@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use wordgame::words::GameState;
+
+use crate::tween::{Easing, Track, Tween};
+use crate::{guess_word, relabel_board, AppState, BoardRefreshedEvent, GameMode, TileLabel, WordAcceptedEvent, TILE_RADIUS};
+
+// An accepted word is played back on the board: the letters it used hop up off their tiles one
+// after another, in the order they're spelled. A letter used twice only hops the first time.
+// The hops are label tweens, so the tile squish carries on underneath, and reduced motion leaves
+// the letters where they are.
+pub struct WordEchoPlugin;
+
+impl Plugin for WordEchoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Playing)
+            .with_system(echo_accepted_word.after(guess_word).after(relabel_board)));
+    }
+}
+
+const HOP_HEIGHT: f32 = TILE_RADIUS / 4.;
+const HOP_SCALE: f32 = 1.3;
+const HOP_STAGGER_SECONDS: f32 = 0.06;
+
+// a refreshed board is swapping its letters with tweens of its own, which win over the hops
+fn echo_accepted_word(mut commands: Commands,
+                      gamestate: Res<GameState>,
+                      mode: Res<GameMode>,
+                      mut ev_word_accepted: EventReader<WordAcceptedEvent>,
+                      mut ev_board_refreshed: EventReader<BoardRefreshedEvent>,
+                      labels: Query<(Entity, &TileLabel)>,
+                    ) {
+    let refreshed = ev_board_refreshed.iter().count() > 0;
+    let word = match ev_word_accepted.iter().last() {
+        Some(ev) if !refreshed && *mode != GameMode::SplitScreen => ev.word.to_ascii_uppercase(),
+        _ => return,
+    };
+
+    let letters = gamestate.target_string.to_ascii_uppercase();
+    let mut hopped = Vec::new();
+    for letter in word.chars() {
+        if hopped.contains(&letter) {
+            continue;
+        }
+        let order = hopped.len();
+        hopped.push(letter);
+
+        let index = match letters.find(letter) {
+            Some(index) => index,
+            None => continue,
+        };
+        for (entity, _) in labels.iter().filter(|(_, label)| label.index == index) {
+            let rest = Vec3::new(0., 0., 1.);
+            let top = Vec3::new(0., HOP_HEIGHT, 1.);
+            commands.entity(entity).insert(Tween::new("word echo")
+                .with_delay(HOP_STAGGER_SECONDS * order as f32)
+                .then(0.1, Easing::CubicOut, [Track::Translation(rest, top), Track::Scale(Vec3::ONE, Vec3::splat(HOP_SCALE))])
+                .then(0.25, Easing::BackOut, [Track::Translation(top, rest), Track::Scale(Vec3::splat(HOP_SCALE), Vec3::ONE)]));
+        }
+    }
+}
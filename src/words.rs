@@ -0,0 +1,491 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::frequency::WordFrequency;
+
+pub const MIN_WORD_LENGTH: usize = 4;
+// word lists keep words down to this length, each board decides whether it takes them
+pub const KIDS_MIN_WORD_LENGTH: usize = 3;
+
+#[derive(Resource)]
+pub struct WordList {
+    pub all_valid_words: Vec<String>,
+    pub potential_pangrams: Vec<String>, // Will be a subset of all words since these are also valid, lowercase ones only
+}
+
+impl WordList {
+    pub fn parse(file_contents: &str) -> WordList {
+        WordList::parse_with_progress(file_contents, |_| {})
+    }
+
+    // progress is called now and then with the fraction of the file parsed so far
+    pub fn parse_with_progress(file_contents: &str, mut progress: impl FnMut(f32)) -> WordList {
+        let mut wordlist = WordList { all_valid_words: Vec::new(), potential_pangrams: Vec::new() };
+
+        for (count, word) in file_contents.split_whitespace().enumerate() {
+            if count % 10_000 == 0 {
+                let offset = word.as_ptr() as usize - file_contents.as_ptr() as usize;
+                progress(offset as f32 / file_contents.len() as f32);
+            }
+
+            if is_valid_word(word) {
+                wordlist.all_valid_words.push(String::from(word));
+
+                // a proper noun can't be an answer (see find_answers), so its board may have no pangram
+                if word_to_bits(word).count_ones() == 7 && word.bytes().all(|c| c.is_ascii_lowercase()) {
+                    wordlist.potential_pangrams.push(String::from(word));
+                }
+            }
+        }
+
+        progress(1.0);
+        wordlist
+    }
+}
+
+#[derive(Clone)]
+pub struct FoundWord {
+    pub word: String, // lowercase, as it appears in the word list
+    pub player: usize,
+}
+
+#[derive(Resource, Clone)]
+pub struct GameState {
+    pub target_string: String,
+    pub target_bits: u32,
+    pub required_letter: char,
+    pub required_bit: u32, // every letter a word has to use, only more than one on the mega board
+    pub correct_words: Vec<FoundWord>,
+    pub answers: Vec<String>, // every word in the list that check_word would accept on this board
+    pub curated_answers: bool, // the answers came with an imported puzzle, and are the only words accepted rather than the list's
+    pub player_count: usize,
+    pub current_player: usize,
+    pub time_played: f32, // seconds spent on this board, not counting time in menus
+    pub rank_times: Vec<(usize, f32)>, // each rank past the first as it was reached, with time_played then
+    pub bonus_score: u32, // partial credit from the bonus round after giving up, and streak and combo extras
+    pub spent_score: u32, // paid for hints
+    pub hints_used: u32,
+    pub min_word_length: usize,
+    pub rarity_bonus: Option<HashMap<String, u32>>, // extra points for each answer under rarity scoring, see weigh_by_rarity
+    pub letter_limits: Option<[u8; 26]>, // tiles of each letter when a tile can only be used once in a word, None lets letters repeat
+    pub scoring: ScoringRules,
+}
+
+impl Default for GameState {
+    fn default() -> GameState {
+        GameState {
+            target_string: String::new(),
+            target_bits: 0,
+            required_letter: ' ',
+            required_bit: 0,
+            correct_words: Vec::new(),
+            answers: Vec::new(),
+            curated_answers: false,
+            player_count: 1,
+            current_player: 0,
+            time_played: 0.,
+            rank_times: Vec::new(),
+            bonus_score: 0,
+            spent_score: 0,
+            hints_used: 0,
+            min_word_length: MIN_WORD_LENGTH,
+            rarity_bonus: None,
+            letter_limits: None,
+            scoring: ScoringRules::default(),
+        }
+    }
+}
+
+impl GameState {
+    pub fn is_found(&self, word: &str) -> bool {
+        self.correct_words.iter().any(|found| found.word == word)
+    }
+
+    // switches the board to rarity scoring, once its answers are settled
+    pub fn weigh_by_rarity(&mut self, frequency: &WordFrequency) {
+        self.rarity_bonus = Some(self.answers.iter().map(|answer| (answer.clone(), frequency.rate(answer).bonus(self.scoring.word_score(answer)))).collect());
+    }
+
+    // what a word is worth on this board, its score under the board's rules plus any rarity bonus
+    pub fn word_points(&self, word: &str) -> u32 {
+        self.scoring.word_score(word) + self.rarity_bonus.as_ref().and_then(|bonus| bonus.get(word)).copied().unwrap_or(0)
+    }
+
+    pub fn score(&self) -> u32 {
+        (self.correct_words.iter().map(|found| self.word_points(&found.word)).sum::<u32>() + self.bonus_score).saturating_sub(self.spent_score)
+    }
+
+    pub fn player_score(&self, player: usize) -> u32 {
+        self.correct_words.iter()
+            .filter(|found| found.player == player)
+            .map(|found| self.word_points(&found.word))
+            .sum()
+    }
+
+    // every answer on the board, not counting any bonus points on top
+    pub fn max_score(&self) -> u32 {
+        self.answers.iter().map(|answer| self.word_points(answer)).sum()
+    }
+
+    // an index into RANKS
+    pub fn rank(&self) -> usize {
+        let max_score = self.max_score().max(1);
+        let percent = self.score().min(max_score) * 100 / max_score;
+        RANKS.iter().rposition(|(_, threshold)| percent >= *threshold).unwrap_or(0)
+    }
+}
+
+// named ranks by the percentage of the board's points found, lowest first
+pub const RANKS: [(&str, u32); 9] = [
+    ("Beginner", 0),
+    ("Good Start", 2),
+    ("Moving Up", 5),
+    ("Good", 8),
+    ("Solid", 15),
+    ("Nice", 25),
+    ("Great", 40),
+    ("Amazing", 50),
+    ("Genius", 70),
+];
+
+pub fn rank_by_name(name: &str) -> Option<usize> {
+    RANKS.iter().position(|(rank, _)| rank.eq_ignore_ascii_case(name) || rank.replace(' ', "-").eq_ignore_ascii_case(name))
+}
+
+// only for letters, check_word turns anything else down before it gets here
+pub fn alphabet_index(letter: u8) -> usize {
+    if letter.is_ascii_uppercase() {
+        letter as usize - 'A' as usize
+    }
+    else if letter.is_ascii_lowercase() {
+        letter as usize - 'a' as usize   
+    }
+    else {
+        panic!("not a letter");
+    }
+}
+
+pub fn word_to_bits(word: &str) -> u32 {
+    let mut val = 0;
+
+    for c in word.as_bytes() {
+        val |= 1 << alphabet_index(*c);
+    }
+
+    val
+}
+
+pub fn is_valid_word(word: &str) -> bool {
+    if word.len() < KIDS_MIN_WORD_LENGTH {
+        return false;
+    }
+    
+    for c in word.as_bytes() {
+        if !c.is_ascii_alphabetic() {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn bits_to_letters(bits: u32) -> String {
+    let mut s = String::new();
+
+    for c in 'A'..='Z' {
+        if bits & 1 << alphabet_index(c as u8) != 0 {
+            s.push(c);
+        }
+    }
+
+    s
+}
+
+pub fn is_pangram(word: &str) -> bool {
+    word_to_bits(word).count_ones() == 7
+}
+
+// How words are scored, so a mode or a mod can change it without touching the code. The game
+// reads them from scoring.ron in its assets, and the defaults are the usual rules: four letter
+// words (and three in kids mode) are worth one point, longer words a point per letter, and
+// pangrams seven more.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringRules {
+    pub short_word_length: usize, // words up to this long are worth short_word_points
+    pub short_word_points: u32,
+    pub points_per_letter: u32,     // for the longer words
+    pub pangram_bonus: u32,
+    pub perfect_pangram_bonus: u32, // on top of pangram_bonus for a pangram using each letter once
+    pub streak_step: u32,           // words found in a row for each step up the streak multiplier
+    pub max_streak_multiplier: u32,
+}
+
+impl Default for ScoringRules {
+    fn default() -> ScoringRules {
+        ScoringRules {
+            short_word_length: 4,
+            short_word_points: 1,
+            points_per_letter: 1,
+            pangram_bonus: 7,
+            perfect_pangram_bonus: 0,
+            streak_step: 3,
+            max_streak_multiplier: 3,
+        }
+    }
+}
+
+impl ScoringRules {
+    pub fn word_score(&self, word: &str) -> u32 {
+        let length_score = if word.len() <= self.short_word_length { self.short_word_points } else { word.len() as u32 * self.points_per_letter };
+        match is_pangram(word) {
+            true if word.len() == 7 => length_score + self.pangram_bonus + self.perfect_pangram_bonus,
+            true => length_score + self.pangram_bonus,
+            false => length_score,
+        }
+    }
+
+    // what the next word found is worth, times its own score, after a streak of this many words
+    pub fn streak_multiplier(&self, streak: u32) -> u32 {
+        (1 + streak / self.streak_step.max(1)).min(self.max_streak_multiplier.max(1))
+    }
+}
+
+// one letter changed, added or dropped, checked without building the whole edit distance table
+pub fn one_edit_apart(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+
+    let prefix = short.iter().zip(long).take_while(|(x, y)| x == y).count();
+    if short.len() == long.len() {
+        prefix < short.len() && short[prefix + 1..] == long[prefix + 1..]
+    }
+    else {
+        short[prefix..] == long[prefix + 1..]
+    }
+}
+
+fn uses_board_letters(word_bits: u32, target_bits: u32, required_bit: u32) -> bool {
+    (word_bits & required_bit == required_bit) && only_board_letters(word_bits, target_bits)
+}
+
+fn only_board_letters(word_bits: u32, target_bits: u32) -> bool {
+    (word_bits ^ target_bits) & word_bits == 0
+}
+
+// no letter more times than it has tiles
+fn within_letter_limits(word: &str, limits: &[u8; 26]) -> bool {
+    let mut used = [0; 26];
+    word.bytes().all(|c| {
+        used[alphabet_index(c)] += 1;
+        used[alphabet_index(c)] <= limits[alphabet_index(c)]
+    })
+}
+
+// capitalised entries are proper nouns, check_word only ever looks up the lowercase form
+pub fn find_answers(wordlist: &WordList, target_bits: u32, required_bit: u32, min_length: usize, limits: Option<&[u8; 26]>) -> Vec<String> {
+    wordlist.all_valid_words.iter()
+        .filter(|word| word.len() >= min_length)
+        .filter(|word| word.bytes().all(|c| c.is_ascii_lowercase()))
+        .filter(|word| uses_board_letters(word_to_bits(word), target_bits, required_bit))
+        .filter(|word| limits.is_none_or(|limits| within_letter_limits(word, limits)))
+        .cloned()
+        .collect()
+}
+
+// why check_word turned a guess down, shown after the word on the hint line
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RejectReason {
+    TooShort,
+    OffBoardLetter,
+    OutOfTiles, // more of a letter than the board has tiles for it
+    MissingRequired,
+    AlreadyFound,
+    NearMiss, // not a word, but one letter away from an answer still to find
+    NotInWordList,
+    NotLetters, // something other than a to z in it
+    Custom(&'static str), // from a WordValidator, shown after the word like the rest
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            RejectReason::TooShort => "is too short!",
+            RejectReason::OffBoardLetter => "uses a letter not on the board",
+            RejectReason::OutOfTiles => "uses a letter more times than it's on the board",
+            RejectReason::MissingRequired => "is missing the required letter",
+            RejectReason::AlreadyFound => "was already found",
+            RejectReason::NearMiss => "is close, one letter off!",
+            RejectReason::NotInWordList => "is not in word list",
+            RejectReason::NotLetters => "has something in it that isn't a letter",
+            RejectReason::Custom(reason) => reason,
+        })
+    }
+}
+
+// Ok says whether the word is a pangram
+pub fn check_word(word: &str, gamestate: &GameState, wordlist: &WordList) -> Result<bool, RejectReason> {
+    if !word.bytes().all(|c| c.is_ascii_alphabetic()) {
+        return Err(RejectReason::NotLetters);
+    }
+
+    let lowercase = word.to_ascii_lowercase();
+    let word_bits = word_to_bits(word);
+    let listed = if gamestate.curated_answers { gamestate.answers.contains(&lowercase) } else { wordlist.all_valid_words.contains(&lowercase) };
+    if word.len() < gamestate.min_word_length {
+        Err(RejectReason::TooShort)
+    }
+    else if !only_board_letters(word_bits, gamestate.target_bits) {
+        Err(RejectReason::OffBoardLetter)
+    }
+    else if !gamestate.letter_limits.is_none_or(|limits| within_letter_limits(word, &limits)) {
+        Err(RejectReason::OutOfTiles)
+    }
+    else if word_bits & gamestate.required_bit != gamestate.required_bit {
+        Err(RejectReason::MissingRequired)
+    }
+    else if listed {
+        if gamestate.is_found(&lowercase) {
+            Err(RejectReason::AlreadyFound)
+        }
+        else {
+            // twelve letters to choose from makes seven nothing special
+            Ok(gamestate.letter_limits.is_none() && word_bits.count_ones() == 7)
+        }
+    }
+    // close to an answer still to find, without saying which
+    else if gamestate.answers.iter().any(|answer| !gamestate.is_found(answer) && one_edit_apart(answer, &lowercase)) {
+        Err(RejectReason::NearMiss)
+    }
+    else {
+        Err(RejectReason::NotInWordList)
+    }
+}
+
+// An extra pass a word has to get through once check_word has taken it, for games built on this
+// one with rules of their own, like no proper nouns or theme words only. Validators are added to
+// the WordValidators resource and run in the order they were added, the first to turn a word down
+// gives the reason, usually a RejectReason::Custom.
+pub trait WordValidator: Send + Sync {
+    // the word is lowercase, as it appears in the word list
+    fn validate(&self, word: &str, gamestate: &GameState) -> Result<(), RejectReason>;
+}
+
+#[derive(Resource, Default)]
+pub struct WordValidators(Vec<Box<dyn WordValidator>>);
+
+impl WordValidators {
+    pub fn add(&mut self, validator: impl WordValidator + 'static) {
+        self.0.push(Box::new(validator));
+    }
+
+    // check_word, then every validator on a word it takes
+    pub fn check_word(&self, word: &str, gamestate: &GameState, wordlist: &WordList) -> Result<bool, RejectReason> {
+        let pangram = check_word(word, gamestate, wordlist)?;
+        let lowercase = word.to_ascii_lowercase();
+        for validator in self.0.iter() {
+            validator.validate(&lowercase, gamestate)?;
+        }
+        Ok(pangram)
+    }
+
+    // answers the validators would turn down can't be found, so they don't count towards the ranks
+    pub fn filter_answers(&self, gamestate: &mut GameState) {
+        if self.0.is_empty() {
+            return;
+        }
+
+        let answers = std::mem::take(&mut gamestate.answers);
+        gamestate.answers = answers.into_iter()
+            .filter(|answer| self.0.iter().all(|validator| validator.validate(answer, gamestate).is_ok()))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(answers: &[&str]) -> (GameState, WordList) {
+        let wordlist = WordList::parse(&answers.join("\n"));
+        let target_bits = word_to_bits("planted");
+        let gamestate = GameState {
+            target_string: bits_to_letters(target_bits),
+            target_bits,
+            required_letter: 'A',
+            required_bit: 1 << alphabet_index(b'a'),
+            answers: answers.iter().map(|answer| answer.to_string()).collect(),
+            ..GameState::default()
+        };
+        (gamestate, wordlist)
+    }
+
+    #[test]
+    fn proper_nouns_are_not_pangrams() {
+        let wordlist = WordList::parse("Planted\nplanted\nJukebox");
+        assert_eq!(wordlist.potential_pangrams, vec![String::from("planted")]);
+    }
+
+    #[test]
+    fn check_word_accepts_answers() {
+        let (gamestate, wordlist) = board(&["plan", "plant", "planted"]);
+        assert_eq!(check_word("plan", &gamestate, &wordlist), Ok(false));
+        assert_eq!(check_word("PLANT", &gamestate, &wordlist), Ok(false));
+        assert_eq!(check_word("planted", &gamestate, &wordlist), Ok(true));
+    }
+
+    #[test]
+    fn check_word_reasons() {
+        let (mut gamestate, wordlist) = board(&["plan", "plant", "planted"]);
+        gamestate.correct_words.push(FoundWord { word: String::from("plan"), player: 0 });
+        assert_eq!(check_word("pan", &gamestate, &wordlist), Err(RejectReason::TooShort));
+        assert_eq!(check_word("plank", &gamestate, &wordlist), Err(RejectReason::OffBoardLetter));
+        assert_eq!(check_word("dent", &gamestate, &wordlist), Err(RejectReason::MissingRequired));
+        assert_eq!(check_word("plan", &gamestate, &wordlist), Err(RejectReason::AlreadyFound));
+        assert_eq!(check_word("plante", &gamestate, &wordlist), Err(RejectReason::NearMiss));
+        assert_eq!(check_word("adept", &gamestate, &wordlist), Err(RejectReason::NotInWordList));
+    }
+
+    #[test]
+    fn check_word_turns_down_non_letters() {
+        let (gamestate, wordlist) = board(&["plan"]);
+        assert_eq!(check_word("ab1", &gamestate, &wordlist), Err(RejectReason::NotLetters));
+        assert_eq!(check_word("plán", &gamestate, &wordlist), Err(RejectReason::NotLetters));
+        assert_eq!(check_word("pl-an", &gamestate, &wordlist), Err(RejectReason::NotLetters));
+        assert_eq!(check_word("", &gamestate, &wordlist), Err(RejectReason::TooShort));
+    }
+
+    #[test]
+    fn check_word_counts_tiles() {
+        let (mut gamestate, wordlist) = board(&["plan", "papal"]);
+        let mut limits = [0; 26];
+        for c in "planted".bytes() {
+            limits[alphabet_index(c)] += 1;
+        }
+        gamestate.letter_limits = Some(limits);
+        assert_eq!(check_word("plan", &gamestate, &wordlist), Ok(false));
+        assert_eq!(check_word("papal", &gamestate, &wordlist), Err(RejectReason::OutOfTiles));
+    }
+
+    #[test]
+    fn one_edit() {
+        assert!(one_edit_apart("plant", "plank"));
+        assert!(one_edit_apart("plan", "plant"));
+        assert!(one_edit_apart("plant", "pant"));
+        assert!(one_edit_apart("lead", "plead"));
+        assert!(!one_edit_apart("plan", "plan"));
+        assert!(!one_edit_apart("plan", "clan1"));
+        assert!(!one_edit_apart("deal", "lead"));
+        assert!(!one_edit_apart("plan", "planted"));
+    }
+
+    #[test]
+    fn letters_include_z() {
+        assert_eq!(bits_to_letters(word_to_bits("zebra")), "ABERZ");
+        assert_eq!(bits_to_letters(word_to_bits("abcdefghijklmnopqrstuvwxyz")).len(), 26);
+    }
+}